@@ -1,5 +1,6 @@
 use anyhow::Result;
 use std::env;
+use std::sync::OnceLock;
 use tracing::{debug, error, info};
 
 use crate::handlers::handle_command;
@@ -7,11 +8,88 @@ use crate::{Command, MKSAPExtractor};
 
 pub const DOTENV_PATH: &str = "../.env";
 pub const BASE_URL: &str = "https://mksap.acponline.org";
-pub const OUTPUT_DIR: &str = "../mksap_data";
+/// Data directory used when neither `--output-dir`/`--data-dir`, the config file nor
+/// `MKSAP_OUTPUT_DIR` names one.
+pub const DEFAULT_OUTPUT_DIR: &str = "../mksap_data";
+pub const DEFAULT_SESSION_COOKIE_NAME: &str = "_mksap19_session";
+
+static SITE_CONFIG: OnceLock<SiteConfig> = OnceLock::new();
+static OUTPUT_DIR: OnceLock<String> = OnceLock::new();
+
+/// Set the data directory for this run; the first call wins.
+pub(crate) fn configure_output_dir(output_dir: Option<String>) {
+    if let Some(output_dir) = output_dir {
+        OUTPUT_DIR.set(output_dir).ok();
+    }
+}
+
+/// The resolved data directory (`../mksap_data` unless overridden).
+pub fn output_dir() -> &'static str {
+    OUTPUT_DIR
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_OUTPUT_DIR)
+}
+
+/// Edition-specific site settings, so a new MKSAP edition only needs env changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteConfig {
+    /// Base URL for API and browser requests (`MKSAP_BASE_URL`).
+    pub base_url: String,
+    /// Name of the authenticated session cookie (`MKSAP_COOKIE_NAME`).
+    pub session_cookie_name: String,
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        Self {
+            base_url: BASE_URL.to_string(),
+            session_cookie_name: DEFAULT_SESSION_COOKIE_NAME.to_string(),
+        }
+    }
+}
+
+impl SiteConfig {
+    /// Load from `MKSAP_BASE_URL` and `MKSAP_COOKIE_NAME` (`MKSAP_SESSION_COOKIE_NAME` is
+    /// still honoured), falling back to the current edition's values.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| env::var(key).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let value = |key: &str| {
+            lookup(key)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let defaults = Self::default();
+        Self {
+            base_url: value("MKSAP_BASE_URL")
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or(defaults.base_url),
+            session_cookie_name: value("MKSAP_COOKIE_NAME")
+                .or_else(|| value("MKSAP_SESSION_COOKIE_NAME"))
+                .unwrap_or(defaults.session_cookie_name),
+        }
+    }
+
+    /// Host the session cookie is scoped to (`mksap.acponline.org`).
+    pub fn cookie_domain(base_url: &str) -> Option<String> {
+        url::Url::parse(base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+    }
+}
+
+/// Site settings loaded from the environment on first use (after `.env` is read).
+pub fn site_config() -> &'static SiteConfig {
+    SITE_CONFIG.get_or_init(SiteConfig::from_env)
+}
 
 pub async fn run(args: Vec<String>) -> Result<()> {
     load_env();
     init_tracing();
+    crate::shutdown::install_ctrl_c_handler();
 
     info!("MKSAP Question Bank Extractor (Rust)");
     info!("=====================================");
@@ -50,7 +128,7 @@ pub async fn maybe_inspect_api(extractor: &MKSAPExtractor) -> Result<()> {
 }
 
 pub async fn inspect_api(extractor: &MKSAPExtractor) -> Result<()> {
-    let url = crate::endpoints::question_json(BASE_URL, "cvmcq25001");
+    let url = crate::endpoints::question_json(&extractor.base_url, "cvmcq25001");
 
     println!("\n=== FETCHING API RESPONSE ===");
     println!("URL: {}", url);
@@ -85,3 +163,31 @@ pub async fn inspect_api(extractor: &MKSAPExtractor) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_config_defaults_and_env_overrides() {
+        assert_eq!(SiteConfig::from_lookup(|_| None), SiteConfig::default());
+
+        let config = SiteConfig::from_lookup(|key| match key {
+            "MKSAP_BASE_URL" => Some("https://mksap20.acponline.org/".to_string()),
+            "MKSAP_COOKIE_NAME" => Some("_mksap20_session".to_string()),
+            "MKSAP_SESSION_COOKIE_NAME" => Some("_ignored_session".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.base_url, "https://mksap20.acponline.org");
+        assert_eq!(config.session_cookie_name, "_mksap20_session");
+        assert_eq!(
+            SiteConfig::cookie_domain(&config.base_url).as_deref(),
+            Some("mksap20.acponline.org")
+        );
+
+        let legacy = SiteConfig::from_lookup(|key| {
+            (key == "MKSAP_SESSION_COOKIE_NAME").then(|| "_mksap20_session".to_string())
+        });
+        assert_eq!(legacy.session_cookie_name, "_mksap20_session");
+    }
+}
@@ -2,9 +2,18 @@ use anyhow::{bail, Context, Result};
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
 use std::path::Path;
 use tracing::warn;
 
+use super::asset_store::VideoMetadata;
+use crate::error::ExtractError;
+
+pub const FIGURE_CLOUDFRONT_BASE: &str = "https://d2chybfyz5ban.cloudfront.net/hashed_figures";
+/// Hashed video files live alongside hashed figures on the same CloudFront distribution.
+pub const VIDEO_CLOUDFRONT_BASE: &str = "https://d2chybfyz5ban.cloudfront.net/hashed_videos";
+
 #[derive(Debug, Deserialize)]
 struct FigureResponse {
     pub id: String,
@@ -16,14 +25,21 @@ struct FigureResponse {
 struct ImageInfo {
     pub extension: String,
     pub hash: String,
+    /// Byte size of the image file, when the API reports one.
+    #[serde(default, rename = "fileSize", alias = "size", alias = "byteSize")]
+    pub file_size: Option<u64>,
 }
 
+/// Enough leading bytes for every signature in [`image_signature_matches`].
+const SIGNATURE_BYTES: u64 = 16;
+
 #[derive(Debug, Deserialize)]
 pub struct TableResponse {
     pub id: String,
     pub title: Option<Value>,
     #[serde(rename = "shortTitle")]
     pub short_title: Option<Value>,
+    pub number: Option<Value>,
     pub footnotes: Option<Value>,
     #[serde(rename = "jsonContent")]
     pub json_content: Value,
@@ -35,27 +51,19 @@ pub async fn fetch_table(
     table_id: &str,
 ) -> Result<Option<TableResponse>> {
     let url = crate::endpoints::table_json(base_url, table_id);
-    let response = match client.get(&url).send().await {
-        Ok(resp) => resp,
-        Err(err) => {
-            warn!(
-                "Failed to reach API for table {}: {}. Retry later.",
-                table_id, err
-            );
-            return Ok(None);
-        }
-    };
+    super::rate_limit::acquire().await;
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach API for table {}", table_id))?;
     if response.status() == reqwest::StatusCode::NOT_FOUND {
         warn!("Table not found: {}", table_id);
         return Ok(None);
     }
-    let response = match response.error_for_status() {
-        Ok(resp) => resp,
-        Err(err) => {
-            warn!("Failed to fetch table {}: {}", table_id, err);
-            return Ok(None);
-        }
-    };
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("Failed to fetch table {}", table_id))?;
     let table = response
         .json::<TableResponse>()
         .await
@@ -70,19 +78,27 @@ pub async fn fetch_question_json(
     question_id: &str,
 ) -> Result<Value> {
     let url = crate::endpoints::question_json(base_url, question_id);
+    super::rate_limit::acquire().await;
     let response = client
         .get(&url)
         .send()
         .await
         .context("Failed to reach API; check network connectivity and retry")?;
-    if response.status() == reqwest::StatusCode::NOT_FOUND {
-        bail!("Question ID not found: {}", question_id);
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ExtractError::from_status(status, &url, response.headers()).into());
     }
-    let response = response.error_for_status()?;
-    response
-        .json::<Value>()
+    let text = response
+        .text()
         .await
-        .context("Failed to decode question JSON")
+        .context("Failed to read question JSON")?;
+    serde_json::from_str(&text).map_err(|source| {
+        ExtractError::Parse {
+            what: format!("question JSON for {}", question_id),
+            source,
+        }
+        .into()
+    })
 }
 
 pub async fn download_figure(
@@ -92,27 +108,19 @@ pub async fn download_figure(
     figure_id: &str,
 ) -> Result<Option<String>> {
     let url = crate::endpoints::figure_json(base_url, figure_id);
-    let response = match client.get(&url).send().await {
-        Ok(resp) => resp,
-        Err(err) => {
-            warn!(
-                "Failed to reach API for figure {}: {}. Retry later.",
-                figure_id, err
-            );
-            return Ok(None);
-        }
-    };
+    super::rate_limit::acquire().await;
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach API for figure {}", figure_id))?;
     if response.status() == reqwest::StatusCode::NOT_FOUND {
         warn!("Figure not found: {}", figure_id);
         return Ok(None);
     }
-    let response = match response.error_for_status() {
-        Ok(resp) => resp,
-        Err(err) => {
-            warn!("Failed to fetch figure {}: {}", figure_id, err);
-            return Ok(None);
-        }
-    };
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("Failed to fetch figure {}", figure_id))?;
     let figure = response
         .json::<FigureResponse>()
         .await
@@ -122,25 +130,184 @@ pub async fn download_figure(
         "{}.{}.{}",
         figure.id, figure.image_info.hash, figure.image_info.extension
     );
-    let download_url = format!(
-        "https://d2chybfyz5ban.cloudfront.net/hashed_figures/{}",
-        filename
-    );
+    let download_url = figure_download_url(&filename);
 
     let dest_dir = question_dir.join("figures");
     std::fs::create_dir_all(&dest_dir)?;
     let dest_path = dest_dir.join(&filename);
+    let extension = &figure.image_info.extension;
+    if dest_path.exists() && verify_image_file(&dest_path, extension, None).is_err() {
+        warn!(
+            "Re-downloading {}: existing file is not a {} image",
+            dest_path.display(),
+            extension
+        );
+        std::fs::remove_file(&dest_path)?;
+    }
     if !dest_path.exists() {
-        let bytes = client
-            .get(&download_url)
-            .send()
-            .await?
-            .error_for_status()?
-            .bytes()
-            .await?;
-        std::fs::write(&dest_path, bytes)?;
+        super::rate_limit::acquire().await;
+        let response = crate::http::send_download(client, &download_url).await?;
+        let response = crate::http::ensure_not_redirect(response)?.error_for_status()?;
+        let (part, bytes) = crate::http::stream_to_part(response, &dest_path).await?;
+        let expected = figure
+            .image_info
+            .file_size
+            .map(|expected| (expected, bytes));
+        if let Err(err) = verify_image_file(&part, extension, expected) {
+            std::fs::remove_file(&part)?;
+            return Err(ExtractError::ContentMismatch {
+                url: download_url,
+                reason: format!("{:#}", err),
+            }
+            .into());
+        }
+        std::fs::rename(&part, &dest_path)?;
     }
 
     let relative = Path::new("figures").join(&filename);
     Ok(Some(relative.to_string_lossy().to_string()))
 }
+
+/// Check `path` starts with the signature for `extension` (JPEG, PNG and GIF; other
+/// extensions pass) and, given `(expected, written)` sizes, that they agree.
+fn verify_image_file(path: &Path, extension: &str, sizes: Option<(u64, u64)>) -> Result<()> {
+    let mut head = Vec::new();
+    std::fs::File::open(path)?
+        .take(SIGNATURE_BYTES)
+        .read_to_end(&mut head)?;
+    if image_signature_matches(extension, &head) == Some(false) {
+        bail!(
+            "expected a {} image but got {}",
+            extension,
+            describe_content(&head)
+        );
+    }
+    if let Some((expected, written)) = sizes {
+        if expected != written {
+            bail!(
+                "metadata size is {} bytes but {} were downloaded",
+                expected,
+                written
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether `head` carries the magic bytes for `extension`; `None` for unchecked extensions.
+fn image_signature_matches(extension: &str, head: &[u8]) -> Option<bool> {
+    match extension.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => Some(head.starts_with(&[0xFF, 0xD8, 0xFF])),
+        "png" => Some(head.starts_with(b"\x89PNG\r\n\x1a\n")),
+        "gif" => Some(head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a")),
+        _ => None,
+    }
+}
+
+fn describe_content(head: &[u8]) -> &'static str {
+    let text = String::from_utf8_lossy(head)
+        .trim_start()
+        .to_ascii_lowercase();
+    if text.is_empty() {
+        "an empty file"
+    } else if text.starts_with('<') {
+        "HTML/XML"
+    } else if text.starts_with('{') || text.starts_with('[') {
+        "JSON"
+    } else {
+        [("jpg", "a JPEG"), ("png", "a PNG"), ("gif", "a GIF")]
+            .into_iter()
+            .find(|(extension, _)| image_signature_matches(extension, head) == Some(true))
+            .map_or("unrecognized bytes", |(_, description)| description)
+    }
+}
+
+/// CloudFront URL for a hashed figure file named `{figure_id}.{hash}.{extension}`.
+pub fn figure_download_url(filename: &str) -> String {
+    format!("{}/{}", FIGURE_CLOUDFRONT_BASE, filename)
+}
+
+/// CloudFront URL for a hashed video file (`{VIDEO_CLOUDFRONT_BASE}/{video_id}.{hash}.mp4`).
+pub fn video_download_url(video_id: &str, mp4_hash: &str) -> String {
+    format!("{}/{}.{}.mp4", VIDEO_CLOUDFRONT_BASE, video_id, mp4_hash)
+}
+
+/// Download a video directly using the `mp4Hash` from content metadata, without a browser.
+///
+/// Returns `Ok(None)` when the metadata has no hash for this video, so the caller can fall
+/// back to a manual/browser download.
+pub async fn download_video_from_metadata(
+    client: &Client,
+    question_dir: &Path,
+    video_id: &str,
+    videos_by_id: &HashMap<String, VideoMetadata>,
+) -> Result<Option<String>> {
+    let Some(mp4_hash) = videos_by_id
+        .get(video_id)
+        .and_then(|metadata| metadata.mp4_hash.as_deref())
+    else {
+        return Ok(None);
+    };
+
+    let filename = format!("{}.{}.mp4", video_id, mp4_hash);
+    let dest_dir = question_dir.join("videos");
+    std::fs::create_dir_all(&dest_dir)?;
+    let dest_path = dest_dir.join(&filename);
+    if !dest_path.exists() {
+        super::rate_limit::acquire().await;
+        let response =
+            crate::http::send_download(client, &video_download_url(video_id, mp4_hash)).await?;
+        let response = crate::http::ensure_not_redirect(response)?.error_for_status()?;
+        crate::http::stream_to_file(response, &dest_path).await?;
+    }
+
+    let relative = Path::new("videos").join(&filename);
+    Ok(Some(relative.to_string_lossy().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_video_without_hash_is_left_for_fallback() {
+        let temp = tempfile::tempdir().unwrap();
+        let client = Client::new();
+        let downloaded =
+            download_video_from_metadata(&client, temp.path(), "vidcv001", &HashMap::new())
+                .await
+                .unwrap();
+        assert_eq!(downloaded, None);
+        assert!(!temp.path().join("videos").exists());
+    }
+
+    #[test]
+    fn test_verify_image_file_rejects_html_and_size_mismatch() {
+        let temp = tempfile::tempdir().unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = temp.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path
+        };
+        let jpeg = write("cvfig001.jpg", &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10]);
+        let login_page = write("cvfig002.jpg", b"\n<!DOCTYPE html><html>Sign in</html>");
+        let png = write("cvfig003.png", b"\x89PNG\r\n\x1a\n....");
+
+        assert!(verify_image_file(&jpeg, "JPG", Some((6, 6))).is_ok());
+        let err = verify_image_file(&login_page, "jpg", None).unwrap_err();
+        assert_eq!(err.to_string(), "expected a jpg image but got HTML/XML");
+        let err = verify_image_file(&png, "gif", None).unwrap_err();
+        assert_eq!(err.to_string(), "expected a gif image but got a PNG");
+        let err = verify_image_file(&png, "png", Some((2048, 12))).unwrap_err();
+        assert!(err.to_string().contains("2048 bytes"));
+        assert!(verify_image_file(&login_page, "webp", None).is_ok());
+    }
+
+    #[test]
+    fn test_video_download_url_uses_hash() {
+        assert_eq!(
+            video_download_url("vidcv001", "abc123"),
+            "https://d2chybfyz5ban.cloudfront.net/hashed_videos/vidcv001.abc123.mp4"
+        );
+    }
+}
@@ -4,20 +4,26 @@ pub use super::asset_types::{
     FigureReference, QuestionMedia, SvgReference, SvgSource, TableReference, VideoReference,
 };
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
 use super::asset_api::fetch_question_json;
+use super::asset_download::{has_expected_media, MediaKinds};
 use super::asset_metadata::for_each_figure_snapshot;
+use super::asset_store::{
+    collect_question_entries, collect_question_entry_map, QuestionTypeFilter,
+};
 use super::content_ids::{
     classify_content_id, count_inline_tables, extract_content_ids,
     extract_table_ids_from_tables_content, inline_table_id, ContentIdKind,
 };
-use crate::io::read_all_checkpoint_ids;
+use crate::cli::MediaOptions;
+use crate::io::{read_all_checkpoint_ids, write_atomic};
+use crate::progress::Progress;
 
 // ============================================================================
 // Discovery Configuration
@@ -81,7 +87,7 @@ impl DiscoveryResults {
 
     /// Save to JSON file
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
-        let json = serde_json::to_string_pretty(self)?;
+        let json = crate::utils::to_json_string(self)?;
         std::fs::write(path, json)?;
         Ok(())
     }
@@ -93,6 +99,57 @@ impl DiscoveryResults {
     }
 }
 
+// ============================================================================
+// Partial Discovery Checkpoint
+// ============================================================================
+
+/// Number of scanned questions between partial checkpoint writes.
+const PARTIAL_SAVE_INTERVAL: usize = 100;
+
+/// Incremental discovery state, written while scanning so an interrupted run can resume.
+/// Questions without media are kept in `scanned` so they are not fetched again.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PartialDiscovery {
+    scanned: BTreeSet<String>,
+    questions: HashMap<String, QuestionMedia>,
+}
+
+impl PartialDiscovery {
+    fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        write_atomic(path, serde_json::to_string(self)?)
+    }
+
+    fn record(&mut self, question_id: String, media: Option<QuestionMedia>) {
+        if let Some(media) = media {
+            self.questions.insert(question_id.clone(), media);
+        }
+        self.scanned.insert(question_id);
+    }
+
+    fn pending<'a>(&self, question_ids: &'a HashSet<String>) -> HashSet<&'a String> {
+        question_ids
+            .iter()
+            .filter(|id| !self.scanned.contains(*id))
+            .collect()
+    }
+}
+
+/// Output of a `--limit` sample run (`media_discovery.json` -> `media_discovery.sample.json`).
+pub fn sample_discovery_path(discovery_file: &Path) -> PathBuf {
+    discovery_file.with_extension("sample.json")
+}
+
+/// Partial checkpoint path for a discovery file
+/// (`media_discovery.json` -> `media_discovery.partial.json`).
+pub fn partial_discovery_path(discovery_file: &Path) -> PathBuf {
+    discovery_file.with_extension("partial.json")
+}
+
 // ============================================================================
 // API-Based Discovery
 // ============================================================================
@@ -102,74 +159,303 @@ use reqwest::Client;
 use std::collections::HashSet;
 use std::sync::Arc;
 
+/// Inputs for [`discover_media_questions`], built from the media CLI options.
+#[derive(Default)]
+pub struct DiscoveryOptions {
+    pub base_url: String,
+    pub concurrent_requests: usize,
+    /// Extractor checkpoints holding the question IDs to scan.
+    pub checkpoint_dir: PathBuf,
+    /// Data directory, used for question IDs without checkpoints and by `only_missing`.
+    pub data_dir: PathBuf,
+    /// Where the results are saved: the discovery file, or its `.sample.json` sibling for a
+    /// `limit` run.
+    pub output_file: PathBuf,
+    /// Incremental checkpoint written while scanning (`--resume` continues from it).
+    pub partial_path: PathBuf,
+    pub resume: bool,
+    /// Full discovery results; a narrowed scan keeps the entries it did not rescan.
+    pub discovery_file: PathBuf,
+    pub type_filter: Option<QuestionTypeFilter>,
+    /// Skip questions whose discovered media of `kinds` is already on disk (`--only-missing`).
+    pub only_missing: bool,
+    pub kinds: MediaKinds,
+    /// Question ID prefixes of the systems to skip (`--exclude-system`).
+    pub excluded_prefixes: Vec<String>,
+    /// Scan only the first IDs in sorted order for a quick sample (`--limit`).
+    pub limit: Option<usize>,
+}
+
+impl DiscoveryOptions {
+    pub fn from_media_options(options: &MediaOptions) -> Result<Self> {
+        let categories = crate::config::build_categories_from_config();
+        crate::config::ensure_known_codes(&categories, &options.excluded_systems)?;
+        let excluded_prefixes = categories
+            .into_iter()
+            .filter(|category| options.excluded_systems.contains(&category.code))
+            .map(|category| category.question_prefix)
+            .collect();
+        let discovery_file = PathBuf::from(&options.discovery_file);
+        // A sample never replaces the full results media-download takes its targets from
+        let output_file = match options.limit {
+            Some(_) => sample_discovery_path(&discovery_file),
+            None => discovery_file.clone(),
+        };
+        Ok(Self {
+            base_url: options.base_url.clone(),
+            concurrent_requests: options.concurrent_requests,
+            checkpoint_dir: PathBuf::from(&options.checkpoint_dir),
+            data_dir: PathBuf::from(&options.data_dir),
+            partial_path: partial_discovery_path(&output_file),
+            output_file,
+            resume: options.resume,
+            discovery_file,
+            type_filter: options.question_type_filter()?,
+            only_missing: options.only_missing,
+            kinds: MediaKinds::from_media_options(options),
+            excluded_prefixes,
+            limit: options.limit,
+        })
+    }
+
+    /// Whether this run scans only part of the corpus, so its results must be merged into
+    /// the previous ones instead of replacing them.
+    fn narrows_scan(&self) -> bool {
+        self.only_missing || !self.excluded_prefixes.is_empty() || self.type_filter.is_some()
+    }
+}
+
 /// Discover questions with media by scanning question JSON for media references:
-/// 1. Load all discovered question IDs from extractor checkpoints
+/// 1. Load all discovered question IDs from the extractor checkpoints
 /// 2. Fetch each question JSON and collect media references
 /// 3. Keep only questions that contain any media references
+///
+/// Question IDs come from the checkpoints, or from the question directories under the data
+/// directory when there are none. Progress is checkpointed to the partial path; with
+/// `resume`, questions already recorded there are skipped and their results merged into
+/// the final output. When the scan is narrowed (`only_missing`, excluded systems, a type
+/// filter) the previous results in the discovery file are kept for every question it does
+/// not rescan; a `limit` sample is never merged.
 pub async fn discover_media_questions(
     client: &Client,
-    base_url: &str,
-    concurrent_limit: usize,
+    options: &DiscoveryOptions,
 ) -> Result<DiscoveryResults> {
-    info!("Step 1: Loading all discovered question IDs from checkpoints...");
+    info!("Step 1: Loading all discovered question IDs...");
 
-    let all_question_ids = load_all_question_ids_from_checkpoints()?;
+    let mut all_question_ids =
+        load_all_question_ids_from_checkpoints(&options.checkpoint_dir, &options.data_dir)?;
     info!("Loaded {} total question IDs", all_question_ids.len());
+    if let Some(filter) = &options.type_filter {
+        let total = all_question_ids.len();
+        all_question_ids.retain(|question_id| filter.matches(question_id));
+        filter.report(all_question_ids.len(), total);
+    }
+    if !options.excluded_prefixes.is_empty() {
+        let total = all_question_ids.len();
+        all_question_ids.retain(|question_id| {
+            !options
+                .excluded_prefixes
+                .iter()
+                .any(|prefix| question_id.starts_with(prefix.as_str()))
+        });
+        info!(
+            "--exclude-system: skipping {} questions, {} left to scan",
+            total - all_question_ids.len(),
+            all_question_ids.len()
+        );
+    }
+    let previous = if options.narrows_scan() {
+        load_previous_discovery(&options.discovery_file)?
+    } else {
+        None
+    };
+    let mut skipped_complete = 0;
+    if options.only_missing {
+        let entries = collect_question_entry_map(&options.data_dir.to_string_lossy())?;
+        let expected = previous.as_ref().map(|previous| &previous.questions);
+        let total = all_question_ids.len();
+        all_question_ids.retain(|question_id| {
+            let (Some(entry), Some(expected)) = (
+                entries.get(question_id),
+                expected.and_then(|expected| expected.get(question_id)),
+            ) else {
+                return true;
+            };
+            !has_expected_media(expected, entry, options.kinds)
+        });
+        skipped_complete = total - all_question_ids.len();
+        info!(
+            "--only-missing: skipping {} questions with complete media, {} left to scan",
+            skipped_complete,
+            all_question_ids.len()
+        );
+    }
+    if let Some(limit) = options
+        .limit
+        .filter(|limit| *limit < all_question_ids.len())
+    {
+        warn!(
+            "--limit {}: scanning only the first {} of {} question IDs; this is a sample, \
+             not a full discovery",
+            limit,
+            limit,
+            all_question_ids.len()
+        );
+        all_question_ids = crate::utils::first_sorted_ids(all_question_ids, limit)
+            .into_iter()
+            .collect();
+    }
+
+    let partial_path = options.partial_path.as_path();
+    let mut partial = if options.resume && partial_path.exists() {
+        let partial = PartialDiscovery::load(partial_path)?;
+        info!(
+            "Resuming from {}: {} questions already scanned",
+            partial_path.display(),
+            partial.scanned.len()
+        );
+        partial
+    } else {
+        if partial_path.exists() {
+            info!(
+                "Ignoring existing {} (pass --resume to continue from it)",
+                partial_path.display()
+            );
+        }
+        PartialDiscovery::default()
+    };
 
     info!("Step 2: Loading content metadata for figure formats...");
-    let figures_by_id = Arc::new(load_figure_metadata(client, base_url).await?);
+    let figures_by_id = Arc::new(load_figure_metadata(client, &options.base_url).await?);
     info!("Loaded {} figure metadata entries", figures_by_id.len());
 
     info!("Step 3: Scanning questions for media references...");
 
-    let (questions_with_media, mut stats) = scan_questions_for_media(
+    let mut stats = scan_questions_for_media(
         client,
-        base_url,
+        &options.base_url,
         &all_question_ids,
-        concurrent_limit,
+        options.concurrent_requests,
         figures_by_id,
+        &mut partial,
+        partial_path,
     )
     .await?;
+    let mut questions_with_media = partial.questions;
 
     info!("Found {} questions with media", questions_with_media.len());
 
-    stats.finalize(all_question_ids.len(), questions_with_media.len());
+    let mut total_scanned = all_question_ids.len();
+    if let Some(previous) = previous.filter(|_| options.limit.is_none()) {
+        let mut kept = 0;
+        for (question_id, media) in previous.questions {
+            if !all_question_ids.contains(&question_id) {
+                stats.update_with_question(&question_id, &media);
+                questions_with_media.insert(question_id, media);
+                kept += 1;
+            }
+        }
+        info!(
+            "Kept {} questions from {} that this run did not rescan",
+            kept,
+            options.discovery_file.display()
+        );
+        total_scanned = previous
+            .metadata
+            .statistics
+            .total_questions_scanned
+            .max(total_scanned + kept);
+    }
+
+    stats.skipped_complete = skipped_complete;
+    stats.finalize(total_scanned, questions_with_media.len());
 
     Ok(DiscoveryResults::new(
         questions_with_media,
         stats,
-        base_url.to_string(),
-        concurrent_limit,
+        options.base_url.clone(),
+        options.concurrent_requests,
     ))
 }
 
-/// Load all question IDs from extractor checkpoint files
-fn load_all_question_ids_from_checkpoints() -> Result<HashSet<String>> {
-    let checkpoint_dir = Path::new("../mksap_data/.checkpoints");
-
-    if !checkpoint_dir.exists() {
-        anyhow::bail!(
-            "Checkpoint directory not found: {}. Run the extractor first to discover questions.",
-            checkpoint_dir.display()
+/// The discovery results a narrowed scan merges into, if an earlier run wrote them.
+fn load_previous_discovery(path: &Path) -> Result<Option<DiscoveryResults>> {
+    if !path.exists() {
+        info!(
+            "No previous discovery results in {}; every question is scanned",
+            path.display()
         );
+        return Ok(None);
     }
+    DiscoveryResults::load_from_file(path)
+        .with_context(|| format!("Failed to read discovery results from {}", path.display()))
+        .map(Some)
+}
 
-    read_all_checkpoint_ids(checkpoint_dir)
+/// Load all question IDs from extractor checkpoint files. Without any (e.g. a corpus copied
+/// from elsewhere), fall back to the question directories already in `data_dir`.
+pub(crate) fn load_all_question_ids_from_checkpoints(
+    checkpoint_dir: &Path,
+    data_dir: &Path,
+) -> Result<HashSet<String>> {
+    if checkpoint_dir.is_dir() {
+        let ids = read_all_checkpoint_ids(checkpoint_dir)?;
+        if !ids.is_empty() {
+            info!(
+                "Question IDs source: checkpoints in {}",
+                checkpoint_dir.display()
+            );
+            return Ok(ids);
+        }
+    }
+
+    if data_dir.is_dir() {
+        let ids: HashSet<String> = collect_question_entries(&data_dir.to_string_lossy())?
+            .into_iter()
+            .map(|entry| entry.question_id)
+            .collect();
+        if !ids.is_empty() {
+            info!(
+                "Question IDs source: no checkpoint files in {}; using {} question directories under {}",
+                checkpoint_dir.display(),
+                ids.len(),
+                data_dir.display()
+            );
+            return Ok(ids);
+        }
+    }
+
+    let resolved =
+        std::path::absolute(checkpoint_dir).unwrap_or_else(|_| checkpoint_dir.to_path_buf());
+    anyhow::bail!(
+        "No checkpoint files in {} and no question directories under {}. Run the extractor \
+         first to discover questions, or point --data-dir/--checkpoint-dir at an existing one.",
+        resolved.display(),
+        data_dir.display()
+    );
 }
 
-/// Scan questions via API to find which contain media references
+/// Scan questions via API to find which contain media references, recording
+/// results into `partial` and checkpointing it every `PARTIAL_SAVE_INTERVAL` questions
 async fn scan_questions_for_media(
     client: &Client,
     base_url: &str,
     question_ids: &HashSet<String>,
     concurrent_limit: usize,
     figures_by_id: Arc<HashMap<String, FigureReference>>,
-) -> Result<(HashMap<String, QuestionMedia>, DiscoveryStatistics)> {
-    let mut questions_with_media = HashMap::new();
+    partial: &mut PartialDiscovery,
+    partial_path: &Path,
+) -> Result<DiscoveryStatistics> {
     let mut stats = DiscoveryStatistics::default();
-    let mut processed = 0;
+    for (question_id, media) in &partial.questions {
+        stats.update_with_question(question_id, media);
+    }
+
+    let pending = partial.pending(question_ids);
     let total = question_ids.len();
-    let mut stream = stream::iter(question_ids.iter().cloned())
+    let progress = Progress::new(total, "questions checked", PARTIAL_SAVE_INTERVAL);
+    progress.set_position(total - pending.len());
+    let mut stream = stream::iter(pending.into_iter().cloned())
         .map(|question_id| {
             let client = client.clone();
             let base_url = base_url.to_string();
@@ -184,25 +470,33 @@ async fn scan_questions_for_media(
 
     while let Some((question_id, result)) = stream.next().await {
         match result {
-            Ok(Some(media)) => {
-                stats.update_with_question(&question_id, &media);
-                questions_with_media.insert(question_id, media);
+            Ok(media) => {
+                if let Some(media) = &media {
+                    stats.update_with_question(&question_id, media);
+                }
+                partial.record(question_id, media);
             }
-            Ok(None) => {}
             Err(e) => {
                 warn!("Failed to check {}: {}", question_id, e);
+                crate::events::emit(
+                    "media_discovery_failed",
+                    Some(&question_id),
+                    format!("{:#}", e),
+                );
                 stats.failed_requests += 1;
             }
         }
 
-        processed += 1;
-        if processed % 100 == 0 {
-            info!("Progress: {}/{} questions checked", processed, total);
+        progress.inc();
+        if progress.position().is_multiple_of(PARTIAL_SAVE_INTERVAL) {
+            partial.save(partial_path)?;
         }
     }
 
+    progress.finish();
+    partial.save(partial_path)?;
     info!("Completed checking all {} questions", total);
-    Ok((questions_with_media, stats))
+    Ok(stats)
 }
 
 /// Fetch a specific question and collect media references
@@ -225,7 +519,7 @@ fn extract_system_code(question_id: &str) -> &str {
     }
 }
 
-fn build_question_media(
+pub(super) fn build_question_media(
     question_id: &str,
     json: &Value,
     figures_by_id: &HashMap<String, FigureReference>,
@@ -243,47 +537,39 @@ fn build_question_media(
 
     for content_id in content_ids {
         match classify_content_id(&content_id) {
-            Some(ContentIdKind::Figure) => {
-                if seen_figures.insert(content_id.clone()) {
-                    if let Some(reference) = figures_by_id.get(&content_id) {
-                        figures.push(reference.clone());
-                    } else {
-                        figures.push(FigureReference {
-                            figure_id: content_id,
-                            extension: "unknown".to_string(),
-                            title: None,
-                            width: 0,
-                            height: 0,
-                        });
-                    }
-                }
-            }
-            Some(ContentIdKind::Table) => {
-                if seen_tables.insert(content_id.clone()) {
-                    tables.push(TableReference {
-                        table_id: content_id,
+            Some(ContentIdKind::Figure) if seen_figures.insert(content_id.clone()) => {
+                if let Some(reference) = figures_by_id.get(&content_id) {
+                    figures.push(reference.clone());
+                } else {
+                    figures.push(FigureReference {
+                        figure_id: content_id,
+                        extension: "unknown".to_string(),
                         title: None,
+                        width: 0,
+                        height: 0,
                     });
                 }
             }
-            Some(ContentIdKind::Video) => {
-                if seen_videos.insert(content_id.clone()) {
-                    videos.push(VideoReference {
-                        video_id: content_id.clone(),
-                        title: None,
-                        canonical_location: question_id.to_string(),
-                    });
-                }
+            Some(ContentIdKind::Table) if seen_tables.insert(content_id.clone()) => {
+                tables.push(TableReference {
+                    table_id: content_id,
+                    title: None,
+                });
             }
-            Some(ContentIdKind::Svg) => {
-                if seen_svgs.insert(content_id.clone()) {
-                    svgs.push(SvgReference {
-                        svg_id: content_id.clone(),
-                        source: SvgSource::ContentId(content_id),
-                    });
-                }
+            Some(ContentIdKind::Video) if seen_videos.insert(content_id.clone()) => {
+                videos.push(VideoReference {
+                    video_id: content_id.clone(),
+                    title: None,
+                    canonical_location: question_id.to_string(),
+                });
             }
-            None => {}
+            Some(ContentIdKind::Svg) if seen_svgs.insert(content_id.clone()) => {
+                svgs.push(SvgReference {
+                    svg_id: content_id.clone(),
+                    source: SvgSource::ContentId(content_id),
+                });
+            }
+            _ => {}
         }
     }
 
@@ -322,11 +608,11 @@ fn build_question_media(
     })
 }
 
-async fn load_figure_metadata(
+pub(super) async fn load_figure_metadata(
     client: &Client,
     base_url: &str,
 ) -> Result<HashMap<String, FigureReference>> {
-    let metadata = super::fetch_content_metadata(client, base_url).await?;
+    let metadata = super::fetch_content_metadata_cached(client, base_url).await?;
     let mut figures_by_id = HashMap::new();
 
     for_each_figure_snapshot(&metadata, |_, snapshot| {
@@ -351,3 +637,103 @@ async fn load_figure_metadata(
 
     Ok(figures_by_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_discovery_round_trip_skips_scanned_questions() {
+        let temp = tempfile::tempdir().unwrap();
+        let discovery_file = temp.path().join("media_discovery.json");
+        let partial_path = partial_discovery_path(&discovery_file);
+        assert_eq!(
+            partial_path.file_name().unwrap(),
+            "media_discovery.partial.json"
+        );
+
+        let mut partial = PartialDiscovery::default();
+        partial.record(
+            "cvmcq24001".to_string(),
+            Some(QuestionMedia {
+                subspecialty: Some("cv".to_string()),
+                figures: Vec::new(),
+                tables: Vec::new(),
+                videos: Vec::new(),
+                svgs: Vec::new(),
+            }),
+        );
+        partial.record("cvmcq24002".to_string(), None);
+        partial.save(&partial_path).unwrap();
+
+        let loaded = PartialDiscovery::load(&partial_path).unwrap();
+        assert_eq!(loaded.questions.len(), 1);
+        assert!(loaded.scanned.contains("cvmcq24002"));
+
+        let all_ids: HashSet<String> = ["cvmcq24001", "cvmcq24002", "cvmcq24003"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let pending = loaded.pending(&all_ids);
+        assert_eq!(pending.len(), 1);
+        assert!(pending.contains(&"cvmcq24003".to_string()));
+    }
+
+    #[test]
+    fn test_sample_runs_write_beside_the_discovery_file() {
+        let discovery_file = Path::new("mksap_data/media_discovery.json");
+        let sample_path = sample_discovery_path(discovery_file);
+        assert_eq!(
+            sample_path,
+            Path::new("mksap_data/media_discovery.sample.json")
+        );
+        assert_eq!(
+            partial_discovery_path(&sample_path).file_name().unwrap(),
+            "media_discovery.sample.partial.json"
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_ids_load_from_given_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let checkpoint_dir = temp.path().join("data").join(".checkpoints");
+        std::fs::create_dir_all(&checkpoint_dir).unwrap();
+        std::fs::write(
+            checkpoint_dir.join("cv_ids.txt"),
+            "cvmcq24001
+cvmcq24002
+",
+        )
+        .unwrap();
+
+        let data_dir = temp.path().join("data");
+        let ids = load_all_question_ids_from_checkpoints(&checkpoint_dir, &data_dir).unwrap();
+        assert_eq!(ids.len(), 2);
+
+        let missing = temp.path().join("elsewhere").join(".checkpoints");
+        let missing_data = temp.path().join("elsewhere");
+        let err = load_all_question_ids_from_checkpoints(&missing, &missing_data)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(&missing.display().to_string()), "{}", err);
+    }
+
+    #[test]
+    fn test_question_ids_fall_back_to_data_dir_without_checkpoints() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("data");
+        for id in ["cvmcq24001", "cvmcq24002"] {
+            let question_dir = data_dir.join("cv").join(id);
+            std::fs::create_dir_all(&question_dir).unwrap();
+            std::fs::write(question_dir.join(format!("{}.json", id)), "{}").unwrap();
+        }
+        std::fs::create_dir_all(data_dir.join("en").join("enmcq24001")).unwrap();
+        let checkpoint_dir = data_dir.join(".checkpoints");
+        std::fs::create_dir_all(&checkpoint_dir).unwrap();
+
+        let ids = load_all_question_ids_from_checkpoints(&checkpoint_dir, &data_dir).unwrap();
+        let mut ids: Vec<_> = ids.into_iter().collect();
+        ids.sort();
+        assert_eq!(ids, vec!["cvmcq24001", "cvmcq24002"]);
+    }
+}
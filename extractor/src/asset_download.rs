@@ -1,59 +1,153 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use reqwest::Client;
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
-use super::asset_api::{download_figure, fetch_question_json, fetch_table, TableResponse};
-use super::asset_metadata::{extract_html_text, for_each_figure_snapshot};
+use super::asset_api::{
+    download_figure, download_video_from_metadata, fetch_question_json, fetch_table, TableResponse,
+};
+use super::asset_api::{figure_download_url, video_download_url};
+use super::asset_discovery::{build_question_media, DiscoveryResults, QuestionMedia};
+use super::asset_metadata::{
+    extract_display_number, extract_html_text, for_each_figure_snapshot, for_each_metadata_item,
+    for_each_video_snapshot, number_from_label, resolve_metadata_id,
+};
+use super::asset_stats::{render_figure_extensions, tally_figure_extensions};
 use super::asset_store::{
-    collect_question_entry_map, load_discovery_results, select_targets, update_question_json,
-    FigureMetadata, MediaUpdate, QuestionEntry, TableMetadata,
+    collect_question_entry_map, select_targets, update_question_json, FigureMetadata, MediaUpdate,
+    QuestionEntry, QuestionTypeFilter, TableMetadata, VideoMetadata,
 };
 use super::content_ids::{
     classify_content_id, collect_inline_table_nodes, extract_content_ids,
     extract_table_ids_from_tables_content, inline_table_id, ContentIdKind,
 };
-use super::table_render::{pretty_format_html, render_node, render_table_html};
+use super::media_failures::{
+    load_media_failures, media_failures_path, record_media_failures, write_media_failures,
+    MediaFailure, MediaFailureKind,
+};
+use super::media_plan::{MediaPlan, PlannedMedia};
+use super::media_store::MediaStore;
+use super::table_render::{pretty_format_html, render_captioned_table_html, render_node};
+use crate::cli::MediaOptions;
+use crate::error::{find_extract_error, ExtractError};
 
-pub async fn run_media_download(
-    client: &Client,
-    base_url: &str,
-    data_dir: &str,
-    discovery_file: &str,
-    question_id: Option<&str>,
-    download_figures: bool,
-    download_tables: bool,
-) -> Result<()> {
-    let discovered_ids = if question_id.is_none() {
-        let discovery_path = Path::new(discovery_file);
-        let discovered_ids = load_discovery_results(discovery_path)?;
-        if discovered_ids.is_empty() {
-            warn!(
-                "No questions found in discovery file: {}",
-                discovery_path.display()
-            );
+/// Which asset kinds a media download run fetches.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MediaKinds {
+    pub figures: bool,
+    pub tables: bool,
+    pub videos: bool,
+}
+
+impl MediaKinds {
+    pub fn from_media_options(options: &MediaOptions) -> Self {
+        Self {
+            figures: !options.skip_figures,
+            tables: !options.skip_tables,
+            videos: options.download_videos,
         }
-        discovered_ids
+    }
+
+    fn any(self) -> bool {
+        self.figures || self.tables || self.videos
+    }
+}
+
+/// Inputs for [`run_media_download`] and [`plan_media_download`], built from the media CLI
+/// options.
+#[derive(Default)]
+pub struct MediaDownloadOptions {
+    pub base_url: String,
+    pub data_dir: String,
+    pub discovery_file: String,
+    /// Download only this question instead of every discovered one (`--question-id`).
+    pub question_id: Option<String>,
+    pub type_filter: Option<QuestionTypeFilter>,
+    pub kinds: MediaKinds,
+    /// Keep one copy of identical figures in `.media_store/` (`--dedupe-media`).
+    pub dedupe_media: bool,
+    /// Fail when any discovery-expected media file is missing afterwards (`--strict-media`).
+    pub strict_media: bool,
+    /// Re-download questions whose media is already on disk (`--refresh`).
+    pub refresh: bool,
+}
+
+impl MediaDownloadOptions {
+    pub fn from_media_options(options: &MediaOptions) -> Result<Self> {
+        Ok(Self {
+            base_url: options.base_url.clone(),
+            data_dir: options.data_dir.clone(),
+            discovery_file: options.discovery_file.clone(),
+            question_id: options.question_id.clone(),
+            type_filter: options.question_type_filter()?,
+            kinds: MediaKinds::from_media_options(options),
+            dedupe_media: options.dedupe_media,
+            strict_media: options.strict_media,
+            refresh: options.refresh,
+        })
+    }
+}
+
+/// What every question's media fetch shares within one download run.
+#[derive(Clone, Copy)]
+struct MediaFetch<'a> {
+    client: &'a Client,
+    base_url: &'a str,
+    figure_metadata_by_id: &'a HashMap<String, FigureMetadata>,
+    video_metadata_by_id: &'a HashMap<String, VideoMetadata>,
+    kinds: MediaKinds,
+}
+
+pub async fn run_media_download(client: &Client, options: &MediaDownloadOptions) -> Result<()> {
+    let MediaDownloadOptions {
+        kinds,
+        dedupe_media,
+        strict_media,
+        refresh,
+        ..
+    } = *options;
+    let base_url = options.base_url.as_str();
+    let data_dir = options.data_dir.as_str();
+    let discovery_file = options.discovery_file.as_str();
+    let question_id = options.question_id.as_deref();
+    let mut media_store = dedupe_media.then(|| MediaStore::new(Path::new(data_dir)));
+    let discovery = load_download_discovery(Path::new(discovery_file), question_id)?;
+    let discovered_ids = discovered_question_ids(discovery.as_ref(), question_id);
+
+    let content_metadata = if kinds.figures || kinds.videos {
+        super::fetch_content_metadata_cached(client, base_url).await?
     } else {
-        HashSet::new()
+        Value::Null
     };
-
-    let figure_metadata_by_id = if download_figures {
-        load_figure_metadata(client, base_url).await?
+    let figure_metadata_by_id = if kinds.figures {
+        load_figure_metadata(&content_metadata)
     } else {
         HashMap::new()
     };
-
-    let entry_map = collect_question_entry_map(data_dir)?;
-    let targets = if let Some(question_id) = question_id {
-        vec![question_id.to_string()]
+    let video_metadata_by_id = if kinds.videos {
+        load_video_metadata(&content_metadata)
     } else {
-        select_targets(None, &discovered_ids, "discovery file")?
+        HashMap::new()
     };
+
+    let entry_map = collect_question_entry_map(data_dir)?;
+    let targets = download_targets(question_id, &discovered_ids, options.type_filter.as_ref())?;
     info!("Processing {} questions for media downloads", targets.len());
+    let fetch = MediaFetch {
+        client,
+        base_url,
+        figure_metadata_by_id: &figure_metadata_by_id,
+        video_metadata_by_id: &video_metadata_by_id,
+        kinds,
+    };
 
+    let mut failures = FailureSummary::default();
+    let mut failed_media = Vec::new();
+    let mut processed = HashSet::new();
+    let mut figure_extensions = BTreeMap::new();
+    let mut skipped_complete = 0usize;
     for (idx, qid) in targets.iter().enumerate() {
         if (idx % 25) == 0 && idx > 0 {
             info!("Progress: {}/{}", idx, targets.len());
@@ -61,102 +155,687 @@ pub async fn run_media_download(
 
         let Some(entry) = entry_map.get(qid) else {
             warn!("Question {} not found in data directory; skipping", qid);
+            crate::events::emit(
+                "media_not_in_data_dir",
+                Some(qid),
+                "question directory missing",
+            );
+            failures.record(FailureCause::NotInDataDir);
             continue;
         };
 
-        if let Err(err) = process_question_entry(
-            client,
-            base_url,
+        processed.insert(qid.as_str());
+        let expected = discovery
+            .as_ref()
+            .and_then(|discovery| discovery.questions.get(qid));
+        match process_question_entry(
+            &fetch,
             entry,
-            &figure_metadata_by_id,
-            download_figures,
-            download_tables,
+            expected,
+            media_store.as_mut(),
+            &mut failed_media,
+            refresh,
         )
         .await
         {
-            warn!("Media download failed for {}: {}", qid, err);
+            Ok(None) => skipped_complete += 1,
+            Ok(Some(update)) => tally_figure_extensions(
+                &mut figure_extensions,
+                update
+                    .metadata
+                    .figures
+                    .iter()
+                    .map(|figure| figure.extension.as_deref().unwrap_or_default()),
+            ),
+            Err(err) => {
+                warn!("Media download failed for {}: {}", qid, err);
+                let cause = FailureCause::classify(&err);
+                crate::events::emit(
+                    "media_download_failed",
+                    Some(qid),
+                    format!("{}: {:#}", cause.label(), err),
+                );
+                failures.record(cause);
+                failed_media.push(MediaFailure::new(
+                    qid,
+                    MediaFailureKind::Question,
+                    None,
+                    crate::endpoints::question_json(base_url, qid),
+                    &err,
+                ));
+            }
+        }
+    }
+
+    if let Some(store) = media_store.as_ref().filter(|store| store.stats().files > 0) {
+        info!("{}", store.summary());
+    }
+    if !figure_extensions.is_empty() {
+        info!(
+            "{}",
+            render_figure_extensions(&figure_extensions).trim_end()
+        );
+    }
+    info!(
+        "Media download summary: {}/{} questions processed without errors",
+        targets.len() - failures.total(),
+        targets.len()
+    );
+    if skipped_complete > 0 {
+        info!(
+            "Skipped (complete): {} questions already had every discovered item on disk (--refresh re-downloads them)",
+            skipped_complete
+        );
+    }
+    if failures.total() > 0 {
+        warn!("{}", failures.render());
+        for hint in failures.hints() {
+            warn!("  - {}", hint);
         }
     }
+    let skipped_too_large = failed_media
+        .iter()
+        .filter(|failure| failure.too_large)
+        .count();
+    if skipped_too_large > 0 {
+        warn!(
+            "Skipped (too large): {} media files over --max-file-size ({} bytes); raise it and run retry-media-failures to fetch them",
+            skipped_too_large,
+            crate::http::max_file_size()
+        );
+    }
+    let failures_path = media_failures_path(data_dir);
+    if !failed_media.is_empty() {
+        warn!(
+            "{} media fetches failed; logged to {} (re-run them with retry-media-failures)",
+            failed_media.len(),
+            failures_path.display()
+        );
+    }
+    record_media_failures(&failures_path, &processed, failed_media)?;
+
+    if strict_media {
+        let Some(discovery) = discovery.as_ref() else {
+            bail!(
+                "--strict-media needs discovery results in {}",
+                discovery_file
+            );
+        };
+        let gaps = find_media_gaps(discovery, &entry_map, &targets, kinds);
+        if !gaps.is_empty() {
+            bail!(
+                "Strict media check failed: {} expected media file(s) missing:\n  - {}",
+                gaps.len(),
+                gaps.join("\n  - ")
+            );
+        }
+        info!("Strict media check passed: all expected media present");
+    }
 
     Ok(())
 }
 
-async fn process_question_entry(
+/// Plan the same download as [`run_media_download`] without fetching media or writing
+/// anything: every discovery-expected item of the target questions with the URL it would be
+/// fetched from and whether it is already on disk. A `--question-id` without discovery
+/// results is planned from its question JSON.
+pub async fn plan_media_download(
+    client: &Client,
+    options: &MediaDownloadOptions,
+) -> Result<MediaPlan> {
+    let base_url = options.base_url.as_str();
+    let kinds = options.kinds;
+    let question_id = options.question_id.as_deref();
+    let discovery = load_download_discovery(Path::new(&options.discovery_file), question_id)?;
+    let discovered_ids = discovered_question_ids(discovery.as_ref(), question_id);
+    let targets = download_targets(question_id, &discovered_ids, options.type_filter.as_ref())?;
+
+    let content_metadata = if kinds.figures || kinds.videos {
+        super::fetch_content_metadata_read_cached(client, base_url).await?
+    } else {
+        Value::Null
+    };
+    let figure_urls = load_figure_download_urls(&content_metadata);
+    let video_metadata_by_id = load_video_metadata(&content_metadata);
+
+    let entry_map = collect_question_entry_map(&options.data_dir)?;
+    let mut plan = MediaPlan {
+        questions: targets.len(),
+        items: Vec::new(),
+    };
+    for qid in &targets {
+        let Some(entry) = entry_map.get(qid) else {
+            warn!("{} has no question directory; not planned", qid);
+            continue;
+        };
+        // Like the real run, a question missing from discovery is read from its question JSON
+        let expected = match discovery.as_ref().and_then(|d| d.questions.get(qid)) {
+            Some(expected) => Some(expected.clone()),
+            None => {
+                let question = fetch_question_json(client, base_url, qid).await?;
+                build_question_media(qid, &question, &HashMap::new())
+            }
+        };
+        let Some(expected) = expected else {
+            debug!("{} references no media; not planned", qid);
+            continue;
+        };
+        for (kind, media_id, on_disk) in expected_media_status(&expected, entry, kinds) {
+            let url = match kind {
+                "figure" => figure_urls
+                    .get(&media_id)
+                    .cloned()
+                    .unwrap_or_else(|| crate::endpoints::figure_json(base_url, &media_id)),
+                "table" => crate::endpoints::table_json(base_url, &media_id),
+                _ => match video_metadata_by_id
+                    .get(&media_id)
+                    .and_then(|metadata| metadata.mp4_hash.as_deref())
+                {
+                    Some(hash) => video_download_url(&media_id, hash),
+                    // Without an mp4Hash the real run skips the video too
+                    None => continue,
+                },
+            };
+            plan.items.push(PlannedMedia {
+                question_id: qid.clone(),
+                kind,
+                media_id,
+                url: Some(url),
+                on_disk,
+                bytes: None,
+            });
+        }
+    }
+    Ok(plan)
+}
+
+/// Discovery results for a media download. A single `question_id` can run without them when
+/// the discovery file does not exist; its media is then fetched as the question lists it.
+fn load_download_discovery(
+    discovery_path: &Path,
+    question_id: Option<&str>,
+) -> Result<Option<DiscoveryResults>> {
+    if question_id.is_some() && !discovery_path.exists() {
+        return Ok(None);
+    }
+    let discovery = DiscoveryResults::load_from_file(discovery_path).with_context(|| {
+        format!(
+            "Failed to read discovery results from {}",
+            discovery_path.display()
+        )
+    })?;
+    if discovery.questions.is_empty() {
+        warn!(
+            "No questions found in discovery file: {}",
+            discovery_path.display()
+        );
+    }
+    Ok(Some(discovery))
+}
+
+/// Every discovered question, unless a single `question_id` is targeted.
+fn discovered_question_ids(
+    discovery: Option<&DiscoveryResults>,
+    question_id: Option<&str>,
+) -> HashSet<String> {
+    match (discovery, question_id) {
+        (Some(discovery), None) => discovery.questions.keys().cloned().collect(),
+        _ => HashSet::new(),
+    }
+}
+
+/// Questions a media download processes: `question_id` alone, or every discovered question,
+/// narrowed by `type_filter`.
+fn download_targets(
+    question_id: Option<&str>,
+    discovered_ids: &HashSet<String>,
+    type_filter: Option<&QuestionTypeFilter>,
+) -> Result<Vec<String>> {
+    let mut targets = if let Some(question_id) = question_id {
+        vec![question_id.to_string()]
+    } else {
+        select_targets(None, discovered_ids, "discovery file")?
+    };
+    if let Some(filter) = type_filter {
+        let total = targets.len();
+        targets.retain(|question_id| filter.matches(question_id));
+        filter.report(targets.len(), total);
+    }
+    Ok(targets)
+}
+
+/// CloudFront URL of every figure whose content metadata carries an image hash.
+fn load_figure_download_urls(metadata: &Value) -> HashMap<String, String> {
+    let mut urls = HashMap::new();
+    for_each_metadata_item(metadata, "figures", |key, figure| {
+        let figure_id = resolve_metadata_id(figure, key);
+        let image_info = &figure["imageInfo"];
+        if let (Some(hash), Some(extension)) = (
+            image_info.get("hash").and_then(Value::as_str),
+            image_info.get("extension").and_then(Value::as_str),
+        ) {
+            let filename = format!("{}.{}.{}", figure_id, hash, extension);
+            urls.insert(figure_id.to_string(), figure_download_url(&filename));
+        }
+    });
+    urls
+}
+
+/// Why a question's media could not be processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum FailureCause {
+    NotInDataDir,
+    Auth,
+    Http(u16),
+    Redirect,
+    Network,
+    /// The file on the wire was not the expected image (often an HTML login page).
+    ContentMismatch,
+    Filesystem,
+    Other,
+}
+
+impl FailureCause {
+    /// Classify an error by walking its source chain for reqwest/IO errors.
+    pub(crate) fn classify(err: &anyhow::Error) -> Self {
+        if let Some(extract_err) = find_extract_error(err) {
+            match extract_err {
+                ExtractError::Auth(_) => return Self::Auth,
+                ExtractError::Redirect { .. } => return Self::Redirect,
+                ExtractError::ContentMismatch { .. } => return Self::ContentMismatch,
+                other => {
+                    if let Some(status) = other.status() {
+                        return Self::Http(status);
+                    }
+                }
+            }
+        }
+        for cause in err.chain() {
+            if let Some(http_err) = cause.downcast_ref::<reqwest::Error>() {
+                return match http_err.status() {
+                    Some(status) => Self::from_status(status),
+                    None => Self::Network,
+                };
+            }
+            if cause.downcast_ref::<std::io::Error>().is_some() {
+                return Self::Filesystem;
+            }
+        }
+        Self::Other
+    }
+
+    fn from_status(status: reqwest::StatusCode) -> Self {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => Self::Auth,
+            status => Self::Http(status.as_u16()),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Self::NotInDataDir => "skipped: not in data dir".to_string(),
+            Self::Auth => "failed: auth".to_string(),
+            Self::Http(status) => format!("failed: HTTP {}", status),
+            Self::Redirect => "failed: redirect".to_string(),
+            Self::Network => "failed: network".to_string(),
+            Self::ContentMismatch => "failed: content mismatch".to_string(),
+            Self::Filesystem => "failed: filesystem".to_string(),
+            Self::Other => "failed: other".to_string(),
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::NotInDataDir => Some("not in data dir: re-run extraction for those questions"),
+            Self::Auth => Some("auth: refresh the session cookie (MKSAP_SESSION)"),
+            Self::Http(404) => Some("HTTP 404: re-run media-discover to refresh discovery results"),
+            Self::Redirect => Some("redirect: usually an expired session redirecting to login"),
+            Self::Network => Some("network: retry later or raise --request-timeout"),
+            Self::ContentMismatch => Some(
+                "content mismatch: not the expected image (often a login page); refresh the session and run retry-media-failures",
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Failure counts per cause, rendered as "12 skipped: not in data dir; 3 failed: HTTP 404".
+#[derive(Debug, Default)]
+pub(crate) struct FailureSummary {
+    counts: BTreeMap<FailureCause, usize>,
+}
+
+impl FailureSummary {
+    pub(crate) fn record(&mut self, cause: FailureCause) {
+        *self.counts.entry(cause).or_default() += 1;
+    }
+
+    pub(crate) fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub(crate) fn render(&self) -> String {
+        self.counts
+            .iter()
+            .map(|(cause, count)| format!("{} {}", count, cause.label()))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    fn hints(&self) -> Vec<&'static str> {
+        self.counts.keys().filter_map(FailureCause::hint).collect()
+    }
+}
+
+/// Re-attempt every fetch in `media_failures.jsonl`, keeping only those that fail again.
+/// Question-level failures are re-run in full for `kinds`. Returns (recovered, remaining).
+pub async fn retry_media_failures(
     client: &Client,
     base_url: &str,
-    entry: &QuestionEntry,
-    figure_metadata_by_id: &HashMap<String, FigureMetadata>,
-    download_figures: bool,
-    download_tables: bool,
-) -> Result<()> {
-    if !download_figures && !download_tables {
-        return Ok(());
+    data_dir: &str,
+    kinds: MediaKinds,
+) -> Result<(usize, usize)> {
+    let log_path = media_failures_path(data_dir);
+    let logged = load_media_failures(&log_path)?;
+    if logged.is_empty() {
+        info!("No media failures logged in {}", log_path.display());
+        return Ok((0, 0));
     }
+    info!("Retrying {} failed media fetches", logged.len());
 
-    let question = fetch_question_json(client, base_url, &entry.question_id).await?;
-    let update = collect_media_updates(
+    let needs_metadata = logged.iter().any(|failure| {
+        failure.kind != MediaFailureKind::Table
+            && (failure.kind != MediaFailureKind::Question || kinds.figures || kinds.videos)
+    });
+    let content_metadata = if needs_metadata {
+        super::fetch_content_metadata_cached(client, base_url).await?
+    } else {
+        Value::Null
+    };
+    let figure_metadata_by_id = load_figure_metadata(&content_metadata);
+    let video_metadata_by_id = load_video_metadata(&content_metadata);
+
+    let entry_map = collect_question_entry_map(data_dir)?;
+    let fetch = MediaFetch {
+        client,
+        base_url,
+        figure_metadata_by_id: &figure_metadata_by_id,
+        video_metadata_by_id: &video_metadata_by_id,
+        kinds,
+    };
+    let mut recovered = 0;
+    let mut remaining = Vec::new();
+    for failure in logged {
+        let Some(entry) = entry_map.get(&failure.question_id) else {
+            warn!(
+                "Question {} not found in data directory; keeping its failure",
+                failure.question_id
+            );
+            remaining.push(failure);
+            continue;
+        };
+
+        let retried = retry_media_failure(&fetch, entry, &failure).await;
+        match retried {
+            Ok(failed_again) if failed_again.is_empty() => recovered += 1,
+            Ok(failed_again) => remaining.extend(failed_again),
+            Err(err) => {
+                warn!(
+                    "Retry failed for {} {}: {:#}",
+                    failure.question_id,
+                    failure.content_id.as_deref().unwrap_or("(question)"),
+                    err
+                );
+                remaining.push(failure.with_error(&err));
+            }
+        }
+    }
+
+    write_media_failures(&log_path, &remaining)?;
+    Ok((recovered, remaining.len()))
+}
+
+/// Retry one logged failure, returning whatever failed this time.
+async fn retry_media_failure(
+    fetch: &MediaFetch<'_>,
+    entry: &QuestionEntry,
+    failure: &MediaFailure,
+) -> Result<Vec<MediaFailure>> {
+    let MediaFetch {
         client,
         base_url,
-        &entry.question_dir,
-        &question,
         figure_metadata_by_id,
-        download_figures,
-        download_tables,
-    )
-    .await?;
+        video_metadata_by_id,
+        ..
+    } = *fetch;
+    let content_id = match (failure.kind, failure.content_id.as_deref()) {
+        (MediaFailureKind::Question, _) | (_, None) => {
+            let mut failed_again = Vec::new();
+            process_question_entry(fetch, entry, None, None, &mut failed_again, true).await?;
+            return Ok(failed_again);
+        }
+        (_, Some(content_id)) => content_id,
+    };
+
+    let mut update = MediaUpdate::default();
+    match failure.kind {
+        MediaFailureKind::Figure => {
+            let Some(path) =
+                download_figure(client, base_url, &entry.question_dir, content_id).await?
+            else {
+                bail!("Figure not found: {}", content_id);
+            };
+            update.images.push(path.clone());
+            update.metadata.figures.push(figure_metadata_for(
+                figure_metadata_by_id,
+                content_id,
+                Some(path),
+                None,
+                None,
+            ));
+        }
+        MediaFailureKind::Table => {
+            let Some((path, metadata)) = fetch_and_store_table(
+                client,
+                base_url,
+                &entry.question_dir,
+                content_id,
+                &mut HashMap::new(),
+            )
+            .await?
+            else {
+                bail!("Table not found: {}", content_id);
+            };
+            update.tables.push(path);
+            update.metadata.tables.push(metadata);
+        }
+        MediaFailureKind::Video => {
+            let Some(path) = download_video_from_metadata(
+                client,
+                &entry.question_dir,
+                content_id,
+                video_metadata_by_id,
+            )
+            .await?
+            else {
+                bail!("No mp4Hash for video {}", content_id);
+            };
+            update.videos.push(path.clone());
+            if let Some(metadata) = video_metadata_by_id.get(content_id) {
+                let mut metadata = metadata.clone();
+                metadata.file = Some(path);
+                update.metadata.videos.push(metadata);
+            }
+        }
+        MediaFailureKind::Question => unreachable!("question failures are re-run above"),
+    }
+
+    update_question_json(&entry.json_path, &update)?;
+    info!("Recovered {} for {}", content_id, entry.question_id);
+    Ok(Vec::new())
+}
+
+/// Fetch a question's media, updating its JSON. Individual figure/table/video failures are
+/// appended to `failed_media` rather than failing the whole question.
+///
+/// Unless `refresh` is set, a question whose `expected` discovery items of the selected
+/// kinds are all on disk is skipped without any request and `None` is returned. Without a
+/// discovery entry the question is always fetched.
+async fn process_question_entry(
+    fetch: &MediaFetch<'_>,
+    entry: &QuestionEntry,
+    expected: Option<&QuestionMedia>,
+    media_store: Option<&mut MediaStore>,
+    failed_media: &mut Vec<MediaFailure>,
+    refresh: bool,
+) -> Result<Option<MediaUpdate>> {
+    let kinds = fetch.kinds;
+    if !kinds.any() {
+        return Ok(Some(MediaUpdate::default()));
+    }
+    if !refresh && expected.is_some_and(|expected| has_expected_media(expected, entry, kinds)) {
+        debug!("Skipping {} (media complete)", entry.question_id);
+        return Ok(None);
+    }
+
+    let question = fetch_question_json(fetch.client, fetch.base_url, &entry.question_id).await?;
+    let update = collect_media_updates(fetch, entry, &question, media_store, failed_media).await?;
 
     if update.tables.is_empty()
         && update.images.is_empty()
         && update.videos.is_empty()
         && update.svgs.is_empty()
     {
-        return Ok(());
+        return Ok(Some(update));
     }
 
-    update_question_json(&entry.json_path, &update)
+    update_question_json(&entry.json_path, &update)?;
+    Ok(Some(update))
 }
 
 async fn collect_media_updates(
-    client: &Client,
-    base_url: &str,
-    question_dir: &Path,
+    fetch: &MediaFetch<'_>,
+    entry: &QuestionEntry,
     question: &Value,
-    figure_metadata_by_id: &HashMap<String, FigureMetadata>,
-    download_figures: bool,
-    download_tables: bool,
+    mut media_store: Option<&mut MediaStore>,
+    failed_media: &mut Vec<MediaFailure>,
 ) -> Result<MediaUpdate> {
+    let MediaFetch {
+        client,
+        base_url,
+        figure_metadata_by_id,
+        video_metadata_by_id,
+        kinds,
+    } = *fetch;
+    let question_dir = entry.question_dir.as_path();
+    let download_figures = kinds.figures;
+    let download_tables = kinds.tables;
     let mut update = MediaUpdate::default();
     let mut seen_tables = HashSet::new();
     let mut seen_images = HashSet::new();
+    let mut seen_videos = HashSet::new();
     let mut seen_figure_metadata = HashSet::new();
     let mut seen_table_metadata = HashSet::new();
     let mut table_html_index = HashMap::new();
 
+    let inline_captions = inline_figure_captions(question);
     let content_ids = extract_content_ids(question);
     for content_id in content_ids {
         match classify_content_id(&content_id) {
             Some(ContentIdKind::Figure) if download_figures => {
-                let path = download_figure(client, base_url, question_dir, &content_id).await?;
+                let path = match download_figure(client, base_url, question_dir, &content_id).await
+                {
+                    Ok(path) => path,
+                    Err(err) => {
+                        let url = crate::endpoints::figure_json(base_url, &content_id);
+                        failed_media.push(media_item_failed(
+                            entry,
+                            MediaFailureKind::Figure,
+                            &content_id,
+                            url,
+                            err,
+                        ));
+                        continue;
+                    }
+                };
+                let content_hash = match (media_store.as_deref_mut(), path.as_deref()) {
+                    (Some(store), Some(relative)) => Some(store.dedupe(question_dir, relative)?),
+                    _ => None,
+                };
                 push_unique(&mut update.images, &mut seen_images, path.clone());
                 if seen_figure_metadata.insert(content_id.clone()) {
-                    let mut metadata = figure_metadata_by_id
-                        .get(&content_id)
-                        .cloned()
-                        .unwrap_or_else(|| fallback_figure_metadata(&content_id));
-                    metadata.file = path;
-                    update.metadata.figures.push(metadata);
+                    update.metadata.figures.push(figure_metadata_for(
+                        figure_metadata_by_id,
+                        &content_id,
+                        path,
+                        content_hash,
+                        inline_captions.get(&content_id),
+                    ));
                 }
             }
             Some(ContentIdKind::Table) if download_tables => {
-                if let Some(table) = fetch_table(client, base_url, &content_id).await? {
-                    let html = render_table_html(&table.json_content);
-                    let filename = format!("{}.html", table.id);
-                    let path =
-                        store_table_html(question_dir, &filename, &html, &mut table_html_index)?;
-                    push_unique(&mut update.tables, &mut seen_tables, Some(path.clone()));
-                    if seen_table_metadata.insert(table.id.clone()) {
-                        let metadata = build_table_metadata(&table, Some(path));
-                        update.metadata.tables.push(metadata);
+                match fetch_and_store_table(
+                    client,
+                    base_url,
+                    question_dir,
+                    &content_id,
+                    &mut table_html_index,
+                )
+                .await
+                {
+                    Ok(Some((path, metadata))) => {
+                        push_unique(&mut update.tables, &mut seen_tables, Some(path));
+                        if seen_table_metadata.insert(metadata.table_id.clone()) {
+                            update.metadata.tables.push(metadata);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        let url = crate::endpoints::table_json(base_url, &content_id);
+                        failed_media.push(media_item_failed(
+                            entry,
+                            MediaFailureKind::Table,
+                            &content_id,
+                            url,
+                            err,
+                        ));
+                    }
+                }
+            }
+            Some(ContentIdKind::Video) if kinds.videos && seen_videos.insert(content_id.clone()) => {
+                match download_video_from_metadata(
+                    client,
+                    question_dir,
+                    &content_id,
+                    video_metadata_by_id,
+                )
+                .await
+                {
+                    Ok(Some(path)) => {
+                        update.videos.push(path.clone());
+                        if let Some(metadata) = video_metadata_by_id.get(&content_id) {
+                            let mut metadata = metadata.clone();
+                            metadata.file = Some(path);
+                            update.metadata.videos.push(metadata);
+                        }
+                    }
+                    Ok(None) => warn!(
+                        "No mp4Hash for video {}; download it manually from the VIDEO QUESTION IDS report",
+                        content_id
+                    ),
+                    Err(err) => {
+                        let url = video_metadata_by_id
+                            .get(&content_id)
+                            .and_then(|metadata| metadata.mp4_hash.as_deref())
+                            .map(|hash| video_download_url(&content_id, hash))
+                            .unwrap_or_default();
+                        failed_media.push(media_item_failed(
+                            entry,
+                            MediaFailureKind::Video,
+                            &content_id,
+                            url,
+                            err,
+                        ));
                     }
                 }
             }
@@ -166,14 +845,31 @@ async fn collect_media_updates(
 
     if download_tables {
         for table_id in extract_table_ids_from_tables_content(question) {
-            if let Some(table) = fetch_table(client, base_url, &table_id).await? {
-                let html = render_table_html(&table.json_content);
-                let filename = format!("{}.html", table.id);
-                let path = store_table_html(question_dir, &filename, &html, &mut table_html_index)?;
-                push_unique(&mut update.tables, &mut seen_tables, Some(path.clone()));
-                if seen_table_metadata.insert(table.id.clone()) {
-                    let metadata = build_table_metadata(&table, Some(path));
-                    update.metadata.tables.push(metadata);
+            match fetch_and_store_table(
+                client,
+                base_url,
+                question_dir,
+                &table_id,
+                &mut table_html_index,
+            )
+            .await
+            {
+                Ok(Some((path, metadata))) => {
+                    push_unique(&mut update.tables, &mut seen_tables, Some(path));
+                    if seen_table_metadata.insert(metadata.table_id.clone()) {
+                        update.metadata.tables.push(metadata);
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    let url = crate::endpoints::table_json(base_url, &table_id);
+                    failed_media.push(media_item_failed(
+                        entry,
+                        MediaFailureKind::Table,
+                        &table_id,
+                        url,
+                        err,
+                    ));
                 }
             }
         }
@@ -194,6 +890,7 @@ async fn collect_media_updates(
                     file: Some(relative.clone()),
                     title: None,
                     short_title: None,
+                    number: number_from_label(&html.html),
                     footnotes: Vec::new(),
                     headers: html.headers.clone(),
                 });
@@ -204,6 +901,113 @@ async fn collect_media_updates(
     Ok(update)
 }
 
+/// List discovery-expected figures, tables and (when enabled) videos that are not on disk,
+/// as `"<question_id>: <kind> <id>"` lines.
+fn find_media_gaps(
+    discovery: &DiscoveryResults,
+    entry_map: &HashMap<String, QuestionEntry>,
+    targets: &[String],
+    kinds: MediaKinds,
+) -> Vec<String> {
+    let mut gaps = Vec::new();
+    for qid in targets {
+        let Some(expected) = discovery.questions.get(qid) else {
+            continue;
+        };
+        let Some(entry) = entry_map.get(qid) else {
+            gaps.push(format!("{}: question directory missing", qid));
+            continue;
+        };
+        for (kind, media_id, on_disk) in expected_media_status(expected, entry, kinds) {
+            if !on_disk {
+                gaps.push(format!("{}: {} {}", qid, kind, media_id));
+            }
+        }
+    }
+    gaps
+}
+
+/// Each discovery-expected figure, table and video of `entry` as `(kind, id, on_disk)`.
+fn expected_media_status(
+    expected: &QuestionMedia,
+    entry: &QuestionEntry,
+    kinds: MediaKinds,
+) -> Vec<(&'static str, String, bool)> {
+    let mut status = Vec::new();
+    if kinds.figures {
+        for figure in &expected.figures {
+            let on_disk =
+                has_file_with_prefix(&entry.question_dir.join("figures"), &figure.figure_id);
+            status.push(("figure", figure.figure_id.clone(), on_disk));
+        }
+    }
+    if kinds.tables {
+        let recorded = recorded_table_files(entry);
+        for table in &expected.tables {
+            let stored = entry
+                .question_dir
+                .join("tables")
+                .join(format!("{}.html", table.table_id))
+                .exists();
+            let recorded = recorded
+                .get(&table.table_id)
+                .is_some_and(|file| entry.question_dir.join(file).exists());
+            status.push(("table", table.table_id.clone(), stored || recorded));
+        }
+    }
+    if kinds.videos {
+        for video in &expected.videos {
+            let on_disk = has_file_with_prefix(&entry.question_dir.join("videos"), &video.video_id);
+            status.push(("video", video.video_id.clone(), on_disk));
+        }
+    }
+    status
+}
+
+/// Whether every discovery-expected item of `kinds` is already on disk for `entry`.
+pub(super) fn has_expected_media(
+    expected: &QuestionMedia,
+    entry: &QuestionEntry,
+    kinds: MediaKinds,
+) -> bool {
+    expected_media_status(expected, entry, kinds)
+        .iter()
+        .all(|(_, _, on_disk)| *on_disk)
+}
+
+/// Whether `dir` holds a file named `<id>.<...>` (figures and videos carry a content hash).
+fn has_file_with_prefix(dir: &Path, id: &str) -> bool {
+    let prefix = format!("{}.", id);
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                entry.file_name().to_string_lossy().starts_with(&prefix) && entry.path().exists()
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Table files recorded in `media_metadata`; identical tables share one file on disk.
+fn recorded_table_files(entry: &QuestionEntry) -> HashMap<String, String> {
+    let Ok(text) = std::fs::read_to_string(&entry.json_path) else {
+        return HashMap::new();
+    };
+    let Ok(json) = serde_json::from_str::<Value>(&text) else {
+        return HashMap::new();
+    };
+    json.pointer("/media_metadata/tables")
+        .and_then(|tables| tables.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|table| {
+            Some((
+                table.get("table_id")?.as_str()?.to_string(),
+                table.get("file")?.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
 fn push_unique(target: &mut Vec<String>, seen: &mut HashSet<String>, value: Option<String>) {
     if let Some(value) = value {
         if seen.insert(value.clone()) {
@@ -237,6 +1041,59 @@ fn store_table_html(
     Ok(relative)
 }
 
+/// Warn about a failed figure/table/video fetch and build its failure log entry.
+fn media_item_failed(
+    entry: &QuestionEntry,
+    kind: MediaFailureKind,
+    content_id: &str,
+    url: String,
+    err: anyhow::Error,
+) -> MediaFailure {
+    let failure = MediaFailure::new(&entry.question_id, kind, Some(content_id), url, &err);
+    if failure.too_large {
+        warn!(
+            "Skipping {} for {}: {:#}",
+            content_id, entry.question_id, err
+        );
+    } else {
+        warn!(
+            "Failed to fetch {} for {}: {:#}",
+            content_id, entry.question_id, err
+        );
+    }
+    crate::events::emit(
+        "media_item_failed",
+        Some(&entry.question_id),
+        format!("{}: {:#}", content_id, err),
+    );
+    failure
+}
+
+/// Fetch a table, store its HTML under `tables/` and return the stored path with its
+/// metadata. `Ok(None)` when the API has no such table.
+async fn fetch_and_store_table(
+    client: &Client,
+    base_url: &str,
+    question_dir: &Path,
+    table_id: &str,
+    table_html_index: &mut HashMap<String, String>,
+) -> Result<Option<(String, TableMetadata)>> {
+    let Some(table) = fetch_table(client, base_url, table_id).await? else {
+        return Ok(None);
+    };
+    let html = render_fetched_table(&table);
+    let filename = format!("{}.html", table.id);
+    let path = store_table_html(question_dir, &filename, &html, table_html_index)?;
+    let metadata = build_table_metadata(&table, Some(path.clone()));
+    Ok(Some((path, metadata)))
+}
+
+/// Table HTML with its title as the caption.
+fn render_fetched_table(table: &TableResponse) -> String {
+    let title = extract_html_text(table.title.as_ref());
+    render_captioned_table_html(&table.json_content, title.as_deref())
+}
+
 fn extract_inline_tables(question: &Value) -> Vec<InlineTable> {
     collect_inline_table_nodes(question)
         .into_iter()
@@ -252,14 +1109,10 @@ struct InlineTable {
     headers: Vec<String>,
 }
 
-async fn load_figure_metadata(
-    client: &Client,
-    base_url: &str,
-) -> Result<HashMap<String, FigureMetadata>> {
-    let metadata = super::fetch_content_metadata(client, base_url).await?;
+fn load_figure_metadata(metadata: &Value) -> HashMap<String, FigureMetadata> {
     let mut figures_by_id = HashMap::new();
 
-    for_each_figure_snapshot(&metadata, |figure, snapshot| {
+    for_each_figure_snapshot(metadata, |figure, snapshot| {
         let extension = snapshot.image_info.extension;
         let footnotes = extract_footnotes(figure.get("footnotes"));
 
@@ -278,11 +1131,191 @@ async fn load_figure_metadata(
                 extension,
                 width,
                 height,
+                content_hash: None,
             },
         );
     });
 
-    Ok(figures_by_id)
+    figures_by_id
+}
+
+fn load_video_metadata(metadata: &Value) -> HashMap<String, VideoMetadata> {
+    let mut videos_by_id = HashMap::new();
+    for_each_video_snapshot(metadata, |snapshot| {
+        videos_by_id.insert(
+            snapshot.video_id.clone(),
+            VideoMetadata {
+                video_id: snapshot.video_id,
+                file: None,
+                title: snapshot.title,
+                short_title: snapshot.short_title,
+                width: snapshot.width,
+                height: snapshot.height,
+                caption: snapshot.caption,
+                mp4_hash: snapshot.mp4_hash,
+            },
+        );
+    });
+    videos_by_id
+}
+
+/// Content-metadata details for a downloaded figure, or a bare fallback entry. A caption
+/// found in the question fills in the title, number and footnotes the metadata lacks.
+fn figure_metadata_for(
+    figure_metadata_by_id: &HashMap<String, FigureMetadata>,
+    figure_id: &str,
+    file: Option<String>,
+    content_hash: Option<String>,
+    inline_caption: Option<&InlineCaption>,
+) -> FigureMetadata {
+    let mut metadata = figure_metadata_by_id
+        .get(figure_id)
+        .cloned()
+        .unwrap_or_else(|| fallback_figure_metadata(figure_id));
+    metadata.file = file;
+    metadata.content_hash = content_hash;
+    if let Some(caption) = inline_caption {
+        if metadata.title.is_none() {
+            metadata.title = caption.title.clone();
+        }
+        if metadata.number.is_none() {
+            metadata.number = metadata.title.as_deref().and_then(number_from_label);
+        }
+        if metadata.footnotes.is_empty() {
+            metadata.footnotes = caption.footnotes.clone();
+        }
+    }
+    metadata
+}
+
+/// Caption text shown next to a figure in the question body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct InlineCaption {
+    title: Option<String>,
+    footnotes: Vec<String>,
+}
+
+/// Captions in the question JSON by figure ID: a `figcaption`/`caption` node that is a child
+/// or the next sibling of the node referencing the figure. The first caption found wins.
+fn inline_figure_captions(question: &Value) -> HashMap<String, InlineCaption> {
+    let mut captions = HashMap::new();
+    collect_inline_captions(question, &mut captions);
+    captions
+}
+
+fn collect_inline_captions(value: &Value, captions: &mut HashMap<String, InlineCaption>) {
+    match value {
+        Value::Object(map) => {
+            let caption = match map.get("children") {
+                Some(Value::Array(children)) => {
+                    children.iter().find(|child| is_caption_node(child))
+                }
+                _ => None,
+            };
+            if let Some(caption) = caption {
+                add_inline_caption(value, caption, captions);
+            }
+            for child in map.values() {
+                collect_inline_captions(child, captions);
+            }
+        }
+        Value::Array(items) => {
+            for pair in items.windows(2) {
+                if is_caption_node(&pair[1]) {
+                    add_inline_caption(&pair[0], &pair[1], captions);
+                }
+            }
+            for item in items {
+                collect_inline_captions(item, captions);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn add_inline_caption(
+    node: &Value,
+    caption: &Value,
+    captions: &mut HashMap<String, InlineCaption>,
+) {
+    let figure_ids = referenced_figure_ids(node);
+    if figure_ids.is_empty() {
+        return;
+    }
+    let parsed = parse_inline_caption(caption);
+    if parsed.title.is_none() && parsed.footnotes.is_empty() {
+        return;
+    }
+    for figure_id in figure_ids {
+        captions.entry(figure_id).or_insert_with(|| parsed.clone());
+    }
+}
+
+/// Figure IDs a node points at through `contentIds`, `figureId` or its own `id`.
+fn referenced_figure_ids(node: &Value) -> Vec<String> {
+    let mut ids: Vec<String> = node
+        .get("contentIds")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .chain(
+            ["figureId", "id"]
+                .iter()
+                .filter_map(|key| node.get(key)?.as_str()),
+        )
+        .filter(|id| classify_content_id(id) == Some(ContentIdKind::Figure))
+        .map(str::to_string)
+        .collect();
+    ids.dedup();
+    ids
+}
+
+fn node_tag(value: &Value) -> Option<&str> {
+    value
+        .get("tagName")
+        .or_else(|| value.get("type"))
+        .and_then(Value::as_str)
+}
+
+fn is_caption_node(value: &Value) -> bool {
+    node_tag(value).is_some_and(|tag| {
+        tag.eq_ignore_ascii_case("figcaption") || tag.eq_ignore_ascii_case("caption")
+    })
+}
+
+fn is_footnote_node(value: &Value) -> bool {
+    node_tag(value).is_some_and(|tag| tag.eq_ignore_ascii_case("footnote"))
+        || value
+            .get("className")
+            .and_then(Value::as_str)
+            .is_some_and(|class| class.contains("footnote"))
+}
+
+/// Caption text becomes the title; nested footnote nodes are kept as rendered HTML.
+fn parse_inline_caption(caption: &Value) -> InlineCaption {
+    let mut title = String::new();
+    let mut footnotes = Vec::new();
+    let children = match caption.get("children") {
+        Some(Value::Array(children)) => children.as_slice(),
+        Some(child) => std::slice::from_ref(child),
+        None => &[],
+    };
+    for child in children {
+        if is_footnote_node(child) {
+            let rendered = render_value_as_html(child.get("children").unwrap_or(child));
+            if !rendered.trim().is_empty() {
+                footnotes.push(rendered.trim().to_string());
+            }
+        } else {
+            title.push_str(&extract_text(child));
+        }
+    }
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    InlineCaption {
+        title: (!title.is_empty()).then_some(title),
+        footnotes,
+    }
 }
 
 fn fallback_figure_metadata(figure_id: &str) -> FigureMetadata {
@@ -296,14 +1329,17 @@ fn fallback_figure_metadata(figure_id: &str) -> FigureMetadata {
         extension: None,
         width: None,
         height: None,
+        content_hash: None,
     }
 }
 
 fn build_table_metadata(table: &TableResponse, file: Option<String>) -> TableMetadata {
+    let title = extract_html_text(table.title.as_ref());
     TableMetadata {
         table_id: table.id.clone(),
         file,
-        title: extract_html_text(table.title.as_ref()),
+        number: extract_display_number(table.number.as_ref(), title.as_deref()),
+        title,
         short_title: extract_html_text(table.short_title.as_ref()),
         footnotes: extract_footnotes(table.footnotes.as_ref()),
         headers: extract_table_headers(&table.json_content),
@@ -383,3 +1419,177 @@ fn render_value_as_html(value: &Value) -> String {
         _ => String::new(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `/status/<code>` with that HTTP status.
+    async fn spawn_status_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let read = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..read]);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/");
+                    let status = path.trim_start_matches("/status/");
+                    let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n", status);
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    async fn status_error(base_url: &str, status: u16) -> anyhow::Error {
+        let response = reqwest::get(format!("{}/status/{}", base_url, status))
+            .await
+            .unwrap();
+        anyhow::Error::from(response.error_for_status().unwrap_err())
+    }
+
+    fn discovery_with(question_id: &str) -> DiscoveryResults {
+        let media: super::super::asset_discovery::QuestionMedia = serde_json::from_value(serde_json::json!({
+            "subspecialty": null,
+            "figures": [{ "figure_id": "cvfig001", "extension": "png", "title": null, "width": 1, "height": 1 }],
+            "tables": [{ "table_id": "cvtab001", "title": null }]
+        }))
+        .unwrap();
+        DiscoveryResults::new(
+            HashMap::from([(question_id.to_string(), media)]),
+            Default::default(),
+            String::new(),
+            1,
+        )
+    }
+
+    #[test]
+    fn test_strict_media_gaps_report_missing_figure_until_complete() {
+        let temp = tempfile::tempdir().unwrap();
+        let question_dir = temp.path().join("cv").join("cvmcq24001");
+        std::fs::create_dir_all(question_dir.join("tables")).unwrap();
+        std::fs::write(question_dir.join("cvmcq24001.json"), "{}").unwrap();
+        std::fs::write(question_dir.join("tables/cvtab001.html"), "<table/>").unwrap();
+
+        let discovery = discovery_with("cvmcq24001");
+        let entry_map = collect_question_entry_map(temp.path().to_str().unwrap()).unwrap();
+        let targets = vec!["cvmcq24001".to_string()];
+        let kinds = MediaKinds {
+            figures: true,
+            tables: true,
+            videos: false,
+        };
+
+        let gaps = find_media_gaps(&discovery, &entry_map, &targets, kinds);
+        assert_eq!(gaps, vec!["cvmcq24001: figure cvfig001"]);
+
+        std::fs::create_dir_all(question_dir.join("figures")).unwrap();
+        std::fs::write(question_dir.join("figures/cvfig001.abc123.png"), b"png").unwrap();
+        assert!(find_media_gaps(&discovery, &entry_map, &targets, kinds).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failure_summary_classifies_mixed_causes() {
+        let base_url = spawn_status_server().await;
+        let mut summary = FailureSummary::default();
+
+        for _ in 0..2 {
+            summary.record(FailureCause::NotInDataDir);
+        }
+        for _ in 0..3 {
+            summary.record(FailureCause::classify(&status_error(&base_url, 404).await));
+        }
+        summary.record(FailureCause::classify(&status_error(&base_url, 401).await));
+        summary.record(FailureCause::classify(&status_error(&base_url, 403).await));
+        summary.record(FailureCause::classify(
+            &anyhow::Error::from(std::io::Error::other("disk full")).context("write failed"),
+        ));
+        summary.record(FailureCause::classify(
+            &anyhow::Error::from(ExtractError::Redirect {
+                url: format!("{}/api/figures/cvfig001.json", base_url),
+                status: 302,
+                location: "/users/sign_in".to_string(),
+            })
+            .context("Failed to fetch cvfig001"),
+        ));
+        summary.record(FailureCause::classify(&anyhow::Error::from(
+            ExtractError::ContentMismatch {
+                url: format!("{}/cvfig001.png", base_url),
+                reason: "not a png image".to_string(),
+            },
+        )));
+
+        assert_eq!(summary.total(), 10);
+        assert_eq!(
+            summary.render(),
+            "2 skipped: not in data dir; 2 failed: auth; 3 failed: HTTP 404; 1 failed: redirect; 1 failed: content mismatch; 1 failed: filesystem"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused_is_network_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let err = reqwest::get(format!("http://{}/", addr)).await.unwrap_err();
+        let err = anyhow::Error::from(err).context("Failed to reach API");
+        assert_eq!(FailureCause::classify(&err), FailureCause::Network);
+    }
+
+    #[test]
+    fn test_inline_caption_fills_missing_figure_metadata() {
+        let question = serde_json::json!({
+            "stimulus": [
+                { "type": "p", "children": ["See the radiograph."], "contentIds": ["cvfig001"] },
+                {
+                    "type": "figcaption",
+                    "children": [
+                        "Figure 2. Chest   radiograph",
+                        { "type": "footnote", "children": ["Courtesy of J. Smith."] }
+                    ]
+                }
+            ],
+            "exposition": [{
+                "type": "figure",
+                "contentIds": ["cvfig002"],
+                "children": [{ "tagName": "figcaption", "children": ["Inline ECG"] }]
+            }]
+        });
+        let captions = inline_figure_captions(&question);
+        assert_eq!(captions.len(), 2);
+
+        let metadata = figure_metadata_for(
+            &HashMap::new(),
+            "cvfig001",
+            Some("figures/cvfig001.png".to_string()),
+            None,
+            captions.get("cvfig001"),
+        );
+        assert_eq!(
+            metadata.title.as_deref(),
+            Some("Figure 2. Chest radiograph")
+        );
+        assert_eq!(metadata.number.as_deref(), Some("2"));
+        assert_eq!(metadata.footnotes, vec!["Courtesy of J. Smith."]);
+
+        // Metadata-endpoint values take precedence over the inline caption
+        let mut from_endpoint = fallback_figure_metadata("cvfig002");
+        from_endpoint.title = Some("Electrocardiogram".to_string());
+        let by_id = HashMap::from([("cvfig002".to_string(), from_endpoint)]);
+        let metadata =
+            figure_metadata_for(&by_id, "cvfig002", None, None, captions.get("cvfig002"));
+        assert_eq!(metadata.title.as_deref(), Some("Electrocardiogram"));
+        assert!(metadata.footnotes.is_empty());
+    }
+}
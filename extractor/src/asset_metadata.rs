@@ -1,4 +1,6 @@
+use regex::Regex;
 use serde_json::Value;
+use std::sync::OnceLock;
 
 #[derive(Clone, Debug, Default)]
 pub struct ImageInfo {
@@ -74,15 +76,32 @@ pub fn extract_image_info(value: &Value) -> ImageInfo {
     info
 }
 
+/// Displayed figure/table number ("2" for "Figure 2"), from the metadata `number` field
+/// (string or integer) or, failing that, a "Figure N"/"Table N" label in the title.
+pub fn extract_display_number(number: Option<&Value>, title: Option<&str>) -> Option<String> {
+    let from_field = match number {
+        Some(Value::String(text)) => Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+        Some(Value::Number(num)) => Some(num.to_string()),
+        _ => None,
+    };
+    from_field.or_else(|| title.and_then(number_from_label))
+}
+
+/// Find a "Figure 2" / "Table 1A" label in text or HTML and return its number.
+pub fn number_from_label(text: &str) -> Option<String> {
+    static LABEL_RE: OnceLock<Regex> = OnceLock::new();
+    let label_re = LABEL_RE
+        .get_or_init(|| Regex::new(r"(?i)\b(?:figure|fig\.|table)\s+(\d+[A-Za-z]?)\b").unwrap());
+    label_re.captures(text).map(|caps| caps[1].to_string())
+}
+
 pub fn parse_figure_snapshot(value: &Value, fallback_id: Option<&str>) -> FigureSnapshot {
+    let title = extract_html_text(value.get("title"));
     FigureSnapshot {
         figure_id: resolve_metadata_id(value, fallback_id).to_string(),
-        title: extract_html_text(value.get("title")),
+        number: extract_display_number(value.get("number"), title.as_deref()),
+        title,
         short_title: extract_html_text(value.get("shortTitle")),
-        number: value
-            .get("number")
-            .and_then(|val| val.as_str())
-            .map(|val| val.to_string()),
         image_info: extract_image_info(value),
     }
 }
@@ -96,3 +115,103 @@ where
         f(figure, snapshot);
     });
 }
+
+#[derive(Clone, Debug)]
+pub struct VideoSnapshot {
+    pub video_id: String,
+    pub title: Option<String>,
+    pub short_title: Option<String>,
+    pub caption: Option<String>,
+    pub mp4_hash: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// `mp4Hash` may sit on the video entry itself or inside its `videoInfo` block.
+pub fn parse_video_snapshot(value: &Value, fallback_id: Option<&str>) -> VideoSnapshot {
+    let info = value.get("videoInfo");
+    let field = |key: &str| {
+        value
+            .get(key)
+            .or_else(|| info.and_then(|info| info.get(key)))
+    };
+    let dimension = |key: &str| {
+        field(key)
+            .and_then(|val| val.as_u64())
+            .map(|val| val as u32)
+    };
+    VideoSnapshot {
+        video_id: resolve_metadata_id(value, fallback_id).to_string(),
+        title: extract_html_text(value.get("title")),
+        short_title: extract_html_text(value.get("shortTitle")),
+        caption: extract_html_text(value.get("caption")),
+        mp4_hash: field("mp4Hash")
+            .and_then(|val| val.as_str())
+            .filter(|hash| !hash.is_empty())
+            .map(|hash| hash.to_string()),
+        width: dimension("width"),
+        height: dimension("height"),
+    }
+}
+
+pub fn for_each_video_snapshot<F>(metadata: &Value, mut f: F)
+where
+    F: FnMut(VideoSnapshot),
+{
+    for_each_metadata_item(metadata, "videos", |fallback_id, video| {
+        f(parse_video_snapshot(video, fallback_id));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_number_from_field_or_title() {
+        let figure = serde_json::json!({ "id": "cvfig001", "number": 2 });
+        assert_eq!(
+            parse_figure_snapshot(&figure, None).number.as_deref(),
+            Some("2")
+        );
+
+        let figure = serde_json::json!({
+            "id": "cvfig002",
+            "title": { "__html": "<strong>Figure 3B.</strong> Chest radiograph" }
+        });
+        assert_eq!(
+            parse_figure_snapshot(&figure, None).number.as_deref(),
+            Some("3B")
+        );
+
+        assert_eq!(
+            extract_display_number(Some(&Value::String("4".into())), Some("Table 9")),
+            Some("4".to_string())
+        );
+        assert_eq!(
+            number_from_label("<caption>Table 1. Causes of Syncope</caption>"),
+            Some("1".to_string())
+        );
+        assert_eq!(number_from_label("Tabletop exercise"), None);
+    }
+
+    #[test]
+    fn test_video_snapshot_reads_hash_from_entry_or_video_info() {
+        let metadata = serde_json::json!({
+            "videos": {
+                "vidcv001": { "title": "Echo", "mp4Hash": "abc123", "width": 640, "height": 360 },
+                "vidcv002": { "videoInfo": { "mp4Hash": "def456" } },
+                "vidcv003": { "title": "No hash" }
+            }
+        });
+        let mut snapshots = Vec::new();
+        for_each_video_snapshot(&metadata, |snapshot| snapshots.push(snapshot));
+        snapshots.sort_by(|a, b| a.video_id.cmp(&b.video_id));
+
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[0].mp4_hash.as_deref(), Some("abc123"));
+        assert_eq!(snapshots[0].width, Some(640));
+        assert_eq!(snapshots[1].mp4_hash.as_deref(), Some("def456"));
+        assert_eq!(snapshots[2].mp4_hash, None);
+    }
+}
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::asset_types::QuestionMedia;
 
+/// Extension recorded for figures missing from content metadata.
+const UNKNOWN_EXTENSION: &str = "unknown";
+
 // ============================================================================
 // Statistics Tracking
 // ============================================================================
@@ -30,6 +33,9 @@ pub struct DiscoveryStatistics {
     pub svg_figures: usize,
     pub jpg_figures: usize,
     pub png_figures: usize,
+    /// Every figure extension seen, lowercased; `unknown` means the figure had no metadata.
+    #[serde(default)]
+    pub figure_extensions: BTreeMap<String, usize>,
 
     // Combinations
     pub questions_with_multiple_types: usize,
@@ -38,6 +44,10 @@ pub struct DiscoveryStatistics {
     // By specialty
     pub by_subspecialty: HashMap<String, usize>,
 
+    // By question type (mcq, qqq, vdx, cor, ...), counting questions with media
+    #[serde(default)]
+    pub by_question_type: HashMap<String, usize>,
+
     // By product type (kept for backward compatibility, currently unused)
     pub by_product_type: HashMap<String, usize>,
 
@@ -50,6 +60,9 @@ pub struct DiscoveryStatistics {
     // Error tracking
     pub failed_requests: usize,
     pub skipped_questions: usize,
+    /// Questions left out by `--only-missing` because their media was already on disk.
+    #[serde(default)]
+    pub skipped_complete: usize,
 }
 
 impl DiscoveryStatistics {
@@ -72,6 +85,10 @@ impl DiscoveryStatistics {
                     _ => {}
                 }
             }
+            tally_figure_extensions(
+                &mut self.figure_extensions,
+                question.figures.iter().map(|fig| fig.extension.as_str()),
+            );
         }
 
         if has_tables {
@@ -113,6 +130,13 @@ impl DiscoveryStatistics {
                 .entry(subspecialty.clone())
                 .or_insert(0) += 1;
         }
+
+        if let Some(question_type) = question_type(question_id) {
+            *self
+                .by_question_type
+                .entry(question_type.to_string())
+                .or_insert(0) += 1;
+        }
     }
 
     /// Finalize statistics after all questions processed
@@ -157,6 +181,12 @@ impl DiscoveryStatistics {
             "- Skipped (invalidated): {}\n",
             self.skipped_questions
         ));
+        if self.skipped_complete > 0 {
+            report.push_str(&format!(
+                "- Skipped (already complete): {}\n",
+                self.skipped_complete
+            ));
+        }
         report.push_str(&format!("- Failed: {}\n", self.failed_requests));
         report.push('\n');
 
@@ -185,6 +215,11 @@ impl DiscoveryStatistics {
         report.push_str(&format!("- PNG format: {}\n", self.png_figures));
         report.push('\n');
 
+        if !self.figure_extensions.is_empty() {
+            report.push_str(&render_figure_extensions(&self.figure_extensions));
+            report.push('\n');
+        }
+
         report.push_str("MEDIA COMBINATIONS\n");
         report.push_str(&format!(
             "- Questions with multiple media types: {}\n",
@@ -206,6 +241,24 @@ impl DiscoveryStatistics {
             report.push('\n');
         }
 
+        if !self.by_question_type.is_empty() {
+            report.push_str("MEDIA BY QUESTION TYPE\n");
+            let mut question_types: Vec<_> = self.by_question_type.iter().collect();
+            question_types.sort_by_key(|(k, _)| *k);
+            for (question_type, count) in question_types {
+                let videos = self
+                    .video_question_ids
+                    .iter()
+                    .filter(|id| self::question_type(id) == Some(question_type.as_str()))
+                    .count();
+                report.push_str(&format!(
+                    "- {}: {} questions ({} with videos)\n",
+                    question_type, count, videos
+                ));
+            }
+            report.push('\n');
+        }
+
         if !self.by_product_type.is_empty() {
             report.push_str("BY PRODUCT TYPE\n");
             let mut product_types: Vec<_> = self.by_product_type.iter().collect();
@@ -243,3 +296,128 @@ impl DiscoveryStatistics {
         report
     }
 }
+
+/// Add each extension (lowercased, blank counted as `unknown`) to `tally`.
+pub(super) fn tally_figure_extensions<'a>(
+    tally: &mut BTreeMap<String, usize>,
+    extensions: impl IntoIterator<Item = &'a str>,
+) {
+    for extension in extensions {
+        let extension = extension
+            .trim()
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        let key = if extension.is_empty() {
+            UNKNOWN_EXTENSION.to_string()
+        } else {
+            extension
+        };
+        *tally.entry(key).or_insert(0) += 1;
+    }
+}
+
+/// `FIGURE EXTENSIONS` section, most common first; `unknown` entries are flagged.
+pub(super) fn render_figure_extensions(tally: &BTreeMap<String, usize>) -> String {
+    let mut section = String::from("FIGURE EXTENSIONS\n");
+    let mut entries: Vec<_> = tally.iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    for (extension, count) in entries {
+        if extension == UNKNOWN_EXTENSION {
+            section.push_str(&format!(
+                "- {}: {} (no figure metadata; check content_metadata.json)\n",
+                extension, count
+            ));
+        } else {
+            section.push_str(&format!("- {}: {}\n", extension, count));
+        }
+    }
+    section
+}
+
+/// Question type code from an ID: `cvmcq24001` -> `mcq` (system code and digits stripped).
+pub(super) fn question_type(question_id: &str) -> Option<&str> {
+    let without_digits = question_id.trim_end_matches(|c: char| c.is_ascii_digit());
+    without_digits
+        .get(2..)
+        .filter(|question_type| !question_type.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_type_from_id() {
+        assert_eq!(question_type("cvmcq24001"), Some("mcq"));
+        assert_eq!(question_type("hpvdx25012"), Some("vdx"));
+        assert_eq!(question_type("cv24001"), None);
+    }
+
+    #[test]
+    fn test_report_breaks_media_down_by_question_type() {
+        let media =
+            |value: serde_json::Value| -> QuestionMedia { serde_json::from_value(value).unwrap() };
+        let video = media(serde_json::json!({
+            "subspecialty": null,
+            "videos": [{ "video_id": "vid1", "title": null, "canonical_location": "" }]
+        }));
+        let table = media(serde_json::json!({
+            "subspecialty": null,
+            "tables": [{ "table_id": "tbl1", "title": null }]
+        }));
+
+        let mut stats = DiscoveryStatistics::default();
+        stats.update_with_question("cvvdx24001", &video);
+        stats.update_with_question("cvvdx24002", &video);
+        stats.update_with_question("cvmcq24003", &table);
+        stats.finalize(3, 3);
+
+        assert_eq!(stats.by_question_type.get("vdx"), Some(&2));
+        assert_eq!(stats.by_question_type.get("mcq"), Some(&1));
+        let report = stats.generate_report("now");
+        assert!(report.contains(
+            "MEDIA BY QUESTION TYPE\n- mcq: 1 questions (0 with videos)\n- vdx: 2 questions (2 with videos)\n"
+        ));
+    }
+
+    #[test]
+    fn test_figure_extension_tally_flags_unknown() {
+        let figure = |id: &str, extension: &str| {
+            serde_json::json!({
+                "figure_id": id, "extension": extension, "title": null, "width": 0, "height": 0
+            })
+        };
+        let question: QuestionMedia = serde_json::from_value(serde_json::json!({
+            "subspecialty": "cv",
+            "figures": [
+                figure("f1", "jpg"),
+                figure("f2", "PNG"),
+                figure("f3", "png"),
+                figure("f4", "unknown"),
+                figure("f5", "gif")
+            ]
+        }))
+        .unwrap();
+        let other: QuestionMedia = serde_json::from_value(serde_json::json!({
+            "subspecialty": "cv",
+            "figures": [figure("f6", "png"), figure("f7", "")]
+        }))
+        .unwrap();
+
+        let mut stats = DiscoveryStatistics::default();
+        stats.update_with_question("cvmcq24001", &question);
+        stats.update_with_question("cvmcq24002", &other);
+
+        let expected: BTreeMap<String, usize> =
+            [("gif", 1), ("jpg", 1), ("png", 3), ("unknown", 2)]
+                .into_iter()
+                .map(|(extension, count)| (extension.to_string(), count))
+                .collect();
+        assert_eq!(stats.figure_extensions, expected);
+
+        let report = stats.generate_report("now");
+        assert!(report.contains(
+            "FIGURE EXTENSIONS\n- png: 3\n- unknown: 2 (no figure metadata; check content_metadata.json)\n- gif: 1\n- jpg: 1\n"
+        ));
+    }
+}
@@ -1,10 +1,13 @@
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use tracing::info;
 
-use super::asset_discovery::DiscoveryResults;
+use super::asset_stats::question_type;
+use crate::extractor::QUESTION_TYPE_CODES;
+use crate::utils::DirScanner;
 
 #[derive(Clone, Debug)]
 pub struct QuestionEntry {
@@ -50,6 +53,9 @@ pub struct FigureMetadata {
     pub extension: Option<String>,
     pub width: Option<u32>,
     pub height: Option<u32>,
+    /// SHA-256 of the file when it is linked from the shared media store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +64,9 @@ pub struct TableMetadata {
     pub file: Option<String>,
     pub title: Option<String>,
     pub short_title: Option<String>,
+    /// Displayed number ("1" for "Table 1"), for resolving cross-references in the critique.
+    #[serde(default)]
+    pub number: Option<String>,
     pub footnotes: Vec<String>,
     pub headers: Vec<String>,
 }
@@ -80,14 +89,18 @@ pub struct SvgMetadata {
     pub file: Option<String>,
     pub title: Option<String>,
     pub caption: Option<String>,
+    /// PNG rendering written by `--rasterize-svgs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub png: Option<String>,
 }
 
 pub fn collect_question_entries(data_dir: &str) -> Result<Vec<QuestionEntry>> {
     let root = ensure_data_dir(data_dir)?;
     let mut entries = Vec::new();
+    let mut scanner = DirScanner::from_config();
 
-    for category_path in list_dirs(&root)? {
-        for q_path in list_dirs(&category_path)? {
+    for category_path in scanner.subdirs(&root)? {
+        for q_path in scanner.subdirs(&category_path)? {
             if let Some(entry) = build_question_entry(q_path) {
                 entries.push(entry);
             }
@@ -124,11 +137,53 @@ pub fn select_targets(
     Ok(targets)
 }
 
-pub fn load_discovery_results(path: &Path) -> Result<HashSet<String>> {
-    let results = DiscoveryResults::load_from_file(path)
-        .with_context(|| format!("Failed to read discovery results from {}", path.display()))?;
+/// `--question-type mcq,vdx`: keep only questions whose ID has one of these type segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestionTypeFilter {
+    types: BTreeSet<String>,
+}
+
+impl QuestionTypeFilter {
+    pub fn parse(value: &str) -> Result<Self> {
+        let types: BTreeSet<String> = value
+            .split(',')
+            .map(|item| item.trim().to_ascii_lowercase())
+            .filter(|item| !item.is_empty())
+            .collect();
+        if types.is_empty() {
+            bail!("--question-type needs at least one type (e.g. mcq,vdx)");
+        }
+        if let Some(unknown) = types
+            .iter()
+            .find(|item| !QUESTION_TYPE_CODES.contains(&item.as_str()))
+        {
+            bail!(
+                "Unknown question type '{}' (expected one of: {})",
+                unknown,
+                QUESTION_TYPE_CODES.join(", ")
+            );
+        }
+        Ok(Self { types })
+    }
+
+    pub fn matches(&self, question_id: &str) -> bool {
+        question_type(question_id).is_some_and(|question_type| self.types.contains(question_type))
+    }
+
+    /// Log how many of `total` questions the filter kept.
+    pub fn report(&self, kept: usize, total: usize) {
+        info!(
+            "Question type filter ({}): {} of {} questions kept, {} skipped",
+            self.label(),
+            kept,
+            total,
+            total - kept
+        );
+    }
 
-    Ok(results.questions.keys().cloned().collect())
+    pub fn label(&self) -> String {
+        self.types.iter().cloned().collect::<Vec<_>>().join(",")
+    }
 }
 
 pub fn update_question_json(json_path: &Path, update: &MediaUpdate) -> Result<()> {
@@ -147,7 +202,7 @@ pub fn update_question_json(json_path: &Path, update: &MediaUpdate) -> Result<()
         merge_media_metadata(&mut value, &update.metadata)?;
     }
 
-    let updated = serde_json::to_string_pretty(&value)?;
+    let updated = crate::utils::to_json_string(&value)?;
     fs::write(json_path, updated)
         .with_context(|| format!("Failed to write {}", json_path.display()))?;
     Ok(())
@@ -185,6 +240,7 @@ fn upsert_figure_metadata(target: &mut Vec<FigureMetadata>, item: FigureMetadata
         merge_option(&mut existing.extension, item.extension);
         merge_option(&mut existing.width, item.width);
         merge_option(&mut existing.height, item.height);
+        merge_option(&mut existing.content_hash, item.content_hash);
         merge_vec_unique(&mut existing.footnotes, item.footnotes);
     } else {
         target.push(item);
@@ -196,6 +252,7 @@ fn upsert_table_metadata(target: &mut Vec<TableMetadata>, item: TableMetadata) {
         merge_option(&mut existing.file, item.file);
         merge_option(&mut existing.title, item.title);
         merge_option(&mut existing.short_title, item.short_title);
+        merge_option(&mut existing.number, item.number);
         merge_vec_unique(&mut existing.footnotes, item.footnotes);
         merge_vec_unique(&mut existing.headers, item.headers);
     } else {
@@ -222,6 +279,7 @@ fn upsert_svg_metadata(target: &mut Vec<SvgMetadata>, item: SvgMetadata) {
         merge_option(&mut existing.file, item.file);
         merge_option(&mut existing.title, item.title);
         merge_option(&mut existing.caption, item.caption);
+        merge_option(&mut existing.png, item.png);
     } else {
         target.push(item);
     }
@@ -253,18 +311,6 @@ fn ensure_data_dir(data_dir: &str) -> Result<PathBuf> {
     bail!("Data directory not found: {}", data_dir)
 }
 
-fn list_dirs(path: &Path) -> Result<Vec<PathBuf>> {
-    let mut dirs = Vec::new();
-    for entry in fs::read_dir(path).context("Failed to read directory")? {
-        let entry = entry.context("Failed to read directory entry")?;
-        let entry_path = entry.path();
-        if entry_path.is_dir() {
-            dirs.push(entry_path);
-        }
-    }
-    Ok(dirs)
-}
-
 fn build_question_entry(question_dir: PathBuf) -> Option<QuestionEntry> {
     let qid = question_dir.file_name()?.to_str()?.to_string();
     let json_path = question_dir.join(format!("{}.json", qid));
@@ -317,3 +363,30 @@ fn insert_unique_strings(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_type_filter_excludes_other_types() {
+        let filter = QuestionTypeFilter::parse("mcq").unwrap();
+        assert!(filter.matches("cvmcq24001"));
+        assert!(!filter.matches("cvvdx24001"));
+        assert!(!filter.matches("cv24001"));
+
+        let mut ids = vec![
+            "cvmcq24001".to_string(),
+            "hpvdx25012".to_string(),
+            "gimcq24003".to_string(),
+        ];
+        ids.retain(|id| filter.matches(id));
+        assert_eq!(ids, vec!["cvmcq24001", "gimcq24003"]);
+
+        let filter = QuestionTypeFilter::parse(" MCQ, vdx ").unwrap();
+        assert_eq!(filter.label(), "mcq,vdx");
+        assert!(filter.matches("hpvdx25012"));
+        assert!(QuestionTypeFilter::parse("xyz").is_err());
+        assert!(QuestionTypeFilter::parse(",").is_err());
+    }
+}
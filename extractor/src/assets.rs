@@ -12,12 +12,31 @@ mod asset_stats;
 pub mod asset_store;
 #[path = "asset_types.rs"]
 mod asset_types;
+#[path = "calibrate.rs"]
+pub mod calibrate;
 #[path = "content_ids.rs"]
 pub mod content_ids;
+#[path = "media_dimensions.rs"]
+pub mod media_dimensions;
+#[path = "media_failures.rs"]
+pub mod media_failures;
+#[path = "media_index.rs"]
+pub mod media_index;
+#[path = "media_plan.rs"]
+pub mod media_plan;
+#[path = "media_store.rs"]
+pub mod media_store;
+#[cfg(test)]
+#[path = "mock_api_tests.rs"]
+mod mock_api_tests;
+#[path = "rate_limit.rs"]
+pub mod rate_limit;
 #[path = "svg_browser.rs"]
 pub mod svg_browser;
 #[path = "svg_download.rs"]
 pub mod svg_download;
+#[path = "svg_rasterize.rs"]
+pub mod svg_rasterize;
 #[path = "table_render.rs"]
 pub mod table_render;
 
@@ -27,6 +46,11 @@ use serde_json::Value;
 use tracing::{info, warn};
 
 pub fn build_client() -> Result<Client> {
+    build_download_client(crate::http::DEFAULT_MAX_REDIRECTS)
+}
+
+/// Session client for media downloads, following at most `max_redirects` redirects.
+pub fn build_download_client(max_redirects: usize) -> Result<Client> {
     let session_cookie = crate::session::load_session_cookie()
         .context("Session cookie not set. Set MKSAP_SESSION or login via browser.")?;
 
@@ -37,11 +61,52 @@ pub fn build_client() -> Result<Client> {
         warn!("MKSAP_SESSION is empty; API may return 401 Unauthorized.");
     }
 
-    crate::http::build_client_with_headers(headers)
+    crate::http::build_client_with_redirects(
+        headers,
+        crate::http::configured_timeouts(),
+        max_redirects,
+    )
+}
+
+/// `content_metadata.json`, served from the response cache when it holds a fresh copy.
+pub async fn fetch_content_metadata_cached(client: &Client, base_url: &str) -> Result<Value> {
+    fetch_content_metadata_with_cache(client, base_url, crate::cache::response_cache()).await
+}
+
+/// `content_metadata.json` from the response cache when it holds a fresh copy, else fetched
+/// without caching it (dry runs leave the output tree untouched).
+pub async fn fetch_content_metadata_read_cached(client: &Client, base_url: &str) -> Result<Value> {
+    let url = crate::endpoints::content_metadata(base_url);
+    if let Some(metadata) = crate::cache::response_cache().and_then(|cache| cache.get(&url)) {
+        info!("Using cached content metadata");
+        return Ok(metadata);
+    }
+    fetch_content_metadata(client, base_url).await
+}
+
+async fn fetch_content_metadata_with_cache(
+    client: &Client,
+    base_url: &str,
+    cache: Option<&crate::cache::ResponseCache>,
+) -> Result<Value> {
+    let url = crate::endpoints::content_metadata(base_url);
+    if let Some(metadata) = cache.and_then(|cache| cache.get(&url)) {
+        info!("Using cached content metadata");
+        return Ok(metadata);
+    }
+
+    let metadata = fetch_content_metadata(client, base_url).await?;
+    if let Some(cache) = cache {
+        if let Err(err) = cache.put(&url, &metadata) {
+            warn!("Failed to cache content metadata: {:#}", err);
+        }
+    }
+    Ok(metadata)
 }
 
 pub async fn fetch_content_metadata(client: &Client, base_url: &str) -> Result<Value> {
     let url = crate::endpoints::content_metadata(base_url);
+    rate_limit::acquire().await;
     let response = client
         .get(&url)
         .send()
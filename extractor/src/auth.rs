@@ -3,8 +3,11 @@ use serde_json::json;
 use tracing::{info, warn};
 
 use super::MKSAPExtractor;
+use crate::error::ExtractError;
 
 impl MKSAPExtractor {
+    /// Post credentials to the known JSON login endpoints. Fails with [`ExtractError::Auth`]
+    /// when none of them accepts.
     pub async fn login(&mut self, username: &str, password: &str) -> Result<()> {
         info!("Attempting to authenticate with MKSAP as {}...", username);
 
@@ -50,9 +53,7 @@ impl MKSAPExtractor {
             }
         }
 
-        warn!("Could not find a valid authentication endpoint. Proceeding without authentication.");
-        // Continue anyway - some content might be accessible
-        Ok(())
+        Err(ExtractError::Auth("no login endpoint accepted the credentials".to_string()).into())
     }
 
     pub async fn is_already_authenticated(&self) -> Result<bool> {
@@ -139,20 +140,18 @@ pub async fn authenticate_extractor(extractor: &mut MKSAPExtractor) -> Result<()
     // Step 2: Try automatic login
     info!("Step 1: Attempting automatic login with provided credentials...");
     match extractor.login(&username, &password).await {
-        Ok(_) if extractor.is_authenticated() => {
+        Ok(_) => {
             info!("✓ Authentication successful, proceeding with extraction");
             return Ok(());
         }
-        Ok(_) => {
+        Err(e) => {
+            warn!("Step 2: Automatic authentication error: {}", e);
             if let Ok(true) = extractor.is_already_authenticated().await {
                 info!("✓ Authentication successful via HTTP check");
                 extractor.set_authenticated(true);
                 return Ok(());
             }
         }
-        Err(e) => {
-            warn!("Step 2: Automatic authentication error: {}", e);
-        }
     }
 
     // Step 3: Fall back to browser login
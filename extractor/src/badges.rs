@@ -0,0 +1,231 @@
+//! `progress-badges`: per-system completion badges for a status page, from the same
+//! found/discovered counts `validate` reports.
+//!
+//! Writes `<system>.svg` (plus `all.svg`) into `badges/` under the data directory, or with
+//! `--format json` a single `badges.json` mapping each system to a shields.io endpoint object
+//! (`schemaVersion`, `label`, `message`, `color`).
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::validator::{Completion, DataValidator, NEAR_COMPLETE_THRESHOLD};
+
+pub const BADGES_DIR_NAME: &str = "badges";
+pub const BADGES_JSON_FILE_NAME: &str = "badges.json";
+/// Key of the badge covering every system.
+const OVERALL_LABEL: &str = "all";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeFormat {
+    Svg,
+    Json,
+}
+
+impl BadgeFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "svg" => Ok(BadgeFormat::Svg),
+            "json" => Ok(BadgeFormat::Json),
+            _ => bail!("Unknown badge format '{}': expected svg or json", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressBadge {
+    pub label: String,
+    pub found: usize,
+    pub discovered: usize,
+}
+
+/// shields.io endpoint badge (`https://img.shields.io/endpoint?url=...`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShieldsEndpoint {
+    pub schema_version: u8,
+    pub label: String,
+    pub message: String,
+    pub color: &'static str,
+}
+
+impl ProgressBadge {
+    /// Whole percent, rounded down so a badge never reads 100% before it is green.
+    pub fn percent(&self) -> usize {
+        if self.discovered == 0 {
+            return if self.found > 0 { 100 } else { 0 };
+        }
+        self.found * 100 / self.discovered
+    }
+
+    /// shields.io color name: green when complete, yellow from 90%, red otherwise.
+    pub fn color(&self) -> &'static str {
+        match Completion::of(self.found, self.discovered, NEAR_COMPLETE_THRESHOLD) {
+            Completion::Complete => "brightgreen",
+            Completion::NearlyComplete => "yellow",
+            Completion::Partial | Completion::Missing => "red",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        format!("{}%", self.percent())
+    }
+
+    pub fn endpoint(&self) -> ShieldsEndpoint {
+        ShieldsEndpoint {
+            schema_version: 1,
+            label: self.label.clone(),
+            message: self.message(),
+            color: self.color(),
+        }
+    }
+
+    /// A flat, shields-style badge: grey label on the left, colored percentage on the right.
+    pub fn render_svg(&self) -> String {
+        let message = self.message();
+        let label_width = text_width(&self.label);
+        let message_width = text_width(&message);
+        let width = label_width + message_width;
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+  <title>{label}: {message}</title>
+  <rect width="{label_width}" height="20" fill="#555"/>
+  <rect x="{label_width}" width="{message_width}" height="20" fill="{fill}"/>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{message_x}" y="14">{message}</text>
+  </g>
+</svg>
+"##,
+            label = self.label,
+            fill = svg_fill(self.color()),
+            label_x = label_width / 2,
+            message_x = label_width + message_width / 2,
+        )
+    }
+}
+
+/// One badge per validated system, sorted by system code, followed by the overall badge.
+pub fn compute_progress_badges(output_dir: &str) -> Result<Vec<ProgressBadge>> {
+    let result = DataValidator::validate_extraction(output_dir)?;
+    let mut badges: Vec<ProgressBadge> = result
+        .systems_verified
+        .iter()
+        .map(|system| ProgressBadge {
+            label: system.system_id.clone(),
+            found: system.found_count,
+            discovered: system.discovered_count,
+        })
+        .collect();
+    badges.sort_by(|a, b| a.label.cmp(&b.label));
+    let overall = ProgressBadge {
+        label: OVERALL_LABEL.to_string(),
+        found: badges.iter().map(|badge| badge.found).sum(),
+        discovered: badges.iter().map(|badge| badge.discovered).sum(),
+    };
+    badges.push(overall);
+    Ok(badges)
+}
+
+/// Write `badges` into `out_dir`; returns the files written.
+pub fn write_progress_badges(
+    badges: &[ProgressBadge],
+    out_dir: &Path,
+    format: BadgeFormat,
+) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let mut written = Vec::new();
+    match format {
+        BadgeFormat::Svg => {
+            for badge in badges {
+                let path = out_dir.join(format!("{}.svg", badge.label));
+                fs::write(&path, badge.render_svg())
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                written.push(path);
+            }
+        }
+        BadgeFormat::Json => {
+            let endpoints: BTreeMap<&str, ShieldsEndpoint> = badges
+                .iter()
+                .map(|badge| (badge.label.as_str(), badge.endpoint()))
+                .collect();
+            let path = out_dir.join(BADGES_JSON_FILE_NAME);
+            fs::write(&path, crate::utils::to_json_string(&endpoints)?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+/// Rough Verdana 11px advance plus padding; close enough for short labels.
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+fn svg_fill(color: &str) -> &'static str {
+    match color {
+        "brightgreen" => "#4c1",
+        "yellow" => "#dfb317",
+        _ => "#e05d44",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn badge(found: usize, discovered: usize) -> ProgressBadge {
+        ProgressBadge {
+            label: "cv".to_string(),
+            found,
+            discovered,
+        }
+    }
+
+    #[test]
+    fn test_badge_colors_follow_completion_thresholds() {
+        let colors: Vec<_> = [(240, 240), (230, 240), (216, 240), (215, 240), (0, 240)]
+            .iter()
+            .map(|&(found, discovered)| {
+                (
+                    badge(found, discovered).message(),
+                    badge(found, discovered).color(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            colors,
+            vec![
+                ("100%".to_string(), "brightgreen"),
+                ("95%".to_string(), "yellow"),
+                ("90%".to_string(), "yellow"),
+                ("89%".to_string(), "red"),
+                ("0%".to_string(), "red"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_badges_as_svg_and_shields_json() {
+        let temp = tempfile::tempdir().unwrap();
+        let badges = vec![badge(230, 240)];
+
+        let written = write_progress_badges(&badges, temp.path(), BadgeFormat::Svg).unwrap();
+        assert_eq!(written, vec![temp.path().join("cv.svg")]);
+        let svg = fs::read_to_string(&written[0]).unwrap();
+        assert!(svg.contains(r##"fill="#dfb317""##));
+        assert!(svg.contains("<title>cv: 95%</title>"));
+
+        let written = write_progress_badges(&badges, temp.path(), BadgeFormat::Json).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+        assert_eq!(
+            json["cv"],
+            serde_json::json!({"schemaVersion": 1, "label": "cv", "message": "95%", "color": "yellow"})
+        );
+    }
+}
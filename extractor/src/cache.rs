@@ -0,0 +1,107 @@
+//! On-disk cache for large, slow-changing API responses (`<output_dir>/.cache/`).
+//!
+//! `content_metadata.json` is needed by discovery, figure/video download and the
+//! SVG step; caching it keyed by URL lets one run (or several within the TTL)
+//! fetch it once. `--no-cache` forces a refresh.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+pub const CACHE_DIR_NAME: &str = ".cache";
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+static RESPONSE_CACHE: OnceLock<Option<ResponseCache>> = OnceLock::new();
+
+/// JSON responses stored as `<dir>/<sha256(url)>.json`, valid for `ttl` after writing.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+        }
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:x}.json", Sha256::digest(url.as_bytes())))
+    }
+
+    /// The cached body for `url`, if present and younger than the TTL.
+    pub fn get(&self, url: &str) -> Option<Value> {
+        let path = self.path_for(url);
+        let age = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age > self.ttl {
+            debug!("Cache entry for {} expired ({}s old)", url, age.as_secs());
+            return None;
+        }
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                warn!("Ignoring corrupt cache entry {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    pub fn put(&self, url: &str, value: &Value) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create cache dir {}", self.dir.display()))?;
+        let path = self.path_for(url);
+        crate::io::write_atomic(&path, serde_json::to_vec(value)?)
+            .with_context(|| format!("Failed to write cache entry {}", path.display()))
+    }
+}
+
+/// Enable the response cache under `<output_dir>/.cache/` (or disable it with `--no-cache`).
+/// Only the first call takes effect; without a call nothing is cached.
+pub(crate) fn configure_response_cache(output_dir: &str, enabled: bool, ttl: Duration) {
+    let cache =
+        enabled.then(|| ResponseCache::new(PathBuf::from(output_dir).join(CACHE_DIR_NAME), ttl));
+    if RESPONSE_CACHE.set(cache).is_err() {
+        warn!("Response cache already configured; ignoring override");
+    }
+}
+
+pub(crate) fn response_cache() -> Option<&'static ResponseCache> {
+    RESPONSE_CACHE.get().and_then(Option::as_ref)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip_and_expiry() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(temp.path().join(CACHE_DIR_NAME), DEFAULT_CACHE_TTL);
+        let url = "https://example.test/api/content_metadata.json";
+        assert!(cache.get(url).is_none());
+
+        let value = serde_json::json!({ "figures": [{ "id": "cvfig001" }] });
+        cache.put(url, &value).unwrap();
+        assert_eq!(cache.get(url), Some(value.clone()));
+        assert!(cache
+            .get("https://other.test/api/content_metadata.json")
+            .is_none());
+
+        let expired = ResponseCache::new(temp.path().join(CACHE_DIR_NAME), Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(expired.get(url).is_none());
+    }
+}
@@ -0,0 +1,209 @@
+//! Concurrency calibration for media discovery (`media-discover --calibrate`).
+//!
+//! Fetches a small sample of question JSON at increasing concurrency, measuring
+//! throughput and error rate at each level, and recommends the highest level that
+//! both stays under the error threshold and still buys meaningful throughput.
+
+use anyhow::Result;
+use futures::future;
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::asset_api::fetch_question_json;
+
+/// Concurrency levels probed, in order.
+pub const CALIBRATION_LEVELS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+/// Levels with a higher share of failed requests are rejected.
+pub const MAX_ERROR_RATE: f64 = 0.05;
+/// A level must beat the best accepted one so far by this factor to be worth its extra load.
+const MIN_THROUGHPUT_GAIN: f64 = 1.1;
+/// Distinct questions fetched during calibration.
+const SAMPLE_SIZE: usize = 32;
+
+/// Measured outcome of one concurrency level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationSample {
+    pub concurrency: usize,
+    pub requests: usize,
+    pub errors: usize,
+    pub elapsed: Duration,
+}
+
+impl CalibrationSample {
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.requests as f64
+        }
+    }
+
+    /// Successful requests per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            0.0
+        } else {
+            (self.requests - self.errors) as f64 / secs
+        }
+    }
+}
+
+/// Pick the concurrency to use from samples ordered by increasing concurrency.
+///
+/// Levels are accepted until the first one whose error rate exceeds `max_error_rate`
+/// (the server is pushing back, so nothing above it is trusted). Among accepted
+/// levels, a higher one only wins if it raises throughput by at least 10%.
+/// Returns `None` when even the first level fails the error threshold.
+pub fn recommend_concurrency(samples: &[CalibrationSample], max_error_rate: f64) -> Option<usize> {
+    let mut best: Option<&CalibrationSample> = None;
+    for sample in samples {
+        if sample.error_rate() > max_error_rate {
+            break;
+        }
+        match best {
+            Some(current) if sample.throughput() < current.throughput() * MIN_THROUGHPUT_GAIN => {}
+            _ => best = Some(sample),
+        }
+    }
+    best.map(|sample| sample.concurrency)
+}
+
+/// Up to `size` IDs spread evenly across the sorted ID list, so every system is sampled.
+fn sample_question_ids(question_ids: &HashSet<String>, size: usize) -> Vec<String> {
+    let mut sorted: Vec<&String> = question_ids.iter().collect();
+    sorted.sort();
+    if sorted.len() <= size {
+        return sorted.into_iter().cloned().collect();
+    }
+    let step = sorted.len() as f64 / size as f64;
+    (0..size)
+        .map(|index| sorted[(index as f64 * step) as usize].clone())
+        .collect()
+}
+
+/// Probe each of `levels` in turn, stopping after the first level over the error threshold.
+pub async fn calibrate_concurrency(
+    client: &Client,
+    base_url: &str,
+    question_ids: &HashSet<String>,
+    levels: &[usize],
+) -> Result<Vec<CalibrationSample>> {
+    let sample = sample_question_ids(question_ids, SAMPLE_SIZE);
+    if sample.is_empty() {
+        anyhow::bail!("No question IDs available to calibrate with");
+    }
+
+    let mut samples = Vec::new();
+    for &concurrency in levels {
+        let sample = probe_level(client, base_url, &sample, concurrency).await;
+        info!(
+            "Concurrency {:>2}: {} requests, {} errors ({:.1}%), {:.1} req/s",
+            sample.concurrency,
+            sample.requests,
+            sample.errors,
+            sample.error_rate() * 100.0,
+            sample.throughput()
+        );
+        samples.push(sample);
+        if sample.error_rate() > MAX_ERROR_RATE {
+            warn!(
+                "Error rate above {:.0}% at concurrency {}; not probing higher",
+                MAX_ERROR_RATE * 100.0,
+                concurrency
+            );
+            break;
+        }
+    }
+    Ok(samples)
+}
+
+/// Fetch enough questions (cycling through `question_ids`) to keep `concurrency` requests busy.
+async fn probe_level(
+    client: &Client,
+    base_url: &str,
+    question_ids: &[String],
+    concurrency: usize,
+) -> CalibrationSample {
+    let requests = (concurrency * 4).max(8);
+    let started = Instant::now();
+    let errors = stream::iter(question_ids.iter().cycle().take(requests))
+        .map(|question_id| fetch_question_json(client, base_url, question_id))
+        .buffer_unordered(concurrency)
+        .filter(|result| future::ready(result.is_err()))
+        .count()
+        .await;
+    CalibrationSample {
+        concurrency,
+        requests,
+        errors,
+        elapsed: started.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Synthetic curve: (concurrency, errors out of 100, requests per second).
+    fn curve(points: &[(usize, usize, f64)]) -> Vec<CalibrationSample> {
+        points
+            .iter()
+            .map(|&(concurrency, errors, rate)| CalibrationSample {
+                concurrency,
+                requests: 100,
+                errors,
+                elapsed: Duration::from_secs_f64((100 - errors) as f64 / rate),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_recommends_highest_clean_level_while_throughput_scales() {
+        let samples = curve(&[(1, 0, 5.0), (2, 0, 10.0), (4, 1, 19.0), (8, 2, 35.0)]);
+        assert_eq!(recommend_concurrency(&samples, MAX_ERROR_RATE), Some(8));
+    }
+
+    #[test]
+    fn test_stops_at_first_level_over_error_threshold() {
+        // 32 looks fine again, but 16 already tripped rate limiting
+        let samples = curve(&[(4, 0, 20.0), (8, 3, 38.0), (16, 20, 30.0), (32, 0, 80.0)]);
+        assert_eq!(recommend_concurrency(&samples, MAX_ERROR_RATE), Some(8));
+    }
+
+    #[test]
+    fn test_prefers_lower_level_once_throughput_plateaus() {
+        let samples = curve(&[
+            (1, 0, 5.0),
+            (2, 0, 9.5),
+            (4, 0, 18.0),
+            (8, 0, 19.0),
+            (16, 0, 19.5),
+        ]);
+        assert_eq!(recommend_concurrency(&samples, MAX_ERROR_RATE), Some(4));
+    }
+
+    #[test]
+    fn test_no_recommendation_when_every_level_fails() {
+        let samples = curve(&[(1, 50, 2.0)]);
+        assert_eq!(recommend_concurrency(&samples, MAX_ERROR_RATE), None);
+        assert_eq!(recommend_concurrency(&[], MAX_ERROR_RATE), None);
+    }
+
+    #[test]
+    fn test_sample_question_ids_spreads_across_systems() {
+        let ids: HashSet<String> = ["cv", "en", "gi", "hm"]
+            .iter()
+            .flat_map(|system| (1..=10).map(move |n| format!("{}mcq24{:03}", system, n)))
+            .collect();
+        let sample = sample_question_ids(&ids, 4);
+        assert_eq!(
+            sample,
+            vec!["cvmcq24001", "enmcq24001", "gimcq24001", "hmmcq24001"]
+        );
+        assert_eq!(sample_question_ids(&ids, 100).len(), 40);
+    }
+}
@@ -0,0 +1,353 @@
+//! Compatibility check for discovery checkpoints (`validate-checkpoints`).
+//!
+//! Extraction writes `.checkpoints/<system>_ids.txt` and `discovery_metadata.json`;
+//! media discovery, reporting and stats read them back. This confirms the files
+//! still have the shape every reader expects before a run fails halfway through.
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::get_organ_system_by_id;
+use crate::extractor::checkpoint_dir;
+use crate::io::checkpoint_system_id;
+
+const DISCOVERY_METADATA_FILE: &str = "discovery_metadata.json";
+
+/// Fields every reader of `discovery_metadata.json` deserializes per system.
+const REQUIRED_SYSTEM_FIELDS: [(&str, FieldKind); 6] = [
+    ("system_code", FieldKind::String),
+    ("discovered_count", FieldKind::Count),
+    ("discovery_timestamp", FieldKind::String),
+    ("candidates_tested", FieldKind::Count),
+    ("hit_rate", FieldKind::Number),
+    ("question_types_found", FieldKind::StringArray),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    String,
+    Count,
+    Number,
+    StringArray,
+}
+
+impl FieldKind {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            FieldKind::String => value.is_string(),
+            FieldKind::Count => value.is_u64(),
+            FieldKind::Number => value.is_number(),
+            FieldKind::StringArray => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            FieldKind::String => "a string",
+            FieldKind::Count => "a non-negative integer",
+            FieldKind::Number => "a number",
+            FieldKind::StringArray => "an array of strings",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointIssue {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct CheckpointReport {
+    pub files_checked: usize,
+    pub ids_checked: usize,
+    /// Problems that will break a reader.
+    pub errors: Vec<CheckpointIssue>,
+    /// Suspicious but readable data (e.g. stale counts).
+    pub warnings: Vec<CheckpointIssue>,
+}
+
+impl CheckpointReport {
+    fn error(&mut self, path: &Path, message: impl Into<String>) {
+        self.errors.push(CheckpointIssue {
+            path: path.to_path_buf(),
+            message: message.into(),
+        });
+    }
+
+    fn warn(&mut self, path: &Path, message: impl Into<String>) {
+        self.warnings.push(CheckpointIssue {
+            path: path.to_path_buf(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Check every `*_ids.txt` checkpoint and `discovery_metadata.json` under `output_dir`.
+pub fn validate_checkpoints(output_dir: &str) -> Result<CheckpointReport> {
+    let checkpoint_dir = checkpoint_dir(output_dir);
+    if !checkpoint_dir.is_dir() {
+        bail!(
+            "Checkpoint directory not found: {}. Run the extractor first to discover questions.",
+            checkpoint_dir.display()
+        );
+    }
+
+    let mut report = CheckpointReport::default();
+    let mut id_counts = HashMap::new();
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&checkpoint_dir)
+        .context("Failed to read checkpoint directory")?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.to_string_lossy().ends_with("_ids.txt"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        if let Some((system_id, count)) = check_ids_file(&path, &mut report)? {
+            id_counts.insert(system_id, count);
+        }
+    }
+
+    let metadata_path = checkpoint_dir.join(DISCOVERY_METADATA_FILE);
+    if metadata_path.exists() {
+        check_discovery_metadata(&metadata_path, &id_counts, &mut report)?;
+    } else {
+        report.warn(
+            &metadata_path,
+            "missing; discovery statistics and completion percentages will be unavailable",
+        );
+    }
+
+    Ok(report)
+}
+
+/// Validate checkpoints, print the findings, and fail when any reader would break.
+pub fn run_checkpoint_validation(output_dir: &str) -> Result<()> {
+    let report = validate_checkpoints(output_dir)?;
+
+    println!("\n=== Checkpoint Compatibility ===\n");
+    println!(
+        "Checked {} files ({} question IDs)",
+        report.files_checked, report.ids_checked
+    );
+    for issue in &report.warnings {
+        println!("⚠️  {}: {}", issue.path.display(), issue.message);
+    }
+    for issue in &report.errors {
+        println!("❌ {}: {}", issue.path.display(), issue.message);
+    }
+
+    if !report.is_compatible() {
+        bail!(
+            "{} checkpoint incompatibilities found in {}",
+            report.errors.len(),
+            checkpoint_dir(output_dir).display()
+        );
+    }
+    println!("✅ Checkpoints are compatible\n");
+    Ok(())
+}
+
+/// Returns the system code and ID count when the file name is usable.
+fn check_ids_file(path: &Path, report: &mut CheckpointReport) -> Result<Option<(String, usize)>> {
+    report.files_checked += 1;
+
+    let Some(system_id) = checkpoint_system_id(path) else {
+        report.error(path, "file name must be <system>_ids.txt");
+        return Ok(None);
+    };
+    if get_organ_system_by_id(&system_id).is_none() {
+        report.error(path, format!("unknown system code '{}'", system_id));
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read checkpoint file {}", path.display()))?;
+    let id_re = Regex::new(&format!(r"^{}[a-z]+\d{{5}}$", regex::escape(&system_id)))?;
+
+    let mut count = 0;
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        count += 1;
+        if line != line.trim() {
+            report.warn(
+                path,
+                format!("line {}: surrounding whitespace in '{}'", index + 1, line),
+            );
+        }
+        if !id_re.is_match(line.trim()) {
+            report.error(
+                path,
+                format!(
+                    "line {}: '{}' is not a {}<type><yy><nnn> question ID",
+                    index + 1,
+                    line.trim(),
+                    system_id
+                ),
+            );
+        }
+    }
+    report.ids_checked += count;
+
+    Ok(Some((system_id, count)))
+}
+
+fn check_discovery_metadata(
+    path: &Path,
+    id_counts: &HashMap<String, usize>,
+    report: &mut CheckpointReport,
+) -> Result<()> {
+    report.files_checked += 1;
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let metadata: Value = match serde_json::from_str(&content) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            report.error(path, format!("invalid JSON: {}", err));
+            return Ok(());
+        }
+    };
+
+    for field in ["version", "last_updated"] {
+        if !metadata.get(field).is_some_and(Value::is_string) {
+            report.error(path, format!("'{}' must be a string", field));
+        }
+    }
+    let Some(systems) = metadata.get("systems").and_then(Value::as_array) else {
+        report.error(path, "'systems' must be an array");
+        return Ok(());
+    };
+
+    for (index, system) in systems.iter().enumerate() {
+        let label = system
+            .get("system_code")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("systems[{}]", index));
+
+        for (field, kind) in REQUIRED_SYSTEM_FIELDS {
+            match system.get(field) {
+                None => report.error(path, format!("{}: missing '{}'", label, field)),
+                Some(value) if !kind.matches(value) => report.error(
+                    path,
+                    format!("{}: '{}' must be {}", label, field, kind.describe()),
+                ),
+                Some(_) => {}
+            }
+        }
+
+        let discovered = system.get("discovered_count").and_then(Value::as_u64);
+        if let (Some(discovered), Some(count)) = (discovered, id_counts.get(&label)) {
+            if discovered as usize != *count {
+                report.warn(
+                    path,
+                    format!(
+                        "{}: discovered_count is {} but {}_ids.txt lists {} IDs",
+                        label, discovered, label, count
+                    ),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::CHECKPOINT_DIR_NAME;
+
+    fn write_checkpoints(root: &Path, ids: &str, metadata: &str) {
+        let dir = root.join(CHECKPOINT_DIR_NAME);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("cv_ids.txt"), ids).unwrap();
+        fs::write(dir.join(DISCOVERY_METADATA_FILE), metadata).unwrap();
+    }
+
+    #[test]
+    fn test_well_formed_checkpoints_are_compatible() {
+        let temp = tempfile::tempdir().unwrap();
+        write_checkpoints(
+            temp.path(),
+            "cvmcq24001\ncvqqq25002",
+            r#"{
+                "version": "1.0.0",
+                "last_updated": "2025-01-01T00:00:00Z",
+                "systems": [{
+                    "system_code": "cv",
+                    "discovered_count": 2,
+                    "discovery_timestamp": "2025-01-01T00:00:00Z",
+                    "candidates_tested": 100,
+                    "hit_rate": 0.02,
+                    "question_types_found": ["mcq", "qqq"]
+                }]
+            }"#,
+        );
+
+        let report = validate_checkpoints(temp.path().to_str().unwrap()).unwrap();
+        assert!(report.is_compatible(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        assert_eq!(report.files_checked, 2);
+        assert_eq!(report.ids_checked, 2);
+    }
+
+    #[test]
+    fn test_malformed_checkpoints_are_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        write_checkpoints(
+            temp.path(),
+            "cvmcq24001\nenmcq24001\ncvmcq24001,cvmcq24002",
+            r#"{
+                "version": "1.0.0",
+                "systems": [{
+                    "system_code": "cv",
+                    "discovered_count": "3",
+                    "discovery_timestamp": "2025-01-01T00:00:00Z",
+                    "hit_rate": 0.02,
+                    "question_types_found": ["mcq"]
+                }]
+            }"#,
+        );
+        fs::write(temp.path().join(CHECKPOINT_DIR_NAME).join("zz_ids.txt"), "").unwrap();
+
+        let report = validate_checkpoints(temp.path().to_str().unwrap()).unwrap();
+        assert!(!report.is_compatible());
+        let messages: Vec<&str> = report
+            .errors
+            .iter()
+            .map(|issue| issue.message.as_str())
+            .collect();
+        assert!(messages.iter().any(|m| m.contains("'enmcq24001'")));
+        assert!(messages.iter().any(|m| m.contains("cvmcq24001,cvmcq24002")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("unknown system code 'zz'")));
+        assert!(messages.iter().any(|m| m.contains("'last_updated'")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("missing 'candidates_tested'")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("'discovered_count' must be")));
+    }
+
+    #[test]
+    fn test_missing_checkpoint_dir_is_an_error() {
+        let temp = tempfile::tempdir().unwrap();
+        assert!(validate_checkpoints(temp.path().to_str().unwrap()).is_err());
+    }
+}
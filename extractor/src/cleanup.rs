@@ -1,7 +1,8 @@
-use anyhow::Result;
-use std::collections::HashSet;
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tracing::{info, warn};
 
@@ -9,10 +10,113 @@ use crate::models::ApiQuestionResponse;
 
 use super::{MKSAPExtractor, CHECKPOINT_DIR_NAME};
 
+/// A question ID extracted into more than one directory.
+#[derive(Debug, Clone)]
+pub struct DuplicateQuestion {
+    pub question_id: String,
+    /// Newest `extracted_at` first; the first copy is the one `--fix` keeps.
+    pub copies: Vec<DuplicateCopy>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DuplicateCopy {
+    pub system_id: String,
+    pub path: PathBuf,
+    /// `None` when the JSON is missing, unreadable or has no valid timestamp.
+    pub extracted_at: Option<DateTime<FixedOffset>>,
+}
+
 impl MKSAPExtractor {
+    /// Every question ID whose directory appears under more than one system, sorted by ID.
+    pub fn find_duplicate_questions(&self) -> Result<Vec<DuplicateQuestion>> {
+        let mut skip_dirs = HashSet::new();
+        skip_dirs.insert(CHECKPOINT_DIR_NAME);
+        let entries = crate::io::scan_question_directories(
+            Path::new(&self.output_dir),
+            &skip_dirs,
+            |entry| {
+                entry
+                    .path
+                    .join(format!("{}.json", entry.question_id))
+                    .is_file()
+            },
+        )?;
+
+        let mut by_id: BTreeMap<String, Vec<DuplicateCopy>> = BTreeMap::new();
+        for entry in entries {
+            let extracted_at = read_extracted_at(&entry.path, &entry.question_id);
+            by_id
+                .entry(entry.question_id)
+                .or_default()
+                .push(DuplicateCopy {
+                    system_id: entry.system_id,
+                    path: entry.path,
+                    extracted_at,
+                });
+        }
+
+        Ok(by_id
+            .into_iter()
+            .filter(|(_, copies)| copies.len() > 1)
+            .map(|(question_id, mut copies)| {
+                copies.sort_by(|a, b| {
+                    b.extracted_at
+                        .cmp(&a.extracted_at)
+                        .then_with(|| a.path.cmp(&b.path))
+                });
+                DuplicateQuestion {
+                    question_id,
+                    copies,
+                }
+            })
+            .collect())
+    }
+
+    /// Keep the newest copy of each duplicate and move the others to
+    /// `mksap_data_failed/duplicates/<system>/<question_id>`. Returns the number moved.
+    pub fn resolve_duplicate_questions(&self, duplicates: &[DuplicateQuestion]) -> Result<usize> {
+        let mut moved_count = 0;
+        let duplicates_dir = self.duplicates_dir();
+
+        for duplicate in duplicates {
+            for copy in duplicate.copies.iter().skip(1) {
+                let dest = duplicates_dir
+                    .join(&copy.system_id)
+                    .join(&duplicate.question_id);
+                if dest.exists() {
+                    warn!(
+                        "Not moving {}: {} already exists",
+                        copy.path.display(),
+                        dest.display()
+                    );
+                    continue;
+                }
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create {}", parent.display()))?;
+                }
+                match fs::rename(&copy.path, &dest) {
+                    Ok(()) => {
+                        info!(
+                            "Moved duplicate {} to {}",
+                            copy.path.display(),
+                            dest.display()
+                        );
+                        moved_count += 1;
+                    }
+                    Err(e) => {
+                        warn!("Failed to move duplicate {}: {}", copy.path.display(), e);
+                    }
+                }
+            }
+        }
+
+        Ok(moved_count)
+    }
+
     pub async fn cleanup_retired_questions(&self) -> Result<usize> {
         let mut moved_count = 0;
-        let retired_dir = self.failed_root().join("retired");
+        let retired_dir = self.retired_dir();
         fs::create_dir_all(&retired_dir)?;
 
         info!("Scanning extracted questions for retired entries...");
@@ -146,3 +250,53 @@ impl MKSAPExtractor {
         }
     }
 }
+
+fn read_extracted_at(question_dir: &Path, question_id: &str) -> Option<DateTime<FixedOffset>> {
+    let json_path = question_dir.join(format!("{}.json", question_id));
+    let contents = fs::read_to_string(json_path).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    DateTime::parse_from_rfc3339(value.get("extracted_at")?.as_str()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question_with;
+
+    #[test]
+    fn test_find_and_resolve_duplicates_keeps_newest() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("mksap_data");
+        let write = |system: &str, question_id: &str, extracted_at: &str| {
+            let extracted_at = serde_json::json!({ "extracted_at": extracted_at });
+            write_question_with(&data_dir, system, question_id, extracted_at);
+        };
+        write("cv", "cvmcq24001", "2025-01-01T10:00:00+00:00");
+        write("fc", "cvmcq24001", "2025-03-01T10:00:00-05:00");
+        write("gi", "cvmcq24001", "not a date");
+        write("cv", "cvmcq24002", "2025-01-01T10:00:00+00:00");
+        fs::create_dir_all(data_dir.join("en").join("cvmcq24002")).unwrap();
+
+        let extractor =
+            MKSAPExtractor::new("http://localhost", data_dir.to_str().unwrap()).unwrap();
+        let duplicates = extractor.find_duplicate_questions().unwrap();
+        assert_eq!(duplicates.len(), 1);
+        let systems: Vec<_> = duplicates[0]
+            .copies
+            .iter()
+            .map(|copy| copy.system_id.as_str())
+            .collect();
+        assert_eq!(systems, vec!["fc", "cv", "gi"]);
+
+        assert_eq!(
+            extractor.resolve_duplicate_questions(&duplicates).unwrap(),
+            2
+        );
+        assert!(data_dir.join("fc/cvmcq24001").is_dir());
+        assert!(!data_dir.join("cv/cvmcq24001").exists());
+        let moved = temp.path().join("mksap_data_failed/duplicates");
+        assert!(moved.join("cv/cvmcq24001/cvmcq24001.json").is_file());
+        assert!(moved.join("gi/cvmcq24001").is_dir());
+        assert!(extractor.find_duplicate_questions().unwrap().is_empty());
+    }
+}
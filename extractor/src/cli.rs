@@ -1,30 +1,93 @@
 //! CLI argument parsing and option structs.
 
+use anyhow::Result;
 use std::path::Path;
+use std::time::Duration;
 
-use crate::app::{BASE_URL, OUTPUT_DIR};
+use crate::app::output_dir;
+use crate::assets::asset_store::QuestionTypeFilter;
+use crate::http::HttpTimeouts;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct StandardizeOptions {
     pub dry_run: bool,
     pub system_filter: Option<String>,
+    pub force: bool,
+    pub normalize_whitespace: bool,
+    /// Media base rewrite as `old=new` (`--rewrite-media-base`).
+    pub rewrite_media_base: Option<String>,
+    /// Normalize media path separators/extension case and fix file names (`--repair-media-paths`).
+    pub repair_media_paths: bool,
 }
 
 #[derive(Debug)]
 pub struct RunOptions {
     pub refresh_existing: bool,
+    /// Maximum in-flight API requests (`--concurrency N`); falls back to the config file's
+    /// `concurrency`, then `MKSAP_CONCURRENCY`.
+    pub concurrency: Option<usize>,
+    /// System codes to extract (`--system cv`, repeatable); empty means all.
+    pub systems: Vec<String>,
+    /// System codes to skip (`--exclude-system np`, repeatable); wins over `systems`.
+    pub excluded_systems: Vec<String>,
+    /// Per-question latency profile path (`--profile [path]`).
+    pub profile: Option<String>,
+    /// Number of slowest questions to summarize (`--profile-top N`).
+    pub profile_top: usize,
+    /// Only re-extract questions updated after this date (`--since YYYY-MM-DD|last`).
+    pub since: Option<String>,
+    /// Extract at most this many questions per system (`--limit N`).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SqliteExportOptions {
+    /// Destination SQLite database path.
+    pub db_path: String,
+}
+
+#[derive(Debug)]
+pub struct StemExportOptions {
+    /// Output format: `json` or `csv`.
+    pub format: String,
+    /// Optional system code filter (e.g. `cv`).
+    pub system_filter: Option<String>,
+    /// Destination file; defaults to `question_stems.<format>`.
+    pub out_path: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AnkiExportOptions {
+    /// Optional system code filter (e.g. `cv`).
+    pub system_filter: Option<String>,
+    /// Destination `.apkg` path.
+    pub out_path: String,
+}
+
+#[derive(Debug)]
+pub struct SystemExportOptions {
+    /// System code to export (e.g. `cv`).
+    pub system: Option<String>,
+    /// Destination folder; defaults to `mksap_<system>` (or `mksap_topics`).
+    pub into: Option<String>,
+    /// Arrange questions by learning plan topic instead of by system.
+    pub group_by_topic: bool,
 }
 
 #[derive(Debug)]
 pub struct MediaOptions {
-    /// Base API URL (default: https://mksap.acponline.org).
+    /// Base API URL (default: `MKSAP_BASE_URL` or https://mksap.acponline.org).
     pub base_url: String,
     /// Output directory for extracted data.
     pub data_dir: String,
+    /// Discovery checkpoints read by media discovery (default: `<data_dir>/.checkpoints`).
+    pub checkpoint_dir: String,
     /// Discovery metadata file path.
     pub discovery_file: String,
     /// Optional question ID filter.
     pub question_id: Option<String>,
+    /// Question types to keep, e.g. `mcq,vdx` (`--question-type`).
+    pub question_types: Option<String>,
     /// Download all discovered items when true.
     pub all: bool,
     /// Skip figure downloads.
@@ -33,8 +96,21 @@ pub struct MediaOptions {
     pub skip_tables: bool,
     /// Skip SVG downloads.
     pub skip_svgs: bool,
+    /// Download videos directly via their `mp4Hash` metadata.
+    pub download_videos: bool,
+    /// Keep one copy of identical figures in `.media_store/` and link to it.
+    pub dedupe_media: bool,
+    /// Fail the download when any discovery-expected media file is missing afterwards.
+    pub strict_media: bool,
+    /// Re-download media for questions whose recorded files are all on disk (`--refresh`).
+    pub refresh: bool,
     /// Concurrent request count for discovery.
     pub concurrent_requests: usize,
+    /// Probe increasing concurrency levels and recommend one instead of discovering.
+    pub calibrate: bool,
+    /// Requests per second shared by all media requests (`--rps`, 0 disables the limit).
+    /// Unlimited by default under `--calibrate`, which measures the server itself.
+    pub rps: f64,
     /// WebDriver URL for SVG browser downloads.
     pub webdriver_url: String,
     /// Run browser in headless mode.
@@ -47,40 +123,121 @@ pub struct MediaOptions {
     pub password: Option<String>,
     /// Timeout in seconds for browser login.
     pub login_timeout_secs: u64,
+    /// WebDriver connection attempts before giving up.
+    pub webdriver_connect_attempts: u32,
+    /// Browser sessions loading question pages in parallel (`--browser-concurrency`).
+    pub browser_concurrency: usize,
+    /// Maximum redirects followed by download clients (0 disables redirects).
+    pub max_redirects: usize,
+    /// Resume media discovery from its partial checkpoint.
+    pub resume: bool,
+    /// Skip questions whose media is already recorded and on disk (`--only-missing`).
+    pub only_missing: bool,
+    /// Render SVGs to PNG after the browser step.
+    pub rasterize_svgs: bool,
+    /// DPI for SVG rasterization (`--svg-dpi`).
+    pub svg_dpi: f32,
+    /// Print what a download would fetch without fetching or writing (`--dry-run`).
+    pub dry_run: bool,
+    /// Scan at most this many question IDs in media discovery (`--limit N`).
+    pub limit: Option<usize>,
+    /// Systems whose questions media discovery skips (`--exclude-system`, repeatable).
+    pub excluded_systems: Vec<String>,
 }
 
 impl MediaOptions {
     pub fn from_args(args: &[String]) -> Self {
+        let calibrate = has_flag(args, "--calibrate");
+        let settings = crate::settings::file_config().resolve(args);
+        let data_dir = settings
+            .output_dir
+            .unwrap_or_else(|| output_dir().to_string());
         Self {
-            base_url: resolve_media_base_url(args),
-            data_dir: resolve_media_data_dir(args),
-            discovery_file: resolve_media_discovery_file(args),
+            base_url: settings
+                .base_url
+                .unwrap_or_else(|| crate::app::site_config().base_url.clone()),
+            checkpoint_dir: parse_arg_value(args, "--checkpoint-dir").unwrap_or_else(|| {
+                Path::new(&data_dir)
+                    .join(".checkpoints")
+                    .to_string_lossy()
+                    .into_owned()
+            }),
+            data_dir,
+            discovery_file: settings
+                .discovery_file
+                .unwrap_or_else(default_discovery_file),
             question_id: parse_arg_value(args, "--question-id"),
+            question_types: parse_arg_value(args, "--question-type"),
             all: has_flag(args, "--all"),
             skip_figures: has_flag(args, "--skip-figures"),
             skip_tables: has_flag(args, "--skip-tables"),
             skip_svgs: has_flag(args, "--skip-svgs"),
+            download_videos: has_flag(args, "--download-videos"),
+            dedupe_media: has_flag(args, "--dedupe-media"),
+            strict_media: has_flag(args, "--strict-media"),
+            refresh: has_flag(args, "--refresh"),
             concurrent_requests: resolve_media_concurrency(args),
-            webdriver_url: parse_arg_value(args, "--webdriver-url")
+            calibrate,
+            rps: parse_arg_value(args, "--rps")
+                .and_then(|value| value.parse::<f64>().ok())
+                .filter(|rps| rps.is_finite() && *rps >= 0.0)
+                .unwrap_or(if calibrate {
+                    0.0
+                } else {
+                    crate::assets::rate_limit::DEFAULT_RPS
+                }),
+            webdriver_url: settings
+                .webdriver_url
                 .unwrap_or_else(|| "http://localhost:9515".to_string()),
-            headless: parse_bool_arg(args, "--headless", true),
-            interactive_login: parse_bool_arg(args, "--interactive-login", false),
-            username: parse_arg_value(args, "--username"),
-            password: parse_arg_value(args, "--password"),
-            login_timeout_secs: parse_arg_value(args, "--login-timeout-secs")
-                .and_then(|value| value.parse::<u64>().ok())
-                .unwrap_or(120),
+            headless: settings.login.headless.unwrap_or(true),
+            interactive_login: settings.login.interactive.unwrap_or(false),
+            username: settings.login.username,
+            password: settings.login.password,
+            login_timeout_secs: settings.login.timeout_secs.unwrap_or(120),
+            webdriver_connect_attempts: parse_arg_value(args, "--webdriver-connect-attempts")
+                .and_then(|value| value.parse::<u32>().ok())
+                .unwrap_or(5),
+            browser_concurrency: parse_arg_value(args, "--browser-concurrency")
+                .and_then(|value| value.parse::<usize>().ok())
+                .filter(|count| *count > 0)
+                .unwrap_or(1),
+            max_redirects: resolve_max_redirects(args),
+            resume: has_flag(args, "--resume"),
+            only_missing: has_flag(args, "--only-missing"),
+            rasterize_svgs: has_flag(args, "--rasterize-svgs"),
+            svg_dpi: parse_arg_value(args, "--svg-dpi")
+                .and_then(|value| value.parse::<f32>().ok())
+                .filter(|dpi| *dpi > 0.0)
+                .unwrap_or(crate::assets::svg_rasterize::DEFAULT_SVG_DPI),
+            dry_run: has_flag(args, "--dry-run"),
+            limit: parse_limit(args),
+            excluded_systems: parse_arg_values(args, "--exclude-system"),
         }
     }
+
+    /// Parsed `--question-type` filter, if one was given.
+    pub fn question_type_filter(&self) -> Result<Option<QuestionTypeFilter>> {
+        self.question_types
+            .as_deref()
+            .map(QuestionTypeFilter::parse)
+            .transpose()
+    }
 }
 
 pub fn parse_standardize_options(args: &[String]) -> StandardizeOptions {
     let dry_run = has_flag(args, "--dry-run");
     let system_filter = parse_arg_value(args, "--system");
 
+    let force = has_flag(args, "--force");
+    let normalize_whitespace = has_flag(args, "--normalize-whitespace");
+
     StandardizeOptions {
         dry_run,
         system_filter,
+        force,
+        normalize_whitespace,
+        rewrite_media_base: parse_arg_value(args, "--rewrite-media-base"),
+        repair_media_paths: has_flag(args, "--repair-media-paths"),
     }
 }
 
@@ -89,7 +246,90 @@ pub fn parse_run_options(args: &[String]) -> RunOptions {
         arg == "--refresh-existing" || arg == "--overwrite-existing" || arg == "--overwrite"
     });
 
-    RunOptions { refresh_existing }
+    let concurrency = crate::settings::file_config().resolve(args).concurrency;
+
+    RunOptions {
+        refresh_existing,
+        concurrency,
+        systems: parse_arg_values(args, "--system"),
+        excluded_systems: parse_arg_values(args, "--exclude-system"),
+        profile: parse_arg_value(args, "--profile").or_else(|| {
+            has_flag(args, "--profile").then(|| {
+                Path::new(output_dir())
+                    .join(crate::profile::PROFILE_FILE_NAME)
+                    .to_string_lossy()
+                    .to_string()
+            })
+        }),
+        profile_top: parse_arg_value(args, "--profile-top")
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or(crate::profile::DEFAULT_PROFILE_TOP),
+        since: parse_arg_value(args, "--since"),
+        limit: parse_limit(args),
+    }
+}
+
+/// `--watch [--interval <dur>]`: the interval between repeated runs, or `None` to run once.
+pub fn parse_watch_interval(args: &[String]) -> Option<Duration> {
+    has_flag(args, "--watch").then(|| {
+        parse_arg_value(args, "--interval")
+            .and_then(|value| crate::watch::parse_interval(&value))
+            .unwrap_or(crate::watch::DEFAULT_WATCH_INTERVAL)
+    })
+}
+
+pub fn parse_sqlite_export_options(args: &[String]) -> SqliteExportOptions {
+    let db_path = parse_arg_value(args, "--db-path").unwrap_or_else(|| "mksap.sqlite".to_string());
+
+    SqliteExportOptions { db_path }
+}
+
+pub fn parse_stem_export_options(args: &[String]) -> StemExportOptions {
+    StemExportOptions {
+        format: parse_arg_value(args, "--format").unwrap_or_else(|| "json".to_string()),
+        system_filter: parse_arg_value(args, "--system"),
+        out_path: parse_arg_value(args, "--out"),
+    }
+}
+
+pub fn parse_anki_export_options(args: &[String]) -> AnkiExportOptions {
+    AnkiExportOptions {
+        system_filter: parse_arg_value(args, "--system"),
+        out_path: parse_arg_value(args, "--out").unwrap_or_else(|| "mksap.apkg".to_string()),
+    }
+}
+
+pub fn parse_system_export_options(args: &[String]) -> SystemExportOptions {
+    SystemExportOptions {
+        system: parse_arg_value(args, "--system"),
+        into: parse_arg_value(args, "--into"),
+        group_by_topic: has_flag(args, "--group-by-topic"),
+    }
+}
+
+/// Parse `--request-timeout` / `--connect-timeout` / `--download-timeout` (seconds), falling
+/// back to defaults.
+pub fn parse_http_timeouts(args: &[String]) -> HttpTimeouts {
+    let defaults = HttpTimeouts::default();
+    let seconds = |key: &str| {
+        parse_arg_value(args, key)
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    };
+
+    HttpTimeouts {
+        request: seconds("--request-timeout").unwrap_or(defaults.request),
+        connect: seconds("--connect-timeout").unwrap_or(defaults.connect),
+        download: seconds("--download-timeout").unwrap_or(defaults.download),
+    }
+}
+
+/// Parse `--max-file-size <bytes>` (0 disables the cap), falling back to the default.
+pub fn parse_max_file_size(args: &[String]) -> u64 {
+    parse_arg_value(args, "--max-file-size")
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(crate::http::DEFAULT_MAX_FILE_SIZE)
 }
 
 pub(crate) fn parse_arg_value(args: &[String], key: &str) -> Option<String> {
@@ -103,6 +343,22 @@ pub(crate) fn parse_arg_value(args: &[String], key: &str) -> Option<String> {
         })
 }
 
+/// Every value given for a repeatable option (`--system cv --system en` or `--system=cv`).
+pub(crate) fn parse_arg_values(args: &[String], key: &str) -> Vec<String> {
+    let prefix = format!("{}=", key);
+    let mut values = Vec::new();
+    for (idx, arg) in args.iter().enumerate() {
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            values.push(value.to_string());
+        } else if arg == key {
+            if let Some(value) = args.get(idx + 1).filter(|value| !value.starts_with('-')) {
+                values.push(value.clone());
+            }
+        }
+    }
+    values
+}
+
 pub(crate) fn has_flag(args: &[String], flag: &str) -> bool {
     args.iter().any(|arg| arg == flag)
 }
@@ -123,21 +379,27 @@ pub(crate) fn parse_bool_arg(args: &[String], flag: &str, default: bool) -> bool
     default
 }
 
-fn resolve_media_base_url(args: &[String]) -> String {
-    parse_arg_value(args, "--base-url").unwrap_or_else(|| BASE_URL.to_string())
+/// `--limit N` for quick sample runs; zero is ignored.
+fn parse_limit(args: &[String]) -> Option<usize> {
+    parse_arg_value(args, "--limit")
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|limit| *limit > 0)
 }
 
-fn resolve_media_data_dir(args: &[String]) -> String {
-    parse_arg_value(args, "--data-dir").unwrap_or_else(|| OUTPUT_DIR.to_string())
+fn resolve_max_redirects(args: &[String]) -> usize {
+    if has_flag(args, "--no-redirects") {
+        return 0;
+    }
+    parse_arg_value(args, "--max-redirects")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(crate::http::DEFAULT_MAX_REDIRECTS)
 }
 
-fn resolve_media_discovery_file(args: &[String]) -> String {
-    parse_arg_value(args, "--discovery-file").unwrap_or_else(|| {
-        Path::new(OUTPUT_DIR)
-            .join("media_discovery.json")
-            .to_string_lossy()
-            .to_string()
-    })
+fn default_discovery_file() -> String {
+    Path::new(output_dir())
+        .join("media_discovery.json")
+        .to_string_lossy()
+        .to_string()
 }
 
 fn resolve_media_concurrency(args: &[String]) -> usize {
@@ -146,3 +408,20 @@ fn resolve_media_concurrency(args: &[String]) -> usize {
         .filter(|value| *value > 0)
         .unwrap_or(10)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_run_options_collect_repeated_systems() {
+        let options =
+            parse_run_options(&args(&["run", "--system", "cv", "--system=en", "--system"]));
+        assert_eq!(options.systems, vec!["cv", "en"]);
+        assert!(parse_run_options(&args(&["run"])).systems.is_empty());
+    }
+}
@@ -6,12 +6,34 @@ pub enum Command {
     CleanupFlat,
     DiscoveryStats,
     RetryMissing,
+    RetryMediaFailures,
     ListMissing,
     Standardize,
     MediaDiscover,
     MediaDownload,
     SvgBrowser,
     ExtractAll,
+    ExportSqlite,
+    ExportStems,
+    ExportAnki,
+    ExportSystem,
+    MediaDimensions,
+    ProgressHistory,
+    DiscoveryReport,
+    RasterizeSvgs,
+    ValidateCheckpoints,
+    Diff,
+    ExportSchema,
+    ExportMarkdown,
+    MediaIndex,
+    Stats,
+    VerifyJson,
+    MediaManifest,
+    FindDuplicates,
+    Login,
+    ProgressBadges,
+    Search,
+    Doctor,
 }
 
 impl Command {
@@ -22,21 +44,68 @@ impl Command {
             Some("cleanup-flat") => Command::CleanupFlat,
             Some("discovery-stats") => Command::DiscoveryStats,
             Some("retry-missing") => Command::RetryMissing,
+            Some("retry-media-failures") => Command::RetryMediaFailures,
             Some("list-missing") => Command::ListMissing,
             Some("standardize") => Command::Standardize,
             Some("media-discover") => Command::MediaDiscover,
             Some("media-download") => Command::MediaDownload,
             Some("svg-browser") => Command::SvgBrowser,
             Some("extract-all") => Command::ExtractAll,
+            Some("export-sqlite") => Command::ExportSqlite,
+            Some("export-stems") => Command::ExportStems,
+            Some("export-anki") => Command::ExportAnki,
+            Some("export-system") => Command::ExportSystem,
+            Some("media-dimensions") => Command::MediaDimensions,
+            Some("progress-history") => Command::ProgressHistory,
+            Some("discovery-report") => Command::DiscoveryReport,
+            Some("rasterize-svgs") => Command::RasterizeSvgs,
+            Some("validate-checkpoints") => Command::ValidateCheckpoints,
+            Some("diff") => Command::Diff,
+            Some("export-schema") => Command::ExportSchema,
+            Some("export-markdown") => Command::ExportMarkdown,
+            Some("media-index") => Command::MediaIndex,
+            Some("stats") => Command::Stats,
+            Some("verify-json") => Command::VerifyJson,
+            Some("media-manifest") => Command::MediaManifest,
+            Some("find-duplicates") => Command::FindDuplicates,
+            Some("login") => Command::Login,
+            Some("progress-badges") => Command::ProgressBadges,
+            Some("search") => Command::Search,
+            Some("doctor") => Command::Doctor,
             _ => Command::Run,
         }
     }
 
+    /// Commands that write into the output tree and must not run concurrently.
+    pub fn is_mutating(self) -> bool {
+        matches!(
+            self,
+            Command::Run
+                | Command::CleanupRetired
+                | Command::CleanupFlat
+                | Command::RetryMissing
+                | Command::RetryMediaFailures
+                | Command::ListMissing
+                | Command::Standardize
+                | Command::MediaDiscover
+                | Command::MediaDownload
+                | Command::SvgBrowser
+                | Command::ExtractAll
+                | Command::RasterizeSvgs
+                | Command::FindDuplicates
+                | Command::ProgressHistory
+                | Command::MediaIndex
+                | Command::MediaManifest
+                | Command::ProgressBadges
+        )
+    }
+
     pub fn requires_auth(self) -> bool {
         matches!(
             self,
             Command::Run
                 | Command::RetryMissing
+                | Command::RetryMediaFailures
                 | Command::ListMissing
                 | Command::MediaDiscover
                 | Command::MediaDownload
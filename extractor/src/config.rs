@@ -173,6 +173,59 @@ pub fn build_categories_from_config() -> Vec<Category> {
         .collect()
 }
 
+/// Restrict `categories` to the given system codes, keeping config order.
+///
+/// An empty `systems` list keeps every category; unknown codes are an error.
+pub fn select_categories(
+    categories: &[Category],
+    systems: &[String],
+) -> anyhow::Result<Vec<Category>> {
+    if systems.is_empty() {
+        return Ok(categories.to_vec());
+    }
+    ensure_known_codes(categories, systems)?;
+    Ok(categories
+        .iter()
+        .filter(|category| systems.contains(&category.code))
+        .cloned()
+        .collect())
+}
+
+/// [`select_categories`] for `--system`, then drop every `--exclude-system` code; an
+/// exclusion wins when a code is in both lists. Unknown codes in either list are an error.
+pub fn filter_categories(
+    categories: &[Category],
+    systems: &[String],
+    excluded: &[String],
+) -> anyhow::Result<Vec<Category>> {
+    ensure_known_codes(categories, excluded)?;
+    Ok(select_categories(categories, systems)?
+        .into_iter()
+        .filter(|category| !excluded.contains(&category.code))
+        .collect())
+}
+
+/// Fail when any of `codes` is not one of `categories`.
+pub fn ensure_known_codes(categories: &[Category], codes: &[String]) -> anyhow::Result<()> {
+    let unknown: Vec<&str> = codes
+        .iter()
+        .map(String::as_str)
+        .filter(|code| !categories.iter().any(|category| category.code == *code))
+        .collect();
+    if !unknown.is_empty() {
+        let known: Vec<&str> = categories
+            .iter()
+            .map(|category| category.code.as_str())
+            .collect();
+        anyhow::bail!(
+            "Unknown system code(s): {} (known: {})",
+            unknown.join(", "),
+            known.join(", ")
+        );
+    }
+    Ok(())
+}
+
 /// Look up a question system code by its unique identifier.
 ///
 /// # Arguments
@@ -196,3 +249,52 @@ pub fn build_categories_from_config() -> Vec<Category> {
 pub fn get_organ_system_by_id(id: &str) -> Option<OrganSystem> {
     init_organ_systems().into_iter().find(|s| s.id == id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_categories_filters_and_rejects_unknown_codes() {
+        let categories = build_categories_from_config();
+        assert_eq!(select_categories(&categories, &[]).unwrap().len(), 16);
+
+        let selected =
+            select_categories(&categories, &["rm".to_string(), "cv".to_string()]).unwrap();
+        let codes: Vec<&str> = selected
+            .iter()
+            .map(|category| category.code.as_str())
+            .collect();
+        assert_eq!(codes, vec!["cv", "rm"]);
+
+        let err = select_categories(&categories, &["cv".to_string(), "zz".to_string()])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown system code(s): zz"));
+    }
+
+    #[test]
+    fn test_filter_categories_exclusions_win_over_systems() {
+        let categories = build_categories_from_config();
+        let codes = |systems: &[&str], excluded: &[&str]| -> Vec<String> {
+            let owned =
+                |list: &[&str]| list.iter().map(|code| code.to_string()).collect::<Vec<_>>();
+            filter_categories(&categories, &owned(systems), &owned(excluded))
+                .unwrap()
+                .into_iter()
+                .map(|category| category.code)
+                .collect()
+        };
+
+        let all_but_two = codes(&[], &["np", "cv"]);
+        assert_eq!(all_but_two.len(), 14);
+        assert!(!all_but_two.contains(&"np".to_string()));
+        assert_eq!(codes(&["cv", "en"], &["cv"]), vec!["en"]);
+        assert_eq!(codes(&["cv"], &["en"]), vec!["cv"]);
+
+        let err = filter_categories(&categories, &[], &["zz".to_string()])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown system code(s): zz"));
+    }
+}
@@ -1,5 +1,7 @@
+use regex::Regex;
 use serde_json::Value;
 use std::collections::HashSet;
+use std::sync::OnceLock;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ContentIdKind {
@@ -87,45 +89,52 @@ pub fn collect_inline_table_nodes(value: &Value) -> Vec<&Value> {
     tables
 }
 
-fn matches_prefix(content_id: &str, prefixes: &[&str]) -> bool {
-    let lower = content_id.to_ascii_lowercase();
-    prefixes.iter().any(|prefix| {
-        lower.starts_with(prefix) || lower.get(2..).is_some_and(|tail| tail.starts_with(prefix))
-    })
-}
-
-pub fn is_figure_id(content_id: &str) -> bool {
-    matches_prefix(content_id, &["fig"])
-}
-
-pub fn is_table_id(content_id: &str) -> bool {
-    matches_prefix(content_id, &["tab"])
-}
-
-pub fn is_video_id(content_id: &str) -> bool {
-    matches_prefix(content_id, &["vid"])
-}
-
-pub fn is_svg_id(content_id: &str) -> bool {
-    matches_prefix(content_id, &["svg"])
-}
-
+/// Media kind of a content ID: an optional two-letter system code, the type token and a
+/// number, e.g. `cvfig24001`, `pmtab24009a`, `svg001`. Anything else is not media.
 pub fn classify_content_id(content_id: &str) -> Option<ContentIdKind> {
-    if is_figure_id(content_id) {
-        return Some(ContentIdKind::Figure);
-    }
-    if is_table_id(content_id) {
-        return Some(ContentIdKind::Table);
-    }
-    if is_video_id(content_id) {
-        return Some(ContentIdKind::Video);
-    }
-    if is_svg_id(content_id) {
-        return Some(ContentIdKind::Svg);
+    static CONTENT_ID_RE: OnceLock<Regex> = OnceLock::new();
+    let content_id_re = CONTENT_ID_RE
+        .get_or_init(|| Regex::new(r"(?i)^(?:[a-z]{2})?(fig|tab|vid|svg)[0-9]+[a-z]?$").unwrap());
+    let token = content_id_re.captures(content_id)?.get(1)?.as_str();
+    match token.to_ascii_lowercase().as_str() {
+        "fig" => Some(ContentIdKind::Figure),
+        "tab" => Some(ContentIdKind::Table),
+        "vid" => Some(ContentIdKind::Video),
+        "svg" => Some(ContentIdKind::Svg),
+        _ => None,
     }
-    None
 }
 
 pub fn inline_table_id(index: usize) -> String {
     format!("inline_table_{}", index + 1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_content_id_is_anchored() {
+        let cases = [
+            ("cvfig001", Some(ContentIdKind::Figure)),
+            ("CVFIG24001", Some(ContentIdKind::Figure)),
+            ("pmtab24009", Some(ContentIdKind::Table)),
+            ("nptab25001a", Some(ContentIdKind::Table)),
+            ("cvvid001", Some(ContentIdKind::Video)),
+            ("cvsvg001", Some(ContentIdKind::Svg)),
+            ("svg001", Some(ContentIdKind::Svg)),
+            ("svgtable001", None),
+            ("cvsvgtab001", None),
+            ("cvtabfig001", None),
+            ("cvfig", None),
+            ("cvmcq24001", None),
+            ("figure_1", None),
+            ("inline_table_1", None),
+            ("xcvfig001", None),
+            ("cvfig001.png", None),
+        ];
+        for (content_id, expected) in cases {
+            assert_eq!(classify_content_id(content_id), expected, "{}", content_id);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Compare two extraction snapshots (`diff <old_dir> <new_dir>`).
+//!
+//! Reports questions added, removed, or changed between two `mksap_data`
+//! trees. Fields that change on every extraction (`extracted_at`,
+//! `metadata.question_updated`) are ignored.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::export::load_questions;
+use crate::models::QuestionData;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedQuestion {
+    pub question_id: String,
+    pub system: String,
+    /// Names of the fields that differ, e.g. `["critique", "options"]`.
+    pub fields: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemDiffCounts {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct SnapshotDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ChangedQuestion>,
+    pub unchanged: usize,
+    pub by_system: BTreeMap<String, SystemDiffCounts>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Human-readable report listing IDs per change kind and counts per system.
+    pub fn render(&self, old_dir: &str, new_dir: &str) -> String {
+        let mut report = String::new();
+        report.push_str(&format!("Snapshot diff: {} -> {}\n\n", old_dir, new_dir));
+        report.push_str(&format!(
+            "Added: {}  Removed: {}  Changed: {}  Unchanged: {}\n\n",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len(),
+            self.unchanged
+        ));
+
+        if !self.by_system.is_empty() {
+            report.push_str("BY SYSTEM\n");
+            for (system, counts) in &self.by_system {
+                report.push_str(&format!(
+                    "- {}: +{} -{} ~{}\n",
+                    system, counts.added, counts.removed, counts.changed
+                ));
+            }
+            report.push('\n');
+        }
+
+        if !self.added.is_empty() {
+            report.push_str("ADDED\n");
+            for question_id in &self.added {
+                report.push_str(&format!("+ {}\n", question_id));
+            }
+            report.push('\n');
+        }
+
+        if !self.removed.is_empty() {
+            report.push_str("REMOVED\n");
+            for question_id in &self.removed {
+                report.push_str(&format!("- {}\n", question_id));
+            }
+            report.push('\n');
+        }
+
+        if !self.changed.is_empty() {
+            report.push_str("CHANGED\n");
+            for change in &self.changed {
+                report.push_str(&format!(
+                    "~ {} [{}]\n",
+                    change.question_id,
+                    change.fields.join(", ")
+                ));
+            }
+            report.push('\n');
+        }
+
+        report
+    }
+}
+
+/// Compare every question under `old_dir` with the same question under `new_dir`.
+pub fn diff_snapshots(old_dir: &str, new_dir: &str) -> Result<SnapshotDiff> {
+    let old: HashMap<String, QuestionData> = load_questions(old_dir)?
+        .into_iter()
+        .map(|(entry, question)| (entry.question_id, question))
+        .collect();
+    let new = load_questions(new_dir)?;
+
+    let mut diff = SnapshotDiff::default();
+    let mut seen = std::collections::HashSet::new();
+
+    for (entry, question) in &new {
+        seen.insert(entry.question_id.as_str());
+        let counts = diff.by_system.entry(question.category.clone()).or_default();
+        match old.get(&entry.question_id) {
+            None => {
+                counts.added += 1;
+                diff.added.push(entry.question_id.clone());
+            }
+            Some(previous) => {
+                let fields = changed_fields(previous, question);
+                if fields.is_empty() {
+                    diff.unchanged += 1;
+                } else {
+                    counts.changed += 1;
+                    diff.changed.push(ChangedQuestion {
+                        question_id: entry.question_id.clone(),
+                        system: question.category.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut removed: Vec<&QuestionData> = old
+        .iter()
+        .filter(|(question_id, _)| !seen.contains(question_id.as_str()))
+        .map(|(_, question)| question)
+        .collect();
+    removed.sort_by(|a, b| a.question_id.cmp(&b.question_id));
+    for question in removed {
+        diff.by_system
+            .entry(question.category.clone())
+            .or_default()
+            .removed += 1;
+        diff.removed.push(question.question_id.clone());
+    }
+
+    Ok(diff)
+}
+
+/// Diff two snapshot directories and print the report.
+pub fn run_snapshot_diff(old_dir: &str, new_dir: &str) -> Result<()> {
+    for dir in [old_dir, new_dir] {
+        if !std::path::Path::new(dir).is_dir() {
+            bail!("Snapshot directory not found: {}", dir);
+        }
+    }
+
+    let diff = diff_snapshots(old_dir, new_dir)?;
+    print!("{}", diff.render(old_dir, new_dir));
+    if diff.is_empty() {
+        println!("No differences found.");
+    }
+    Ok(())
+}
+
+fn changed_fields(old: &QuestionData, new: &QuestionData) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    let mut check = |name: &'static str, differs: bool| {
+        if differs {
+            fields.push(name);
+        }
+    };
+
+    check(
+        "educational_objective",
+        normalize(&old.educational_objective) != normalize(&new.educational_objective),
+    );
+    check(
+        "question_text",
+        normalize(&old.question_text) != normalize(&new.question_text),
+    );
+    check(
+        "question_stem",
+        normalize(&old.question_stem) != normalize(&new.question_stem),
+    );
+    check("options", option_texts(old) != option_texts(new));
+    check(
+        "correct_answer",
+        old.user_performance.correct_answer != new.user_performance.correct_answer,
+    );
+    check("peer_percentages", peer_stats(old) != peer_stats(new));
+    check(
+        "critique",
+        normalize(&old.critique) != normalize(&new.critique),
+    );
+    check(
+        "key_points",
+        normalize_all(&old.key_points) != normalize_all(&new.key_points),
+    );
+    check(
+        "references",
+        normalize(&old.references) != normalize(&new.references),
+    );
+    check(
+        "related_content",
+        to_value(&old.related_content) != to_value(&new.related_content),
+    );
+    check("media", to_value(&old.media) != to_value(&new.media));
+
+    fields
+}
+
+/// Collapse whitespace so reformatting alone is not reported as a change.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn normalize_all(texts: &[String]) -> Vec<String> {
+    texts.iter().map(|text| normalize(text)).collect()
+}
+
+fn option_texts(question: &QuestionData) -> Vec<(String, String)> {
+    question
+        .options
+        .iter()
+        .map(|option| (option.letter.clone(), normalize(&option.text)))
+        .collect()
+}
+
+fn peer_stats(question: &QuestionData) -> Vec<(String, u32)> {
+    question
+        .options
+        .iter()
+        .map(|option| (option.letter.clone(), option.peer_percentage))
+        .collect()
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question;
+    use std::fs;
+    use std::path::Path;
+
+    fn edit_question(data_dir: &Path, system: &str, question_id: &str, edit: impl Fn(&mut Value)) {
+        let path = data_dir
+            .join(system)
+            .join(question_id)
+            .join(format!("{}.json", question_id));
+        let mut value: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        edit(&mut value);
+        fs::write(&path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_and_changed() {
+        let temp = tempfile::tempdir().unwrap();
+        let old_dir = temp.path().join("old");
+        let new_dir = temp.path().join("new");
+        for id in ["cvmcq24001", "cvmcq24002", "cvmcq24003"] {
+            write_question(&old_dir, "cv", id);
+        }
+        for id in ["cvmcq24001", "cvmcq24002", "cvmcq24004"] {
+            write_question(&new_dir, "cv", id);
+        }
+        edit_question(&new_dir, "cv", "cvmcq24001", |value| {
+            value["critique"] = "Updated critique".into();
+            value["options"][1]["text"] = "Changed".into();
+            value["extracted_at"] = "2026-01-01T00:00:00Z".into();
+        });
+        edit_question(&new_dir, "cv", "cvmcq24002", |value| {
+            value["extracted_at"] = "2026-01-01T00:00:00Z".into();
+            value["metadata"]["question_updated"] = "01/01/2026".into();
+            value["critique"] = "  Critique \n".into();
+        });
+
+        let diff = diff_snapshots(old_dir.to_str().unwrap(), new_dir.to_str().unwrap()).unwrap();
+        assert_eq!(diff.added, vec!["cvmcq24004"]);
+        assert_eq!(diff.removed, vec!["cvmcq24003"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].question_id, "cvmcq24001");
+        assert_eq!(diff.changed[0].fields, vec!["options", "critique"]);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(
+            diff.by_system.get("cv"),
+            Some(&SystemDiffCounts {
+                added: 1,
+                removed: 1,
+                changed: 1
+            })
+        );
+
+        let report = diff.render("old", "new");
+        assert!(report.contains("~ cvmcq24001 [options, critique]"));
+        assert!(report.contains("- cv: +1 -1 ~1"));
+    }
+}
@@ -10,6 +10,7 @@ use std::time::Duration;
 use tokio::time::sleep;
 use tracing::debug;
 
+use crate::error::ExtractError;
 use crate::models::{DiscoveryMetadata, DiscoveryMetadataCollection};
 use crate::utils::parse_env;
 
@@ -50,14 +51,14 @@ impl MKSAPExtractor {
     ) -> Result<Vec<String>> {
         let question_ids = self.generate_question_ids(question_prefix);
         let total_to_try = question_ids.len();
-        let concurrency = Self::concurrency_limit();
+        let concurrency = self.concurrency;
 
         debug!("Testing {} potential question IDs...", total_to_try);
 
         let existing_ids = Arc::new(existing_ids.clone());
         let mut tested = 0usize;
 
-        let mut stream = stream::iter(question_ids.into_iter())
+        let mut stream = stream::iter(question_ids)
             .map(|question_id| {
                 let existing_ids = Arc::clone(&existing_ids);
                 async move {
@@ -157,6 +158,7 @@ impl MKSAPExtractor {
         let max_429_retries = parse_env("MKSAP_DISCOVERY_429_RETRIES", 8u32);
         let mut attempt = 0u32;
         let mut rate_limit_attempt = 0u32;
+        let _permit = self.request_permits.acquire().await?;
 
         loop {
             attempt += 1;
@@ -167,7 +169,7 @@ impl MKSAPExtractor {
                     status if status.is_success() => return Ok(true),
                     reqwest::StatusCode::NOT_FOUND => return Ok(false),
                     reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
-                        return Err(anyhow::anyhow!("Authentication expired"));
+                        return Err(ExtractError::Auth("session expired".to_string()).into());
                     }
                     reqwest::StatusCode::TOO_MANY_REQUESTS => {
                         rate_limit_attempt += 1;
@@ -270,7 +272,6 @@ impl MKSAPExtractor {
         let metadata_path = checkpoint_dir.join("discovery_metadata.json");
         let json = serde_json::to_string_pretty(metadata)
             .context("Failed to serialize discovery metadata")?;
-        fs::write(&metadata_path, json).context("Failed to write discovery metadata file")?;
-        Ok(())
+        super::io::write_atomic(&metadata_path, json)
     }
 }
@@ -0,0 +1,285 @@
+//! Preflight checks (`doctor`): session cookie, question API, content metadata, WebDriver
+//! and writable output/checkpoint directories, printed as a pass/fail checklist before a
+//! long run.
+
+use anyhow::{bail, Result};
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::ExtractError;
+
+/// Question fetched to prove the session works (the same one `--inspect-api` uses).
+pub const DOCTOR_QUESTION_ID: &str = "cvmcq25001";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+const WRITE_PROBE_FILE_NAME: &str = ".doctor_write_probe";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &'static str, result: Result<String>) -> Self {
+        match result {
+            Ok(detail) => DoctorCheck {
+                name,
+                passed: true,
+                detail,
+            },
+            Err(err) => DoctorCheck {
+                name,
+                passed: false,
+                detail: format!("{:#}", err),
+            },
+        }
+    }
+}
+
+pub struct DoctorTargets<'a> {
+    pub base_url: &'a str,
+    pub session_cookie: Option<&'a str>,
+    pub question_id: &'a str,
+    pub webdriver_url: &'a str,
+    pub output_dir: &'a str,
+    pub checkpoint_dir: &'a str,
+}
+
+/// Run every check; none stops the others.
+pub async fn run_checks(targets: &DoctorTargets<'_>) -> Vec<DoctorCheck> {
+    let session_cookie = targets
+        .session_cookie
+        .map(str::trim)
+        .filter(|cookie| !cookie.is_empty());
+    let headers = match session_cookie.map(crate::http::session_cookie_headers) {
+        Some(Ok(headers)) => headers,
+        _ => HeaderMap::new(),
+    };
+
+    let mut checks = vec![DoctorCheck::new(
+        "Session cookie",
+        match session_cookie {
+            Some(cookie) => Ok(format!(
+                "{} is set ({} characters)",
+                crate::session::session_cookie_name(),
+                cookie.len()
+            )),
+            None => Err(anyhow::anyhow!(
+                "not set; run `login`, pass --cookies-file or set MKSAP_SESSION"
+            )),
+        },
+    )];
+    let client = match crate::http::build_client_with_headers(headers) {
+        Ok(client) => client,
+        Err(err) => {
+            checks.push(DoctorCheck::new("HTTP client", Err(err)));
+            return checks;
+        }
+    };
+    checks.push(DoctorCheck::new(
+        "Question API",
+        check_question(&client, targets.base_url, targets.question_id).await,
+    ));
+    checks.push(DoctorCheck::new(
+        "Content metadata",
+        check_json(
+            &client,
+            &crate::endpoints::content_metadata(targets.base_url),
+        )
+        .await,
+    ));
+    checks.push(DoctorCheck::new(
+        "WebDriver",
+        check_webdriver(targets.webdriver_url).await,
+    ));
+    checks.push(DoctorCheck::new(
+        "Output directory",
+        check_writable(Path::new(targets.output_dir)),
+    ));
+    checks.push(DoctorCheck::new(
+        "Checkpoint directory",
+        check_writable(Path::new(targets.checkpoint_dir)),
+    ));
+    checks
+}
+
+pub async fn run_doctor(targets: &DoctorTargets<'_>) -> Result<()> {
+    let checks = run_checks(targets).await;
+    println!("\n=== MKSAP Preflight ===\n");
+    for check in &checks {
+        let mark = if check.passed { "✓" } else { "✗" };
+        println!("{} {:<22} {}", mark, check.name, check.detail);
+    }
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if failed > 0 {
+        bail!("{} of {} preflight checks failed", failed, checks.len());
+    }
+    println!("\n✅ All preflight checks passed\n");
+    Ok(())
+}
+
+/// An authenticated question fetch: HTTP 200 with question JSON, not `{"error": ...}`.
+async fn check_question(client: &Client, base_url: &str, question_id: &str) -> Result<String> {
+    let url = crate::endpoints::question_json(base_url, question_id);
+    let value = fetch_json(client, &url).await?;
+    if let Some(error) = value.get("error") {
+        bail!("{} answered with an error: {}", url, error);
+    }
+    Ok(format!("{} returned question JSON", url))
+}
+
+async fn check_json(client: &Client, url: &str) -> Result<String> {
+    fetch_json(client, url).await?;
+    Ok(format!("{} is reachable", url))
+}
+
+async fn fetch_json(client: &Client, url: &str) -> Result<Value> {
+    let response = crate::http::send_with_retry(client.get(url), CHECK_TIMEOUT, 0).await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ExtractError::from_status(status, url, response.headers()).into());
+    }
+    let text = response.text().await?;
+    serde_json::from_str(&text).map_err(|source| {
+        ExtractError::Parse {
+            what: url.to_string(),
+            source,
+        }
+        .into()
+    })
+}
+
+/// ChromeDriver (and other W3C drivers) answer `GET /status`. Asked with a bare client: the
+/// driver is local and must not see the session cookie or go through a proxy.
+async fn check_webdriver(webdriver_url: &str) -> Result<String> {
+    let client = Client::builder().no_proxy().build()?;
+    let url = format!("{}/status", webdriver_url.trim_end_matches('/'));
+    let response = crate::http::send_with_retry(client.get(&url), CHECK_TIMEOUT, 0)
+        .await
+        .map_err(|err| {
+            err.context(format!(
+                "no WebDriver at {} (only needed for svg-browser)",
+                webdriver_url
+            ))
+        })?;
+    if !response.status().is_success() {
+        bail!("{} answered HTTP {}", url, response.status());
+    }
+    Ok(format!("{} responded", webdriver_url))
+}
+
+/// Probe `dir` with a throwaway file. A missing dir is reported, not created: the check
+/// then probes the nearest existing parent, where the first run would create it.
+fn check_writable(dir: &Path) -> Result<String> {
+    if dir.is_dir() {
+        probe_write(dir)?;
+        return Ok(format!("{} is writable", dir.display()));
+    }
+    let parent = dir
+        .ancestors()
+        .skip(1)
+        .map(|ancestor| {
+            if ancestor.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                ancestor
+            }
+        })
+        .find(|ancestor| ancestor.is_dir())
+        .ok_or_else(|| anyhow::anyhow!("{} does not exist", dir.display()))?;
+    probe_write(parent).map_err(|err| err.context(format!("{} does not exist", dir.display())))?;
+    Ok(format!(
+        "{} does not exist yet; {} is writable, so the first run can create it",
+        dir.display(),
+        parent.display()
+    ))
+}
+
+fn probe_write(dir: &Path) -> Result<()> {
+    let probe = dir.join(WRITE_PROBE_FILE_NAME);
+    fs::write(&probe, b"ok")
+        .map_err(|err| anyhow::anyhow!("cannot write in {}: {}", dir.display(), err))?;
+    fs::remove_file(&probe).ok();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_doctor_reports_each_check() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24001.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "cvmcq24001"}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24002.json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"error": "Not authorized"}"#),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/content_metadata.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .mount(&server)
+            .await;
+        // The session cookie must not reach the WebDriver
+        Mock::given(method("GET"))
+            .and(path("/status"))
+            .and(header_exists("cookie"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"value": {}}"#))
+            .mount(&server)
+            .await;
+
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().join("data");
+        let checkpoint_dir = output_dir.join(".checkpoints");
+        let base_url = server.uri();
+        let mut targets = DoctorTargets {
+            base_url: &base_url,
+            session_cookie: Some("abc123"),
+            question_id: "cvmcq24001",
+            webdriver_url: &base_url,
+            output_dir: output_dir.to_str().unwrap(),
+            checkpoint_dir: checkpoint_dir.to_str().unwrap(),
+        };
+
+        let checks = run_checks(&targets).await;
+        assert_eq!(checks.len(), 6);
+        assert!(checks.iter().all(|check| check.passed), "{:?}", checks);
+        // Missing dirs are reported, not created
+        assert!(!output_dir.exists());
+        assert!(checks[5].detail.contains("does not exist yet"));
+        fs::create_dir_all(&checkpoint_dir).unwrap();
+        let checks = run_checks(&targets).await;
+        assert!(checks[5].passed && checks[5].detail.ends_with("is writable"));
+        assert!(!checkpoint_dir.join(WRITE_PROBE_FILE_NAME).exists());
+
+        targets.session_cookie = Some("  ");
+        targets.question_id = "cvmcq24002";
+        let failed: Vec<_> = run_checks(&targets)
+            .await
+            .into_iter()
+            .filter(|check| !check.passed)
+            .map(|check| check.name)
+            .collect();
+        assert_eq!(failed, vec!["Session cookie", "Question API"]);
+        assert!(run_doctor(&targets).await.is_err());
+    }
+}
@@ -14,6 +14,10 @@ pub(crate) fn content_metadata(base_url: &str) -> String {
     format!("{}/api/content_metadata.json", base_url)
 }
 
+pub(crate) fn login_page(base_url: &str) -> String {
+    format!("{}/users/sign_in", base_url)
+}
+
 pub(crate) fn answered_questions(base_url: &str) -> String {
     format!(
         "{}/app/question-bank/content-areas/cv/answered-questions",
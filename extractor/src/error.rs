@@ -0,0 +1,149 @@
+//! Typed errors for the fetch and auth layers, so callers can tell an expired session from a
+//! rate limit or a server error without matching on message text.
+//!
+//! Everything above these layers still returns `anyhow::Result`; `?` converts an
+//! [`ExtractError`] like any other error, and [`find_extract_error`] recovers it from the chain.
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Wait after a 429 that carries no usable `Retry-After`.
+const DEFAULT_RATE_LIMIT_WAIT_SECS: u64 = 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExtractError {
+    /// 401/403, or a login that was not accepted: the session is missing or expired.
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    #[error("HTTP {status} from {url}")]
+    Http { status: u16, url: String },
+    #[error("Failed to parse {what}: {source}")]
+    Parse {
+        what: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A 3xx that was not (or could no longer be) followed, usually a login redirect.
+    #[error("Redirect not followed for {url}: HTTP {status} -> {location}")]
+    Redirect {
+        url: String,
+        status: u16,
+        location: String,
+    },
+    /// A download whose bytes are not the expected file type or size.
+    #[error("Content mismatch for {url}: {reason}")]
+    ContentMismatch { url: String, reason: String },
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Rate limited by {url}")]
+    RateLimited {
+        url: String,
+        /// Seconds from the `Retry-After` header, when present.
+        retry_after: Option<u64>,
+    },
+    /// A media file over `--max-file-size`. `size` is the advertised length, or the bytes
+    /// received when a stream without one was cut off.
+    #[error("File too large: {url} is {size} bytes (--max-file-size {limit})")]
+    TooLarge { url: String, size: u64, limit: u64 },
+}
+
+impl ExtractError {
+    /// Map a non-success status to the matching variant.
+    pub fn from_status(status: StatusCode, url: &str, headers: &HeaderMap) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                ExtractError::Auth(format!("HTTP {} from {}", status.as_u16(), url))
+            }
+            StatusCode::NOT_FOUND => ExtractError::NotFound(url.to_string()),
+            StatusCode::TOO_MANY_REQUESTS => ExtractError::RateLimited {
+                url: url.to_string(),
+                retry_after: headers
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok()),
+            },
+            status => ExtractError::Http {
+                status: status.as_u16(),
+                url: url.to_string(),
+            },
+        }
+    }
+
+    /// HTTP status behind the error, if it came from a response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ExtractError::Http { status, .. } => Some(*status),
+            ExtractError::NotFound(_) => Some(404),
+            ExtractError::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Rate limits and 5xx responses are transient; everything else will fail again.
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            ExtractError::RateLimited { .. } => true,
+            ExtractError::Http { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// How long to wait before retry number `attempt` (1-based).
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        match self {
+            ExtractError::RateLimited { retry_after, .. } => {
+                Duration::from_secs(retry_after.unwrap_or(DEFAULT_RATE_LIMIT_WAIT_SECS))
+            }
+            _ => Duration::from_millis(500 * 2u64.pow(attempt.saturating_sub(1))),
+        }
+    }
+}
+
+/// The [`ExtractError`] anywhere in `err`'s chain.
+pub fn find_extract_error(err: &anyhow::Error) -> Option<&ExtractError> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<ExtractError>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_status_mapping_and_retriability() {
+        let url = "https://mksap.example/api/questions/cvmcq24001.json";
+        let none = HeaderMap::new();
+        let mut retry_after = HeaderMap::new();
+        retry_after.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+
+        let auth = ExtractError::from_status(StatusCode::FORBIDDEN, url, &none);
+        assert!(matches!(auth, ExtractError::Auth(_)));
+        assert!(!auth.is_retriable());
+
+        let limited = ExtractError::from_status(StatusCode::TOO_MANY_REQUESTS, url, &retry_after);
+        assert!(limited.is_retriable());
+        assert_eq!(limited.retry_delay(1), Duration::from_secs(7));
+
+        let unavailable = ExtractError::from_status(StatusCode::SERVICE_UNAVAILABLE, url, &none);
+        assert!(unavailable.is_retriable());
+        assert_eq!(unavailable.retry_delay(2), Duration::from_secs(1));
+        let missing = ExtractError::from_status(StatusCode::NOT_FOUND, url, &none);
+        assert_eq!(missing.status(), Some(404));
+        assert!(!missing.is_retriable());
+
+        let too_large = ExtractError::TooLarge {
+            url: url.to_string(),
+            size: 2048,
+            limit: 1024,
+        };
+        assert!(!too_large.is_retriable());
+
+        let err = anyhow::Error::from(unavailable).context("Failed to fetch cvmcq24001");
+        assert_eq!(
+            find_extract_error(&err).and_then(ExtractError::status),
+            Some(503)
+        );
+    }
+}
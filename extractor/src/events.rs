@@ -0,0 +1,120 @@
+//! Optional structured event sink (`--events path.jsonl`).
+//!
+//! Warnings and errors that matter for post-run analysis are also written as one
+//! JSON object per line, alongside the normal log output.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+
+static EVENT_SINK: OnceLock<EventSink> = OnceLock::new();
+
+#[derive(Debug, Serialize)]
+struct Event<'a> {
+    timestamp: String,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    question_id: Option<&'a str>,
+    details: &'a str,
+}
+
+pub(crate) struct EventSink {
+    file: Mutex<File>,
+}
+
+impl EventSink {
+    /// Open `path` for appending, creating it (and its parent directory) if needed.
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open event log {}", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub(crate) fn emit(&self, kind: &str, question_id: Option<&str>, details: &str) -> Result<()> {
+        let event = Event {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            kind,
+            question_id,
+            details,
+        };
+        let line = serde_json::to_string(&event)?;
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::anyhow!("event log lock poisoned"))?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// Route events to `path` for the rest of the run. Only the first call takes effect.
+pub(crate) fn configure_events(path: Option<&str>) -> Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let sink = EventSink::open(Path::new(path))?;
+    if EVENT_SINK.set(sink).is_err() {
+        warn!("Event log already configured; ignoring {}", path);
+    }
+    Ok(())
+}
+
+/// Record a structured event if `--events` is set; a no-op otherwise.
+pub(crate) fn emit(kind: &str, question_id: Option<&str>, details: impl AsRef<str>) {
+    if let Some(sink) = EVENT_SINK.get() {
+        if let Err(err) = sink.emit(kind, question_id, details.as_ref()) {
+            warn!("Failed to write event: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_failure_event_is_one_json_object_per_line() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("logs").join("events.jsonl");
+        let sink = EventSink::open(&path).unwrap();
+        sink.emit(
+            "media_download_failed",
+            Some("cvmcq24001"),
+            "HTTP status client error (404 Not Found)",
+        )
+        .unwrap();
+        sink.emit("discovery_failed", None, "timeout").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["type"], "media_download_failed");
+        assert_eq!(lines[0]["question_id"], "cvmcq24001");
+        assert_eq!(
+            lines[0]["details"],
+            "HTTP status client error (404 Not Found)"
+        );
+        assert!(lines[0]["timestamp"].is_string());
+        assert!(lines[1].get("question_id").is_none());
+    }
+}
@@ -0,0 +1,123 @@
+//! Exporters that turn the extracted question tree into other formats.
+
+#[path = "export_anki.rs"]
+pub mod export_anki;
+#[path = "export_schema.rs"]
+pub mod export_schema;
+#[path = "export_sqlite.rs"]
+pub mod export_sqlite;
+#[path = "export_stems.rs"]
+pub mod export_stems;
+#[path = "export_system.rs"]
+pub mod export_system;
+#[path = "export_topics.rs"]
+pub mod export_topics;
+
+use anyhow::{Context, Result};
+use std::fs;
+use tracing::warn;
+
+use crate::assets::asset_store::{collect_question_entries, QuestionEntry};
+use crate::models::QuestionData;
+
+/// Load every parseable question under `data_dir`, sorted by question ID.
+///
+/// Unreadable or malformed JSON files are logged and skipped so one bad file
+/// does not abort a whole export.
+pub(crate) fn load_questions(data_dir: &str) -> Result<Vec<(QuestionEntry, QuestionData)>> {
+    let mut entries = collect_question_entries(data_dir)?;
+    entries.sort_by(|a, b| a.question_id.cmp(&b.question_id));
+
+    let mut questions = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match read_question(&entry) {
+            Ok(question) => questions.push((entry, question)),
+            Err(err) => {
+                warn!("Skipping {}: {:#}", entry.question_id, err);
+                crate::events::emit(
+                    "parse_failed",
+                    Some(&entry.question_id),
+                    format!("{:#}", err),
+                );
+            }
+        }
+    }
+
+    Ok(questions)
+}
+
+fn read_question(entry: &QuestionEntry) -> Result<QuestionData> {
+    let content = fs::read_to_string(&entry.json_path)
+        .with_context(|| format!("Failed to read {}", entry.json_path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", entry.json_path.display()))
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Write a minimal but complete question JSON under `data_dir/system/question_id/`.
+    pub(crate) fn write_question(data_dir: &Path, system: &str, question_id: &str) {
+        write_question_with(data_dir, system, question_id, serde_json::json!({}));
+    }
+
+    /// Like [`write_question`], with the top-level fields in `overrides` replacing (or added
+    /// to) the sample's. Returns the path of the written JSON file.
+    pub(crate) fn write_question_with(
+        data_dir: &Path,
+        system: &str,
+        question_id: &str,
+        overrides: serde_json::Value,
+    ) -> PathBuf {
+        let mut question = sample_question(system, question_id);
+        question
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+        let question_dir = data_dir.join(system).join(question_id);
+        fs::create_dir_all(&question_dir).unwrap();
+        let json_path = question_dir.join(format!("{}.json", question_id));
+        fs::write(&json_path, serde_json::to_string_pretty(&question).unwrap()).unwrap();
+        json_path
+    }
+
+    /// A minimal but complete question as JSON.
+    pub(crate) fn sample_question(system: &str, question_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "question_id": question_id,
+            "category": system,
+            "category_name": system.to_uppercase(),
+            "educational_objective": "Objective",
+            "metadata": {
+                "care_types": [],
+                "patient_types": [],
+                "high_value_care": false,
+                "hospitalist": false,
+                "question_updated": ""
+            },
+            "question_text": "Question",
+            "question_stem": "Stem",
+            "options": [
+                { "letter": "A", "text": "First", "peer_percentage": 60 },
+                { "letter": "B", "text": "Second", "peer_percentage": 40 }
+            ],
+            "user_performance": {
+                "user_answer": null,
+                "correct_answer": "A",
+                "result": null,
+                "time_taken": null
+            },
+            "critique": "Critique",
+            "critique_links": [
+                { "href": "/syllabus/cv", "text": "Syllabus", "target": null, "title": null, "rel": null }
+            ],
+            "key_points": ["Key point"],
+            "references": "Ref one.\nRef two.",
+            "related_content": { "syllabus": [] },
+            "media": { "tables": [], "images": ["figures/f1.png"], "svgs": [], "videos": [] },
+            "extracted_at": "2025-01-01T00:00:00Z"
+        })
+    }
+}
@@ -0,0 +1,445 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::models::QuestionData;
+
+use super::load_questions;
+
+// Fixed IDs keep re-exports mapped onto the same note type and deck in Anki
+const MODEL_ID: i64 = 1_716_000_000_001;
+const DECK_ID: i64 = 1_716_000_000_002;
+const DECK_NAME: &str = "MKSAP";
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+/// Legacy (schema 11) collection layout understood by every Anki importer.
+const SCHEMA: &str = r#"
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null,
+    scm integer not null, ver integer not null, dty integer not null,
+    usn integer not null, ls integer not null, conf text not null,
+    models text not null, decks text not null, dconf text not null, tags text not null
+);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null,
+    mod integer not null, usn integer not null, tags text not null,
+    flds text not null, sfld integer not null, csum integer not null,
+    flags integer not null, data text not null
+);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null,
+    ord integer not null, mod integer not null, usn integer not null,
+    type integer not null, queue integer not null, due integer not null,
+    ivl integer not null, factor integer not null, reps integer not null,
+    lapses integer not null, left integer not null, odue integer not null,
+    odid integer not null, flags integer not null, data text not null
+);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null,
+    ease integer not null, ivl integer not null, lastIvl integer not null,
+    factor integer not null, time integer not null, type integer not null
+);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+"#;
+
+#[derive(Debug, Default)]
+pub struct AnkiExportSummary {
+    pub notes: usize,
+    pub media: usize,
+}
+
+/// A figure copied into the package, stored as zip entry `index` and named `name` in Anki.
+struct MediaFile {
+    name: String,
+    bytes: Vec<u8>,
+}
+
+/// Export questions as an Anki `.apkg` deck: one basic note per question, figures
+/// embedded as Anki media, tagged by system and high-value-care status.
+pub fn export_anki(
+    data_dir: &str,
+    output_path: &Path,
+    system_filter: Option<&str>,
+) -> Result<AnkiExportSummary> {
+    let questions: Vec<_> = load_questions(data_dir)?
+        .into_iter()
+        .filter(|(_, question)| system_filter.is_none_or(|system| question.category == system))
+        .collect();
+
+    let collection_path = output_path.with_extension("anki2.tmp");
+    if collection_path.exists() {
+        fs::remove_file(&collection_path)?;
+    }
+    let conn = Connection::open(&collection_path).with_context(|| {
+        format!(
+            "Failed to create Anki collection: {}",
+            collection_path.display()
+        )
+    })?;
+    conn.execute_batch(SCHEMA)
+        .context("Failed to create Anki schema")?;
+
+    let now = chrono::Utc::now();
+    let now_secs = now.timestamp();
+    let now_ms = now.timestamp_millis();
+    insert_collection(&conn, now_secs)?;
+
+    let mut summary = AnkiExportSummary::default();
+    let mut media_files: Vec<MediaFile> = Vec::new();
+    for (idx, (entry, question)) in questions.iter().enumerate() {
+        let mut images = Vec::new();
+        for image in &question.media.images {
+            let source = entry.question_dir.join(image);
+            match fs::read(&source) {
+                Ok(bytes) => {
+                    let file_name = Path::new(image)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| image.clone());
+                    let name = format!("{}_{}", question.question_id, file_name);
+                    images.push(name.clone());
+                    media_files.push(MediaFile { name, bytes });
+                }
+                Err(err) => warn!("Skipping media {}: {}", source.display(), err),
+            }
+        }
+
+        let note_id = now_ms + idx as i64;
+        insert_note(&conn, note_id, now_secs, question, &images)
+            .with_context(|| format!("Failed to export {}", question.question_id))?;
+        summary.notes += 1;
+    }
+    drop(conn);
+    summary.media = media_files.len();
+
+    let collection = fs::read(&collection_path)?;
+    fs::remove_file(&collection_path)?;
+    write_package(output_path, &collection, &media_files)?;
+
+    info!(
+        "Exported {} notes ({} media files) to {}",
+        summary.notes,
+        summary.media,
+        output_path.display()
+    );
+    Ok(summary)
+}
+
+fn insert_collection(conn: &Connection, now_secs: i64) -> Result<()> {
+    let model = json!({
+        MODEL_ID.to_string(): {
+            "id": MODEL_ID,
+            "name": "MKSAP Question",
+            "type": 0,
+            "mod": now_secs,
+            "usn": -1,
+            "sortf": 0,
+            "did": DECK_ID,
+            "tmpls": [{
+                "name": "Card 1",
+                "ord": 0,
+                "qfmt": "{{Front}}",
+                "afmt": "{{FrontSide}}<hr id=answer>{{Back}}",
+                "did": null,
+                "bqfmt": "",
+                "bafmt": ""
+            }],
+            "flds": [
+                { "name": "Front", "ord": 0, "sticky": false, "rtl": false, "font": "Arial", "size": 20, "media": [] },
+                { "name": "Back", "ord": 1, "sticky": false, "rtl": false, "font": "Arial", "size": 20, "media": [] }
+            ],
+            "css": ".card { font-family: arial; font-size: 18px; text-align: left; }",
+            "latexPre": "\\documentclass[12pt]{article}\n\\begin{document}\n",
+            "latexPost": "\\end{document}",
+            "tags": [],
+            "vers": [],
+            "req": [[0, "any", [0]]]
+        }
+    });
+    let deck = |id: i64, name: &str| {
+        json!({
+            "id": id,
+            "name": name,
+            "mod": now_secs,
+            "usn": -1,
+            "desc": "",
+            "dyn": 0,
+            "conf": 1,
+            "collapsed": false,
+            "extendNew": 10,
+            "extendRev": 50,
+            "newToday": [0, 0],
+            "revToday": [0, 0],
+            "lrnToday": [0, 0],
+            "timeToday": [0, 0]
+        })
+    };
+    let decks = json!({
+        "1": deck(1, "Default"),
+        DECK_ID.to_string(): deck(DECK_ID, DECK_NAME),
+    });
+    let dconf = json!({
+        "1": {
+            "id": 1,
+            "name": "Default",
+            "mod": 0,
+            "usn": 0,
+            "maxTaken": 60,
+            "autoplay": true,
+            "timer": 0,
+            "replayq": true,
+            "dyn": false,
+            "new": { "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500, "order": 1, "perDay": 20, "bury": true, "separate": true },
+            "rev": { "perDay": 100, "ease4": 1.3, "fuzz": 0.05, "maxIvl": 36500, "ivlFct": 1, "bury": true, "minSpace": 1 },
+            "lapse": { "delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0 }
+        }
+    });
+    let conf = json!({
+        "activeDecks": [1],
+        "curDeck": 1,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "curModel": null,
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true
+    });
+
+    conn.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![
+            now_secs,
+            now_secs * 1000,
+            now_secs * 1000,
+            conf.to_string(),
+            model.to_string(),
+            decks.to_string(),
+            dconf.to_string(),
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_note(
+    conn: &Connection,
+    note_id: i64,
+    now_secs: i64,
+    question: &QuestionData,
+    images: &[String],
+) -> Result<()> {
+    let front = render_front(question, images);
+    let back = render_back(question);
+    let sort_field = strip_tags(&front);
+    let fields = format!("{}{}{}", front, FIELD_SEPARATOR, back);
+
+    conn.execute(
+        "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+        params![
+            note_id,
+            // Question IDs are stable, so re-imports update notes instead of duplicating
+            question.question_id,
+            MODEL_ID,
+            now_secs,
+            note_tags(question),
+            fields,
+            sort_field,
+            field_checksum(&sort_field),
+        ],
+    )?;
+    conn.execute(
+        "INSERT INTO cards VALUES (?1, ?1, ?2, 0, ?3, -1, 0, 0, ?4, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+        params![note_id, DECK_ID, now_secs, note_id % 1_000_000],
+    )?;
+    Ok(())
+}
+
+fn render_front(question: &QuestionData, images: &[String]) -> String {
+    let mut html = String::new();
+    for text in [&question.question_text, &question.question_stem] {
+        if !text.trim().is_empty() {
+            html.push_str(&format!("<p>{}</p>", text_to_html(text)));
+        }
+    }
+    for image in images {
+        html.push_str(&format!("<p><img src=\"{}\"></p>", escape_html(image)));
+    }
+    html.push_str("<ol type=\"A\">");
+    for option in &question.options {
+        html.push_str(&format!("<li>{}</li>", text_to_html(&option.text)));
+    }
+    html.push_str("</ol>");
+    html
+}
+
+fn render_back(question: &QuestionData) -> String {
+    let mut html = String::new();
+    if let Some(answer) = question.user_performance.correct_answer.as_deref() {
+        html.push_str(&format!("<p><b>Answer: {}</b></p>", escape_html(answer)));
+    }
+    if !question.critique.trim().is_empty() {
+        html.push_str(&format!("<p>{}</p>", text_to_html(&question.critique)));
+    }
+    if !question.key_points.is_empty() {
+        html.push_str("<p><b>Key Points</b></p><ul>");
+        for key_point in &question.key_points {
+            html.push_str(&format!("<li>{}</li>", text_to_html(key_point)));
+        }
+        html.push_str("</ul>");
+    }
+    if !question.references.trim().is_empty() {
+        html.push_str(&format!(
+            "<p><small>{}</small></p>",
+            text_to_html(&question.references)
+        ));
+    }
+    html
+}
+
+/// Space-delimited Anki tags: the system code plus `high_value_care` when flagged.
+fn note_tags(question: &QuestionData) -> String {
+    let mut tags = vec![question.category.clone()];
+    if question.metadata.high_value_care {
+        tags.push("high_value_care".to_string());
+    }
+    format!(" {} ", tags.join(" "))
+}
+
+/// Anki's duplicate-check checksum: first 8 hex digits of the SHA-1 of the sort field.
+fn field_checksum(sort_field: &str) -> i64 {
+    let digest = Sha1::digest(sort_field.as_bytes());
+    i64::from(u32::from_be_bytes([
+        digest[0], digest[1], digest[2], digest[3],
+    ]))
+}
+
+fn write_package(output_path: &Path, collection: &[u8], media_files: &[MediaFile]) -> Result<()> {
+    if let Some(parent) = output_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let file = File::create(output_path)
+        .with_context(|| format!("Failed to create {}", output_path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("collection.anki2", options)?;
+    zip.write_all(collection)?;
+
+    let mut media_map = HashMap::new();
+    for (index, media) in media_files.iter().enumerate() {
+        zip.start_file(index.to_string(), options)?;
+        zip.write_all(&media.bytes)?;
+        media_map.insert(index.to_string(), media.name.clone());
+    }
+    zip.start_file("media", options)?;
+    zip.write_all(serde_json::to_string(&media_map)?.as_bytes())?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn text_to_html(text: &str) -> String {
+    escape_html(text.trim()).replace('\n', "<br>")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn strip_tags(html: &str) -> String {
+    static TAG_RE: OnceLock<Regex> = OnceLock::new();
+    let tag_re = TAG_RE.get_or_init(|| Regex::new(r"<[^>]*>").unwrap());
+    tag_re
+        .replace_all(html, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question;
+    use std::io::Read;
+
+    fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_export_anki_writes_notes_media_and_tags() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("data");
+        write_question(&data_dir, "cv", "cvmcq24001");
+        write_question(&data_dir, "en", "enmcq24001");
+        let figure_dir = data_dir.join("cv").join("cvmcq24001").join("figures");
+        fs::create_dir_all(&figure_dir).unwrap();
+        fs::write(figure_dir.join("f1.png"), b"png-bytes").unwrap();
+        let apkg = temp.path().join("deck.apkg");
+
+        let summary = export_anki(data_dir.to_str().unwrap(), &apkg, Some("cv")).unwrap();
+        assert_eq!(summary.notes, 1);
+        assert_eq!(summary.media, 1);
+
+        let mut archive = zip::ZipArchive::new(File::open(&apkg).unwrap()).unwrap();
+        let media: HashMap<String, String> =
+            serde_json::from_slice(&read_entry(&mut archive, "media")).unwrap();
+        assert_eq!(media.get("0").unwrap(), "cvmcq24001_f1.png");
+        assert_eq!(read_entry(&mut archive, "0"), b"png-bytes");
+
+        let collection_path = temp.path().join("collection.anki2");
+        fs::write(
+            &collection_path,
+            read_entry(&mut archive, "collection.anki2"),
+        )
+        .unwrap();
+        let conn = Connection::open(&collection_path).unwrap();
+        let (guid, tags, fields): (String, String, String) = conn
+            .query_row("SELECT guid, tags, flds FROM notes", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!(guid, "cvmcq24001");
+        assert_eq!(tags.trim(), "cv");
+        let (front, back) = fields.split_once(FIELD_SEPARATOR).unwrap();
+        assert!(front.contains("<img src=\"cvmcq24001_f1.png\">"));
+        assert!(front.contains("<li>First</li>"));
+        assert!(back.contains("Answer: A"));
+        assert!(back.contains("<li>Key point</li>"));
+        let cards: i64 = conn
+            .query_row("SELECT COUNT(*) FROM cards", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cards, 1);
+    }
+}
@@ -0,0 +1,72 @@
+//! JSON Schema for the per-question output (`export-schema`).
+//!
+//! Generated from the `QuestionData` type itself, so it cannot drift from what
+//! the extractor actually writes.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::models::QuestionData;
+
+/// JSON Schema (draft-07) describing one `<question_id>.json` file.
+pub fn question_schema() -> serde_json::Value {
+    let schema = schemars::schema_for!(QuestionData);
+    serde_json::to_value(schema).expect("JSON Schema always serializes")
+}
+
+/// Write the schema to `out_path`, or print it to stdout when no path is given.
+pub fn export_schema(out_path: Option<&Path>) -> Result<()> {
+    let json = serde_json::to_string_pretty(&question_schema())?;
+    match out_path {
+        Some(path) => {
+            if let Some(parent) = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+            {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, format!("{}\n", json))
+                .with_context(|| format!("Failed to write schema to {}", path.display()))?;
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::sample_question;
+    use jsonschema::JSONSchema;
+
+    #[test]
+    fn test_schema_accepts_sample_and_rejects_missing_required_field() {
+        let schema = JSONSchema::compile(&question_schema()).unwrap();
+
+        let sample = sample_question("cv", "cvmcq24001");
+        assert!(schema.is_valid(&sample));
+
+        let mut missing = sample.clone();
+        missing.as_object_mut().unwrap().remove("question_stem");
+        assert!(!schema.is_valid(&missing));
+
+        let mut wrong_type = sample;
+        wrong_type["options"][0]["peer_percentage"] = "sixty".into();
+        assert!(!schema.is_valid(&wrong_type));
+    }
+
+    #[test]
+    fn test_export_schema_writes_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("schema").join("question.schema.json");
+        export_schema(Some(&path)).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["title"], "QuestionData");
+        let required = written["required"].as_array().unwrap();
+        assert!(required.contains(&"question_id".into()));
+        assert!(!required.contains(&"critique_links".into()));
+    }
+}
@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Transaction};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+use crate::models::QuestionData;
+
+use super::load_questions;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS questions (
+    question_id TEXT PRIMARY KEY,
+    category TEXT NOT NULL,
+    category_name TEXT NOT NULL,
+    educational_objective TEXT NOT NULL,
+    question_text TEXT NOT NULL,
+    question_stem TEXT NOT NULL,
+    correct_answer TEXT,
+    critique TEXT NOT NULL,
+    extracted_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_questions_category ON questions(category);
+
+CREATE TABLE IF NOT EXISTS options (
+    question_id TEXT NOT NULL REFERENCES questions(question_id),
+    letter TEXT NOT NULL,
+    text TEXT NOT NULL,
+    peer_percentage INTEGER NOT NULL,
+    PRIMARY KEY (question_id, letter)
+);
+CREATE INDEX IF NOT EXISTS idx_options_question_id ON options(question_id);
+
+CREATE TABLE IF NOT EXISTS key_points (
+    question_id TEXT NOT NULL REFERENCES questions(question_id),
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (question_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_key_points_question_id ON key_points(question_id);
+
+CREATE TABLE IF NOT EXISTS media (
+    question_id TEXT NOT NULL REFERENCES questions(question_id),
+    media_type TEXT NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY (question_id, media_type, path)
+);
+CREATE INDEX IF NOT EXISTS idx_media_question_id ON media(question_id);
+
+CREATE TABLE IF NOT EXISTS "references" (
+    question_id TEXT NOT NULL REFERENCES questions(question_id),
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (question_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_references_question_id ON "references"(question_id);
+
+CREATE TABLE IF NOT EXISTS critique_links (
+    question_id TEXT NOT NULL REFERENCES questions(question_id),
+    position INTEGER NOT NULL,
+    href TEXT NOT NULL,
+    text TEXT NOT NULL,
+    title TEXT,
+    PRIMARY KEY (question_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_critique_links_question_id ON critique_links(question_id);
+"#;
+
+const CHILD_TABLES: [&str; 5] = [
+    "options",
+    "key_points",
+    "media",
+    "\"references\"",
+    "critique_links",
+];
+
+#[derive(Debug, Default)]
+pub struct SqliteExportSummary {
+    pub questions: usize,
+    pub systems: usize,
+    pub options: usize,
+    pub key_points: usize,
+    pub media: usize,
+    pub references: usize,
+    pub critique_links: usize,
+}
+
+/// Export every question under `data_dir` into a SQLite database at `db_path`.
+///
+/// Questions are upserted on `question_id`, so re-running the export against an
+/// existing database refreshes rows instead of duplicating them. Each system is
+/// written in its own transaction.
+pub fn export_sqlite(data_dir: &str, db_path: &Path) -> Result<SqliteExportSummary> {
+    let questions = load_questions(data_dir)?;
+    let mut conn = Connection::open(db_path)
+        .with_context(|| format!("Failed to open SQLite database: {}", db_path.display()))?;
+    conn.execute_batch(SCHEMA)
+        .context("Failed to create SQLite schema")?;
+
+    let mut by_system: BTreeMap<&str, Vec<&QuestionData>> = BTreeMap::new();
+    for (_, question) in &questions {
+        by_system
+            .entry(question.category.as_str())
+            .or_default()
+            .push(question);
+    }
+
+    let mut summary = SqliteExportSummary::default();
+    for (system, system_questions) in by_system {
+        let tx = conn.transaction()?;
+        for question in system_questions {
+            upsert_question(&tx, question, &mut summary)
+                .with_context(|| format!("Failed to export {}", question.question_id))?;
+        }
+        tx.commit()
+            .with_context(|| format!("Failed to commit SQLite export for {}", system))?;
+        summary.systems += 1;
+    }
+
+    info!(
+        "Exported {} questions to {}",
+        summary.questions,
+        db_path.display()
+    );
+    Ok(summary)
+}
+
+fn upsert_question(
+    tx: &Transaction,
+    question: &QuestionData,
+    summary: &mut SqliteExportSummary,
+) -> Result<()> {
+    let qid = question.question_id.as_str();
+    tx.execute(
+        "INSERT INTO questions (
+            question_id, category, category_name, educational_objective,
+            question_text, question_stem, correct_answer, critique, extracted_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        ON CONFLICT(question_id) DO UPDATE SET
+            category = excluded.category,
+            category_name = excluded.category_name,
+            educational_objective = excluded.educational_objective,
+            question_text = excluded.question_text,
+            question_stem = excluded.question_stem,
+            correct_answer = excluded.correct_answer,
+            critique = excluded.critique,
+            extracted_at = excluded.extracted_at",
+        params![
+            qid,
+            question.category,
+            question.category_name,
+            question.educational_objective,
+            question.question_text,
+            question.question_stem,
+            question.user_performance.correct_answer,
+            question.critique,
+            question.extracted_at,
+        ],
+    )?;
+    summary.questions += 1;
+
+    // Child rows are replaced wholesale so removed options/media do not linger
+    for table in CHILD_TABLES {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE question_id = ?1", table),
+            params![qid],
+        )?;
+    }
+
+    for option in &question.options {
+        tx.execute(
+            "INSERT INTO options (question_id, letter, text, peer_percentage)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![qid, option.letter, option.text, option.peer_percentage],
+        )?;
+        summary.options += 1;
+    }
+
+    for (position, key_point) in question.key_points.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO key_points (question_id, position, text) VALUES (?1, ?2, ?3)",
+            params![qid, position as i64, key_point],
+        )?;
+        summary.key_points += 1;
+    }
+
+    let media = &question.media;
+    let media_rows = [
+        ("table", &media.tables),
+        ("image", &media.images),
+        ("svg", &media.svgs),
+        ("video", &media.videos),
+    ];
+    for (media_type, paths) in media_rows {
+        for path in paths {
+            tx.execute(
+                "INSERT OR IGNORE INTO media (question_id, media_type, path)
+                 VALUES (?1, ?2, ?3)",
+                params![qid, media_type, path],
+            )?;
+            summary.media += 1;
+        }
+    }
+
+    let references = question
+        .references
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    for (position, reference) in references.enumerate() {
+        tx.execute(
+            "INSERT INTO \"references\" (question_id, position, text) VALUES (?1, ?2, ?3)",
+            params![qid, position as i64, reference],
+        )?;
+        summary.references += 1;
+    }
+
+    for (position, link) in question.critique_links.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO critique_links (question_id, position, href, text, title)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![qid, position as i64, link.href, link.text, link.title],
+        )?;
+        summary.critique_links += 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question;
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_export_inserts_questions_queryable_by_system() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("data");
+        write_question(&data_dir, "cv", "cvmcq24001");
+        write_question(&data_dir, "en", "enmcq24001");
+        let db_path = temp.path().join("mksap.sqlite");
+
+        let summary = export_sqlite(data_dir.to_str().unwrap(), &db_path).unwrap();
+        assert_eq!(summary.questions, 2);
+        assert_eq!(summary.systems, 2);
+
+        let conn = Connection::open(&db_path).unwrap();
+        let cv_ids: Vec<String> = conn
+            .prepare("SELECT question_id FROM questions WHERE category = 'cv'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(cv_ids, vec!["cvmcq24001".to_string()]);
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT COUNT(*) FROM options WHERE question_id = 'cvmcq24001'"
+            ),
+            2
+        );
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM \"references\""), 4);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM key_points"), 2);
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT COUNT(*) FROM critique_links WHERE href = '/syllabus/cv'"
+            ),
+            2
+        );
+    }
+
+    #[test]
+    fn test_export_rerun_upserts_instead_of_duplicating() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("data");
+        write_question(&data_dir, "cv", "cvmcq24001");
+        let db_path = temp.path().join("mksap.sqlite");
+
+        export_sqlite(data_dir.to_str().unwrap(), &db_path).unwrap();
+        export_sqlite(data_dir.to_str().unwrap(), &db_path).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM questions"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM options"), 2);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM media"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM key_points"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM critique_links"), 1);
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+use crate::models::QuestionData;
+
+use super::load_questions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StemFormat {
+    Json,
+    Csv,
+}
+
+impl StemFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(StemFormat::Json),
+            "csv" => Ok(StemFormat::Csv),
+            other => bail!(
+                "Unsupported stem export format: {} (expected json or csv)",
+                other
+            ),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            StemFormat::Json => "json",
+            StemFormat::Csv => "csv",
+        }
+    }
+}
+
+/// A question with everything that would reveal the answer stripped out.
+#[derive(Debug, Serialize)]
+pub struct StemRecord {
+    pub question_id: String,
+    pub system: String,
+    pub question_text: String,
+    pub question_stem: String,
+    pub options: Vec<StemOption>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StemOption {
+    pub letter: String,
+    pub text: String,
+}
+
+impl From<&QuestionData> for StemRecord {
+    fn from(question: &QuestionData) -> Self {
+        Self {
+            question_id: question.question_id.clone(),
+            system: question.category.clone(),
+            question_text: question.question_text.clone(),
+            question_stem: question.question_stem.clone(),
+            options: question
+                .options
+                .iter()
+                .map(|option| StemOption {
+                    letter: option.letter.clone(),
+                    text: option.text.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Export question stems and options (no answers, peer stats or critique) for blind testing.
+pub fn export_stems(
+    data_dir: &str,
+    output_path: &Path,
+    format: StemFormat,
+    system_filter: Option<&str>,
+) -> Result<usize> {
+    let records: Vec<StemRecord> = load_questions(data_dir)?
+        .iter()
+        .filter(|(_, question)| system_filter.is_none_or(|system| question.category == system))
+        .map(|(_, question)| StemRecord::from(question))
+        .collect();
+
+    let content = match format {
+        StemFormat::Json => {
+            serde_json::to_string_pretty(&records).context("Failed to serialize stems")?
+        }
+        StemFormat::Csv => render_csv(&records),
+    };
+    fs::write(output_path, content)
+        .with_context(|| format!("Failed to write {}", output_path.display()))?;
+
+    info!(
+        "Exported {} question stems to {}",
+        records.len(),
+        output_path.display()
+    );
+    Ok(records.len())
+}
+
+fn render_csv(records: &[StemRecord]) -> String {
+    let max_options = records
+        .iter()
+        .map(|record| record.options.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut header = vec![
+        "question_id".to_string(),
+        "system".to_string(),
+        "question_text".to_string(),
+        "question_stem".to_string(),
+    ];
+    for idx in 0..max_options {
+        header.push(format!("option_{}_letter", idx + 1));
+        header.push(format!("option_{}_text", idx + 1));
+    }
+
+    let mut lines = vec![header.join(",")];
+    for record in records {
+        let mut fields = vec![
+            csv_field(&record.question_id),
+            csv_field(&record.system),
+            csv_field(&record.question_text),
+            csv_field(&record.question_stem),
+        ];
+        for idx in 0..max_options {
+            let option = record.options.get(idx);
+            fields.push(csv_field(option.map_or("", |o| o.letter.as_str())));
+            fields.push(csv_field(option.map_or("", |o| o.text.as_str())));
+        }
+        lines.push(fields.join(","));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question;
+
+    #[test]
+    fn test_export_excludes_answer_revealing_fields() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("data");
+        write_question(&data_dir, "cv", "cvmcq24001");
+        write_question(&data_dir, "en", "enmcq24001");
+
+        let json_path = temp.path().join("stems.json");
+        let count = export_stems(
+            data_dir.to_str().unwrap(),
+            &json_path,
+            StemFormat::Json,
+            Some("cv"),
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+
+        let exported: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        let record = &exported[0];
+        assert_eq!(record["question_id"], "cvmcq24001");
+        assert_eq!(record["options"][0]["text"], "First");
+        for field in [
+            "critique",
+            "correct_answer",
+            "user_performance",
+            "key_points",
+        ] {
+            assert!(record.get(field).is_none(), "{} leaked into export", field);
+        }
+        assert!(record["options"][0].get("peer_percentage").is_none());
+        assert!(record["options"][0].get("is_correct").is_none());
+
+        let csv_path = temp.path().join("stems.csv");
+        export_stems(data_dir.to_str().unwrap(), &csv_path, StemFormat::Csv, None).unwrap();
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.starts_with("question_id,system,question_text,question_stem,"));
+        assert!(!csv.contains("Critique"));
+        assert!(!csv.contains("60"));
+    }
+
+    #[test]
+    fn test_csv_field_escapes_quotes_and_commas() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
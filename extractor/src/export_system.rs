@@ -0,0 +1,231 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+use crate::assets::asset_store::collect_question_entries;
+
+const INDEX_FILE_NAME: &str = "index.json";
+
+#[derive(Debug, Default)]
+pub struct SystemExportSummary {
+    pub questions: usize,
+    pub files: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct SystemIndex {
+    system: String,
+    system_name: String,
+    exported_at: String,
+    questions: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct IndexEntry {
+    question_id: String,
+    json: String,
+    files: usize,
+}
+
+/// Copy one system's question directories (JSON + media) into `dest/<system>/` with an
+/// `index.json`, so the folder can be shared or pointed at by other commands on its own.
+pub fn export_system(data_dir: &str, system: &str, dest: &Path) -> Result<SystemExportSummary> {
+    let Some(system_config) = crate::config::get_organ_system_by_id(system) else {
+        bail!("Unknown system code: {}", system);
+    };
+
+    let mut entries: Vec<_> = collect_question_entries(data_dir)?
+        .into_iter()
+        .filter(|entry| {
+            entry
+                .question_dir
+                .parent()
+                .and_then(|parent| parent.file_name())
+                .is_some_and(|name| name == system)
+        })
+        .collect();
+    entries.sort_by(|a, b| a.question_id.cmp(&b.question_id));
+
+    let system_dest = dest.join(system);
+    fs::create_dir_all(&system_dest)
+        .with_context(|| format!("Failed to create {}", system_dest.display()))?;
+
+    let mut summary = SystemExportSummary::default();
+    let mut index = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        let question_dest = system_dest.join(&entry.question_id);
+        let files = copy_question_dir(&entry.question_dir, &question_dest)
+            .with_context(|| format!("Failed to export {}", entry.question_id))?;
+        summary.questions += 1;
+        summary.files += files;
+
+        let json_name = entry
+            .json_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{}.json", entry.question_id));
+        index.push(IndexEntry {
+            question_id: entry.question_id.clone(),
+            json: format!("{}/{}/{}", system, entry.question_id, json_name),
+            files,
+        });
+    }
+
+    let index = SystemIndex {
+        system: system.to_string(),
+        system_name: system_config.name,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        questions: index,
+    };
+    fs::write(
+        dest.join(INDEX_FILE_NAME),
+        serde_json::to_string_pretty(&index)?,
+    )?;
+
+    info!(
+        "Exported {} {} questions ({} files) to {}",
+        summary.questions,
+        system,
+        summary.files,
+        dest.display()
+    );
+    Ok(summary)
+}
+
+/// Recursively copy a question directory, rewriting absolute paths inside JSON files.
+/// Returns the number of files written.
+pub(super) fn copy_question_dir(source: &Path, dest: &Path) -> Result<usize> {
+    fs::create_dir_all(dest)?;
+    let mut files = 0;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = dest.join(entry.file_name());
+        if file_type.is_dir() {
+            files += copy_question_dir(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            if entry.path().extension().is_some_and(|ext| ext == "json") {
+                let contents = fs::read_to_string(entry.path())?;
+                match serde_json::from_str::<Value>(&contents) {
+                    Ok(mut json) => {
+                        relativize_paths(&mut json, source);
+                        fs::write(&target, serde_json::to_string_pretty(&json)?)?;
+                    }
+                    Err(_) => {
+                        fs::write(&target, contents)?;
+                    }
+                }
+            } else {
+                fs::copy(entry.path(), &target)?;
+            }
+            files += 1;
+        }
+    }
+    Ok(files)
+}
+
+/// Rewrite absolute paths pointing into `question_dir` to paths relative to it.
+fn relativize_paths(value: &mut Value, question_dir: &Path) {
+    match value {
+        Value::String(text) => {
+            if let Some(relative) = relative_path(text, question_dir) {
+                *text = relative;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                relativize_paths(item, question_dir);
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                relativize_paths(item, question_dir);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn relative_path(text: &str, question_dir: &Path) -> Option<String> {
+    let path = Path::new(text);
+    if !path.is_absolute() {
+        return None;
+    }
+    if let Ok(relative) = path.strip_prefix(question_dir) {
+        return Some(relative.to_string_lossy().to_string());
+    }
+    // Absolute paths recorded on another machine: keep what follows the question directory
+    let question_id = question_dir.file_name()?.to_str()?;
+    let marker = format!("/{}/", question_id);
+    text.find(&marker)
+        .map(|idx| text[idx + marker.len()..].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question;
+
+    #[test]
+    fn test_export_system_copies_only_that_system_with_media() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("data");
+        write_question(&data_dir, "cv", "cvmcq24001");
+        write_question(&data_dir, "cv", "cvmcq24002");
+        write_question(&data_dir, "en", "enmcq24001");
+        let question_dir = data_dir.join("cv").join("cvmcq24001");
+        fs::create_dir_all(question_dir.join("figures")).unwrap();
+        fs::write(question_dir.join("figures").join("f1.png"), b"png-bytes").unwrap();
+
+        // Simulate a JSON that recorded an absolute media path
+        let json_path = question_dir.join("cvmcq24001.json");
+        let mut json: Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        json["media"]["images"] =
+            serde_json::json!([question_dir.join("figures/f1.png").to_string_lossy()]);
+        fs::write(&json_path, json.to_string()).unwrap();
+
+        let dest = temp.path().join("cv_export");
+        let summary = export_system(data_dir.to_str().unwrap(), "cv", &dest).unwrap();
+        assert_eq!(summary.questions, 2);
+        assert_eq!(summary.files, 3);
+
+        let mut exported: Vec<String> = fs::read_dir(dest.join("cv"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        exported.sort();
+        assert_eq!(exported, vec!["cvmcq24001", "cvmcq24002"]);
+        assert!(!dest.join("en").exists());
+
+        let exported_dir = dest.join("cv").join("cvmcq24001");
+        assert_eq!(
+            fs::read(exported_dir.join("figures").join("f1.png")).unwrap(),
+            b"png-bytes"
+        );
+        let exported_json: Value = serde_json::from_str(
+            &fs::read_to_string(exported_dir.join("cvmcq24001.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(exported_json["media"]["images"][0], "figures/f1.png");
+
+        let index: Value =
+            serde_json::from_str(&fs::read_to_string(dest.join(INDEX_FILE_NAME)).unwrap()).unwrap();
+        assert_eq!(index["system"], "cv");
+        assert_eq!(index["questions"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            index["questions"][0]["json"],
+            "cv/cvmcq24001/cvmcq24001.json"
+        );
+    }
+
+    #[test]
+    fn test_export_system_rejects_unknown_system() {
+        let temp = tempfile::tempdir().unwrap();
+        let err = export_system(temp.path().to_str().unwrap(), "zz", temp.path()).unwrap_err();
+        assert!(err.to_string().contains("Unknown system code"));
+    }
+}
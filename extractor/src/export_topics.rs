@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use super::export_system::copy_question_dir;
+use super::load_questions;
+
+const INDEX_FILE_NAME: &str = "index.json";
+pub const UNCATEGORIZED_TOPIC: &str = "uncategorized";
+
+#[derive(Debug, Default)]
+pub struct TopicExportSummary {
+    pub questions: usize,
+    pub topics: usize,
+    pub uncategorized: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct TopicIndexEntry {
+    topic: String,
+    path: String,
+    questions: Vec<String>,
+}
+
+/// Copy questions into `dest/<topic>/<sub-topic>/<question_id>/`, using the learning plan topic
+/// (`A > B` hierarchies become nested folders). Questions without a topic go under
+/// `uncategorized/`. An `index.json` lists the questions filed under each topic.
+pub fn export_by_topic(
+    data_dir: &str,
+    system_filter: Option<&str>,
+    dest: &Path,
+) -> Result<TopicExportSummary> {
+    let mut topics: BTreeMap<PathBuf, (String, Vec<String>)> = BTreeMap::new();
+    let mut summary = TopicExportSummary::default();
+
+    for (entry, question) in load_questions(data_dir)? {
+        if system_filter.is_some_and(|system| question.category != system) {
+            continue;
+        }
+
+        let topic = question.related_content.learning_plan_topic.trim();
+        let segments = topic_segments(topic);
+        let (label, topic_dir) = if segments.is_empty() {
+            summary.uncategorized += 1;
+            (
+                UNCATEGORIZED_TOPIC.to_string(),
+                PathBuf::from(UNCATEGORIZED_TOPIC),
+            )
+        } else {
+            (
+                topic.to_string(),
+                segments.iter().map(|segment| slugify(segment)).collect(),
+            )
+        };
+
+        let question_dest = dest.join(&topic_dir).join(&entry.question_id);
+        copy_question_dir(&entry.question_dir, &question_dest)
+            .with_context(|| format!("Failed to export {}", entry.question_id))?;
+        summary.questions += 1;
+
+        topics
+            .entry(topic_dir)
+            .or_insert_with(|| (label, Vec::new()))
+            .1
+            .push(entry.question_id);
+    }
+
+    summary.topics = topics.len();
+    let index: Vec<TopicIndexEntry> = topics
+        .into_iter()
+        .map(|(path, (topic, questions))| TopicIndexEntry {
+            topic,
+            path: path.to_string_lossy().replace('\\', "/"),
+            questions,
+        })
+        .collect();
+    fs::create_dir_all(dest)?;
+    fs::write(
+        dest.join(INDEX_FILE_NAME),
+        serde_json::to_string_pretty(&index)?,
+    )?;
+
+    info!(
+        "Exported {} questions into {} topics ({} uncategorized) at {}",
+        summary.questions,
+        summary.topics,
+        summary.uncategorized,
+        dest.display()
+    );
+    Ok(summary)
+}
+
+fn topic_segments(topic: &str) -> Vec<&str> {
+    topic
+        .split('>')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Folder-safe topic name: lowercase alphanumerics joined by single dashes.
+fn slugify(segment: &str) -> String {
+    let mut slug = String::with_capacity(segment.len());
+    for ch in segment.chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        UNCATEGORIZED_TOPIC.to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question;
+    use serde_json::Value;
+
+    fn set_topic(data_dir: &Path, system: &str, qid: &str, topic: &str) {
+        let path = data_dir
+            .join(system)
+            .join(qid)
+            .join(format!("{}.json", qid));
+        let mut json: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        json["related_content"]["learning_plan_topic"] = Value::String(topic.to_string());
+        fs::write(&path, json.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_questions_grouped_by_topic_with_uncategorized_fallback() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("data");
+        write_question(&data_dir, "cv", "cvmcq24001");
+        write_question(&data_dir, "cv", "cvmcq24002");
+        write_question(&data_dir, "en", "enmcq24001");
+        set_topic(
+            &data_dir,
+            "cv",
+            "cvmcq24001",
+            "Heart Failure > Acute Decompensation",
+        );
+        set_topic(&data_dir, "en", "enmcq24001", "Diabetes Mellitus");
+
+        let dest = temp.path().join("topics");
+        let summary = export_by_topic(data_dir.to_str().unwrap(), None, &dest).unwrap();
+        assert_eq!(summary.questions, 3);
+        assert_eq!(summary.topics, 3);
+        assert_eq!(summary.uncategorized, 1);
+
+        assert!(dest
+            .join("heart-failure/acute-decompensation/cvmcq24001/cvmcq24001.json")
+            .exists());
+        assert!(dest.join("diabetes-mellitus/enmcq24001").is_dir());
+        assert!(dest.join("uncategorized/cvmcq24002").is_dir());
+
+        let index: Value =
+            serde_json::from_str(&fs::read_to_string(dest.join(INDEX_FILE_NAME)).unwrap()).unwrap();
+        let uncategorized = index
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["topic"] == UNCATEGORIZED_TOPIC)
+            .unwrap();
+        assert_eq!(
+            uncategorized["questions"],
+            serde_json::json!(["cvmcq24002"])
+        );
+
+        let filtered = export_by_topic(
+            data_dir.to_str().unwrap(),
+            Some("en"),
+            &temp.path().join("en_topics"),
+        )
+        .unwrap();
+        assert_eq!(filtered.questions, 1);
+        assert_eq!(filtered.uncategorized, 0);
+    }
+}
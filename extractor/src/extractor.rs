@@ -1,9 +1,14 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 use tracing::warn;
 
+use crate::profile::ExtractionProfile;
 use crate::utils::parse_env;
 #[path = "auth.rs"]
 pub mod auth;
@@ -13,34 +18,101 @@ mod cleanup;
 mod discovery;
 #[path = "io.rs"]
 pub mod io;
+#[path = "render.rs"]
+pub mod render;
 #[path = "retry.rs"]
 mod retry;
 #[path = "workflow.rs"]
 mod workflow;
 
-const QUESTION_TYPE_CODES: [&str; 6] = ["mcq", "qqq", "vdx", "cor", "mqq", "sq"];
-const CHECKPOINT_DIR_NAME: &str = ".checkpoints";
+pub(crate) use workflow::CategoryExtraction;
+
+pub(crate) const QUESTION_TYPE_CODES: [&str; 6] = ["mcq", "qqq", "vdx", "cor", "mqq", "sq"];
+pub(crate) const CHECKPOINT_DIR_NAME: &str = ".checkpoints";
 const FAILED_DIR_NAME: &str = "mksap_data_failed";
+/// Directory under the output dir holding discovery ids, run markers and other progress files.
+pub(crate) fn checkpoint_dir(output_dir: &str) -> PathBuf {
+    Path::new(output_dir).join(CHECKPOINT_DIR_NAME)
+}
+
+/// Conservative default to stay clear of MKSAP rate limits.
+const DEFAULT_CONCURRENCY: usize = 5;
 
+#[derive(Clone)]
 pub struct MKSAPExtractor {
     pub base_url: String,
     pub output_dir: String,
     pub client: Client,
     authenticated: bool,
+    concurrency: usize,
+    /// Shared across categories so concurrent workers never exceed `concurrency` requests.
+    request_permits: Arc<Semaphore>,
+    /// Per-question latency recorder (`--profile`).
+    profile: Option<Arc<ExtractionProfile>>,
+    /// Existing questions are only re-fetched when updated after this (`--since`).
+    since: Option<DateTime<Utc>>,
+    /// Extract at most this many questions per system (`--limit`).
+    limit: Option<usize>,
 }
 
 impl MKSAPExtractor {
     pub fn new(base_url: &str, output_dir: &str) -> Result<Self> {
         fs::create_dir_all(output_dir).context("Failed to create output directory")?;
+        let client = crate::http::build_client_with_headers(HeaderMap::new())?;
+        let concurrency = Self::concurrency_limit();
 
         Ok(Self {
             base_url: base_url.to_string(),
             output_dir: output_dir.to_string(),
-            client: Client::new(),
+            client,
             authenticated: false,
+            concurrency,
+            request_permits: Arc::new(Semaphore::new(concurrency)),
+            profile: None,
+            since: None,
+            limit: None,
         })
     }
 
+    /// Override the request concurrency (e.g. from `--concurrency`). Zero is ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = concurrency;
+            self.request_permits = Arc::new(Semaphore::new(concurrency));
+        }
+        self
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Record how long each question takes to fetch and write.
+    pub fn with_profile(mut self, profile: Arc<ExtractionProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub fn profile(&self) -> Option<&ExtractionProfile> {
+        self.profile.as_deref()
+    }
+
+    /// Re-fetch existing questions, keeping only those updated after `since`.
+    pub fn with_since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only extract the first `limit` pending questions of each system, for sample runs.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
     pub fn with_session_cookie(mut self, session_cookie_value: &str) -> Self {
         let headers = match crate::http::session_cookie_headers(session_cookie_value) {
             Ok(headers) => headers,
@@ -76,13 +148,9 @@ impl MKSAPExtractor {
     }
 
     fn concurrency_limit() -> usize {
-        let default = std::thread::available_parallelism()
-            .map(|count| count.get())
-            .unwrap_or(4);
-
-        let configured = parse_env("MKSAP_CONCURRENCY", default);
+        let configured = parse_env("MKSAP_CONCURRENCY", DEFAULT_CONCURRENCY);
         if configured == 0 {
-            default
+            DEFAULT_CONCURRENCY
         } else {
             configured
         }
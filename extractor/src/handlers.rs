@@ -1,31 +1,120 @@
 //! Command routing and standalone command handling.
 
 use anyhow::Result;
-use tracing::info;
+use std::path::Path;
+use tracing::{info, warn};
 
 use crate::app::maybe_inspect_api;
-use crate::cli::{has_flag, parse_run_options, parse_standardize_options, MediaOptions};
-use crate::runners::{run_extraction, run_media_discovery, run_media_download, run_svg_browser};
+use crate::cli::{
+    has_flag, parse_anki_export_options, parse_arg_value, parse_arg_values, parse_http_timeouts,
+    parse_max_file_size, parse_run_options, parse_sqlite_export_options, parse_standardize_options,
+    parse_stem_export_options, parse_system_export_options, parse_watch_interval, MediaOptions,
+};
+use crate::config::filter_categories;
+use crate::lock::RunLock;
+use crate::runners::{
+    run_discovery_report, run_extraction, run_media_dimensions, run_media_discovery,
+    run_media_download, run_retry_media_failures, run_svg_browser, run_svg_rasterize,
+};
 use crate::session::load_session_cookie;
+use crate::shutdown::shutdown_requested;
+use crate::since::SinceCutoff;
+use crate::watch::repeat_until_stopped;
 use crate::{
-    authenticate_extractor, build_categories_from_config, show_discovery_stats,
-    validate_extraction, Command, MKSAPExtractor, OUTPUT_DIR,
+    authenticate_extractor, build_categories_from_config, record_progress_history,
+    show_discovery_stats, validate_extraction, Command, MKSAPExtractor, ReportFormat,
 };
 
 pub async fn handle_command(command: Command, args: &[String]) -> Result<()> {
-    let session_cookie = load_session_cookie();
+    crate::settings::configure_config(parse_arg_value(args, "--config").as_deref())?;
+    crate::app::configure_output_dir(crate::settings::file_config().resolve(args).output_dir);
+    let output_dir = crate::app::output_dir();
+    crate::http::configure_timeouts(parse_http_timeouts(args));
+    crate::http::configure_max_file_size(parse_max_file_size(args));
+    crate::http::configure_proxy(parse_arg_value(args, "--proxy").as_deref())?;
+    crate::utils::configure_follow_symlinks(has_flag(args, "--follow-symlinks"));
+    crate::utils::configure_compact_json(
+        crate::settings::file_config()
+            .resolve(args)
+            .compact_json
+            .unwrap_or(false),
+    );
+    crate::math::configure_render_math(has_flag(args, "--render-math"));
+    crate::progress::configure_progress(!has_flag(args, "--no-progress"));
+    crate::events::configure_events(parse_arg_value(args, "--events").as_deref())?;
+    crate::cache::configure_response_cache(
+        output_dir,
+        !has_flag(args, "--no-cache"),
+        parse_arg_value(args, "--cache-ttl-secs")
+            .and_then(|value| value.parse().ok())
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(crate::cache::DEFAULT_CACHE_TTL),
+    );
+    let _run_lock = if command.is_mutating() {
+        let lock = RunLock::acquire(output_dir, has_flag(args, "--force-unlock"))?;
+        lock.release_on_signal();
+        Some(lock)
+    } else {
+        None
+    };
     let media_options = MediaOptions::from_args(args);
+    crate::assets::rate_limit::configure_rate_limit(media_options.rps);
+    crate::session::configure_cookies_file(
+        parse_arg_value(args, "--cookies-file").as_deref(),
+        &media_options.base_url,
+    )?;
+    let http_session = if command != Command::Login && has_flag(args, "--http-login") {
+        Some(http_login(&media_options).await?)
+    } else {
+        None
+    };
+    let session_cookie = http_session.or_else(load_session_cookie);
     let base_url = media_options.base_url.clone();
+    if let Some(warning) =
+        crate::session::edition_mismatch(&crate::session::session_cookie_name(), &base_url)
+    {
+        warn!("{}", warning);
+    }
 
-    if handle_standalone_command(command, args, session_cookie.as_deref(), &base_url).await? {
+    if handle_standalone_command(
+        command,
+        args,
+        output_dir,
+        session_cookie.as_deref(),
+        &base_url,
+    )
+    .await?
+    {
         return Ok(());
     }
 
     let categories = build_categories_from_config();
-    let mut extractor = MKSAPExtractor::new(&base_url, OUTPUT_DIR)?;
+    let mut extractor = MKSAPExtractor::new(&base_url, output_dir)?;
     if let Some(cookie) = session_cookie.as_deref() {
         extractor = extractor.with_session_cookie(cookie);
     }
+    let run_options = parse_run_options(args);
+    if let Some(concurrency) = run_options.concurrency {
+        extractor = extractor.with_concurrency(concurrency);
+    }
+    if let Some(path) = run_options.profile.as_deref() {
+        let profile = crate::profile::ExtractionProfile::with_file(Path::new(path))?;
+        info!("Writing extraction profile to {}", path);
+        extractor = extractor.with_profile(std::sync::Arc::new(profile));
+    }
+    if let Some(limit) = run_options.limit {
+        warn!(
+            "--limit {}: extracting at most {} questions per system (sample run, not a full extraction)",
+            limit, limit
+        );
+        extractor = extractor.with_limit(limit);
+    }
+    // Parsed up front so a bad value fails before login; `last` is looked up per run
+    let since = run_options
+        .since
+        .as_deref()
+        .map(SinceCutoff::parse)
+        .transpose()?;
 
     if command.requires_auth() {
         authenticate_extractor(&mut extractor).await?;
@@ -33,9 +122,17 @@ pub async fn handle_command(command: Command, args: &[String]) -> Result<()> {
 
     maybe_inspect_api(&extractor).await?;
 
+    let watch_interval = parse_watch_interval(args);
+    if watch_interval.is_some() {
+        // Ctrl-C between cycles should end the loop cleanly rather than exit mid-write
+        crate::shutdown::enable_graceful_shutdown();
+    }
+
     match command {
         Command::RetryMissing => {
-            let recovered = extractor.retry_missing_json().await?;
+            let recovered = extractor_for_run(&extractor, since, output_dir)
+                .retry_missing_json()
+                .await?;
             info!("Missing JSON recovery complete ({} recovered)", recovered);
         }
         Command::ListMissing => {
@@ -44,32 +141,53 @@ pub async fn handle_command(command: Command, args: &[String]) -> Result<()> {
         }
         Command::Run => {
             let options = parse_run_options(args);
-            run_extraction(
-                &extractor,
-                &categories,
-                OUTPUT_DIR,
-                options.refresh_existing,
-            )
+            let categories =
+                filter_categories(&categories, &options.systems, &options.excluded_systems)?;
+            let (extractor, categories, options) = (&extractor, &categories, &options);
+            repeat_until_stopped(watch_interval, shutdown_requested, || async move {
+                // Each cycle resolves `--since last` against the run the previous one recorded
+                run_extraction(
+                    &extractor_for_run(extractor, since, output_dir),
+                    categories,
+                    output_dir,
+                    options.refresh_existing,
+                    options.profile_top,
+                )
+                .await
+            })
             .await?;
         }
         Command::MediaDiscover => {
-            run_media_discovery(&media_options).await?;
+            let media_options = &media_options;
+            repeat_until_stopped(watch_interval, shutdown_requested, || {
+                run_media_discovery(media_options)
+            })
+            .await?;
         }
         Command::MediaDownload => {
             run_media_download(&media_options).await?;
         }
+        Command::RetryMediaFailures => {
+            run_retry_media_failures(&media_options).await?;
+        }
         Command::SvgBrowser => {
             run_svg_browser(&media_options).await?;
         }
         Command::ExtractAll => {
             let options = parse_run_options(args);
+            let categories =
+                filter_categories(&categories, &options.systems, &options.excluded_systems)?;
             run_extraction(
-                &extractor,
+                &extractor_for_run(&extractor, since, output_dir),
                 &categories,
-                OUTPUT_DIR,
+                output_dir,
                 options.refresh_existing,
+                options.profile_top,
             )
             .await?;
+            if crate::shutdown::shutdown_requested() {
+                return Ok(());
+            }
             run_media_discovery(&media_options).await?;
             run_media_download(&media_options).await?;
             if has_flag(args, "--with-browser") {
@@ -82,57 +200,293 @@ pub async fn handle_command(command: Command, args: &[String]) -> Result<()> {
     Ok(())
 }
 
+/// A copy of `extractor` with the `--since` cutoff for a run starting now.
+fn extractor_for_run(
+    extractor: &MKSAPExtractor,
+    since: Option<SinceCutoff>,
+    output_dir: &str,
+) -> MKSAPExtractor {
+    let extractor = extractor.clone();
+    match since.and_then(|since| since.resolve(output_dir)) {
+        Some(since) => {
+            info!("Only re-extracting questions updated since {}", since);
+            extractor.with_since(since)
+        }
+        None => extractor,
+    }
+}
+
 pub async fn handle_standalone_command(
     command: Command,
     args: &[String],
+    output_dir: &str,
     session_cookie: Option<&str>,
     base_url: &str,
 ) -> Result<bool> {
     match command {
         Command::Validate => {
-            handle_validate().await?;
+            handle_validate(args, output_dir).await?;
             Ok(true)
         }
         Command::Standardize => {
-            handle_standardize(args).await?;
+            handle_standardize(args, output_dir).await?;
             Ok(true)
         }
         Command::CleanupRetired => {
-            handle_cleanup_retired(session_cookie, base_url).await?;
+            handle_cleanup_retired(output_dir, session_cookie, base_url).await?;
             Ok(true)
         }
         Command::CleanupFlat => {
-            handle_cleanup_flat(base_url).await?;
+            handle_cleanup_flat(output_dir, base_url).await?;
+            Ok(true)
+        }
+        Command::FindDuplicates => {
+            handle_find_duplicates(args, output_dir, base_url).await?;
+            Ok(true)
+        }
+        Command::Login => {
+            http_login(&MediaOptions::from_args(args)).await?;
+            Ok(true)
+        }
+        Command::ProgressBadges => {
+            handle_progress_badges(args, output_dir)?;
             Ok(true)
         }
         Command::DiscoveryStats => {
-            handle_discovery_stats().await?;
+            handle_discovery_stats(args, output_dir).await?;
+            Ok(true)
+        }
+        Command::ExportSqlite => {
+            handle_export_sqlite(args, output_dir).await?;
+            Ok(true)
+        }
+        Command::ExportStems => {
+            handle_export_stems(args, output_dir).await?;
+            Ok(true)
+        }
+        Command::ExportAnki => {
+            handle_export_anki(args, output_dir).await?;
+            Ok(true)
+        }
+        Command::ExportSystem => {
+            handle_export_system(args, output_dir).await?;
+            Ok(true)
+        }
+        Command::MediaDimensions => {
+            run_media_dimensions(&MediaOptions::from_args(args)).await?;
+            Ok(true)
+        }
+        Command::ProgressHistory => {
+            record_progress_history(output_dir).await?;
+            Ok(true)
+        }
+        Command::RasterizeSvgs => {
+            run_svg_rasterize(&MediaOptions::from_args(args)).await?;
+            Ok(true)
+        }
+        Command::DiscoveryReport => {
+            let input = parse_arg_value(args, "--input")
+                .unwrap_or_else(|| MediaOptions::from_args(args).discovery_file);
+            run_discovery_report(&input).await?;
+            Ok(true)
+        }
+        Command::VerifyJson => {
+            crate::verify_json::run_verify_json(
+                output_dir,
+                parse_arg_value(args, "--system").as_deref(),
+            )?;
+            Ok(true)
+        }
+        Command::ValidateCheckpoints => {
+            crate::checkpoints::run_checkpoint_validation(output_dir)?;
+            Ok(true)
+        }
+        Command::Diff => {
+            handle_diff(args)?;
+            Ok(true)
+        }
+        Command::ExportSchema => {
+            let out_path = parse_arg_value(args, "--out");
+            crate::export::export_schema::export_schema(out_path.as_deref().map(Path::new))?;
+            if let Some(out_path) = out_path {
+                info!("✓ QuestionData schema written to {}", out_path);
+            }
+            Ok(true)
+        }
+        Command::ExportMarkdown => {
+            handle_export_markdown(args, output_dir)?;
+            Ok(true)
+        }
+        Command::MediaIndex => {
+            use crate::assets::media_index::{write_media_index, MEDIA_INDEX_FILE_NAME};
+
+            let data_dir = MediaOptions::from_args(args).data_dir;
+            let out_path = parse_arg_value(args, "--out").unwrap_or_else(|| {
+                Path::new(&data_dir)
+                    .join(MEDIA_INDEX_FILE_NAME)
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            let count = write_media_index(&data_dir, Path::new(&out_path))?;
+            info!(
+                "✓ Media index written to {} ({} media items)",
+                out_path, count
+            );
+            Ok(true)
+        }
+        Command::MediaManifest => {
+            use crate::assets::media_index::{write_media_manifest, MEDIA_MANIFEST_FILE_NAME};
+
+            let data_dir = MediaOptions::from_args(args).data_dir;
+            let out_path = parse_arg_value(args, "--out").unwrap_or_else(|| {
+                Path::new(&data_dir)
+                    .join(MEDIA_MANIFEST_FILE_NAME)
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            let manifest = write_media_manifest(&data_dir, Path::new(&out_path))?;
+            let shared = manifest
+                .iter()
+                .filter(|entry| entry.question_ids.len() > 1)
+                .count();
+            let missing = manifest
+                .iter()
+                .filter(|entry| entry.bytes.is_none())
+                .count();
+            info!(
+                "✓ Media manifest written to {} ({} media items, {} shared, {} missing on disk)",
+                out_path,
+                manifest.len(),
+                shared,
+                missing
+            );
+            Ok(true)
+        }
+        Command::Stats => {
+            let data_dir = MediaOptions::from_args(args).data_dir;
+            let stats = crate::stats::compute_corpus_stats(&data_dir)?;
+            println!("\n{}", crate::stats::render_corpus_stats(&stats));
+            if let Some(out_path) = parse_arg_value(args, "--json-out") {
+                crate::stats::write_corpus_stats_json(&stats, Path::new(&out_path))?;
+                info!("✓ Corpus stats written to {}", out_path);
+            }
+            Ok(true)
+        }
+        Command::Search => {
+            handle_search(args)?;
+            Ok(true)
+        }
+        Command::Doctor => {
+            let options = MediaOptions::from_args(args);
+            let question_id = parse_arg_value(args, "--question-id")
+                .unwrap_or_else(|| crate::doctor::DOCTOR_QUESTION_ID.to_string());
+            crate::doctor::run_doctor(&crate::doctor::DoctorTargets {
+                base_url,
+                session_cookie,
+                question_id: &question_id,
+                webdriver_url: &options.webdriver_url,
+                output_dir,
+                checkpoint_dir: &options.checkpoint_dir,
+            })
+            .await?;
             Ok(true)
         }
         _ => Ok(false),
     }
 }
 
-async fn handle_validate() -> Result<()> {
-    validate_extraction(OUTPUT_DIR).await?;
+fn handle_export_markdown(args: &[String], output_dir: &str) -> Result<()> {
+    let question_id = parse_arg_value(args, "--question-id").or_else(|| {
+        args.iter()
+            .skip(2)
+            .find(|arg| !arg.starts_with('-'))
+            .cloned()
+    });
+    let Some(question_id) = question_id else {
+        anyhow::bail!("Usage: export-markdown <question_id>");
+    };
+    let path = crate::extractor::render::export_question_markdown(output_dir, &question_id)?;
+    info!("✓ Markdown written to {}", path.display());
     Ok(())
 }
 
-async fn handle_standardize(args: &[String]) -> Result<()> {
-    info!("=== STANDARDIZING JSON FILES ===");
-    let options = parse_standardize_options(args);
-    crate::run_standardization(
-        OUTPUT_DIR,
-        options.dry_run,
-        options.system_filter.as_deref(),
+fn handle_diff(args: &[String]) -> Result<()> {
+    let positional: Vec<&String> = args
+        .iter()
+        .skip(2)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect();
+    let old_dir =
+        parse_arg_value(args, "--old").or_else(|| positional.first().map(|s| s.to_string()));
+    let new_dir =
+        parse_arg_value(args, "--new").or_else(|| positional.get(1).map(|s| s.to_string()));
+    match (old_dir, new_dir) {
+        (Some(old_dir), Some(new_dir)) => crate::diff::run_snapshot_diff(&old_dir, &new_dir),
+        _ => anyhow::bail!("Usage: diff <old_dir> <new_dir>"),
+    }
+}
+
+fn handle_search(args: &[String]) -> Result<()> {
+    let query = parse_arg_value(args, "--query")
+        .or_else(|| args.get(2).filter(|arg| !arg.starts_with('-')).cloned());
+    let Some(query) = query else {
+        anyhow::bail!(
+            "Usage: search <query> [--field stem|critique|options|key_points|references|all] [--system <code>] [--regex]"
+        );
+    };
+    let field = match parse_arg_value(args, "--field") {
+        Some(field) => crate::search::SearchField::parse(&field)?,
+        None => crate::search::SearchField::All,
+    };
+    crate::search::run_search(
+        &MediaOptions::from_args(args).data_dir,
+        &query,
+        has_flag(args, "--regex"),
+        field,
+        parse_arg_value(args, "--system").as_deref(),
+    )
+}
+
+async fn handle_validate(args: &[String], output_dir: &str) -> Result<()> {
+    let excluded_systems = parse_arg_values(args, "--exclude-system");
+    crate::config::ensure_known_codes(&build_categories_from_config(), &excluded_systems)?;
+    let format = match parse_arg_value(args, "--report-format") {
+        Some(format) => ReportFormat::parse(&format)?,
+        None => ReportFormat::Text,
+    };
+    let report_out = parse_arg_value(args, "--report-out");
+    validate_extraction(
+        output_dir,
+        has_flag(args, "--check-media"),
+        has_flag(args, "--include-empty-systems"),
+        &excluded_systems,
+        format,
+        report_out.as_deref().map(Path::new),
     )
     .await?;
+    maybe_write_metrics(args, output_dir)
+}
+
+fn maybe_write_metrics(args: &[String], output_dir: &str) -> Result<()> {
+    if let Some(path) = parse_arg_value(args, "--metrics-textfile") {
+        crate::reporting::write_metrics_textfile(output_dir, Path::new(&path))?;
+    }
+    Ok(())
+}
+
+async fn handle_standardize(args: &[String], output_dir: &str) -> Result<()> {
+    info!("=== STANDARDIZING JSON FILES ===");
+    crate::run_standardization(output_dir, &parse_standardize_options(args)).await?;
     Ok(())
 }
 
-async fn handle_cleanup_retired(session_cookie: Option<&str>, base_url: &str) -> Result<()> {
+async fn handle_cleanup_retired(
+    output_dir: &str,
+    session_cookie: Option<&str>,
+    base_url: &str,
+) -> Result<()> {
     info!("=== CLEANING UP RETIRED QUESTIONS ===");
-    let mut extractor = MKSAPExtractor::new(base_url, OUTPUT_DIR)?;
+    let mut extractor = MKSAPExtractor::new(base_url, output_dir)?;
     if let Some(cookie) = session_cookie {
         extractor = extractor.with_session_cookie(cookie);
     }
@@ -144,15 +498,216 @@ async fn handle_cleanup_retired(session_cookie: Option<&str>, base_url: &str) ->
     Ok(())
 }
 
-async fn handle_cleanup_flat(base_url: &str) -> Result<()> {
+async fn handle_cleanup_flat(output_dir: &str, base_url: &str) -> Result<()> {
     info!("=== CLEANING UP FLAT DUPLICATE JSON FILES ===");
-    let extractor = MKSAPExtractor::new(base_url, OUTPUT_DIR)?;
+    let extractor = MKSAPExtractor::new(base_url, output_dir)?;
     let deleted = extractor.cleanup_flat_duplicates()?;
     info!("\n✓ Cleanup complete: {} flat duplicates deleted", deleted);
     Ok(())
 }
 
-async fn handle_discovery_stats() -> Result<()> {
-    show_discovery_stats(OUTPUT_DIR).await?;
+/// `login` / `--http-login`: sign in with username and password over HTTP (no browser) and
+/// save the session cookie for later runs.
+async fn http_login(options: &MediaOptions) -> Result<String> {
+    let credential = |value: &Option<String>, key: &str| {
+        value
+            .clone()
+            .or_else(|| std::env::var(key).ok())
+            .unwrap_or_default()
+    };
+    let username = credential(&options.username, "MKSAP_USERNAME");
+    let password = credential(&options.password, "MKSAP_PASSWORD");
+    let session =
+        crate::http_login::authenticate_http(&options.base_url, &username, &password).await?;
+    crate::session::save_session_cookie(&session)?;
+    info!("Saved session cookie to ~/.mksap_session");
+    if std::env::var("MKSAP_SESSION").is_ok_and(|value| !value.trim().is_empty()) {
+        warn!("MKSAP_SESSION is set and takes precedence over the saved cookie; unset it to use this login");
+    }
+    Ok(session)
+}
+
+fn handle_progress_badges(args: &[String], output_dir: &str) -> Result<()> {
+    use crate::badges::{
+        compute_progress_badges, write_progress_badges, BadgeFormat, BADGES_DIR_NAME,
+    };
+
+    let format = match parse_arg_value(args, "--format") {
+        Some(value) => BadgeFormat::parse(&value)?,
+        None => BadgeFormat::Svg,
+    };
+    let out_dir = parse_arg_value(args, "--out")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| Path::new(output_dir).join(BADGES_DIR_NAME));
+    let badges = compute_progress_badges(output_dir)?;
+    let written = write_progress_badges(&badges, &out_dir, format)?;
+    for badge in &badges {
+        println!(
+            "{:<4} {}/{} ({}, {})",
+            badge.label,
+            badge.found,
+            badge.discovered,
+            badge.message(),
+            badge.color()
+        );
+    }
+    info!(
+        "✓ Wrote {} badge file(s) to {}",
+        written.len(),
+        out_dir.display()
+    );
+    Ok(())
+}
+
+async fn handle_find_duplicates(args: &[String], output_dir: &str, base_url: &str) -> Result<()> {
+    let fix = has_flag(args, "--fix");
+    let extractor = MKSAPExtractor::new(base_url, output_dir)?;
+    let duplicates = extractor.find_duplicate_questions()?;
+
+    println!("\n=== Duplicate Questions ===\n");
+    for duplicate in &duplicates {
+        println!("{}", duplicate.question_id);
+        for (index, copy) in duplicate.copies.iter().enumerate() {
+            println!(
+                "  {:<5} {}  extracted_at {}",
+                if index == 0 { "keep" } else { "extra" },
+                copy.path.display(),
+                copy.extracted_at
+                    .map(|time| time.to_rfc3339())
+                    .unwrap_or_else(|| "unknown".to_string())
+            );
+        }
+    }
+
+    let extra: usize = duplicates.iter().map(|d| d.copies.len() - 1).sum();
+    println!(
+        "\nDetected {} duplicated question IDs ({} extra copies)",
+        duplicates.len(),
+        extra
+    );
+    if extra == 0 {
+        return Ok(());
+    }
+    if fix {
+        let moved = extractor.resolve_duplicate_questions(&duplicates)?;
+        println!(
+            "Resolved {} of {} extra copies (moved to mksap_data_failed/duplicates/)",
+            moved, extra
+        );
+    } else {
+        println!("Re-run with --fix to keep the newest copy and move the others aside");
+    }
+    Ok(())
+}
+
+async fn handle_discovery_stats(args: &[String], output_dir: &str) -> Result<()> {
+    show_discovery_stats(output_dir, has_flag(args, "--include-empty-systems")).await?;
+    maybe_write_metrics(args, output_dir)
+}
+
+async fn handle_export_sqlite(args: &[String], output_dir: &str) -> Result<()> {
+    info!("=== EXPORTING QUESTIONS TO SQLITE ===");
+    let options = parse_sqlite_export_options(args);
+    let summary =
+        crate::export::export_sqlite::export_sqlite(output_dir, Path::new(&options.db_path))?;
+    info!(
+        "\n✓ Export complete: {} questions across {} systems, {} options, {} key points, {} media, {} references, {} critique links",
+        summary.questions,
+        summary.systems,
+        summary.options,
+        summary.key_points,
+        summary.media,
+        summary.references,
+        summary.critique_links
+    );
+    Ok(())
+}
+
+async fn handle_export_anki(args: &[String], output_dir: &str) -> Result<()> {
+    info!("=== EXPORTING ANKI DECK ===");
+    let options = parse_anki_export_options(args);
+    if let Some(system) = options.system_filter.as_deref() {
+        if crate::config::get_organ_system_by_id(system).is_none() {
+            anyhow::bail!("Unknown system code: {}", system);
+        }
+    }
+    let summary = crate::export::export_anki::export_anki(
+        output_dir,
+        Path::new(&options.out_path),
+        options.system_filter.as_deref(),
+    )?;
+    info!(
+        "\n✓ Export complete: {} notes, {} media files written to {}",
+        summary.notes, summary.media, options.out_path
+    );
+    Ok(())
+}
+
+async fn handle_export_system(args: &[String], output_dir: &str) -> Result<()> {
+    info!("=== EXPORTING SYSTEM ===");
+    let options = parse_system_export_options(args);
+    if options.group_by_topic {
+        return export_by_topic(output_dir, options.system.as_deref(), options.into);
+    }
+    let Some(system) = options.system else {
+        anyhow::bail!("export-system requires --system <code>");
+    };
+    let into = options.into.unwrap_or_else(|| format!("mksap_{}", system));
+    let summary =
+        crate::export::export_system::export_system(output_dir, &system, Path::new(&into))?;
+    info!(
+        "\n✓ Export complete: {} questions, {} files written to {}",
+        summary.questions, summary.files, into
+    );
+    Ok(())
+}
+
+fn export_by_topic(
+    output_dir: &str,
+    system_filter: Option<&str>,
+    into: Option<String>,
+) -> Result<()> {
+    if let Some(system) = system_filter {
+        if crate::config::get_organ_system_by_id(system).is_none() {
+            anyhow::bail!("Unknown system code: {}", system);
+        }
+    }
+    let into = into.unwrap_or_else(|| match system_filter {
+        Some(system) => format!("mksap_{}_topics", system),
+        None => "mksap_topics".to_string(),
+    });
+    let summary =
+        crate::export::export_topics::export_by_topic(output_dir, system_filter, Path::new(&into))?;
+    info!(
+        "\n✓ Export complete: {} questions in {} topics ({} uncategorized) written to {}",
+        summary.questions, summary.topics, summary.uncategorized, into
+    );
+    Ok(())
+}
+
+async fn handle_export_stems(args: &[String], output_dir: &str) -> Result<()> {
+    use crate::export::export_stems::{export_stems, StemFormat};
+
+    info!("=== EXPORTING QUESTION STEMS ===");
+    let options = parse_stem_export_options(args);
+    let format = StemFormat::parse(&options.format)?;
+    if let Some(system) = options.system_filter.as_deref() {
+        if crate::config::get_organ_system_by_id(system).is_none() {
+            anyhow::bail!("Unknown system code: {}", system);
+        }
+    }
+    let out_path = options
+        .out_path
+        .unwrap_or_else(|| format!("question_stems.{}", format.extension()));
+    let count = export_stems(
+        output_dir,
+        Path::new(&out_path),
+        format,
+        options.system_filter.as_deref(),
+    )?;
+    info!(
+        "\n✓ Export complete: {} stems written to {}",
+        count, out_path
+    );
     Ok(())
 }
@@ -0,0 +1,326 @@
+//! Username/password login over plain HTTP (`login`, `--http-login`), for headless servers
+//! without a browser or WebDriver.
+//!
+//! The sign-in page is fetched for its form: the action, hidden fields (including the Rails
+//! CSRF `authenticity_token`) and the email/password field names. The credentials are posted
+//! with the cookies it set, kept in a reqwest cookie jar across every redirect hop, and the
+//! resulting session cookie is checked against the question bank before it is returned.
+
+use anyhow::{bail, Context, Result};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::LOCATION;
+use reqwest::{Client, Response};
+use scraper::{ElementRef, Html, Selector};
+use std::sync::Arc;
+use tracing::info;
+use url::Url;
+
+const MAX_LOGIN_REDIRECTS: usize = 10;
+/// Devise's field names, used when the sign-in page has no recognizable form.
+const DEFAULT_EMAIL_FIELD: &str = "user[email]";
+const DEFAULT_PASSWORD_FIELD: &str = "user[password]";
+const DEFAULT_CSRF_PARAM: &str = "authenticity_token";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LoginForm {
+    action: Url,
+    email_field: String,
+    password_field: String,
+    /// Hidden inputs (CSRF token and the like), posted back unchanged.
+    hidden: Vec<(String, String)>,
+}
+
+/// Value of the cookie `name` the jar would send to `url`.
+fn cookie_value(jar: &Jar, url: &Url, name: &str) -> Option<String> {
+    let header = jar.cookies(url)?;
+    header.to_str().ok()?.split(';').find_map(|pair| {
+        let (key, value) = pair.trim().split_once('=')?;
+        (key == name).then(|| value.to_string())
+    })
+}
+
+/// Log in with `username`/`password` and return the session cookie value.
+pub async fn authenticate_http(base_url: &str, username: &str, password: &str) -> Result<String> {
+    if username.trim().is_empty() || password.is_empty() {
+        bail!("HTTP login needs a username and password (--username/--password or MKSAP_USERNAME/MKSAP_PASSWORD)");
+    }
+    let jar = Arc::new(Jar::default());
+    let client = crate::http::build_client_without_redirects(jar.clone())?;
+
+    let login_url = crate::endpoints::login_page(base_url);
+    info!("Logging in as {} via {}", username, login_url);
+    let login_url =
+        Url::parse(&login_url).with_context(|| format!("Invalid login URL {}", login_url))?;
+    let page = follow_redirects(&client, login_url.clone()).await?;
+    if !page.status().is_success() {
+        bail!("Login page {} returned HTTP {}", page.url(), page.status());
+    }
+    let page_url = page.url().clone();
+    let form = parse_login_form(&page.text().await?, &page_url);
+
+    let mut fields = form.hidden;
+    fields.push((form.email_field, username.to_string()));
+    fields.push((form.password_field, password.to_string()));
+    let response = client
+        .post(form.action.clone())
+        .form(&fields)
+        .send()
+        .await
+        .with_context(|| format!("Failed to submit login form to {}", form.action))?;
+    // A successful sign-in redirects; a rejected one re-renders the form
+    let status = response.status();
+    if !status.is_redirection() {
+        bail!(
+            "Login rejected for {} (HTTP {} from {}); check the username and password",
+            username,
+            status,
+            form.action
+        );
+    }
+    follow_location(&client, response).await?;
+
+    let cookie_name = crate::session::session_cookie_name();
+    let session = cookie_value(&jar, &login_url, &cookie_name)
+        .with_context(|| format!("Login did not set a {} cookie", cookie_name))?;
+
+    let check = client
+        .get(crate::endpoints::answered_questions(base_url))
+        .headers(crate::http::session_cookie_headers(&session)?)
+        .send()
+        .await
+        .context("Failed to verify the new session")?;
+    if check.status() != 200 {
+        bail!(
+            "Login returned a session cookie but the question bank answered HTTP {}",
+            check.status()
+        );
+    }
+    info!("✓ Logged in over HTTP as {}", username);
+    Ok(session)
+}
+
+async fn follow_redirects(client: &Client, url: Url) -> Result<Response> {
+    let response = client.get(url).send().await?;
+    follow_location(client, response).await
+}
+
+/// Walk `Location` headers from `response`; the client's cookie jar collects each hop's cookies.
+async fn follow_location(client: &Client, mut response: Response) -> Result<Response> {
+    for _ in 0..MAX_LOGIN_REDIRECTS {
+        if !response.status().is_redirection() {
+            return Ok(response);
+        }
+        let Some(location) = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return Ok(response);
+        };
+        let next = response
+            .url()
+            .join(location)
+            .with_context(|| format!("Invalid redirect location {}", location))?;
+        response = client.get(next).send().await?;
+    }
+    crate::http::ensure_not_redirect(response)
+}
+
+/// The form holding a password input, or Devise defaults posting back to the page itself.
+fn parse_login_form(html: &str, page_url: &Url) -> LoginForm {
+    let document = Html::parse_document(html);
+    let form_selector = Selector::parse("form").unwrap();
+    let input_selector = Selector::parse("input[name]").unwrap();
+
+    let mut form = LoginForm {
+        action: page_url.clone(),
+        email_field: DEFAULT_EMAIL_FIELD.to_string(),
+        password_field: DEFAULT_PASSWORD_FIELD.to_string(),
+        hidden: Vec::new(),
+    };
+    let login_form = document.select(&form_selector).find(|element| {
+        element
+            .select(&input_selector)
+            .any(|input| input_type(&input) == "password")
+    });
+    if let Some(element) = login_form {
+        if let Some(action) = element
+            .value()
+            .attr("action")
+            .filter(|action| !action.trim().is_empty())
+            .and_then(|action| page_url.join(action.trim()).ok())
+        {
+            form.action = action;
+        }
+        for input in element.select(&input_selector) {
+            let name = input.value().attr("name").unwrap_or_default().to_string();
+            match input_type(&input).as_str() {
+                "hidden" => {
+                    let value = input.value().attr("value").unwrap_or_default();
+                    form.hidden.push((name, value.to_string()));
+                }
+                "password" => form.password_field = name,
+                "email" => form.email_field = name,
+                "text" if is_login_name(&name) => form.email_field = name,
+                _ => {}
+            }
+        }
+    }
+
+    // Rails pages also carry the token in <meta name="csrf-token">
+    let csrf_param =
+        meta_content(&document, "csrf-param").unwrap_or_else(|| DEFAULT_CSRF_PARAM.to_string());
+    if !form.hidden.iter().any(|(name, _)| *name == csrf_param) {
+        if let Some(token) = meta_content(&document, "csrf-token") {
+            form.hidden.push((csrf_param, token));
+        }
+    }
+    form
+}
+
+fn input_type(input: &ElementRef) -> String {
+    input
+        .value()
+        .attr("type")
+        .unwrap_or("text")
+        .to_ascii_lowercase()
+}
+
+fn is_login_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["email", "user", "login"]
+        .iter()
+        .any(|hint| name.contains(hint))
+}
+
+fn meta_content(document: &Html, name: &str) -> Option<String> {
+    let selector = Selector::parse(&format!("meta[name=\"{}\"]", name)).ok()?;
+    document
+        .select(&selector)
+        .find_map(|meta| meta.value().attr("content").map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SIGN_IN_PAGE: &str = r#"<html><head>
+        <meta name="csrf-param" content="authenticity_token">
+        <meta name="csrf-token" content="meta-token">
+        </head><body>
+        <form action="/search"><input type="text" name="q"></form>
+        <form action="/users/sign_in" method="post">
+          <input type="hidden" name="authenticity_token" value="form-token">
+          <input type="email" name="user[login]">
+          <input type="password" name="user[secret]">
+        </form></body></html>"#;
+
+    #[test]
+    fn test_parse_login_form_prefers_password_form_fields() {
+        let page_url = Url::parse("https://mksap.example/users/sign_in").unwrap();
+        let form = parse_login_form(SIGN_IN_PAGE, &page_url);
+        assert_eq!(form.action.as_str(), "https://mksap.example/users/sign_in");
+        assert_eq!(form.email_field, "user[login]");
+        assert_eq!(form.password_field, "user[secret]");
+        assert_eq!(
+            form.hidden,
+            vec![("authenticity_token".to_string(), "form-token".to_string())]
+        );
+
+        let bare = parse_login_form(
+            r#"<meta name="csrf-token" content="meta-token">"#,
+            &page_url,
+        );
+        assert_eq!(bare.email_field, DEFAULT_EMAIL_FIELD);
+        assert_eq!(
+            bare.hidden,
+            vec![("authenticity_token".to_string(), "meta-token".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_cookie_value_honours_scope_secure_and_deletion() {
+        let jar = Jar::default();
+        let origin = Url::parse("https://mksap.acponline.example/users/sign_in").unwrap();
+        jar.add_cookie_str("_session=secret; Path=/; Secure; HttpOnly", &origin);
+        jar.add_cookie_str("remember=1; Path=/", &origin);
+        jar.add_cookie_str("remember=; Path=/; Max-Age=0", &origin);
+
+        assert_eq!(
+            cookie_value(&jar, &origin, "_session").as_deref(),
+            Some("secret")
+        );
+        assert_eq!(cookie_value(&jar, &origin, "remember"), None);
+        let plain = Url::parse("http://mksap.acponline.example/").unwrap();
+        assert_eq!(cookie_value(&jar, &plain, "_session"), None);
+        let other = Url::parse("https://attacker.example/").unwrap();
+        assert_eq!(cookie_value(&jar, &other, "_session"), None);
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_http_posts_form_and_returns_session() {
+        let server = MockServer::start().await;
+        let cookie_name = crate::session::session_cookie_name();
+        Mock::given(method("GET"))
+            .and(path("/users/sign_in"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("set-cookie", format!("{}=anonymous; path=/", cookie_name))
+                    .set_body_string(SIGN_IN_PAGE),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/users/sign_in"))
+            .and(header(
+                "cookie",
+                format!("{}=anonymous", cookie_name).as_str(),
+            ))
+            .and(body_string_contains("authenticity_token=form-token"))
+            .and(body_string_contains("user%5Blogin%5D=me%40example.com"))
+            .and(body_string_contains("user%5Bsecret%5D=hunter2"))
+            .respond_with(
+                ResponseTemplate::new(302)
+                    .insert_header("location", "/home")
+                    .insert_header(
+                        "set-cookie",
+                        format!("{}=signed-in; path=/; HttpOnly", cookie_name),
+                    ),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/users/sign_in"))
+            .respond_with(ResponseTemplate::new(422).set_body_string(SIGN_IN_PAGE))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/home"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/app/question-bank/content-areas/cv/answered-questions",
+            ))
+            .and(header(
+                "cookie",
+                format!("{}=signed-in", cookie_name).as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let session = authenticate_http(&server.uri(), "me@example.com", "hunter2")
+            .await
+            .unwrap();
+        assert_eq!(session, "signed-in");
+
+        let err = authenticate_http(&server.uri(), "me@example.com", "wrong")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Login rejected"), "{err}");
+    }
+}
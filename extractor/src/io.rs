@@ -2,10 +2,13 @@ use anyhow::{Context, Result};
 use std::collections::HashSet;
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tracing::debug;
 
 use crate::models::QuestionData;
+use crate::utils::DirScanner;
 use crate::validator::DataValidator;
 
 use super::{MKSAPExtractor, CHECKPOINT_DIR_NAME, QUESTION_TYPE_CODES};
@@ -27,6 +30,32 @@ pub fn checkpoint_system_id(path: &Path) -> Option<String> {
     }
 }
 
+/// Distinguishes temp files of concurrent writers within one process.
+static ATOMIC_WRITE_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// Replace `path` with `contents` through a temp file that is synced and renamed into place,
+/// so readers and concurrent writers only ever see a complete file (the last rename wins).
+/// Each write uses its own temp file, named after the process and a sequence number.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        ATOMIC_WRITE_SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = path.with_file_name(name);
+    let result = fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result.with_context(|| format!("Failed to write {}", path.display()))
+}
+
 pub fn read_checkpoint_lines(path: &Path) -> Result<Vec<String>> {
     let content = fs::read_to_string(path).context("Failed to read checkpoint file")?;
     Ok(content
@@ -77,29 +106,20 @@ where
         return Ok(results);
     }
 
-    for system_entry in fs::read_dir(root_path).context("Failed to read root directory")? {
-        let system_entry = system_entry.context("Failed to read system entry")?;
-        let system_path = system_entry.path();
-
-        if !system_path.is_dir() {
-            continue;
-        }
-
+    let mut scanner = DirScanner::from_config();
+    for system_path in scanner
+        .subdirs(root_path)
+        .context("Failed to read root directory")?
+    {
         let system_id = match system_path.file_name().and_then(|n| n.to_str()) {
             Some(name) if !skip_dirs.contains(name) => name.to_string(),
             _ => continue,
         };
 
-        for question_entry in
-            fs::read_dir(&system_path).context("Failed to read system directory")?
+        for question_path in scanner
+            .subdirs(&system_path)
+            .context("Failed to read system directory")?
         {
-            let question_entry = question_entry.context("Failed to read question entry")?;
-            let question_path = question_entry.path();
-
-            if !question_path.is_dir() {
-                continue;
-            }
-
             let question_id = match question_path.file_name().and_then(|n| n.to_str()) {
                 Some(name) => name.to_string(),
                 None => continue,
@@ -132,6 +152,22 @@ impl MKSAPExtractor {
             .join(format!("{}.json", question_id))
     }
 
+    /// Where invalidated questions are filed, outside the active data tree.
+    pub(super) fn retired_dir(&self) -> PathBuf {
+        self.failed_root().join("retired")
+    }
+
+    /// Where `find-duplicates --fix` moves the extra copies of a duplicated question.
+    pub(super) fn duplicates_dir(&self) -> PathBuf {
+        self.failed_root().join("duplicates")
+    }
+
+    pub(super) fn retired_json_path(&self, question_id: &str) -> PathBuf {
+        self.retired_dir()
+            .join(question_id)
+            .join(format!("{}.json", question_id))
+    }
+
     pub(super) fn looks_like_question_id(category_code: &str, question_id: &str) -> bool {
         if !question_id.starts_with(category_code) {
             return false;
@@ -174,24 +210,28 @@ impl MKSAPExtractor {
             return Ok(existing_ids);
         }
 
+        for path in DirScanner::from_config()
+            .subdirs(&category_dir)
+            .context("Failed to read category directory")?
+        {
+            let Some(dir_name) = path.file_name().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if !Self::looks_like_question_id(category_code, dir_name) {
+                continue;
+            }
+
+            let json_path = path.join(format!("{}.json", dir_name));
+            if json_path.exists() && Self::is_valid_question_json(&json_path, dir_name) {
+                existing_ids.insert(dir_name.to_string());
+            }
+        }
+
+        // Legacy flat layout: `<category>/<question_id>.json`
         for entry in fs::read_dir(&category_dir).context("Failed to read category directory")? {
             let entry = entry.context("Failed to read directory entry")?;
             let path = entry.path();
 
-            if path.is_dir() {
-                if let Some(dir_name) = path.file_name().and_then(|s| s.to_str()) {
-                    if !Self::looks_like_question_id(category_code, dir_name) {
-                        continue;
-                    }
-
-                    let json_path = path.join(format!("{}.json", dir_name));
-                    if json_path.exists() && Self::is_valid_question_json(&json_path, dir_name) {
-                        existing_ids.insert(dir_name.to_string());
-                    }
-                }
-                continue;
-            }
-
             if !path.is_file() {
                 continue;
             }
@@ -241,8 +281,7 @@ impl MKSAPExtractor {
         unique_ids.dedup();
         let content = unique_ids.join("\n");
 
-        fs::write(&path, content).context("Failed to write checkpoint file")?;
-        Ok(())
+        write_atomic(&path, content)
     }
 
     /// Save extracted question data to nested folder structure.
@@ -284,13 +323,40 @@ impl MKSAPExtractor {
 
         // Save JSON - only JSON file, no metadata.txt
         let json_path = self.question_json_path(category_code, &question.question_id);
-        let json_content = serde_json::to_string_pretty(&question)?;
+        let json_content = crate::utils::to_json_string(&question)?;
         fs::write(&json_path, json_content).context("Failed to write JSON file")?;
 
         tracing::info!("Saved question data for {}", question.question_id);
         Ok(())
     }
 
+    /// Save an invalidated question under `retired/<question_id>/`. An active copy from an
+    /// earlier run is moved there first; an empty placeholder folder is removed.
+    pub fn save_retired_question(
+        &self,
+        category_code: &str,
+        question: &QuestionData,
+    ) -> Result<()> {
+        let active_folder = self.question_dir(category_code, &question.question_id);
+        let retired_folder = self.retired_dir().join(&question.question_id);
+        fs::create_dir_all(self.retired_dir()).context("Failed to create retired folder")?;
+
+        if active_folder.exists() && !retired_folder.exists() {
+            fs::rename(&active_folder, &retired_folder)
+                .context("Failed to move question folder to retired")?;
+        } else {
+            fs::remove_dir(&active_folder).ok();
+        }
+
+        fs::create_dir_all(&retired_folder).context("Failed to create retired folder")?;
+        let json_content = crate::utils::to_json_string(&question)?;
+        fs::write(self.retired_json_path(&question.question_id), json_content)
+            .context("Failed to write JSON file")?;
+
+        tracing::info!("Saved retired question {}", question.question_id);
+        Ok(())
+    }
+
     pub fn save_raw_question_json(
         &self,
         category_code: &str,
@@ -306,8 +372,8 @@ impl MKSAPExtractor {
         let error_path = question_folder.join(format!("{}_error.txt", question_id));
 
         if let Ok(value) = serde_json::from_str::<serde_json::Value>(raw_json) {
-            let pretty = serde_json::to_string_pretty(&value)?;
-            fs::write(&json_path, pretty).context("Failed to write raw JSON file")?;
+            let json_content = crate::utils::to_json_string(&value)?;
+            fs::write(&json_path, json_content).context("Failed to write raw JSON file")?;
         } else {
             let raw_path = question_folder.join(format!("{}_raw.txt", question_id));
             fs::write(&raw_path, raw_json).context("Failed to write raw response file")?;
@@ -387,3 +453,66 @@ impl MKSAPExtractor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_checkpoint_saves_never_interleave() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap().to_string();
+        let id_sets: Vec<Vec<String>> = (0..8)
+            .map(|worker| {
+                (1..=200)
+                    .map(|num| format!("cvmcq2{}{:03}", worker, num))
+                    .collect()
+            })
+            .collect();
+
+        std::thread::scope(|scope| {
+            for ids in &id_sets {
+                let output_dir = &output_dir;
+                scope.spawn(move || {
+                    let extractor = MKSAPExtractor::new("http://localhost", output_dir).unwrap();
+                    for _ in 0..5 {
+                        // Duplicates collapse on write
+                        let mut doubled = ids.clone();
+                        doubled.extend(ids.iter().cloned());
+                        extractor.save_checkpoint_ids("cv", &doubled).unwrap();
+                    }
+                });
+            }
+        });
+
+        let extractor = MKSAPExtractor::new("http://localhost", &output_dir).unwrap();
+        let saved = extractor.load_checkpoint_ids("cv").unwrap().unwrap();
+        assert!(id_sets.contains(&saved), "checkpoint mixes writers");
+        let leftovers: Vec<_> = fs::read_dir(temp.path().join(CHECKPOINT_DIR_NAME))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(leftovers, vec![std::ffi::OsString::from("cv_ids.txt")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_existing_ids_skip_symlinked_question_dirs_like_other_scans() {
+        use crate::export::test_support::write_question;
+
+        let temp = tempfile::tempdir().unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+        let external = tempfile::tempdir().unwrap();
+        write_question(external.path(), "cv", "cvmcq24002");
+        std::os::unix::fs::symlink(
+            external.path().join("cv/cvmcq24002"),
+            temp.path().join("cv/cvmcq24002"),
+        )
+        .unwrap();
+
+        let extractor =
+            MKSAPExtractor::new("http://localhost", temp.path().to_str().unwrap()).unwrap();
+        let existing = extractor.load_existing_question_ids("cv").unwrap();
+        assert_eq!(existing, HashSet::from(["cvmcq24001".to_string()]));
+    }
+}
@@ -1,34 +1,67 @@
 mod app;
 mod assets;
+mod badges;
+mod cache;
+mod checkpoints;
 mod cli;
 mod commands;
 mod config;
+mod diff;
+mod doctor;
 mod endpoints;
+mod error;
+mod events;
+mod export;
 mod extractor;
 mod handlers;
 mod http;
+mod http_login;
+mod lock;
 mod login_browser;
+mod math;
 mod models;
+mod profile;
+mod progress;
 mod reporting;
 mod runners;
+mod search;
 mod session;
+mod settings;
+mod shutdown;
+mod since;
 mod standardize;
+mod stats;
 mod utils;
 mod validator;
+mod verify_json;
+mod watch;
 
 pub use app::inspect_api;
-pub use app::{init_tracing, load_env, maybe_inspect_api, run, BASE_URL, DOTENV_PATH, OUTPUT_DIR};
+pub use app::{
+    init_tracing, load_env, maybe_inspect_api, output_dir, run, site_config, SiteConfig, BASE_URL,
+    DEFAULT_OUTPUT_DIR, DOTENV_PATH,
+};
+pub use checkpoints::{validate_checkpoints, CheckpointIssue, CheckpointReport};
 pub use cli::{
-    parse_run_options, parse_standardize_options, MediaOptions, RunOptions, StandardizeOptions,
+    parse_http_timeouts, parse_max_file_size, parse_run_options, parse_sqlite_export_options,
+    parse_standardize_options, MediaOptions, RunOptions, SqliteExportOptions, StandardizeOptions,
 };
 pub use commands::Command;
 pub use config::{build_categories_from_config, Category};
+pub use diff::{diff_snapshots, ChangedQuestion, SnapshotDiff, SystemDiffCounts};
+pub use error::{find_extract_error, ExtractError};
 pub use extractor::auth::authenticate_extractor;
 pub use extractor::io;
+pub use extractor::render::{export_question_markdown, render_question_markdown};
 pub use extractor::MKSAPExtractor;
 pub use handlers::handle_standalone_command;
+pub use http::HttpTimeouts;
+pub use http_login::authenticate_http;
+pub use profile::{ExtractionProfile, ProfileEntry};
 pub use reporting::{
-    count_discovered_ids, show_discovery_stats, total_discovered_ids, validate_extraction,
+    count_discovered_ids, record_progress_history, show_discovery_stats, total_discovered_ids,
+    validate_extraction, ProgressSnapshot, ReportFormat,
 };
-pub use runners::run_extraction;
-pub use standardize::run_standardization;
+pub use runners::{run_extraction, run_extraction_with_summary, CategoryResult, ExtractionSummary};
+pub use settings::{Config, LoginConfig};
+pub use standardize::{run_standardization, MediaBaseRewrite};
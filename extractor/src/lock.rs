@@ -0,0 +1,113 @@
+//! Advisory lock guarding the output tree against concurrent mutating runs.
+
+use anyhow::{bail, Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::extractor::checkpoint_dir;
+
+const LOCK_FILE_NAME: &str = ".lock";
+
+/// Holds `.checkpoints/.lock` for the lifetime of the guard.
+#[derive(Debug)]
+pub struct RunLock {
+    path: PathBuf,
+}
+
+impl RunLock {
+    /// Acquire the lock for `output_dir`, failing fast if another instance holds it.
+    ///
+    /// With `force_unlock`, an existing (presumably stale) lock is removed first.
+    pub fn acquire(output_dir: &str, force_unlock: bool) -> Result<Self> {
+        let path = lock_path(output_dir);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        if force_unlock && path.exists() {
+            warn!("--force-unlock: removing existing lock {}", path.display());
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove lock {}", path.display()))?;
+        }
+
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                let holder = fs::read_to_string(&path).unwrap_or_default();
+                bail!(
+                    "Another mksap-extractor instance is using {} ({}). \
+                     If no other run is active, re-run with --force-unlock.",
+                    output_dir,
+                    holder.trim()
+                );
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to create {}", path.display()))
+            }
+        };
+
+        writeln!(
+            file,
+            "pid {} since {}",
+            std::process::id(),
+            chrono::Utc::now().to_rfc3339()
+        )?;
+
+        Ok(Self { path })
+    }
+
+    /// Remove the lock if Ctrl-C force-quits the process; a graceful stop drops the guard.
+    pub fn release_on_signal(&self) {
+        crate::shutdown::remove_on_forced_exit(self.path.clone());
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            if err.kind() != ErrorKind::NotFound {
+                warn!("Failed to release lock {}: {}", self.path.display(), err);
+            }
+        }
+    }
+}
+
+fn lock_path(output_dir: &str) -> PathBuf {
+    checkpoint_dir(output_dir).join(LOCK_FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_instance_is_refused_while_lock_held() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+
+        let first = RunLock::acquire(output_dir, false).unwrap();
+        let err = RunLock::acquire(output_dir, false).unwrap_err();
+        assert!(err.to_string().contains("--force-unlock"));
+
+        drop(first);
+        assert!(!lock_path(output_dir).exists());
+        assert!(RunLock::acquire(output_dir, false).is_ok());
+    }
+
+    #[test]
+    fn test_force_unlock_overrides_stale_lock() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        let path = lock_path(output_dir);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "pid 999999 since 2020-01-01T00:00:00Z\n").unwrap();
+
+        assert!(RunLock::acquire(output_dir, false).is_err());
+        let lock = RunLock::acquire(output_dir, true).unwrap();
+        let holder = fs::read_to_string(&lock.path).unwrap();
+        assert!(holder.contains(&format!("pid {}", std::process::id())));
+    }
+}
@@ -93,6 +93,7 @@ impl BrowserLogin {
     /// };
     /// BrowserLogin::interactive_login("https://mksap.acponline.org", Some(check_auth)).await?;
     /// ```
+    #[cfg_attr(not(target_os = "macos"), allow(unreachable_code, unused_variables))]
     pub async fn interactive_login<F, Fut>(
         base_url: &str,
         username: &str,
@@ -0,0 +1,418 @@
+//! Readable Unicode rendering of MathML and inline LaTeX found in question text.
+//!
+//! Without this, MathML is flattened by the generic text walk (`x<sup>2</sup>+1`
+//! becomes "x2+1"). Rendering is opt-in via `--render-math`.
+
+use regex::Regex;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+static RENDER_MATH: AtomicBool = AtomicBool::new(false);
+
+const TAG_KEYS: [&str; 6] = ["tagName", "tag", "type", "elementType", "nodeName", "name"];
+const SPACED_OPERATORS: [&str; 12] = ["+", "-", "−", "=", "×", "÷", "<", ">", "≤", "≥", "±", "·"];
+
+pub(crate) fn configure_render_math(enabled: bool) {
+    RENDER_MATH.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn render_math_enabled() -> bool {
+    RENDER_MATH.load(Ordering::Relaxed)
+}
+
+#[derive(Debug)]
+enum MathNode {
+    Element {
+        tag: String,
+        children: Vec<MathNode>,
+    },
+    Text(String),
+}
+
+/// True when a JSON node is a `<math>` element.
+pub(crate) fn is_math_node(obj: &serde_json::Map<String, Value>) -> bool {
+    node_tag(obj).is_some_and(|tag| tag.eq_ignore_ascii_case("math"))
+}
+
+/// Render a MathML JSON node tree (`{"type": "math", "children": [...]}`) as readable text.
+pub(crate) fn render_mathml_node(node: &Value) -> String {
+    render(&from_json(node)).trim().to_string()
+}
+
+/// Replace inline `<math>…</math>` markup and `\( … \)` / `$ … $` LaTeX with readable text.
+///
+/// A `$ … $` pair only counts as LaTeX when it holds a command, script or group (`\`, `^`,
+/// `_`, `{`), so prices such as "$500 to $1,000" are left alone.
+pub(crate) fn render_math_markup(text: &str) -> String {
+    static MATHML_RE: OnceLock<Regex> = OnceLock::new();
+    let mathml_re = MATHML_RE.get_or_init(|| Regex::new(r"(?is)<math\b[^>]*>.*?</math>").unwrap());
+    let text = mathml_re.replace_all(text, |caps: &regex::Captures| {
+        render(&parse_mathml(&caps[0])).trim().to_string()
+    });
+
+    static LATEX_RE: OnceLock<Regex> = OnceLock::new();
+    let latex_re = LATEX_RE.get_or_init(|| {
+        Regex::new(r"(?s)\\\((.+?)\\\)|\\\[(.+?)\\\]|\$([^$\n]*?[\\^_{][^$\n]*?)\$").unwrap()
+    });
+    latex_re
+        .replace_all(&text, |caps: &regex::Captures| {
+            let body = caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .or_else(|| caps.get(3))
+                .map_or("", |m| m.as_str());
+            render_latex(body)
+        })
+        .to_string()
+}
+
+fn node_tag(obj: &serde_json::Map<String, Value>) -> Option<&str> {
+    TAG_KEYS
+        .iter()
+        .find_map(|key| obj.get(*key).and_then(|tag| tag.as_str()))
+}
+
+fn from_json(node: &Value) -> MathNode {
+    match node {
+        Value::String(text) => MathNode::Text(decode_entities(text)),
+        Value::Array(items) => MathNode::Element {
+            tag: "mrow".to_string(),
+            children: items.iter().map(from_json).collect(),
+        },
+        Value::Object(obj) => MathNode::Element {
+            tag: node_tag(obj).unwrap_or("mrow").to_ascii_lowercase(),
+            children: obj
+                .get("children")
+                .and_then(|children| children.as_array())
+                .map(|children| children.iter().map(from_json).collect())
+                .unwrap_or_default(),
+        },
+        _ => MathNode::Text(String::new()),
+    }
+}
+
+/// Minimal MathML parser: enough structure for rendering, attributes are ignored.
+fn parse_mathml(markup: &str) -> MathNode {
+    static TOKEN_RE: OnceLock<Regex> = OnceLock::new();
+    let token_re =
+        TOKEN_RE.get_or_init(|| Regex::new(r"<(/?)([A-Za-z][\w:-]*)[^>]*?(/?)>|([^<]+)").unwrap());
+    let mut stack: Vec<(String, Vec<MathNode>)> = vec![("mrow".to_string(), Vec::new())];
+
+    for caps in token_re.captures_iter(markup) {
+        if let Some(text) = caps.get(4) {
+            let decoded = decode_entities(text.as_str());
+            if let Some((_, children)) = stack.last_mut() {
+                children.push(MathNode::Text(decoded));
+            }
+            continue;
+        }
+
+        let tag = caps[2].to_ascii_lowercase();
+        let tag = tag.rsplit(':').next().unwrap_or_default().to_string();
+        let closing = !caps[1].is_empty();
+        let self_closing = !caps[3].is_empty();
+
+        if closing {
+            if stack.len() > 1 {
+                let (tag, children) = stack.pop().unwrap_or_default();
+                if let Some((_, parent)) = stack.last_mut() {
+                    parent.push(MathNode::Element { tag, children });
+                }
+            }
+        } else if self_closing {
+            if let Some((_, children)) = stack.last_mut() {
+                children.push(MathNode::Element {
+                    tag,
+                    children: Vec::new(),
+                });
+            }
+        } else {
+            stack.push((tag, Vec::new()));
+        }
+    }
+
+    // Close anything left open by malformed markup
+    while stack.len() > 1 {
+        let (tag, children) = stack.pop().unwrap_or_default();
+        if let Some((_, parent)) = stack.last_mut() {
+            parent.push(MathNode::Element { tag, children });
+        }
+    }
+    let (tag, children) = stack.pop().unwrap_or_default();
+    MathNode::Element { tag, children }
+}
+
+fn render(node: &MathNode) -> String {
+    let (tag, children) = match node {
+        MathNode::Text(text) => return text.trim().to_string(),
+        MathNode::Element { tag, children } => (tag.as_str(), children),
+    };
+    let child = |idx: usize| children.get(idx).map(render).unwrap_or_default();
+
+    match tag {
+        "mo" => {
+            let op = render_children(children);
+            if SPACED_OPERATORS.contains(&op.as_str()) {
+                format!(" {} ", if op == "-" { "−" } else { op.as_str() })
+            } else {
+                op
+            }
+        }
+        "msup" => format!("{}{}", child(0), superscript(&child(1))),
+        "msub" => format!("{}{}", child(0), subscript(&child(1))),
+        "msubsup" => format!(
+            "{}{}{}",
+            child(0),
+            subscript(&child(1)),
+            superscript(&child(2))
+        ),
+        "mfrac" => format!("{}/{}", group(&child(0)), group(&child(1))),
+        "msqrt" => format!("√{}", group(&render_children(children))),
+        "mroot" => format!("{}√{}", superscript(&child(1)), group(&child(0))),
+        "mfenced" => format!("({})", render_children(children)),
+        "annotation" | "annotation-xml" => String::new(),
+        "semantics" => child(0),
+        _ => render_children(children),
+    }
+}
+
+fn render_children(children: &[MathNode]) -> String {
+    let joined: String = children.iter().map(render).collect();
+    joined
+        .split(' ')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parenthesize compound expressions so `a+b/c` stays unambiguous.
+fn group(expr: &str) -> String {
+    let expr = expr.trim();
+    if expr.chars().count() <= 1 || expr.chars().all(|c| c.is_alphanumeric() || c == '.') {
+        expr.to_string()
+    } else {
+        format!("({})", expr)
+    }
+}
+
+fn superscript(expr: &str) -> String {
+    map_script(expr, '^', |c| match c {
+        '0' => Some('⁰'),
+        '1' => Some('¹'),
+        '2' => Some('²'),
+        '3' => Some('³'),
+        '4' => Some('⁴'),
+        '5' => Some('⁵'),
+        '6' => Some('⁶'),
+        '7' => Some('⁷'),
+        '8' => Some('⁸'),
+        '9' => Some('⁹'),
+        '+' => Some('⁺'),
+        '-' | '−' => Some('⁻'),
+        '=' => Some('⁼'),
+        '(' => Some('⁽'),
+        ')' => Some('⁾'),
+        'n' => Some('ⁿ'),
+        'i' => Some('ⁱ'),
+        _ => None,
+    })
+}
+
+fn subscript(expr: &str) -> String {
+    map_script(expr, '_', |c| match c {
+        '0' => Some('₀'),
+        '1' => Some('₁'),
+        '2' => Some('₂'),
+        '3' => Some('₃'),
+        '4' => Some('₄'),
+        '5' => Some('₅'),
+        '6' => Some('₆'),
+        '7' => Some('₇'),
+        '8' => Some('₈'),
+        '9' => Some('₉'),
+        '+' => Some('₊'),
+        '-' | '−' => Some('₋'),
+        '=' => Some('₌'),
+        '(' => Some('₍'),
+        ')' => Some('₎'),
+        _ => None,
+    })
+}
+
+/// Map every character to its script form, or fall back to `^(expr)` / `_(expr)`.
+fn map_script(expr: &str, marker: char, map: impl Fn(char) -> Option<char>) -> String {
+    let expr: String = expr.chars().filter(|c| !c.is_whitespace()).collect();
+    match expr.chars().map(&map).collect::<Option<String>>() {
+        Some(mapped) if !mapped.is_empty() => mapped,
+        _ => format!("{}{}", marker, group(&expr)),
+    }
+}
+
+fn render_latex(body: &str) -> String {
+    // Whole command names only, so `\le` never eats the start of `\left` or `\leq`
+    static COMMAND_RE: OnceLock<Regex> = OnceLock::new();
+    let command_re = COMMAND_RE.get_or_init(|| Regex::new(r"\\([A-Za-z]+|%)").unwrap());
+    let text = command_re.replace_all(body.trim(), |caps: &regex::Captures| {
+        latex_symbol(&caps[1]).map_or_else(|| caps[0].to_string(), str::to_string)
+    });
+
+    static FRAC_RE: OnceLock<Regex> = OnceLock::new();
+    static SQRT_RE: OnceLock<Regex> = OnceLock::new();
+    static SUP_RE: OnceLock<Regex> = OnceLock::new();
+    static SUB_RE: OnceLock<Regex> = OnceLock::new();
+    static TEXT_RE: OnceLock<Regex> = OnceLock::new();
+    let frac_re = FRAC_RE.get_or_init(|| Regex::new(r"\\frac\{([^{}]*)\}\{([^{}]*)\}").unwrap());
+    let sqrt_re = SQRT_RE.get_or_init(|| Regex::new(r"\\sqrt\{([^{}]*)\}").unwrap());
+    let sup_re = SUP_RE.get_or_init(|| Regex::new(r"\^\{([^{}]*)\}|\^([\w+\-])").unwrap());
+    let sub_re = SUB_RE.get_or_init(|| Regex::new(r"_\{([^{}]*)\}|_([\w+\-])").unwrap());
+    let text_re = TEXT_RE.get_or_init(|| Regex::new(r"\\(?:text|mathrm)\{([^{}]*)\}").unwrap());
+
+    let text = text_re.replace_all(&text, "$1");
+    let text = frac_re.replace_all(&text, |caps: &regex::Captures| {
+        format!("{}/{}", group(&caps[1]), group(&caps[2]))
+    });
+    let text = sqrt_re.replace_all(&text, |caps: &regex::Captures| {
+        format!("√{}", group(&caps[1]))
+    });
+    let text = sup_re.replace_all(&text, |caps: &regex::Captures| {
+        superscript(
+            caps.get(1)
+                .or_else(|| caps.get(2))
+                .map_or("", |m| m.as_str()),
+        )
+    });
+    let text = sub_re.replace_all(&text, |caps: &regex::Captures| {
+        subscript(
+            caps.get(1)
+                .or_else(|| caps.get(2))
+                .map_or("", |m| m.as_str()),
+        )
+    });
+
+    text.replace(['{', '}'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Replacement for a symbol command such as `\times`; `None` leaves the command for the
+/// structural rules (`\frac`, `\sqrt`, `\text`).
+fn latex_symbol(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "times" => "×",
+        "cdot" => "·",
+        "div" => "÷",
+        "pm" => "±",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" => "≠",
+        "approx" => "≈",
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "Delta" => "Δ",
+        "mu" => "μ",
+        "%" => "%",
+        "left" | "right" => "",
+        _ => return None,
+    })
+}
+
+fn decode_entities(text: &str) -> String {
+    let named = [
+        ("&minus;", "−"),
+        ("&times;", "×"),
+        ("&divide;", "÷"),
+        ("&plusmn;", "±"),
+        ("&le;", "≤"),
+        ("&ge;", "≥"),
+        ("&nbsp;", " "),
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&amp;", "&"),
+    ];
+    let mut decoded = text.to_string();
+    for (entity, value) in named {
+        decoded = decoded.replace(entity, value);
+    }
+
+    static NUMERIC_RE: OnceLock<Regex> = OnceLock::new();
+    let numeric_re = NUMERIC_RE.get_or_init(|| Regex::new(r"&#(x[0-9a-fA-F]+|[0-9]+);").unwrap());
+    numeric_re
+        .replace_all(&decoded, |caps: &regex::Captures| {
+            let code = &caps[1];
+            let value = match code.strip_prefix('x') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => code.parse::<u32>().ok(),
+            };
+            value
+                .and_then(char::from_u32)
+                .map(String::from)
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mathml_markup_is_converted_not_flattened() {
+        let text = "The ratio <math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo>\
+                    <mfrac><mn>1</mn><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow></mfrac></math> is high.";
+        assert_eq!(
+            render_math_markup(text),
+            "The ratio x² + 1/(a + b) is high."
+        );
+    }
+
+    #[test]
+    fn test_mathml_json_node_is_converted() {
+        let node = serde_json::json!({
+            "type": "math",
+            "children": [
+                { "type": "msub", "children": [
+                    { "type": "mi", "children": ["PaCO"] },
+                    { "type": "mn", "children": ["2"] }
+                ]},
+                { "type": "mo", "children": ["&minus;"] },
+                { "type": "msqrt", "children": [{ "type": "mn", "children": ["16"] }] }
+            ]
+        });
+        let obj = node.as_object().unwrap();
+        assert!(is_math_node(obj));
+        assert_eq!(render_mathml_node(&node), "PaCO₂ − √16");
+    }
+
+    #[test]
+    fn test_latex_is_converted() {
+        assert_eq!(
+            render_math_markup(r"Dose is \(\frac{a}{b} \times 10^{3}\) per day"),
+            "Dose is a/b × 10³ per day"
+        );
+        assert_eq!(render_math_markup("HCO$_3^-$ level"), "HCO₃⁻ level");
+    }
+
+    #[test]
+    fn test_latex_commands_are_replaced_by_whole_name() {
+        assert_eq!(
+            render_math_markup(r"\(\left( x \right) \leq 5 \le 6 \geq 1\)"),
+            "( x ) ≤ 5 ≤ 6 ≥ 1"
+        );
+    }
+
+    #[test]
+    fn test_text_without_math_is_unchanged() {
+        let text = "Costs $5 and <em>more</em>";
+        assert_eq!(render_math_markup(text), text);
+    }
+
+    #[test]
+    fn test_currency_amounts_are_not_treated_as_latex() {
+        let text = "Imaging costs $500 to $1,000 per study, versus $50 for $2 tablets.";
+        assert_eq!(render_math_markup(text), text);
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+
+use super::asset_store::{collect_question_entries, FigureMetadata, VideoMetadata};
+
+const WIDTH_BUCKETS: [(&str, u32); 4] = [
+    ("< 400px", 400),
+    ("400-799px", 800),
+    ("800-1199px", 1200),
+    (">= 1200px", u32::MAX),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DimensionSummary {
+    pub min: u32,
+    pub max: u32,
+    pub median: f64,
+}
+
+impl DimensionSummary {
+    fn from_values(values: &mut [u32]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        let mid = values.len() / 2;
+        let median = if values.len().is_multiple_of(2) {
+            (values[mid - 1] as f64 + values[mid] as f64) / 2.0
+        } else {
+            values[mid] as f64
+        };
+        Some(Self {
+            min: values[0],
+            max: values[values.len() - 1],
+            median,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DimensionStats {
+    /// Entries with usable (non-zero) width and height.
+    pub measured: usize,
+    pub width: Option<DimensionSummary>,
+    pub height: Option<DimensionSummary>,
+    /// Count per width bucket, in `WIDTH_BUCKETS` order.
+    pub width_distribution: Vec<(String, usize)>,
+    /// `question_id/media_id` of entries recorded as 0×0 (metadata not captured).
+    pub zero_dimension: Vec<String>,
+    /// Entries with no width/height recorded at all.
+    pub missing_dimension: usize,
+    #[serde(skip)]
+    widths: Vec<u32>,
+    #[serde(skip)]
+    heights: Vec<u32>,
+}
+
+impl DimensionStats {
+    fn record(&mut self, label: String, width: Option<u32>, height: Option<u32>) {
+        match (width, height) {
+            (Some(0), Some(0)) => self.zero_dimension.push(label),
+            (Some(width), Some(height)) => {
+                self.measured += 1;
+                self.widths.push(width);
+                self.heights.push(height);
+            }
+            _ => self.missing_dimension += 1,
+        }
+    }
+
+    fn finalize(&mut self) {
+        self.width_distribution = WIDTH_BUCKETS
+            .iter()
+            .enumerate()
+            .map(|(idx, (label, upper))| {
+                let lower = if idx == 0 {
+                    0
+                } else {
+                    WIDTH_BUCKETS[idx - 1].1
+                };
+                let count = self
+                    .widths
+                    .iter()
+                    .filter(|width| **width >= lower && **width < *upper)
+                    .count();
+                (label.to_string(), count)
+            })
+            .collect();
+        self.width = DimensionSummary::from_values(&mut self.widths);
+        self.height = DimensionSummary::from_values(&mut self.heights);
+        self.zero_dimension.sort();
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MediaDimensionsReport {
+    pub questions_scanned: usize,
+    pub figures: DimensionStats,
+    pub videos: DimensionStats,
+}
+
+/// Scan every question's `media_metadata` and summarize figure/video dimensions.
+pub fn collect_media_dimensions(data_dir: &str) -> Result<MediaDimensionsReport> {
+    let mut report = MediaDimensionsReport::default();
+
+    for entry in collect_question_entries(data_dir)? {
+        let text = fs::read_to_string(&entry.json_path)
+            .with_context(|| format!("Failed to read {}", entry.json_path.display()))?;
+        let value: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        report.questions_scanned += 1;
+
+        let metadata = &value["media_metadata"];
+        let figures: Vec<FigureMetadata> =
+            serde_json::from_value(metadata["figures"].clone()).unwrap_or_default();
+        for figure in figures {
+            let label = format!("{}/{}", entry.question_id, figure.figure_id);
+            report.figures.record(label, figure.width, figure.height);
+        }
+
+        let videos: Vec<VideoMetadata> =
+            serde_json::from_value(metadata["videos"].clone()).unwrap_or_default();
+        for video in videos {
+            let label = format!("{}/{}", entry.question_id, video.video_id);
+            report.videos.record(label, video.width, video.height);
+        }
+    }
+
+    report.figures.finalize();
+    report.videos.finalize();
+    Ok(report)
+}
+
+impl MediaDimensionsReport {
+    pub fn generate_report(&self) -> String {
+        let mut report = String::new();
+
+        report.push_str("MKSAP Media Dimensions Report\n");
+        report.push_str(&format!("Questions scanned: {}\n", self.questions_scanned));
+        report.push('\n');
+
+        append_section(&mut report, "FIGURES", &self.figures);
+        append_section(&mut report, "VIDEOS", &self.videos);
+
+        report
+    }
+}
+
+fn append_section(report: &mut String, title: &str, stats: &DimensionStats) {
+    report.push_str(&format!("{}\n", title));
+    report.push_str(&format!("- Measured: {}\n", stats.measured));
+    for (label, summary) in [("Width", stats.width), ("Height", stats.height)] {
+        match summary {
+            Some(summary) => report.push_str(&format!(
+                "- {}: min {} / median {:.1} / max {}\n",
+                label, summary.min, summary.median, summary.max
+            )),
+            None => report.push_str(&format!("- {}: n/a\n", label)),
+        }
+    }
+    if !stats.width_distribution.is_empty() {
+        report.push_str("- Width distribution:\n");
+        for (label, count) in &stats.width_distribution {
+            report.push_str(&format!("  - {}: {}\n", label, count));
+        }
+    }
+    report.push_str(&format!(
+        "- Missing dimensions: {}\n",
+        stats.missing_dimension
+    ));
+    report.push_str(&format!(
+        "- Suspicious 0x0 entries: {}\n",
+        stats.zero_dimension.len()
+    ));
+    for label in &stats.zero_dimension {
+        report.push_str(&format!("  - {}\n", label));
+    }
+    report.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question_with;
+
+    fn figure(id: &str, width: u32, height: u32) -> serde_json::Value {
+        serde_json::json!({
+            "figure_id": id, "file": null, "title": null, "short_title": null,
+            "number": null, "footnotes": [], "extension": "png",
+            "width": width, "height": height
+        })
+    }
+
+    #[test]
+    fn test_dimension_stats_and_zero_flagging() {
+        let temp = tempfile::tempdir().unwrap();
+        write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24001",
+            serde_json::json!({
+                "media_metadata": {
+                    "figures": [figure("cvfig001", 100, 50), figure("cvfig002", 300, 150)],
+                    "videos": [{
+                        "video_id": "cvvid001", "file": null, "title": null, "short_title": null,
+                        "width": 640, "height": 360, "caption": null, "mp4_hash": null
+                    }]
+                }
+            }),
+        );
+        write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24002",
+            serde_json::json!({
+                "media_metadata": {
+                    "figures": [figure("cvfig003", 900, 100), figure("cvfig004", 0, 0)]
+                }
+            }),
+        );
+
+        let report = collect_media_dimensions(temp.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(report.questions_scanned, 2);
+        assert_eq!(report.figures.measured, 3);
+        assert_eq!(
+            report.figures.width,
+            Some(DimensionSummary {
+                min: 100,
+                max: 900,
+                median: 300.0
+            })
+        );
+        assert_eq!(report.figures.height.unwrap().median, 100.0);
+        assert_eq!(report.figures.zero_dimension, vec!["cvmcq24002/cvfig004"]);
+        assert_eq!(
+            report.figures.width_distribution[0],
+            ("< 400px".to_string(), 2)
+        );
+        assert_eq!(
+            report.figures.width_distribution[2],
+            ("800-1199px".to_string(), 1)
+        );
+        assert_eq!(report.videos.width.unwrap().median, 640.0);
+        assert!(report
+            .generate_report()
+            .contains("Suspicious 0x0 entries: 1"));
+    }
+
+    #[test]
+    fn test_median_of_even_count_averages_middle_values() {
+        let summary = DimensionSummary::from_values(&mut [400, 100, 300, 200]).unwrap();
+        assert_eq!(summary.median, 250.0);
+        assert!(DimensionSummary::from_values(&mut []).is_none());
+    }
+}
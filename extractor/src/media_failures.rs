@@ -0,0 +1,189 @@
+//! Persistent log of media fetches that failed during `media-download`
+//! (`media_failures.jsonl`), replayed by `retry-media-failures`.
+//!
+//! One JSON object per line. A download run replaces the lines for every question it
+//! processed, so the log only ever lists failures that are still outstanding.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::error::{find_extract_error, ExtractError};
+
+pub const MEDIA_FAILURES_FILE_NAME: &str = "media_failures.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaFailureKind {
+    /// The question JSON itself could not be fetched, so none of its media was tried.
+    Question,
+    Figure,
+    Table,
+    Video,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaFailure {
+    pub question_id: String,
+    pub kind: MediaFailureKind,
+    /// Figure, table or video ID; `None` for a question-level failure.
+    pub content_id: Option<String>,
+    pub url: String,
+    pub error: String,
+    /// Refused by `--max-file-size`; raising the limit and retrying recovers it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub too_large: bool,
+}
+
+impl MediaFailure {
+    pub fn new(
+        question_id: &str,
+        kind: MediaFailureKind,
+        content_id: Option<&str>,
+        url: String,
+        err: &anyhow::Error,
+    ) -> Self {
+        Self {
+            question_id: question_id.to_string(),
+            kind,
+            content_id: content_id.map(str::to_string),
+            url,
+            error: String::new(),
+            too_large: false,
+        }
+        .with_error(err)
+    }
+
+    /// Record `err` as the latest reason this fetch failed.
+    pub fn with_error(self, err: &anyhow::Error) -> Self {
+        Self {
+            error: format!("{:#}", err),
+            too_large: matches!(find_extract_error(err), Some(ExtractError::TooLarge { .. })),
+            ..self
+        }
+    }
+}
+
+pub fn media_failures_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(MEDIA_FAILURES_FILE_NAME)
+}
+
+/// Read the log; a missing file is an empty log and malformed lines are skipped.
+pub fn load_media_failures(path: &Path) -> Result<Vec<MediaFailure>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut failures = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<MediaFailure>(line) {
+            Ok(failure) => failures.push(failure),
+            Err(err) => warn!(
+                "Skipping malformed line {} in {}: {}",
+                index + 1,
+                path.display(),
+                err
+            ),
+        }
+    }
+    Ok(failures)
+}
+
+/// Rewrite the log with `failures`, removing the file once nothing is left.
+pub fn write_media_failures(path: &Path, failures: &[MediaFailure]) -> Result<()> {
+    if failures.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+    let mut text = String::new();
+    for failure in failures {
+        text.push_str(&serde_json::to_string(failure)?);
+        text.push('\n');
+    }
+    fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Replace the logged failures of every `processed` question with this run's `failures`.
+pub fn record_media_failures(
+    path: &Path,
+    processed: &HashSet<&str>,
+    failures: Vec<MediaFailure>,
+) -> Result<()> {
+    let mut log = load_media_failures(path)?;
+    log.retain(|failure| !processed.contains(failure.question_id.as_str()));
+    log.extend(failures);
+    write_media_failures(path, &log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(question_id: &str, content_id: &str) -> MediaFailure {
+        MediaFailure::new(
+            question_id,
+            MediaFailureKind::Table,
+            Some(content_id),
+            format!("http://api/tables/{}.json", content_id),
+            &anyhow::anyhow!("HTTP 500"),
+        )
+    }
+
+    #[test]
+    fn test_record_replaces_only_processed_questions() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = media_failures_path(temp.path().to_str().unwrap());
+        write_media_failures(
+            &path,
+            &[
+                failure("cvmcq24001", "cvtab001"),
+                failure("enmcq24001", "entab001"),
+            ],
+        )
+        .unwrap();
+        fs::write(
+            &path,
+            format!("{}not json\n", fs::read_to_string(&path).unwrap()),
+        )
+        .unwrap();
+
+        let processed = HashSet::from(["cvmcq24001", "gimcq24001"]);
+        record_media_failures(&path, &processed, vec![failure("gimcq24001", "gitab001")]).unwrap();
+        let log = load_media_failures(&path).unwrap();
+        let ids: Vec<_> = log.iter().map(|f| f.question_id.as_str()).collect();
+        assert_eq!(ids, vec!["enmcq24001", "gimcq24001"]);
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains(r#""kind":"table","content_id":"gitab001""#));
+
+        let too_large = MediaFailure::new(
+            "cvmcq24001",
+            MediaFailureKind::Video,
+            Some("cvvid001"),
+            "http://cdn/cvvid001.mp4".to_string(),
+            &anyhow::Error::from(ExtractError::TooLarge {
+                url: "http://cdn/cvvid001.mp4".to_string(),
+                size: 2048,
+                limit: 1024,
+            })
+            .context("Failed to download cvvid001"),
+        );
+        assert!(too_large.too_large && !failure("cvmcq24001", "cvtab001").too_large);
+        write_media_failures(&path, &[too_large]).unwrap();
+        assert!(load_media_failures(&path).unwrap()[0].too_large);
+
+        write_media_failures(&path, &[]).unwrap();
+        assert!(!path.exists());
+        assert!(load_media_failures(&path).unwrap().is_empty());
+    }
+}
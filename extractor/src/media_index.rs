@@ -0,0 +1,313 @@
+//! Combined media index (`media-index`): every question's `media_metadata` in one
+//! `media_index.json`, keyed by media ID. `media-manifest` flattens the same data into
+//! `media_manifest.json` with on-disk file sizes, to spot shared assets and missing files.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::asset_store::collect_question_entries;
+
+pub const MEDIA_INDEX_FILE_NAME: &str = "media_index.json";
+pub const MEDIA_MANIFEST_FILE_NAME: &str = "media_manifest.json";
+
+/// `media_metadata` key, ID field and index type for each media kind.
+const MEDIA_KINDS: [(&str, &str, &str); 4] = [
+    ("figures", "figure_id", "figure"),
+    ("tables", "table_id", "table"),
+    ("videos", "video_id", "video"),
+    ("svgs", "svg_id", "svg"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaIndexEntry {
+    #[serde(rename = "type")]
+    pub media_type: &'static str,
+    pub title: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Every question referencing this media, sorted by question ID.
+    pub owners: Vec<MediaOwner>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MediaOwner {
+    pub question_id: String,
+    /// File path relative to the data directory, when the file was downloaded.
+    pub file: Option<String>,
+}
+
+/// Build the index from every question under `data_dir`. The first owner with a
+/// title or dimensions supplies them.
+pub fn build_media_index(data_dir: &str) -> Result<BTreeMap<String, MediaIndexEntry>> {
+    let root = Path::new(data_dir);
+    let mut entries = collect_question_entries(data_dir)?;
+    entries.sort_by(|a, b| a.question_id.cmp(&b.question_id));
+
+    let mut index: BTreeMap<String, MediaIndexEntry> = BTreeMap::new();
+    for entry in entries {
+        let text = fs::read_to_string(&entry.json_path)
+            .with_context(|| format!("Failed to read {}", entry.json_path.display()))?;
+        let Ok(question) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+        let relative_dir = entry
+            .question_dir
+            .strip_prefix(root)
+            .unwrap_or(&entry.question_dir)
+            .to_path_buf();
+
+        for (key, id_field, media_type) in MEDIA_KINDS {
+            let items = question["media_metadata"][key].as_array().cloned();
+            for item in items.into_iter().flatten() {
+                let Some(media_id) = item.get(id_field).and_then(Value::as_str) else {
+                    continue;
+                };
+                let owner = MediaOwner {
+                    question_id: entry.question_id.clone(),
+                    file: item
+                        .get("file")
+                        .and_then(Value::as_str)
+                        .map(|file| relative_dir.join(file).to_string_lossy().replace('\\', "/")),
+                };
+                let title = item
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                let dimension = |field: &str| {
+                    item.get(field)
+                        .and_then(Value::as_u64)
+                        .map(|value| value as u32)
+                };
+
+                let indexed =
+                    index
+                        .entry(media_id.to_string())
+                        .or_insert_with(|| MediaIndexEntry {
+                            media_type,
+                            title: None,
+                            width: None,
+                            height: None,
+                            owners: Vec::new(),
+                        });
+                indexed.title = indexed.title.take().or(title);
+                indexed.width = indexed.width.or(dimension("width"));
+                indexed.height = indexed.height.or(dimension("height"));
+                if !indexed
+                    .owners
+                    .iter()
+                    .any(|existing| existing.question_id == owner.question_id)
+                {
+                    indexed.owners.push(owner);
+                }
+            }
+        }
+    }
+
+    Ok(index)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MediaManifestEntry {
+    pub content_id: String,
+    #[serde(rename = "type")]
+    pub media_type: &'static str,
+    /// Every question referencing this media, sorted.
+    pub question_ids: Vec<String>,
+    /// Path relative to the data directory: the first owner's copy that exists on disk,
+    /// else the first recorded path.
+    pub file: Option<String>,
+    pub title: Option<String>,
+    /// Size of `file`; `None` when it is missing from disk.
+    pub bytes: Option<u64>,
+}
+
+/// One manifest entry per media item in the index, sorted by content ID.
+pub fn build_media_manifest(data_dir: &str) -> Result<Vec<MediaManifestEntry>> {
+    let root = Path::new(data_dir);
+    let index = build_media_index(data_dir)?;
+    Ok(index
+        .into_iter()
+        .map(|(content_id, entry)| {
+            let files: Vec<&String> = entry
+                .owners
+                .iter()
+                .filter_map(|owner| owner.file.as_ref())
+                .collect();
+            let on_disk = files.iter().find_map(|file| {
+                let metadata = fs::metadata(root.join(file)).ok()?;
+                metadata
+                    .is_file()
+                    .then(|| ((*file).clone(), metadata.len()))
+            });
+            let (file, bytes) = match on_disk {
+                Some((file, bytes)) => (Some(file), Some(bytes)),
+                None => (files.first().map(|file| (*file).clone()), None),
+            };
+            MediaManifestEntry {
+                content_id,
+                media_type: entry.media_type,
+                question_ids: entry
+                    .owners
+                    .into_iter()
+                    .map(|owner| owner.question_id)
+                    .collect(),
+                file,
+                title: entry.title,
+                bytes,
+            }
+        })
+        .collect())
+}
+
+/// Write the manifest to `out_path`, returning the entries for a summary.
+pub fn write_media_manifest(data_dir: &str, out_path: &Path) -> Result<Vec<MediaManifestEntry>> {
+    let manifest = build_media_manifest(data_dir)?;
+    let json = serde_json::to_string_pretty(&manifest)?;
+    fs::write(out_path, json).with_context(|| format!("Failed to write {}", out_path.display()))?;
+    Ok(manifest)
+}
+
+/// Write the index to `out_path` and return how many media items it lists.
+pub fn write_media_index(data_dir: &str, out_path: &Path) -> Result<usize> {
+    let index = build_media_index(data_dir)?;
+    let json = serde_json::to_string_pretty(&index)?;
+    fs::write(out_path, json).with_context(|| format!("Failed to write {}", out_path.display()))?;
+    Ok(index.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question_with;
+
+    #[test]
+    fn test_index_lists_every_owner_of_shared_media() {
+        let temp = tempfile::tempdir().unwrap();
+        let shared_figure = |file: Option<&str>| {
+            serde_json::json!({
+                "figure_id": "cvfig001", "file": file, "title": "ECG",
+                "extension": "png", "width": 640, "height": 480
+            })
+        };
+        write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24001",
+            serde_json::json!({
+                "media_metadata": {
+                    "figures": [shared_figure(Some("figures/cvfig001.png"))],
+                    "tables": [{ "table_id": "cvtab001", "file": "tables/cvtab001.html", "title": "Drugs" }]
+                }
+            }),
+        );
+        write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24002",
+            serde_json::json!({
+                "media_metadata": {
+                    "figures": [shared_figure(None)],
+                    "videos": [{ "video_id": "cvvid001", "file": null, "title": null, "width": 1280, "height": 720 }]
+                }
+            }),
+        );
+
+        let index = build_media_index(temp.path().to_str().unwrap()).unwrap();
+        assert_eq!(index.len(), 3);
+
+        let figure = &index["cvfig001"];
+        assert_eq!(figure.media_type, "figure");
+        assert_eq!(figure.title.as_deref(), Some("ECG"));
+        assert_eq!((figure.width, figure.height), (Some(640), Some(480)));
+        assert_eq!(
+            figure.owners,
+            vec![
+                MediaOwner {
+                    question_id: "cvmcq24001".to_string(),
+                    file: Some("cv/cvmcq24001/figures/cvfig001.png".to_string()),
+                },
+                MediaOwner {
+                    question_id: "cvmcq24002".to_string(),
+                    file: None,
+                },
+            ]
+        );
+
+        let table = &index["cvtab001"];
+        assert_eq!(table.media_type, "table");
+        assert_eq!(table.owners.len(), 1);
+        assert_eq!(index["cvvid001"].width, Some(1280));
+
+        let out_path = temp.path().join(MEDIA_INDEX_FILE_NAME);
+        assert_eq!(
+            write_media_index(temp.path().to_str().unwrap(), &out_path).unwrap(),
+            3
+        );
+        let written: Value = serde_json::from_str(&fs::read_to_string(out_path).unwrap()).unwrap();
+        assert_eq!(written["cvfig001"]["type"], "figure");
+        assert_eq!(
+            written["cvfig001"]["owners"][1]["question_id"],
+            "cvmcq24002"
+        );
+    }
+
+    #[test]
+    fn test_manifest_sizes_files_and_flags_missing_ones() {
+        let temp = tempfile::tempdir().unwrap();
+        let figure =
+            |file: Option<&str>| serde_json::json!({ "figure_id": "cvfig001", "file": file });
+        write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24001",
+            serde_json::json!({
+                "media_metadata": {
+                    "figures": [figure(Some("figures/cvfig001.png"))],
+                    "tables": [{ "table_id": "cvtab001", "file": "tables/cvtab001.html", "title": "Drugs" }]
+                }
+            }),
+        );
+        write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24002",
+            serde_json::json!({
+                       "media_metadata": {
+            "figures": [figure(Some("figures/cvfig001.png"))]
+                       }
+                   }),
+        );
+        // Only the second question's copy of the shared figure is on disk; the table is missing
+        let figures_dir = temp.path().join("cv/cvmcq24002/figures");
+        fs::create_dir_all(&figures_dir).unwrap();
+        fs::write(figures_dir.join("cvfig001.png"), vec![0u8; 1500]).unwrap();
+
+        let out_path = temp.path().join(MEDIA_MANIFEST_FILE_NAME);
+        let manifest = write_media_manifest(temp.path().to_str().unwrap(), &out_path).unwrap();
+        assert_eq!(
+            manifest[0],
+            MediaManifestEntry {
+                content_id: "cvfig001".to_string(),
+                media_type: "figure",
+                question_ids: vec!["cvmcq24001".to_string(), "cvmcq24002".to_string()],
+                file: Some("cv/cvmcq24002/figures/cvfig001.png".to_string()),
+                title: None,
+                bytes: Some(1500),
+            }
+        );
+        assert_eq!(manifest[1].content_id, "cvtab001");
+        assert_eq!(
+            manifest[1].file.as_deref(),
+            Some("cv/cvmcq24001/tables/cvtab001.html")
+        );
+        assert_eq!(manifest[1].bytes, None);
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(out_path).unwrap()).unwrap();
+        assert_eq!(written[1]["type"], "table");
+        assert_eq!(written[1]["title"], "Drugs");
+    }
+}
@@ -0,0 +1,182 @@
+//! Dry-run plans for `media-download --dry-run` and `svg-browser --dry-run`: the media each
+//! run would fetch, where from, and what is already on disk. Nothing is written; sizes come
+//! from HEAD requests.
+
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use super::media_store::format_bytes;
+
+/// HEAD requests in flight while estimating sizes.
+const HEAD_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedMedia {
+    pub question_id: String,
+    /// `figure`, `table`, `video` or `svg`.
+    pub kind: &'static str,
+    pub media_id: String,
+    /// `None` for SVGs, whose URLs are only known once the question page is open in the browser.
+    pub url: Option<String>,
+    pub on_disk: bool,
+    /// `Content-Length` reported by a HEAD request for an item still to fetch.
+    pub bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MediaPlan {
+    pub questions: usize,
+    pub items: Vec<PlannedMedia>,
+}
+
+impl MediaPlan {
+    /// Fill in `bytes` for every item not yet on disk that has a URL.
+    pub async fn estimate_sizes(&mut self, client: &Client) {
+        let sizes: Vec<(usize, Option<u64>)> = stream::iter(
+            self.items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| !item.on_disk)
+                .filter_map(|(index, item)| Some((index, item.url.clone()?))),
+        )
+        .map(|(index, url)| async move { (index, content_length(client, &url).await) })
+        .buffer_unordered(HEAD_CONCURRENCY)
+        .collect()
+        .await;
+        for (index, bytes) in sizes {
+            self.items[index].bytes = bytes;
+        }
+    }
+
+    /// One line per item, then per-kind totals and the estimated download size.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Dry run: {} questions, {} media items (nothing downloaded or written)",
+            self.questions,
+            self.items.len()
+        );
+        for item in &self.items {
+            let _ = writeln!(
+                out,
+                "  {:<12} {:<6} {:<16} {:<8} {}",
+                item.question_id,
+                item.kind,
+                item.media_id,
+                if item.on_disk { "on disk" } else { "fetch" },
+                item.url.as_deref().unwrap_or("(resolved in browser)")
+            );
+        }
+
+        let mut by_kind: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for item in &self.items {
+            let (total, on_disk) = by_kind.entry(item.kind).or_default();
+            *total += 1;
+            *on_disk += usize::from(item.on_disk);
+        }
+        let _ = writeln!(
+            out,
+            "\n{:<8} {:>7} {:>8} {:>9}",
+            "Kind", "Items", "On disk", "To fetch"
+        );
+        for (kind, (total, on_disk)) in &by_kind {
+            let _ = writeln!(
+                out,
+                "{:<8} {:>7} {:>8} {:>9}",
+                kind,
+                total,
+                on_disk,
+                total - on_disk
+            );
+        }
+
+        let to_fetch = self.items.iter().filter(|item| !item.on_disk).count();
+        let sized: Vec<u64> = self.items.iter().filter_map(|item| item.bytes).collect();
+        let _ = writeln!(
+            out,
+            "\nEstimated download: {} ({} of {} item sizes known)",
+            format_bytes(sized.iter().sum()),
+            sized.len(),
+            to_fetch
+        );
+        out
+    }
+}
+
+async fn content_length(client: &Client, url: &str) -> Option<u64> {
+    super::rate_limit::acquire().await;
+    let response = client.head(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn item(
+        kind: &'static str,
+        media_id: &str,
+        url: Option<String>,
+        on_disk: bool,
+    ) -> PlannedMedia {
+        PlannedMedia {
+            question_id: "cvmcq24001".to_string(),
+            kind,
+            media_id: media_id.to_string(),
+            url,
+            on_disk,
+            bytes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_sizes_only_items_still_to_fetch() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/fig.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 2048]))
+            .mount(&server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/video.mp4"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let url = |name: &str| Some(format!("{}/{}", server.uri(), name));
+        let mut plan = MediaPlan {
+            questions: 1,
+            items: vec![
+                item("figure", "cvfig001", url("fig.png"), false),
+                item("figure", "cvfig002", url("fig.png"), true),
+                item("video", "cvvid001", url("video.mp4"), false),
+                item("svg", "cvsvg001", None, false),
+            ],
+        };
+        plan.estimate_sizes(&Client::new()).await;
+
+        let bytes: Vec<_> = plan.items.iter().map(|item| item.bytes).collect();
+        assert_eq!(bytes, vec![Some(2048), None, None, None]);
+
+        let rendered = plan.render();
+        assert!(rendered.contains("(resolved in browser)"));
+        assert!(rendered
+            .lines()
+            .any(|line| line.split_whitespace().collect::<Vec<_>>() == ["figure", "2", "1", "1"]));
+        assert!(rendered.contains("Estimated download: 2.0 KiB (1 of 3 item sizes known)"));
+    }
+}
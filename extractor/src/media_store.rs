@@ -0,0 +1,203 @@
+//! Content-addressed store for figures shared between questions (`--dedupe-media`).
+//!
+//! Each unique figure is kept once under `mksap_data/.media_store/<sha256>.<ext>`;
+//! question directories keep their usual relative path as a link to the stored copy. Links
+//! are relative (`../../../.media_store/...`), so the data directory can be moved or synced.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+#[cfg(unix)]
+use std::path::Component;
+use std::path::{Path, PathBuf};
+
+pub const MEDIA_STORE_DIR: &str = ".media_store";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DedupeStats {
+    /// Files moved into (or matched against) the store.
+    pub files: usize,
+    /// Files whose content was already stored.
+    pub duplicates: usize,
+    pub bytes_saved: u64,
+}
+
+pub struct MediaStore {
+    root: PathBuf,
+    stats: DedupeStats,
+}
+
+impl MediaStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            root: data_dir.join(MEDIA_STORE_DIR),
+            stats: DedupeStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> DedupeStats {
+        self.stats
+    }
+
+    /// Move `question_dir/relative` into the store (or drop it if the content is already
+    /// stored) and leave a link at the original path. Returns the SHA-256 of the content.
+    pub fn dedupe(&mut self, question_dir: &Path, relative: &str) -> Result<String> {
+        let path = question_dir.join(relative);
+        let bytes =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let hash = format!("{:x}", Sha256::digest(&bytes));
+        if fs::symlink_metadata(&path)?.file_type().is_symlink() {
+            return Ok(hash);
+        }
+
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_else(|| "bin".to_string());
+        let stored = self.root.join(format!("{}.{}", hash, extension));
+
+        self.stats.files += 1;
+        if stored.exists() {
+            self.stats.duplicates += 1;
+            self.stats.bytes_saved += bytes.len() as u64;
+            fs::remove_file(&path)?;
+        } else {
+            fs::create_dir_all(&self.root)
+                .with_context(|| format!("Failed to create {}", self.root.display()))?;
+            if fs::rename(&path, &stored).is_err() {
+                fs::write(&stored, &bytes)?;
+                fs::remove_file(&path)?;
+            }
+        }
+        link_to_store(&stored, &path)
+            .with_context(|| format!("Failed to link {} to the media store", path.display()))?;
+        Ok(hash)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Media dedupe: {} figures stored, {} duplicates linked, {} saved",
+            self.stats.files,
+            self.stats.duplicates,
+            format_bytes(self.stats.bytes_saved)
+        )
+    }
+}
+
+#[cfg(unix)]
+fn link_to_store(stored: &Path, link: &Path) -> Result<()> {
+    let link_dir = link.parent().context("Link path has no parent directory")?;
+    let target = relative_to(&fs::canonicalize(link_dir)?, &fs::canonicalize(stored)?);
+    std::os::unix::fs::symlink(target, link)?;
+    Ok(())
+}
+
+/// `target` as a path relative to the directory `from`; both must be canonical.
+#[cfg(unix)]
+fn relative_to(from: &Path, target: &Path) -> PathBuf {
+    let common = from
+        .components()
+        .zip(target.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative: PathBuf = from
+        .components()
+        .skip(common)
+        .map(|_| Component::ParentDir)
+        .collect();
+    relative.extend(target.components().skip(common));
+    relative
+}
+
+#[cfg(not(unix))]
+fn link_to_store(stored: &Path, link: &Path) -> Result<()> {
+    if fs::hard_link(stored, link).is_err() {
+        fs::copy(stored, link)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB)
+    } else {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_figures_are_stored_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path();
+        let mut store = MediaStore::new(data_dir);
+        let mut hashes = Vec::new();
+        for qid in ["cvmcq24001", "cvmcq24002"] {
+            let question_dir = data_dir.join("cv").join(qid);
+            fs::create_dir_all(question_dir.join("figures")).unwrap();
+            fs::write(question_dir.join("figures/cvfig001.abc.png"), b"png-bytes").unwrap();
+            hashes.push(
+                store
+                    .dedupe(&question_dir, "figures/cvfig001.abc.png")
+                    .unwrap(),
+            );
+        }
+
+        assert_eq!(hashes[0], hashes[1]);
+        assert_eq!(
+            store.stats(),
+            DedupeStats {
+                files: 2,
+                duplicates: 1,
+                bytes_saved: 9
+            }
+        );
+        assert_eq!(
+            fs::read_dir(data_dir.join(MEDIA_STORE_DIR))
+                .unwrap()
+                .count(),
+            1
+        );
+        for qid in ["cvmcq24001", "cvmcq24002"] {
+            let figure = data_dir
+                .join("cv")
+                .join(qid)
+                .join("figures/cvfig001.abc.png");
+            assert_eq!(fs::read(&figure).unwrap(), b"png-bytes");
+        }
+
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(data_dir.join("cv/cvmcq24002/figures/cvfig001.abc.png")).unwrap(),
+            Path::new("../../../.media_store").join(format!("{}.png", hashes[0]))
+        );
+
+        // Re-running over an already linked file is a no-op
+        let question_dir = data_dir.join("cv").join("cvmcq24001");
+        store
+            .dedupe(&question_dir, "figures/cvfig001.abc.png")
+            .unwrap();
+        assert_eq!(store.stats().files, 2);
+    }
+
+    #[test]
+    fn test_linked_figures_survive_moving_the_data_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("mksap_data");
+        let question_dir = data_dir.join("cv").join("cvmcq24001");
+        fs::create_dir_all(question_dir.join("figures")).unwrap();
+        fs::write(question_dir.join("figures/cvfig001.abc.png"), b"png-bytes").unwrap();
+        MediaStore::new(&data_dir)
+            .dedupe(&question_dir, "figures/cvfig001.abc.png")
+            .unwrap();
+
+        let moved = temp.path().join("mksap_data_moved");
+        fs::rename(&data_dir, &moved).unwrap();
+        let figure = moved.join("cv/cvmcq24001/figures/cvfig001.abc.png");
+        assert_eq!(fs::read(figure).unwrap(), b"png-bytes");
+    }
+}
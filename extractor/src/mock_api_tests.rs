@@ -0,0 +1,596 @@
+//! Extractor tests against a mock MKSAP API serving fixture responses.
+//!
+//! Fixtures in `tests/fixtures/` mirror the real API schema, including the
+//! `__html` objective shape and `null` option lists.
+
+use serde_json::Value;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use super::asset_api::fetch_question_json;
+use super::asset_discovery::{build_question_media, load_figure_metadata};
+use crate::models::ApiQuestionResponse;
+
+const QUESTION_CV: &str = include_str!("../tests/fixtures/question_cvmcq24001.json");
+const QUESTION_EN: &str = include_str!("../tests/fixtures/question_enmcq24002.json");
+const CONTENT_METADATA: &str = include_str!("../tests/fixtures/content_metadata.json");
+
+async fn mock_api() -> MockServer {
+    let server = MockServer::start().await;
+    for (route, body) in [
+        ("/api/questions/cvmcq24001.json", QUESTION_CV),
+        ("/api/questions/enmcq24002.json", QUESTION_EN),
+        ("/api/content_metadata.json", CONTENT_METADATA),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(body.as_bytes().to_vec(), "application/json"),
+            )
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+/// Write discovery results expecting `media` (a `QuestionMedia` JSON) for one question.
+fn save_discovery(path: &std::path::Path, question_id: &str, media: Value) {
+    use super::asset_discovery::DiscoveryResults;
+
+    DiscoveryResults::new(
+        std::collections::HashMap::from([(
+            question_id.to_string(),
+            serde_json::from_value(media).unwrap(),
+        )]),
+        Default::default(),
+        String::new(),
+        1,
+    )
+    .save_to_file(path)
+    .unwrap();
+}
+
+async fn question_fetches(server: &MockServer, question_id: &str) -> usize {
+    let route = format!("/api/questions/{}.json", question_id);
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == route)
+        .count()
+}
+
+#[tokio::test]
+async fn test_discovery_classifies_media_from_mock_api() {
+    let server = mock_api().await;
+    let client = reqwest::Client::new();
+
+    let figures_by_id = load_figure_metadata(&client, &server.uri()).await.unwrap();
+    let json = fetch_question_json(&client, &server.uri(), "cvmcq24001")
+        .await
+        .unwrap();
+    let media = build_question_media("cvmcq24001", &json, &figures_by_id).unwrap();
+
+    assert_eq!(media.subspecialty.as_deref(), Some("cv"));
+    assert_eq!(media.figures.len(), 1);
+    assert_eq!(media.figures[0].figure_id, "cvfig001");
+    assert_eq!(media.figures[0].extension, "png");
+    assert_eq!(
+        (media.figures[0].width, media.figures[0].height),
+        (640, 480)
+    );
+    let tables: Vec<&str> = media.tables.iter().map(|t| t.table_id.as_str()).collect();
+    assert_eq!(tables, vec!["cvtab001", "cvtab002"]);
+    assert_eq!(media.videos[0].video_id, "cvvid001");
+    assert_eq!(media.svgs[0].svg_id, "cvsvg001");
+
+    let json = fetch_question_json(&client, &server.uri(), "enmcq24002")
+        .await
+        .unwrap();
+    assert!(build_question_media("enmcq24002", &json, &figures_by_id).is_none());
+}
+
+#[tokio::test]
+async fn test_question_data_maps_peer_percentages_and_critique_links() {
+    let server = mock_api().await;
+    let client = reqwest::Client::new();
+    let json = fetch_question_json(&client, &server.uri(), "cvmcq24001")
+        .await
+        .unwrap();
+
+    let response: ApiQuestionResponse = serde_json::from_value(json).unwrap();
+    let question = response.into_question_data("cv".to_string());
+
+    assert_eq!(
+        question.educational_objective,
+        "Diagnose <em>acute</em> decompensated heart failure."
+    );
+    let peers: Vec<(&str, u32)> = question
+        .options
+        .iter()
+        .map(|option| (option.letter.as_str(), option.peer_percentage))
+        .collect();
+    assert_eq!(peers, vec![("A", 12), ("B", 71), ("C", 17)]);
+    assert_eq!(question.options[1].text, "Heart failure");
+    assert_eq!(
+        question.user_performance.correct_answer.as_deref(),
+        Some("B")
+    );
+    assert_eq!(question.critique_links.len(), 1);
+    assert_eq!(
+        question.critique_links[0].href,
+        "/syllabus/cv/heart-failure"
+    );
+    assert_eq!(question.critique_links[0].text, "heart failure syllabus");
+    assert!(question.metadata.high_value_care);
+}
+
+#[tokio::test]
+async fn test_null_sections_deserialize_to_empty() {
+    let server = mock_api().await;
+    let client = reqwest::Client::new();
+    let json: Value = fetch_question_json(&client, &server.uri(), "enmcq24002")
+        .await
+        .unwrap();
+
+    let response: ApiQuestionResponse = serde_json::from_value(json).unwrap();
+    let question = response.into_question_data("en".to_string());
+
+    assert!(question.options.is_empty());
+    assert!(question.key_points.is_empty());
+    assert!(question.critique_links.is_empty());
+    assert_eq!(question.educational_objective, "Recognize thyroid storm.");
+    assert_eq!(question.question_text, "A woman has fever and tachycardia.");
+}
+
+#[tokio::test]
+async fn test_content_metadata_cache_fetches_once() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/content_metadata.json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(CONTENT_METADATA.as_bytes().to_vec(), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = reqwest::Client::new();
+    let temp = tempfile::tempdir().unwrap();
+    let cache = crate::cache::ResponseCache::new(temp.path(), crate::cache::DEFAULT_CACHE_TTL);
+
+    let first = super::fetch_content_metadata_with_cache(&client, &server.uri(), Some(&cache))
+        .await
+        .unwrap();
+    let second = super::fetch_content_metadata_with_cache(&client, &server.uri(), Some(&cache))
+        .await
+        .unwrap();
+    assert_eq!(first, second);
+    assert_eq!(
+        first,
+        serde_json::from_str::<Value>(CONTENT_METADATA).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_failed_table_is_logged_and_recovered_by_retry() {
+    use super::asset_download::{
+        retry_media_failures, run_media_download, MediaDownloadOptions, MediaKinds,
+    };
+    use super::media_failures::{load_media_failures, media_failures_path, MediaFailureKind};
+
+    let server = mock_api().await;
+    let table = |id: &str| {
+        serde_json::json!({
+            "id": id,
+            "title": "Doses",
+            "jsonContent": { "tagName": "table", "children": [] }
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path("/api/tables/cvtab001.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(table("cvtab001")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/tables/cvtab002.json"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/tables/cvtab002.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(table("cvtab002")))
+        .mount(&server)
+        .await;
+
+    let temp = tempfile::tempdir().unwrap();
+    let question_dir = temp.path().join("cv").join("cvmcq24001");
+    std::fs::create_dir_all(&question_dir).unwrap();
+    std::fs::write(question_dir.join("cvmcq24001.json"), "{}").unwrap();
+    let data_dir = temp.path().to_str().unwrap();
+    let client = reqwest::Client::new();
+    let kinds = MediaKinds {
+        figures: false,
+        tables: true,
+        videos: false,
+    };
+
+    run_media_download(
+        &client,
+        &MediaDownloadOptions {
+            base_url: server.uri(),
+            data_dir: data_dir.to_string(),
+            question_id: Some("cvmcq24001".to_string()),
+            kinds,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let log_path = media_failures_path(data_dir);
+    let logged = load_media_failures(&log_path).unwrap();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0].kind, MediaFailureKind::Table);
+    assert_eq!(logged[0].content_id.as_deref(), Some("cvtab002"));
+    assert!(logged[0].url.ends_with("/api/tables/cvtab002.json"));
+    assert!(logged[0].error.contains("500"), "{}", logged[0].error);
+    assert!(question_dir.join("tables/cvtab001.html").exists());
+
+    let (recovered, remaining) = retry_media_failures(&client, &server.uri(), data_dir, kinds)
+        .await
+        .unwrap();
+    assert_eq!((recovered, remaining), (1, 0));
+    assert!(!log_path.exists());
+    assert!(question_dir.join("tables/cvtab002.html").exists());
+    let json: Value = serde_json::from_str(
+        &std::fs::read_to_string(question_dir.join("cvmcq24001.json")).unwrap(),
+    )
+    .unwrap();
+    let table_ids: Vec<&str> = json["media_metadata"]["tables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|table| table["table_id"].as_str())
+        .collect();
+    assert!(table_ids.contains(&"cvtab001") && table_ids.contains(&"cvtab002"));
+}
+
+#[tokio::test]
+async fn test_media_download_skips_complete_questions_unless_refreshed() {
+    use super::asset_download::{run_media_download, MediaDownloadOptions, MediaKinds};
+
+    let server = mock_api().await;
+    let temp = tempfile::tempdir().unwrap();
+    let question_dir = temp.path().join("cv").join("cvmcq24001");
+    std::fs::create_dir_all(question_dir.join("tables")).unwrap();
+    std::fs::write(question_dir.join("tables/cvtab001.html"), "<table></table>").unwrap();
+    let question = serde_json::json!({
+        "media": { "tables": ["tables/cvtab001.html"], "images": [], "svgs": [], "videos": [] },
+        "media_metadata": { "tables": [{ "table_id": "cvtab001", "file": "tables/cvtab001.html" }] }
+    });
+    std::fs::write(
+        question_dir.join("cvmcq24001.json"),
+        serde_json::to_string(&question).unwrap(),
+    )
+    .unwrap();
+    let discovery_file = temp.path().join("media_discovery.json");
+    save_discovery(
+        &discovery_file,
+        "cvmcq24001",
+        serde_json::json!({ "subspecialty": "cv", "tables": [{ "table_id": "cvtab001" }] }),
+    );
+    let data_dir = temp.path().to_str().unwrap();
+    let kinds = MediaKinds {
+        figures: false,
+        tables: true,
+        videos: false,
+    };
+    let download = |refresh: bool| {
+        let uri = server.uri();
+        let discovery_file = discovery_file.to_str().unwrap().to_string();
+        async move {
+            run_media_download(
+                &reqwest::Client::new(),
+                &MediaDownloadOptions {
+                    base_url: uri,
+                    data_dir: data_dir.to_string(),
+                    discovery_file,
+                    question_id: Some("cvmcq24001".to_string()),
+                    kinds,
+                    refresh,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+    };
+
+    download(false).await;
+    assert_eq!(question_fetches(&server, "cvmcq24001").await, 0);
+    download(true).await;
+    assert_eq!(question_fetches(&server, "cvmcq24001").await, 1);
+}
+
+#[tokio::test]
+async fn test_plain_download_fetches_tables_skipped_by_an_earlier_run() {
+    use super::asset_download::{run_media_download, MediaDownloadOptions, MediaKinds};
+
+    let server = mock_api().await;
+    Mock::given(method("GET"))
+        .and(path("/api/tables/cvtab001.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "cvtab001",
+            "title": "Doses",
+            "jsonContent": { "tagName": "table", "children": [] }
+        })))
+        .mount(&server)
+        .await;
+
+    // State after `media-download --skip-tables`: the figure is recorded and on disk
+    let temp = tempfile::tempdir().unwrap();
+    let question_dir = temp.path().join("cv").join("cvmcq24001");
+    std::fs::create_dir_all(question_dir.join("figures")).unwrap();
+    std::fs::write(question_dir.join("figures/cvfig001.abc123.PNG"), b"png").unwrap();
+    let question = serde_json::json!({
+        "media": { "tables": [], "images": ["figures/cvfig001.abc123.PNG"], "svgs": [], "videos": [] },
+        "media_metadata": { "figures": [{ "figure_id": "cvfig001", "file": "figures/cvfig001.abc123.PNG" }] }
+    });
+    std::fs::write(
+        question_dir.join("cvmcq24001.json"),
+        serde_json::to_string(&question).unwrap(),
+    )
+    .unwrap();
+    let discovery_file = temp.path().join("media_discovery.json");
+    save_discovery(
+        &discovery_file,
+        "cvmcq24001",
+        serde_json::json!({
+            "subspecialty": "cv",
+            "figures": [{ "figure_id": "cvfig001", "extension": "png", "title": null, "width": 1, "height": 1 }],
+            "tables": [{ "table_id": "cvtab001" }]
+        }),
+    );
+    let download = |tables: bool| {
+        let options = MediaDownloadOptions {
+            base_url: server.uri(),
+            data_dir: temp.path().to_str().unwrap().to_string(),
+            discovery_file: discovery_file.to_str().unwrap().to_string(),
+            kinds: MediaKinds {
+                figures: true,
+                tables,
+                videos: false,
+            },
+            ..Default::default()
+        };
+        async move {
+            run_media_download(&reqwest::Client::new(), &options)
+                .await
+                .unwrap();
+        }
+    };
+
+    // Another --skip-tables run has nothing left to fetch
+    download(false).await;
+    assert_eq!(question_fetches(&server, "cvmcq24001").await, 0);
+
+    // A plain run still owes the table, so the question is not complete
+    download(true).await;
+    assert_eq!(question_fetches(&server, "cvmcq24001").await, 1);
+    assert!(question_dir.join("tables/cvtab001.html").exists());
+
+    download(true).await;
+    assert_eq!(question_fetches(&server, "cvmcq24001").await, 1);
+}
+
+#[tokio::test]
+async fn test_dry_run_plan_lists_urls_and_disk_status() {
+    use super::asset_discovery::{DiscoveryResults, QuestionMedia};
+    use super::asset_download::{plan_media_download, MediaDownloadOptions, MediaKinds};
+
+    let server = mock_api().await;
+    let temp = tempfile::tempdir().unwrap();
+    let question_dir = temp.path().join("cv").join("cvmcq24001");
+    std::fs::create_dir_all(question_dir.join("figures")).unwrap();
+    std::fs::write(question_dir.join("cvmcq24001.json"), "{}").unwrap();
+    std::fs::write(question_dir.join("figures/cvfig001.abc123.PNG"), b"png").unwrap();
+
+    let media: QuestionMedia = serde_json::from_value(serde_json::json!({
+        "subspecialty": "cv",
+        "figures": [{ "figure_id": "cvfig001", "extension": "png", "title": null, "width": 1, "height": 1 }],
+        "tables": [{ "table_id": "cvtab001", "title": null }],
+        "videos": [{ "video_id": "cvvid001", "title": null, "canonical_location": "" }]
+    }))
+    .unwrap();
+    let discovery_file = temp.path().join("media_discovery.json");
+    DiscoveryResults::new(
+        std::collections::HashMap::from([("cvmcq24001".to_string(), media)]),
+        Default::default(),
+        server.uri(),
+        1,
+    )
+    .save_to_file(&discovery_file)
+    .unwrap();
+
+    let plan = plan_media_download(
+        &reqwest::Client::new(),
+        &MediaDownloadOptions {
+            base_url: server.uri(),
+            data_dir: temp.path().to_str().unwrap().to_string(),
+            discovery_file: discovery_file.to_str().unwrap().to_string(),
+            kinds: MediaKinds {
+                figures: true,
+                tables: true,
+                videos: true,
+            },
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(plan.questions, 1);
+    let items: Vec<(&str, &str, bool)> = plan
+        .items
+        .iter()
+        .map(|item| (item.kind, item.url.as_deref().unwrap(), item.on_disk))
+        .collect();
+    let table_url = format!("{}/api/tables/cvtab001.json", server.uri());
+    assert_eq!(
+        items,
+        vec![
+            (
+                "figure",
+                "https://d2chybfyz5ban.cloudfront.net/hashed_figures/cvfig001.abc123.PNG",
+                true
+            ),
+            ("table", table_url.as_str(), false),
+            (
+                "video",
+                "https://d2chybfyz5ban.cloudfront.net/hashed_videos/cvvid001.def456.mp4",
+                false
+            ),
+        ]
+    );
+    // Planning writes nothing
+    assert_eq!(
+        std::fs::read_to_string(question_dir.join("cvmcq24001.json")).unwrap(),
+        "{}"
+    );
+    assert!(!question_dir.join("tables").exists());
+}
+
+#[tokio::test]
+async fn test_dry_run_plans_a_question_id_without_discovery_results() {
+    use super::asset_download::{plan_media_download, MediaDownloadOptions, MediaKinds};
+
+    let server = mock_api().await;
+    let temp = tempfile::tempdir().unwrap();
+    let question_dir = temp.path().join("cv").join("cvmcq24001");
+    std::fs::create_dir_all(&question_dir).unwrap();
+    std::fs::write(question_dir.join("cvmcq24001.json"), "{}").unwrap();
+
+    let plan = plan_media_download(
+        &reqwest::Client::new(),
+        &MediaDownloadOptions {
+            base_url: server.uri(),
+            data_dir: temp.path().to_str().unwrap().to_string(),
+            discovery_file: temp
+                .path()
+                .join("media_discovery.json")
+                .to_str()
+                .unwrap()
+                .to_string(),
+            question_id: Some("cvmcq24001".to_string()),
+            kinds: MediaKinds {
+                tables: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let tables: Vec<&str> = plan
+        .items
+        .iter()
+        .map(|item| item.media_id.as_str())
+        .collect();
+    assert_eq!(tables, vec!["cvtab001", "cvtab002"]);
+    assert!(!temp.path().join("media_discovery.json").exists());
+}
+
+#[tokio::test]
+async fn test_only_missing_discovery_keeps_complete_questions_in_the_results() {
+    use super::asset_discovery::{discover_media_questions, DiscoveryOptions};
+    use super::asset_download::MediaKinds;
+
+    let server = mock_api().await;
+    let temp = tempfile::tempdir().unwrap();
+    for (system, question_id) in [("cv", "cvmcq24001"), ("en", "enmcq24002")] {
+        let question_dir = temp.path().join(system).join(question_id);
+        std::fs::create_dir_all(&question_dir).unwrap();
+        std::fs::write(question_dir.join(format!("{}.json", question_id)), "{}").unwrap();
+    }
+    // cvmcq24001 already has its one discovered table on disk
+    let tables_dir = temp.path().join("cv/cvmcq24001/tables");
+    std::fs::create_dir_all(&tables_dir).unwrap();
+    std::fs::write(tables_dir.join("cvtab001.html"), "<table></table>").unwrap();
+    let discovery_file = temp.path().join("media_discovery.json");
+    save_discovery(
+        &discovery_file,
+        "cvmcq24001",
+        serde_json::json!({ "subspecialty": "cv", "tables": [{ "table_id": "cvtab001" }] }),
+    );
+
+    let results = discover_media_questions(
+        &reqwest::Client::new(),
+        &DiscoveryOptions {
+            base_url: server.uri(),
+            concurrent_requests: 2,
+            checkpoint_dir: temp.path().join(".checkpoints"),
+            data_dir: temp.path().to_path_buf(),
+            partial_path: temp.path().join("media_discovery.partial.json"),
+            discovery_file,
+            only_missing: true,
+            kinds: MediaKinds {
+                figures: true,
+                tables: true,
+                videos: false,
+            },
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(question_fetches(&server, "cvmcq24001").await, 0);
+    assert_eq!(question_fetches(&server, "enmcq24002").await, 1);
+    // The skipped question stays in the results media-download reads its targets from
+    assert!(results.questions.contains_key("cvmcq24001"));
+    assert_eq!(results.metadata.statistics.skipped_complete, 1);
+    assert_eq!(results.metadata.statistics.total_questions_scanned, 2);
+}
+
+#[tokio::test]
+async fn test_excluded_systems_keep_their_discovery_entries() {
+    use super::asset_discovery::{discover_media_questions, DiscoveryOptions};
+
+    let server = mock_api().await;
+    let temp = tempfile::tempdir().unwrap();
+    for (system, question_id) in [("cv", "cvmcq24001"), ("en", "enmcq24002")] {
+        let question_dir = temp.path().join(system).join(question_id);
+        std::fs::create_dir_all(&question_dir).unwrap();
+        std::fs::write(question_dir.join(format!("{}.json", question_id)), "{}").unwrap();
+    }
+    let discovery_file = temp.path().join("media_discovery.json");
+    save_discovery(
+        &discovery_file,
+        "enmcq24002",
+        serde_json::json!({ "subspecialty": "en", "tables": [{ "table_id": "entab001" }] }),
+    );
+
+    let results = discover_media_questions(
+        &reqwest::Client::new(),
+        &DiscoveryOptions {
+            base_url: server.uri(),
+            concurrent_requests: 2,
+            checkpoint_dir: temp.path().join(".checkpoints"),
+            data_dir: temp.path().to_path_buf(),
+            partial_path: temp.path().join("media_discovery.partial.json"),
+            discovery_file,
+            excluded_prefixes: vec!["en".to_string()],
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(question_fetches(&server, "enmcq24002").await, 0);
+    assert!(results.questions.contains_key("cvmcq24001"));
+    assert!(results.questions.contains_key("enmcq24002"));
+}
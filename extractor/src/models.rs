@@ -1,8 +1,13 @@
 use chrono::Utc;
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::de::Deserializer;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+use crate::assets::table_render::render_node;
 
 type CritiqueLinkKey = (
     String,
@@ -12,30 +17,64 @@ type CritiqueLinkKey = (
     Option<String>,
 );
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QuestionData {
     pub question_id: String,
     pub category: String,
     pub category_name: String,
     pub educational_objective: String,
+    /// `educational_objective` as plain text, without markup or a leading "Objective:" label.
+    #[serde(default)]
+    pub objective_text: String,
+    /// The objective exactly as the API sent it (usually `__html` markup).
+    #[serde(default)]
+    pub objective_html: String,
+    /// "diagnosis", "evaluation", "management" or "prevention", from the objective's leading
+    /// verb; absent when it has none of the recognized verbs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objective_category: Option<String>,
     pub metadata: QuestionMetadata,
     pub question_text: String,
     pub question_stem: String,
     pub options: Vec<AnswerOption>,
     pub user_performance: UserPerformance,
     pub critique: String,
+    /// Critique markup (paragraphs, lists, inline links) for rendering; `critique` is the
+    /// plain-text version. Absent in JSON extracted before it was added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critique_html: Option<String>,
     #[serde(default)]
     pub critique_links: Vec<CritiqueLink>,
     pub key_points: Vec<String>,
     pub references: String,
+    /// `references` split into entries, with PMID/DOI/URL pulled from each.
+    #[serde(default)]
+    pub references_list: Vec<Reference>,
     pub related_content: RelatedContent,
     pub media: MediaFiles,
     #[serde(default)]
     pub media_metadata: Option<serde_json::Value>,
+    /// SHA-256 of the question content (see [`QuestionData::compute_content_hash`]); equal
+    /// hashes mean a re-fetch changed nothing worth rewriting. Empty in older files.
+    #[serde(default)]
+    pub content_hash: String,
     pub extracted_at: String,
+    /// Where and when the question JSON was fetched. Empty in files written before it was
+    /// recorded.
+    #[serde(default)]
+    pub provenance: Provenance,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Provenance {
+    pub source_url: String,
+    pub http_status: u16,
+    /// Schema/version marker from the API payload, when it sends one.
+    pub api_schema_version: Option<String>,
+    pub fetched_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CritiqueLink {
     pub href: String,
     pub text: String,
@@ -44,7 +83,15 @@ pub struct CritiqueLink {
     pub rel: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Reference {
+    pub text: String,
+    pub pmid: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QuestionMetadata {
     pub care_types: Vec<String>,
     pub patient_types: Vec<String>,
@@ -53,27 +100,39 @@ pub struct QuestionMetadata {
     pub question_updated: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AnswerOption {
     pub letter: String,
     pub text: String,
     pub peer_percentage: u32,
+    #[serde(default)]
+    pub is_correct: bool,
+    /// Option markup (bold, sub/superscript) when the API sent a node tree; `text` is the
+    /// plain-text version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UserPerformance {
     pub user_answer: Option<String>,
     pub correct_answer: Option<String>,
+    /// Every correct letter; more than one for select-all-that-apply questions.
+    #[serde(default)]
+    pub correct_answers: Vec<String>,
     pub result: Option<String>,
     pub time_taken: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RelatedContent {
     pub syllabus: Vec<String>,
+    /// Learning plan topic from the API; omitted from JSON when the API has none.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub learning_plan_topic: String,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct MediaFiles {
     pub tables: Vec<String>,
     pub images: Vec<String>,
@@ -90,13 +149,41 @@ pub struct ApiQuestionResponse {
     #[serde(default)]
     pub invalidated: bool,
 
-    #[serde(rename = "correctAnswer", default)]
+    #[serde(
+        rename = "schemaVersion",
+        alias = "apiVersion",
+        default,
+        deserialize_with = "deserialize_version"
+    )]
+    pub schema_version: Option<String>,
+
+    /// When the question was last revised, if the API reports it (`run --since`).
+    #[serde(
+        rename = "updatedAt",
+        alias = "updated_at",
+        alias = "lastUpdated",
+        default
+    )]
+    pub updated_at: Option<String>,
+
+    #[serde(
+        rename = "correctAnswer",
+        default,
+        deserialize_with = "deserialize_correct_answer"
+    )]
     pub correct_answer: String,
 
+    #[serde(
+        rename = "correctAnswers",
+        default,
+        deserialize_with = "deserialize_vec_or_null"
+    )]
+    pub correct_answers: Vec<String>,
+
     #[serde(default, deserialize_with = "deserialize_objective_or_default")]
     pub objective: ApiObjective,
 
-    #[serde(default, deserialize_with = "deserialize_vec_or_null")]
+    #[serde(default, deserialize_with = "deserialize_options")]
     pub options: Vec<ApiAnswerOption>,
 
     #[serde(default, deserialize_with = "deserialize_vec_or_null")]
@@ -117,6 +204,9 @@ pub struct ApiQuestionResponse {
     #[serde(rename = "relatedSection", default)]
     pub related_section: String,
 
+    #[serde(rename = "learningPlanTopic", alias = "learningPlan", default)]
+    pub learning_plan_topic: serde_json::Value,
+
     #[serde(rename = "peerComparison", default)]
     pub peer_comparison: serde_json::Value,
 
@@ -165,25 +255,45 @@ impl ApiQuestionResponse {
             .unwrap_or_else(|| category.clone());
 
         // Extract text content from HTML-like structures
+        let (objective_clean, objective_category) = extract_objective(&self.objective);
         let objective_text = match self.objective {
             ApiObjective::Html { html } => html,
             ApiObjective::Text(text) => text,
         };
+        let objective_html = objective_text.clone();
+        let objective_text = if crate::math::render_math_enabled() {
+            crate::math::render_math_markup(&objective_text)
+        } else {
+            objective_text
+        };
         let stimulus_text = extract_text_from_nodes(&self.stimulus);
         let prompt_text = extract_text_from_nodes(&self.prompt);
         let exposition_text = extract_text_from_nodes(&self.exposition);
+        let critique_html = render_nodes_html(&self.exposition);
         let critique_links = extract_links_from_nodes(&self.exposition);
         let keypoints_list = extract_keypoints(&self.keypoints);
         let references_text = extract_references(&self.references);
+        let references_list = extract_reference_list(&self.references);
 
-        // Extract peer percentages from peerComparison object
+        // Extract peer percentages from peerComparison (object or array form)
         let peer_percentages = extract_peer_percentages(&self.peer_comparison);
+        let learning_plan_topic = extract_learning_plan_topic(&self.learning_plan_topic);
+        let correct_answers = correct_answer_letters(&self.correct_answer, &self.correct_answers);
+        let mut syllabus = vec![self.related_section];
+        for section in syllabus_sections_from_links(&critique_links) {
+            if !syllabus.contains(&section) {
+                syllabus.push(section);
+            }
+        }
 
-        QuestionData {
+        let mut question = QuestionData {
             question_id: self.id.clone(),
             category: category.clone(),
             category_name,
             educational_objective: objective_text,
+            objective_text: objective_clean,
+            objective_html,
+            objective_category,
             metadata: QuestionMetadata {
                 care_types: Vec::new(),
                 patient_types: Vec::new(),
@@ -197,29 +307,210 @@ impl ApiQuestionResponse {
                 .options
                 .into_iter()
                 .map(|o| AnswerOption {
-                    letter: o.letter.clone(),
                     text: extract_text_from_value(&o.text),
+                    html: render_option_html(&o.text),
                     peer_percentage: peer_percentages.get(&o.letter).copied().unwrap_or(0),
+                    is_correct: correct_answers
+                        .iter()
+                        .any(|letter| letter.eq_ignore_ascii_case(&o.letter)),
+                    letter: o.letter,
                 })
                 .collect(),
             user_performance: UserPerformance {
                 user_answer: None,
                 correct_answer: Some(self.correct_answer),
+                correct_answers,
                 result: None,
                 time_taken: None,
             },
             critique: exposition_text,
+            critique_html,
             critique_links,
             key_points: keypoints_list,
             references: references_text,
+            references_list,
             related_content: RelatedContent {
-                syllabus: vec![self.related_section],
+                syllabus,
+                learning_plan_topic,
             },
             media: MediaFiles::default(),
             media_metadata: None,
+            content_hash: String::new(),
             extracted_at: chrono::Local::now().to_rfc3339(),
+            provenance: Provenance {
+                api_schema_version: self.schema_version,
+                ..Provenance::default()
+            },
+        };
+        question.content_hash = question.compute_content_hash();
+        question
+    }
+}
+
+impl QuestionData {
+    /// Hex SHA-256 over the stem, options (by letter, so their order does not matter) with the
+    /// answer key, critique, key points and references. Timestamps, peer statistics, media and provenance
+    /// are left out so an unchanged question always hashes the same.
+    pub fn compute_content_hash(&self) -> String {
+        let mut options: Vec<&AnswerOption> = self.options.iter().collect();
+        options.sort_by(|a, b| a.letter.cmp(&b.letter));
+
+        let mut hasher = Sha256::new();
+        let mut field = |name: &str, value: &str| {
+            // Length-prefixed so adjacent fields cannot run into each other
+            for part in [name, value] {
+                hasher.update((part.len() as u64).to_le_bytes());
+                hasher.update(part.as_bytes());
+            }
+        };
+        field("question_text", &self.question_text);
+        field("question_stem", &self.question_stem);
+        for option in options {
+            field("option", &option.letter);
+            field("option_text", &option.text);
+            field("option_correct", if option.is_correct { "1" } else { "0" });
+        }
+        let mut correct_answers: Vec<&str> = self
+            .user_performance
+            .correct_answers
+            .iter()
+            .map(String::as_str)
+            .collect();
+        correct_answers.sort_unstable();
+        for letter in correct_answers {
+            field("correct_answer", letter);
+        }
+        field("critique", &self.critique);
+        for point in &self.key_points {
+            field("key_point", point);
+        }
+        field("references", &self.references);
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Leading verbs of an objective sentence and the category each one signals.
+const OBJECTIVE_CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "diagnosis",
+        &[
+            "diagnose",
+            "identify",
+            "recognize",
+            "distinguish",
+            "interpret",
+        ],
+    ),
+    ("evaluation", &["evaluate", "assess", "screen", "determine"]),
+    (
+        "management",
+        &[
+            "manage",
+            "treat",
+            "select",
+            "prescribe",
+            "initiate",
+            "choose",
+        ],
+    ),
+    ("prevention", &["prevent", "counsel", "reduce"]),
+];
+
+/// Plain objective sentence (tags stripped, whitespace collapsed, any leading "Objective:"
+/// label removed) and its verb category, if the sentence opens with a recognized verb.
+pub fn extract_objective(objective: &ApiObjective) -> (String, Option<String>) {
+    let raw = match objective {
+        ApiObjective::Html { html } => html.as_str(),
+        ApiObjective::Text(text) => text.as_str(),
+    };
+    static LABEL_RE: OnceLock<Regex> = OnceLock::new();
+    let label =
+        LABEL_RE.get_or_init(|| Regex::new(r"(?i)^(?:educational\s+)?objective\s*:\s*").unwrap());
+    let text = label.replace(&compact_text(raw), "").to_string();
+
+    let first_word = text
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let category = OBJECTIVE_CATEGORIES
+        .iter()
+        .find(|(_, verbs)| verbs.contains(&first_word.as_str()))
+        .map(|(category, _)| category.to_string());
+    (text, category)
+}
+
+/// Learning plan topic arrives either as a plain string or as an object with a title/name.
+fn extract_learning_plan_topic(value: &serde_json::Value) -> String {
+    let topic = match value {
+        serde_json::Value::String(topic) => topic.as_str(),
+        serde_json::Value::Object(object) => ["title", "name", "topic", "text"]
+            .iter()
+            .find_map(|key| object.get(*key).and_then(|v| v.as_str()))
+            .unwrap_or_default(),
+        _ => "",
+    };
+    compact_text(&strip_html_tags(topic))
+}
+
+/// Collect the correct letters from `correctAnswer` ("A", "A,C", "AC") and `correctAnswers`.
+fn correct_answer_letters(correct_answer: &str, correct_answers: &[String]) -> Vec<String> {
+    let mut letters: Vec<String> = Vec::new();
+    let tokens = correct_answer
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .chain(correct_answers.iter().map(String::as_str));
+    for token in tokens {
+        let token = token.trim();
+        if token.is_empty() {
+            continue;
+        }
+        // Run-together letters like "AC" mean several single-letter options
+        let parts: Vec<String> = if token.len() > 1 && token.chars().all(|c| c.is_ascii_uppercase())
+        {
+            token.chars().map(String::from).collect()
+        } else {
+            vec![token.to_string()]
+        };
+        for part in parts {
+            if !letters.contains(&part) {
+                letters.push(part);
+            }
         }
     }
+    letters
+}
+
+/// `correctAnswer` is usually a letter but multi-answer questions may send an array.
+fn deserialize_correct_answer<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum CorrectAnswer {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Option::<CorrectAnswer>::deserialize(deserializer)? {
+        Some(CorrectAnswer::One(answer)) => answer,
+        Some(CorrectAnswer::Many(answers)) => answers.join(","),
+        None => String::new(),
+    })
+}
+
+/// Version markers arrive as strings or bare numbers.
+fn deserialize_version<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(version)) => Some(version),
+            Some(serde_json::Value::Number(version)) => Some(version.to_string()),
+            _ => None,
+        },
+    )
 }
 
 fn deserialize_vec_or_null<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
@@ -230,6 +521,45 @@ where
     Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
 }
 
+/// `options` is usually an array of `{letter, text}`, but some responses key it by letter
+/// (`{"A": text-or-node, ...}`, or `{"A": {"text": ...}}`). Both become a list; the object
+/// form is sorted by letter.
+fn deserialize_options<'de, D>(deserializer: D) -> Result<Vec<ApiAnswerOption>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Options {
+        List(Vec<ApiAnswerOption>),
+        ByLetter(BTreeMap<String, serde_json::Value>),
+    }
+
+    Ok(match Option::<Options>::deserialize(deserializer)? {
+        Some(Options::List(options)) => options,
+        Some(Options::ByLetter(options)) => options
+            .into_iter()
+            .map(|(letter, value)| {
+                // `{"text": ...}` wraps the option text; anything else is the text or node
+                let value = match value {
+                    serde_json::Value::Object(mut map)
+                        if map.contains_key("text") && !map.contains_key("children") =>
+                    {
+                        map.remove("text").unwrap_or_default()
+                    }
+                    other => other,
+                };
+                let text = match value {
+                    serde_json::Value::String(text) => ApiTextValue::Text(text),
+                    node => ApiTextValue::Node(node),
+                };
+                ApiAnswerOption { letter, text }
+            })
+            .collect(),
+        None => Vec::new(),
+    })
+}
+
 fn deserialize_objective_or_default<'de, D>(deserializer: D) -> Result<ApiObjective, D::Error>
 where
     D: Deserializer<'de>,
@@ -255,11 +585,38 @@ fn extract_text_from_value(value: &ApiTextValue) -> String {
     }
 }
 
+fn render_option_html(value: &ApiTextValue) -> Option<String> {
+    match value {
+        ApiTextValue::Text(_) => None,
+        ApiTextValue::Node(node) => {
+            // Not pretty-printed: indentation would change the spacing around inline tags
+            let html = render_node(node).trim().to_string();
+            (!html.is_empty()).then_some(html)
+        }
+    }
+}
+
+/// HTML for a node list such as `exposition`, or `None` when it renders empty.
+fn render_nodes_html(nodes: &[serde_json::Value]) -> Option<String> {
+    // Not pretty-printed, for the same reason as option markup
+    let html = nodes.iter().map(render_node).collect::<String>();
+    let html = html.trim();
+    (!html.is_empty()).then(|| html.to_string())
+}
+
 fn extract_text_from_json(node: &serde_json::Value) -> String {
     let mut text = String::new();
 
+    let render_math = crate::math::render_math_enabled();
+
     match node {
+        serde_json::Value::String(s) if render_math => {
+            text.push_str(&crate::math::render_math_markup(s))
+        }
         serde_json::Value::String(s) => text.push_str(s),
+        serde_json::Value::Object(obj) if render_math && crate::math::is_math_node(obj) => {
+            text.push_str(&crate::math::render_mathml_node(node))
+        }
         serde_json::Value::Object(obj) => {
             if let Some(children) = obj.get("children").and_then(|c| c.as_array()) {
                 for child in children {
@@ -500,6 +857,25 @@ fn extract_links_from_html(
     }
 }
 
+/// Syllabus section IDs ("cvs3") named by critique links into the syllabus, e.g.
+/// `/syllabus/cv/cvs3`, `/syllabus/cv#cvs3` or `?section=cvs3`, in link order.
+fn syllabus_sections_from_links(links: &[CritiqueLink]) -> Vec<String> {
+    let section_re = Regex::new(r"^[a-z]{2}s\d+$").unwrap();
+    let mut sections: Vec<String> = Vec::new();
+    for link in links {
+        let href = link.href.to_ascii_lowercase();
+        if !href.contains("syllabus") && !href.contains("section") {
+            continue;
+        }
+        for token in href.split(['/', '#', '?', '&', '=']) {
+            if section_re.is_match(token) && !sections.iter().any(|known| known == token) {
+                sections.push(token.to_string());
+            }
+        }
+    }
+    sections
+}
+
 /// Helper function to extract keypoints from the keypoints array
 fn extract_keypoints(nodes: &[serde_json::Value]) -> Vec<String> {
     nodes
@@ -511,6 +887,12 @@ fn extract_keypoints(nodes: &[serde_json::Value]) -> Vec<String> {
                         .iter()
                         .filter_map(|child| match child {
                             serde_json::Value::String(s) => Some(s.clone()),
+                            serde_json::Value::Object(o)
+                                if crate::math::render_math_enabled()
+                                    && crate::math::is_math_node(o) =>
+                            {
+                                Some(crate::math::render_mathml_node(child))
+                            }
                             serde_json::Value::Object(o) => o
                                 .get("children")
                                 .and_then(|c| c.as_array())
@@ -535,43 +917,97 @@ fn extract_keypoints(nodes: &[serde_json::Value]) -> Vec<String> {
 /// Helper function to extract references
 fn extract_references(refs: &[serde_json::Value]) -> String {
     refs.iter()
-        .filter_map(|r| {
-            if let Some(arr) = r.as_array() {
-                let ref_text = arr
-                    .iter()
-                    .filter_map(|item| match item {
-                        serde_json::Value::String(s) => Some(s.clone()),
-                        serde_json::Value::Object(o) => o
-                            .get("children")
-                            .and_then(|c| c.as_array())
-                            .and_then(|a| a.first())
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string()),
-                        _ => None,
-                    })
-                    .collect::<Vec<_>>()
-                    .join("");
-
-                if !ref_text.is_empty() {
-                    return Some(ref_text);
-                }
-            }
-            None
-        })
+        .filter_map(|r| reference_text(r.as_array()?))
         .collect::<Vec<_>>()
         .join("\n")
 }
 
+/// Text of one reference entry (an array of strings and link nodes), `None` if empty.
+fn reference_text(items: &[serde_json::Value]) -> Option<String> {
+    let ref_text = items
+        .iter()
+        .filter_map(|item| match item {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Object(o) => o
+                .get("children")
+                .and_then(|c| c.as_array())
+                .and_then(|a| a.first())
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("");
+    (!ref_text.is_empty()).then_some(ref_text)
+}
+
+/// The same entries as [`extract_references`], each with its identifiers.
+fn extract_reference_list(refs: &[serde_json::Value]) -> Vec<Reference> {
+    refs.iter()
+        .filter_map(|r| {
+            let items = r.as_array()?;
+            let text = reference_text(items)?;
+            let hrefs: Vec<String> = extract_links_from_nodes(items)
+                .into_iter()
+                .map(|link| link.href)
+                .collect();
+            Some(parse_reference(text, &hrefs))
+        })
+        .collect()
+}
+
+/// Pull a PMID and DOI from the reference text or its link targets (PubMed and doi.org
+/// URLs included). `url` is the first link, else the first URL written in the text.
+fn parse_reference(text: String, hrefs: &[String]) -> Reference {
+    static PMID_RE: OnceLock<Regex> = OnceLock::new();
+    static DOI_RE: OnceLock<Regex> = OnceLock::new();
+    static URL_RE: OnceLock<Regex> = OnceLock::new();
+    let pmid_re = PMID_RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)(?:\bPMID:?\s*|pubmed\.ncbi\.nlm\.nih\.gov/|ncbi\.nlm\.nih\.gov/pubmed/)(\d{1,9})\b",
+        )
+        .unwrap()
+    });
+    let doi_re = DOI_RE.get_or_init(|| Regex::new(r#"(?i)\b(10\.\d{4,9}/[^\s"<>]+)"#).unwrap());
+    let url_re = URL_RE.get_or_init(|| Regex::new(r#"https?://[^\s"<>]+"#).unwrap());
+    let trim_trailing = |value: &str| value.trim_end_matches(['.', ',', ';', ')']).to_string();
+
+    let sources = || std::iter::once(text.as_str()).chain(hrefs.iter().map(String::as_str));
+    let pmid = sources().find_map(|source| Some(pmid_re.captures(source)?[1].to_string()));
+    let doi = sources().find_map(|source| Some(trim_trailing(&doi_re.captures(source)?[1])));
+    let url = hrefs.first().cloned().or_else(|| {
+        url_re
+            .find(&text)
+            .map(|found| trim_trailing(found.as_str()))
+    });
+
+    Reference {
+        text,
+        pmid,
+        doi,
+        url,
+    }
+}
+
 /// Helper function to extract peer percentages from peerComparison JSON object
 fn extract_peer_percentages(peer_comparison: &serde_json::Value) -> HashMap<String, u32> {
     let mut percentages = HashMap::new();
 
+    // Usually `{"A": 12, ...}`; some responses send `[{"letter": "A", "percent": 12}, ...]`
     if let Some(obj) = peer_comparison.as_object() {
         for (letter, value) in obj {
             if let Some(percentage) = value.as_u64() {
                 percentages.insert(letter.clone(), percentage as u32);
             }
         }
+    } else if let Some(entries) = peer_comparison.as_array() {
+        for entry in entries {
+            let letter = entry.get("letter").and_then(|value| value.as_str());
+            let percentage = entry.get("percent").and_then(|value| value.as_u64());
+            if let (Some(letter), Some(percentage)) = (letter, percentage) {
+                percentages.insert(letter.to_string(), percentage as u32);
+            }
+        }
     }
 
     percentages
@@ -616,3 +1052,384 @@ impl Default for DiscoveryMetadataCollection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_response(extra: serde_json::Value) -> ApiQuestionResponse {
+        let mut value = serde_json::json!({
+            "id": "cvmcq24001",
+            "correctAnswer": "A",
+            "options": [{ "letter": "A", "text": "First" }],
+            "relatedSection": "cvs1"
+        });
+        if let (Some(target), Some(extra)) = (value.as_object_mut(), extra.as_object()) {
+            target.extend(extra.clone());
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_references_list_extracts_pmid_doi_and_url() {
+        let question = api_response(serde_json::json!({
+            "references": [
+                ["Smith J, Lee K. Diuretics in heart failure. N Engl J Med. 2020;382:1-10. PMID: 31899999"],
+                [
+                    "Jones A. Statins revisited. JAMA. 2021. ",
+                    {
+                        "tagName": "a",
+                        "attrs": { "href": "https://doi.org/10.1001/jama.2021.1234" },
+                        "children": ["doi:10.1001/jama.2021.1234"]
+                    }
+                ],
+                ["Guideline, available at https://example.org/hf-guideline."],
+                []
+            ]
+        }))
+        .into_question_data("cv".to_string());
+
+        assert_eq!(question.references_list.len(), 3);
+        assert_eq!(
+            question.references_list[0],
+            Reference {
+                text: "Smith J, Lee K. Diuretics in heart failure. N Engl J Med. 2020;382:1-10. PMID: 31899999"
+                    .to_string(),
+                pmid: Some("31899999".to_string()),
+                doi: None,
+                url: None,
+            }
+        );
+        let doi = &question.references_list[1];
+        assert_eq!(
+            doi.text,
+            "Jones A. Statins revisited. JAMA. 2021. doi:10.1001/jama.2021.1234"
+        );
+        assert_eq!(doi.doi.as_deref(), Some("10.1001/jama.2021.1234"));
+        assert_eq!(
+            doi.url.as_deref(),
+            Some("https://doi.org/10.1001/jama.2021.1234")
+        );
+        assert_eq!(doi.pmid, None);
+        assert_eq!(
+            question.references_list[2].url.as_deref(),
+            Some("https://example.org/hf-guideline")
+        );
+
+        // The joined string is unchanged
+        let joined: Vec<&str> = question.references.lines().collect();
+        assert_eq!(joined.len(), 3);
+        assert_eq!(joined[1], doi.text);
+    }
+
+    #[test]
+    fn test_content_hash_ignores_option_order_and_tracks_option_text() {
+        let hash = |options: serde_json::Value, extra: serde_json::Value| {
+            let mut fields = serde_json::json!({ "options": options, "keypoints": ["Check TSH."] });
+            fields
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            api_response(fields)
+                .into_question_data("cv".to_string())
+                .content_hash
+        };
+        let a = serde_json::json!({ "letter": "A", "text": "Metoprolol" });
+        let b = serde_json::json!({ "letter": "B", "text": "Lisinopril" });
+
+        let original = hash(serde_json::json!([a, b]), serde_json::json!({}));
+        assert_eq!(original.len(), 64);
+        assert_eq!(
+            hash(serde_json::json!([b, a]), serde_json::json!({})),
+            original
+        );
+        assert_eq!(
+            hash(
+                serde_json::json!([a, b]),
+                serde_json::json!({ "hvc": true, "peerComparison": { "A": 60, "B": 40 } })
+            ),
+            original
+        );
+
+        let edited = serde_json::json!({ "letter": "B", "text": "Losartan" });
+        assert_ne!(
+            hash(serde_json::json!([a, edited]), serde_json::json!({})),
+            original
+        );
+
+        // A corrected answer key is a content change
+        assert_ne!(
+            hash(
+                serde_json::json!([a, b]),
+                serde_json::json!({ "correctAnswer": "B" })
+            ),
+            original
+        );
+    }
+
+    #[test]
+    fn test_critique_links_to_syllabus_sections_extend_syllabus() {
+        let question = api_response(serde_json::json!({
+            "exposition": [{
+                "type": "p",
+                "children": [
+                    { "type": "a", "href": "/syllabus/cv/cvs4", "children": ["valve disease"] },
+                    " and ",
+                    { "type": "a", "href": "/syllabus/cv/heart-failure#cvs1", "children": ["HF"] },
+                    "<a href=\"/app/content?section=CVS12\">arrhythmias</a>",
+                    { "type": "a", "href": "https://example.org/cvs9", "children": ["external"] }
+                ]
+            }]
+        }))
+        .into_question_data("cv".to_string());
+        assert_eq!(
+            question.related_content.syllabus,
+            vec!["cvs1", "cvs4", "cvs12"]
+        );
+    }
+
+    #[test]
+    fn test_options_object_shape_matches_array_shape() {
+        let options = |fixture: &str| {
+            let response: ApiQuestionResponse = serde_json::from_str(fixture).unwrap();
+            response
+                .into_question_data("cv".to_string())
+                .options
+                .into_iter()
+                .map(|option| (option.letter, option.text, option.html, option.is_correct))
+                .collect::<Vec<_>>()
+        };
+        let from_array = options(include_str!("../tests/fixtures/question_cvmcq24001.json"));
+        let from_object = options(include_str!(
+            "../tests/fixtures/question_cvmcq24001_options_object.json"
+        ));
+        assert_eq!(from_array.len(), 3);
+        assert_eq!(from_object, from_array);
+        assert_eq!(from_object[1].1, "Heart failure");
+        assert!(from_object[1].3);
+    }
+
+    #[test]
+    fn test_learning_plan_topic_populated_when_present() {
+        let question = api_response(serde_json::json!({
+            "learningPlanTopic": "Heart Failure"
+        }))
+        .into_question_data("cv".to_string());
+        assert_eq!(
+            question.related_content.learning_plan_topic,
+            "Heart Failure"
+        );
+
+        let question = api_response(serde_json::json!({
+            "learningPlan": { "title": "Valvular <em>Heart</em> Disease" }
+        }))
+        .into_question_data("cv".to_string());
+        assert_eq!(
+            question.related_content.learning_plan_topic,
+            "Valvular Heart Disease"
+        );
+    }
+
+    #[test]
+    fn test_multiple_correct_answers_flag_every_option() {
+        let options = serde_json::json!([
+            { "letter": "A", "text": "First" },
+            { "letter": "B", "text": "Second" },
+            { "letter": "C", "text": "Third" }
+        ]);
+        for correct in [
+            serde_json::json!("A,C"),
+            serde_json::json!("AC"),
+            serde_json::json!(["A", "C"]),
+        ] {
+            let question = api_response(serde_json::json!({
+                "correctAnswer": correct,
+                "options": options
+            }))
+            .into_question_data("cv".to_string());
+            assert_eq!(question.user_performance.correct_answers, vec!["A", "C"]);
+            let flagged: Vec<bool> = question.options.iter().map(|o| o.is_correct).collect();
+            assert_eq!(flagged, vec![true, false, true]);
+        }
+    }
+
+    #[test]
+    fn test_critique_html_keeps_structure_beside_plain_text() {
+        let question = api_response(serde_json::json!({
+            "exposition": [
+                { "type": "p", "children": ["Start with ", { "tagName": "b", "children": ["diuretics"] }, "."] },
+                {
+                    "tagName": "ul",
+                    "children": [
+                        { "tagName": "li", "children": [
+                            "See ",
+                            { "tagName": "a", "attrs": { "href": "/syllabus/cv/cvs3" }, "children": ["HF & volume"] }
+                        ] }
+                    ]
+                }
+            ]
+        }))
+        .into_question_data("cv".to_string());
+        assert_eq!(
+            question.critique_html.as_deref(),
+            Some(
+                "<p>Start with <b>diuretics</b>.</p><ul><li>See \
+                 <a href=\"/syllabus/cv/cvs3\">HF &amp; volume</a></li></ul>"
+            )
+        );
+        assert!(question.critique.contains("Start with diuretics."));
+        assert!(question
+            .critique_links
+            .iter()
+            .any(|link| link.href == "/syllabus/cv/cvs3"));
+
+        let empty = api_response(serde_json::json!({})).into_question_data("cv".to_string());
+        assert!(empty.critique_html.is_none());
+        let serialized = serde_json::to_value(&empty).unwrap();
+        assert!(serialized.get("critique_html").is_none());
+
+        // JSON written before the field existed still loads
+        let mut legacy = serialized;
+        legacy.as_object_mut().unwrap().remove("critique_html");
+        assert!(serde_json::from_value::<QuestionData>(legacy).is_ok());
+    }
+
+    #[test]
+    fn test_option_html_preserves_inline_markup() {
+        let question = api_response(serde_json::json!({
+            "options": [
+                {
+                    "letter": "A",
+                    "text": {
+                        "children": [
+                            "Serum Ca",
+                            { "tagName": "sup", "children": ["2+"] },
+                            " and ",
+                            { "tagName": "i", "children": ["BRCA1"] }
+                        ]
+                    }
+                },
+                { "letter": "B", "text": "Plain option" }
+            ]
+        }))
+        .into_question_data("cv".to_string());
+
+        assert_eq!(question.options[0].text, "Serum Ca2+ and BRCA1");
+        assert_eq!(
+            question.options[0].html.as_deref(),
+            Some("Serum Ca<sup>2+</sup> and <i>BRCA1</i>")
+        );
+        assert_eq!(question.options[1].text, "Plain option");
+        assert!(question.options[1].html.is_none());
+        let serialized = serde_json::to_value(&question.options[1]).unwrap();
+        assert!(serialized.get("html").is_none());
+    }
+
+    #[test]
+    fn test_single_correct_answer_keeps_singular_field() {
+        let question = api_response(serde_json::json!({})).into_question_data("cv".to_string());
+        assert_eq!(
+            question.user_performance.correct_answer.as_deref(),
+            Some("A")
+        );
+        assert_eq!(question.user_performance.correct_answers, vec!["A"]);
+        assert!(question.options[0].is_correct);
+    }
+
+    #[test]
+    fn test_provenance_keeps_api_schema_version() {
+        let question = api_response(serde_json::json!({ "schemaVersion": 3 }))
+            .into_question_data("cv".to_string());
+        assert_eq!(question.provenance.api_schema_version.as_deref(), Some("3"));
+
+        // Files written before provenance was recorded still load
+        let mut value = serde_json::to_value(&question).unwrap();
+        value.as_object_mut().unwrap().remove("provenance");
+        let question: QuestionData = serde_json::from_value(value).unwrap();
+        assert_eq!(question.provenance, Provenance::default());
+    }
+
+    #[test]
+    fn test_peer_comparison_array_matches_object_form() {
+        let options = serde_json::json!([
+            { "letter": "A", "text": "First" },
+            { "letter": "B", "text": "Second" }
+        ]);
+        let percentages = |peer_comparison: serde_json::Value| -> Vec<u32> {
+            api_response(serde_json::json!({
+                "options": options,
+                "peerComparison": peer_comparison
+            }))
+            .into_question_data("cv".to_string())
+            .options
+            .iter()
+            .map(|option| option.peer_percentage)
+            .collect()
+        };
+
+        let from_object = percentages(serde_json::json!({ "A": 64, "B": 36 }));
+        let from_array = percentages(serde_json::json!([
+            { "letter": "B", "percent": 36 },
+            { "letter": "A", "percent": 64 }
+        ]));
+        assert_eq!(from_object, vec![64, 36]);
+        assert_eq!(from_array, from_object);
+
+        let partial = percentages(serde_json::json!([
+            { "letter": "A", "percent": 70 },
+            { "letter": "B" },
+            "C"
+        ]));
+        assert_eq!(partial, vec![70, 0]);
+    }
+
+    #[test]
+    fn test_learning_plan_topic_empty_when_absent() {
+        let question = api_response(serde_json::json!({})).into_question_data("cv".to_string());
+        assert_eq!(question.related_content.learning_plan_topic, "");
+
+        let json = serde_json::to_value(&question).unwrap();
+        assert!(json["related_content"].get("learning_plan_topic").is_none());
+    }
+
+    #[test]
+    fn test_extract_objective_from_html() {
+        let objective = ApiObjective::Html {
+            html: "<p><strong>Objective:</strong>  Diagnose <em>acute</em>\n decompensated heart failure.</p>"
+                .to_string(),
+        };
+        assert_eq!(
+            extract_objective(&objective),
+            (
+                "Diagnose acute decompensated heart failure.".to_string(),
+                Some("diagnosis".to_string())
+            )
+        );
+
+        let question = api_response(serde_json::json!({
+            "objective": { "__html": "Treat <em>stable</em> angina." }
+        }))
+        .into_question_data("cv".to_string());
+        assert_eq!(question.objective_text, "Treat stable angina.");
+        assert_eq!(question.objective_html, "Treat <em>stable</em> angina.");
+        assert_eq!(question.objective_category.as_deref(), Some("management"));
+    }
+
+    #[test]
+    fn test_extract_objective_from_text() {
+        let objective =
+            ApiObjective::Text("educational objective: Evaluate a thyroid nodule.".to_string());
+        assert_eq!(
+            extract_objective(&objective),
+            (
+                "Evaluate a thyroid nodule.".to_string(),
+                Some("evaluation".to_string())
+            )
+        );
+        let objective = ApiObjective::Text("  Pathophysiology of   heart failure ".to_string());
+        assert_eq!(
+            extract_objective(&objective),
+            ("Pathophysiology of heart failure".to_string(), None)
+        );
+    }
+}
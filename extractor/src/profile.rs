@@ -0,0 +1,177 @@
+//! Per-question extraction latency (`run --profile`).
+//!
+//! Each processed question's fetch+transform time is kept in memory and, when a
+//! path is given, appended to `extraction_profile.jsonl` as it completes.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub const PROFILE_FILE_NAME: &str = "extraction_profile.jsonl";
+pub const DEFAULT_PROFILE_TOP: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProfileEntry {
+    pub question_id: String,
+    pub system: String,
+    /// Wall-clock time for the request, parse and write.
+    pub millis: f64,
+    /// `ok`, `not_found` or `error`.
+    pub outcome: &'static str,
+}
+
+#[derive(Debug, Default)]
+pub struct ExtractionProfile {
+    entries: Mutex<Vec<ProfileEntry>>,
+    file: Option<Mutex<File>>,
+}
+
+impl ExtractionProfile {
+    /// Profile kept in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Profile that also writes one JSON line per question to `path` (truncated first).
+    pub fn with_file(path: &Path) -> Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create profile {}", path.display()))?;
+        Ok(Self {
+            entries: Mutex::new(Vec::new()),
+            file: Some(Mutex::new(file)),
+        })
+    }
+
+    pub fn record(
+        &self,
+        question_id: &str,
+        system: &str,
+        elapsed: Duration,
+        outcome: &'static str,
+    ) {
+        let entry = ProfileEntry {
+            question_id: question_id.to_string(),
+            system: system.to_string(),
+            millis: elapsed.as_secs_f64() * 1000.0,
+            outcome,
+        };
+
+        if let Some(file) = &self.file {
+            let written = serde_json::to_string(&entry)
+                .map_err(anyhow::Error::from)
+                .and_then(|line| {
+                    let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                    writeln!(file, "{}", line).map_err(anyhow::Error::from)
+                });
+            if let Err(err) = written {
+                warn!("Failed to write profile entry for {}: {}", question_id, err);
+            }
+        }
+
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(entry);
+    }
+
+    pub fn entries(&self) -> Vec<ProfileEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// The `limit` slowest questions, slowest first.
+    pub fn slowest(&self, limit: usize) -> Vec<ProfileEntry> {
+        let mut entries = self.entries();
+        entries.sort_by(|a, b| b.millis.total_cmp(&a.millis));
+        entries.truncate(limit);
+        entries
+    }
+
+    pub fn log_slowest(&self, limit: usize) {
+        let slowest = self.slowest(limit);
+        if slowest.is_empty() {
+            return;
+        }
+        info!("Slowest {} questions:", slowest.len());
+        for entry in slowest {
+            info!(
+                "  {} ({}): {:.0} ms [{}]",
+                entry.question_id, entry.system, entry.millis, entry.outcome
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Category, MKSAPExtractor};
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_profile_records_each_processed_question() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24001.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                include_str!("../tests/fixtures/question_cvmcq24001.json"),
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24002.json"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().join("data");
+        let checkpoints = output_dir.join(".checkpoints");
+        std::fs::create_dir_all(&checkpoints).unwrap();
+        std::fs::write(checkpoints.join("cv_ids.txt"), "cvmcq24001\ncvmcq24002").unwrap();
+
+        let profile_path = temp.path().join(PROFILE_FILE_NAME);
+        let profile = Arc::new(ExtractionProfile::with_file(&profile_path).unwrap());
+        let extractor = MKSAPExtractor::new(&server.uri(), output_dir.to_str().unwrap())
+            .unwrap()
+            .with_profile(profile.clone());
+        let category = Category {
+            code: "cv".to_string(),
+            name: "Cardiovascular Medicine".to_string(),
+            question_prefix: "cv".to_string(),
+        };
+
+        let extracted = extractor.extract_category(&category, false).await.unwrap();
+        assert_eq!(extracted, 1);
+
+        let mut entries = profile.entries();
+        entries.sort_by(|a, b| a.question_id.cmp(&b.question_id));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].question_id, "cvmcq24001");
+        assert_eq!(entries[0].outcome, "ok");
+        assert_eq!(entries[1].outcome, "not_found");
+        assert!(entries.iter().all(|entry| entry.millis > 0.0));
+        assert_eq!(profile.slowest(1).len(), 1);
+
+        let lines = std::fs::read_to_string(&profile_path).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        let first: serde_json::Value = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(first["system"], "cv");
+    }
+}
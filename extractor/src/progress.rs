@@ -0,0 +1,124 @@
+//! Progress reporting for long discovery and extraction loops.
+//!
+//! Draws an `indicatif` bar on interactive terminals; otherwise (or with
+//! `--no-progress`) falls back to periodic `info!` lines so CI logs stay clean.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tracing::info;
+
+static PROGRESS_BARS: AtomicBool = AtomicBool::new(true);
+
+const BAR_TEMPLATE: &str =
+    "[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} ({percent}%) {per_sec} ETA {eta} {msg}";
+
+/// Enable or disable progress bars (`--no-progress` disables them).
+pub(crate) fn configure_progress(enabled: bool) {
+    PROGRESS_BARS.store(enabled, Ordering::Relaxed);
+}
+
+fn bars_enabled() -> bool {
+    PROGRESS_BARS.load(Ordering::Relaxed) && std::io::stdout().is_terminal()
+}
+
+pub(crate) struct Progress {
+    bar: Option<ProgressBar>,
+    position: AtomicU64,
+    length: AtomicU64,
+    label: &'static str,
+    log_every: u64,
+}
+
+impl Progress {
+    /// Progress over `total` items, logging every `log_every` items when no bar is drawn.
+    pub(crate) fn new(total: usize, label: &'static str, log_every: usize) -> Self {
+        let bar = bars_enabled().then(|| {
+            let bar = ProgressBar::new(total as u64);
+            if let Ok(style) = ProgressStyle::with_template(BAR_TEMPLATE) {
+                bar.set_style(style.progress_chars("=> "));
+            }
+            bar.set_message(label);
+            bar
+        });
+        Self {
+            bar,
+            position: AtomicU64::new(0),
+            length: AtomicU64::new(total as u64),
+            label,
+            log_every: log_every.max(1) as u64,
+        }
+    }
+
+    /// Progress that never draws a bar or logs; for callers that track nothing.
+    pub(crate) fn hidden() -> Self {
+        Self {
+            bar: None,
+            position: AtomicU64::new(0),
+            length: AtomicU64::new(0),
+            label: "",
+            log_every: u64::MAX,
+        }
+    }
+
+    /// Grow the total, for work that is only counted once it is discovered.
+    pub(crate) fn inc_length(&self, delta: usize) {
+        self.length.fetch_add(delta as u64, Ordering::Relaxed);
+        if let Some(bar) = &self.bar {
+            bar.inc_length(delta as u64);
+        }
+    }
+
+    /// Start from `position` items already done (e.g. a resumed run).
+    pub(crate) fn set_position(&self, position: usize) {
+        self.position.store(position as u64, Ordering::Relaxed);
+        if let Some(bar) = &self.bar {
+            bar.set_position(position as u64);
+        }
+    }
+
+    /// Record one finished item.
+    pub(crate) fn inc(&self) {
+        let position = self.position.fetch_add(1, Ordering::Relaxed) + 1;
+        match &self.bar {
+            Some(bar) => bar.inc(1),
+            None => {
+                let length = self.length.load(Ordering::Relaxed);
+                if position.is_multiple_of(self.log_every) || position == length {
+                    info!("Progress: {}/{} {}", position, length, self.label);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.position.load(Ordering::Relaxed) as usize
+    }
+
+    /// Clear the bar so the final summary lines print cleanly.
+    pub(crate) fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_counts_without_a_bar() {
+        configure_progress(false);
+        let progress = Progress::new(2, "questions processed", 10);
+        assert!(progress.bar.is_none());
+
+        progress.set_position(1);
+        progress.inc_length(3);
+        progress.inc();
+        progress.inc();
+        assert_eq!(progress.position(), 3);
+        assert_eq!(progress.length.load(Ordering::Relaxed), 5);
+        progress.finish();
+    }
+}
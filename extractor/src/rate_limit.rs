@@ -0,0 +1,100 @@
+//! Requests-per-second limit shared by every media discovery/download request (`--rps`).
+//!
+//! Concurrency limits bound how many requests are in flight, but a burst of fast
+//! responses can still exceed the API's per-second limit. Each outbound request takes
+//! a token from one process-wide bucket first.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Default `--rps`; `--rps 0` disables the limit.
+pub const DEFAULT_RPS: f64 = 10.0;
+
+static RATE_LIMITER: OnceLock<Option<RateLimiter>> = OnceLock::new();
+
+/// Token bucket refilled at `rps` tokens per second, holding at most one token so
+/// requests are spaced evenly instead of released in bursts.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rps: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rps: f64) -> Self {
+        Self {
+            rps,
+            bucket: Mutex::new(Bucket {
+                tokens: 1.0,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a token is available and take it.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.rps).min(1.0);
+                bucket.refilled_at = now;
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.rps)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Limit media requests to `rps` per second for this run (`0` or less disables it).
+/// Only the first call takes effect; without a call requests are not limited.
+pub(crate) fn configure_rate_limit(rps: f64) {
+    let limiter = (rps > 0.0).then(|| RateLimiter::new(rps));
+    if RATE_LIMITER.set(limiter).is_err() {
+        warn!("Rate limit already configured; ignoring override");
+    }
+}
+
+/// Take a token from the shared limiter, if one is configured.
+pub(crate) async fn acquire() {
+    if let Some(limiter) = RATE_LIMITER.get().and_then(Option::as_ref) {
+        limiter.acquire().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_limiter_spaces_concurrent_requests() {
+        let limiter = Arc::new(RateLimiter::new(50.0));
+        let started = Instant::now();
+        let tasks: Vec<_> = (0..11)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        // One token up front, then ten more at 20ms intervals
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(190), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(2), "{:?}", elapsed);
+    }
+}
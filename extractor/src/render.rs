@@ -0,0 +1,210 @@
+//! Markdown rendering of a single question (`export-markdown <question_id>`).
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::assets::asset_store::collect_question_entry_map;
+use crate::models::QuestionData;
+
+/// Render a question as Markdown: stem, options (correct one marked), critique,
+/// key points and references. Source text is escaped so it cannot inject formatting.
+pub fn render_question_markdown(question: &QuestionData) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "# {} — {} ({})\n\n",
+        escape_markdown(&question.question_id),
+        escape_markdown(&question.category_name),
+        escape_markdown(&question.category)
+    ));
+
+    if !question.educational_objective.trim().is_empty() {
+        out.push_str(&format!(
+            "**Educational objective:** {}\n\n",
+            escape_markdown(question.educational_objective.trim())
+        ));
+    }
+
+    out.push_str("## Question\n\n");
+    for text in [&question.question_text, &question.question_stem] {
+        if !text.trim().is_empty() {
+            out.push_str(&escape_markdown(text.trim()));
+            out.push_str("\n\n");
+        }
+    }
+
+    if !question.options.is_empty() {
+        out.push_str("## Options\n\n");
+        for option in &question.options {
+            let marker = if is_correct_option(question, &option.letter) {
+                " **(correct)**"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "- **{}.** {}{}\n",
+                escape_markdown(&option.letter),
+                escape_markdown(&single_line(&option.text)),
+                marker
+            ));
+        }
+        out.push('\n');
+    }
+
+    if !question.critique.trim().is_empty() {
+        out.push_str("## Critique\n\n");
+        out.push_str(&escape_markdown(question.critique.trim()));
+        out.push_str("\n\n");
+    }
+
+    let key_points: Vec<&String> = question
+        .key_points
+        .iter()
+        .filter(|point| !point.trim().is_empty())
+        .collect();
+    if !key_points.is_empty() {
+        out.push_str("## Key Points\n\n");
+        for point in key_points {
+            out.push_str(&format!("- {}\n", escape_markdown(&single_line(point))));
+        }
+        out.push('\n');
+    }
+
+    let references: Vec<&str> = question
+        .references
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if !references.is_empty() {
+        out.push_str("## References\n\n");
+        for reference in references {
+            out.push_str(&format!("- {}\n", escape_markdown(reference)));
+        }
+        out.push('\n');
+    }
+
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// Write `<question_id>.md` beside the question JSON under `data_dir`.
+pub fn export_question_markdown(data_dir: &str, question_id: &str) -> Result<PathBuf> {
+    let entries = collect_question_entry_map(data_dir)?;
+    let entry = entries.get(question_id).with_context(|| {
+        format!(
+            "Question {} not found under {}; extract it first",
+            question_id, data_dir
+        )
+    })?;
+    let content = fs::read_to_string(&entry.json_path)
+        .with_context(|| format!("Failed to read {}", entry.json_path.display()))?;
+    let question: QuestionData = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", entry.json_path.display()))?;
+
+    let out_path = entry.question_dir.join(format!("{}.md", question_id));
+    fs::write(&out_path, render_question_markdown(&question))
+        .with_context(|| format!("Failed to write {}", out_path.display()))?;
+    Ok(out_path)
+}
+
+fn is_correct_option(question: &QuestionData, letter: &str) -> bool {
+    let performance = &question.user_performance;
+    question
+        .options
+        .iter()
+        .any(|option| option.letter == letter && option.is_correct)
+        || performance
+            .correct_answers
+            .iter()
+            .any(|answer| answer == letter)
+        || performance.correct_answer.as_deref() == Some(letter)
+}
+
+/// Collapse line breaks so a list item stays a single item.
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Backslash-escape characters that would otherwise start emphasis, code, links,
+/// HTML, tables or headings, plus list markers at the start of a line.
+fn escape_markdown(text: &str) -> String {
+    text.lines().map(escape_line).collect::<Vec<_>>().join("\n")
+}
+
+fn escape_line(line: &str) -> String {
+    let indent_len = line.len() - line.trim_start().len();
+    let (indent, body) = line.split_at(indent_len);
+
+    let mut out = String::with_capacity(line.len() + 8);
+    out.push_str(indent);
+
+    // "- item", "+ item" and "1. item" would become lists
+    let digits = body.chars().take_while(char::is_ascii_digit).count();
+    let skip = if body.starts_with("- ") || body.starts_with("+ ") {
+        out.push('\\');
+        0
+    } else if digits > 0 && body[digits..].starts_with(". ") {
+        out.push_str(&body[..digits]);
+        out.push('\\');
+        digits
+    } else {
+        0
+    };
+
+    for ch in body[skip..].chars() {
+        if matches!(
+            ch,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' | '~'
+        ) {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_question, write_question};
+
+    #[test]
+    fn test_render_question_markdown_sections() {
+        let mut value = sample_question("cv", "cvmcq24001");
+        value["question_stem"] = "Which is *most* appropriate?".into();
+        value["options"][1]["text"] = "Second | line\nwrapped".into();
+        value["key_points"] = serde_json::json!(["Use [ACE] inhibitors", ""]);
+        let question: QuestionData = serde_json::from_value(value).unwrap();
+
+        let markdown = render_question_markdown(&question);
+        assert!(markdown.starts_with("# cvmcq24001 — CV (cv)\n"));
+        assert!(markdown.contains("Which is \\*most\\* appropriate?"));
+        assert!(markdown.contains("- **A.** First **(correct)**\n"));
+        assert!(markdown.contains("- **B.** Second \\| line wrapped\n"));
+        assert!(markdown.contains("## Key Points\n\n- Use \\[ACE\\] inhibitors\n\n"));
+        assert!(markdown.contains("## References\n\n- Ref one.\n- Ref two.\n"));
+        assert!(markdown.ends_with("Ref two.\n"));
+    }
+
+    #[test]
+    fn test_escape_markdown_line_starts() {
+        assert_eq!(escape_markdown("- not a list"), "\\- not a list");
+        assert_eq!(escape_markdown("12. not a list"), "12\\. not a list");
+        assert_eq!(escape_markdown("# heading\n  + x"), "\\# heading\n  \\+ x");
+        assert_eq!(escape_markdown("2.5 mg/dL - stable"), "2.5 mg/dL - stable");
+        assert_eq!(escape_markdown("a_b <c>"), "a\\_b \\<c\\>");
+    }
+
+    #[test]
+    fn test_export_question_markdown_writes_beside_json() {
+        let temp = tempfile::tempdir().unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+
+        let path = export_question_markdown(temp.path().to_str().unwrap(), "cvmcq24001").unwrap();
+        assert_eq!(path, temp.path().join("cv/cvmcq24001/cvmcq24001.md"));
+        assert!(fs::read_to_string(path).unwrap().contains("## Critique"));
+        assert!(export_question_markdown(temp.path().to_str().unwrap(), "cvmcq24999").is_err());
+    }
+}
@@ -1,39 +1,181 @@
 use anyhow::{Context, Result};
-use std::fs;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use tracing::info;
 
 use crate::config::Category;
-use crate::io::read_checkpoint_lines;
-use crate::models::DiscoveryMetadataCollection;
-use crate::validator::DataValidator;
+use crate::extractor::checkpoint_dir;
+use crate::io::{read_checkpoint_lines, write_atomic};
+use crate::models::{DiscoveryMetadata, DiscoveryMetadataCollection};
+use crate::validator::{DataValidator, ValidationResult};
 
-pub async fn validate_extraction(output_dir: &str) -> Result<()> {
+/// Layout of the `validate` report (`--report-format`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    /// `DataValidator::generate_discovery_report`, for pasting into PRs and issues.
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "text" | "txt" => Ok(ReportFormat::Text),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            _ => anyhow::bail!(
+                "Unknown report format '{}': expected text or markdown",
+                value
+            ),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Text => "txt",
+            ReportFormat::Markdown => "md",
+        }
+    }
+
+    /// `report_out`, or `validation_report.<ext>` in `output_dir`; Markdown always gets `.md`.
+    fn report_path(self, output_dir: &str, report_out: Option<&Path>) -> PathBuf {
+        match report_out {
+            Some(path) if self == ReportFormat::Markdown => path.with_extension("md"),
+            Some(path) => path.to_path_buf(),
+            None => Path::new(output_dir).join(format!("validation_report.{}", self.extension())),
+        }
+    }
+}
+
+/// Validate `output_dir` and print/save the report. With `include_empty_systems`, every
+/// configured system is listed, even at 0.
+pub async fn validate_extraction(
+    output_dir: &str,
+    check_media: bool,
+    include_empty_systems: bool,
+    excluded_systems: &[String],
+    format: ReportFormat,
+    report_out: Option<&Path>,
+) -> Result<()> {
     info!("\n=== VALIDATING EXTRACTED DATA ===");
     info!("Scanning mksap_data directory for extracted questions...\n");
+    if check_media {
+        info!("Media check enabled: verifying referenced files exist on disk");
+    }
 
-    let result = DataValidator::validate_extraction(output_dir)?;
+    if !excluded_systems.is_empty() {
+        info!("Excluding systems: {}", excluded_systems.join(", "));
+    }
 
-    println!("\n{}", DataValidator::generate_report(&result));
-    println!("\n{}", DataValidator::compare_with_specification(&result));
+    let mut result =
+        DataValidator::validate_extraction_excluding(output_dir, check_media, excluded_systems)?;
+    if include_empty_systems {
+        result.include_empty_systems();
+        result
+            .systems_verified
+            .retain(|system| !excluded_systems.contains(&system.system_id));
+    }
 
-    // Save detailed report
-    let report_path = format!("{}/validation_report.txt", output_dir);
-    let mut report = DataValidator::generate_report(&result);
-    report.push_str("\n\n");
-    report.push_str(&DataValidator::compare_with_specification(&result));
+    let report = match format {
+        ReportFormat::Text => {
+            let mut report = DataValidator::generate_report(&result);
+            report.push_str("\n\n");
+            report.push_str(&DataValidator::compare_with_specification(&result));
+            report
+        }
+        ReportFormat::Markdown => {
+            let discovery = load_discovery_metadata(output_dir)?;
+            DataValidator::generate_discovery_report(&result, discovery.as_ref())
+        }
+    };
+    println!("\n{}", report);
 
+    // Save detailed report
+    let report_path = format.report_path(output_dir, report_out);
+    if let Some(parent) = report_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
     fs::write(&report_path, report).context("Failed to write validation report")?;
 
-    info!("Validation report saved to {}", report_path);
+    info!("Validation report saved to {}", report_path.display());
 
     Ok(())
 }
 
-pub async fn show_discovery_stats(output_dir: &str) -> Result<()> {
-    let metadata_path = Path::new(output_dir)
-        .join(".checkpoints")
-        .join("discovery_metadata.json");
+const PROGRESS_HISTORY_FILE: &str = "progress_history.jsonl";
+
+/// One line of `progress_history.jsonl`: per-system counts at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressSnapshot {
+    pub timestamp: String,
+    pub total_found: usize,
+    pub total_discovered: usize,
+    pub total_valid: usize,
+    pub systems: BTreeMap<String, SystemProgress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemProgress {
+    pub found: usize,
+    pub discovered: usize,
+    pub valid: usize,
+}
+
+/// Append the current per-system counts to `progress_history.jsonl` for plotting over time.
+pub async fn record_progress_history(output_dir: &str) -> Result<()> {
+    let snapshot = append_progress_snapshot(output_dir)?;
+    info!(
+        "Recorded progress snapshot: {}/{} questions ({} valid) at {}",
+        snapshot.total_found, snapshot.total_discovered, snapshot.total_valid, snapshot.timestamp
+    );
+    Ok(())
+}
+
+fn append_progress_snapshot(output_dir: &str) -> Result<ProgressSnapshot> {
+    let result = DataValidator::validate_extraction(output_dir)?;
+
+    let systems: BTreeMap<String, SystemProgress> = result
+        .systems_verified
+        .iter()
+        .map(|system| {
+            (
+                system.system_id.clone(),
+                SystemProgress {
+                    found: system.found_count,
+                    discovered: system.discovered_count,
+                    valid: system.valid_count,
+                },
+            )
+        })
+        .collect();
+    let snapshot = ProgressSnapshot {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        total_found: result.total_questions,
+        total_discovered: systems.values().map(|system| system.discovered).sum(),
+        total_valid: result.valid_questions,
+        systems,
+    };
+
+    let history_path = Path::new(output_dir).join(PROGRESS_HISTORY_FILE);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_path)
+        .with_context(|| format!("Failed to open {}", history_path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&snapshot)?)
+        .context("Failed to append progress snapshot")?;
+
+    Ok(snapshot)
+}
+
+pub async fn show_discovery_stats(output_dir: &str, include_empty_systems: bool) -> Result<()> {
+    let metadata_path = checkpoint_dir(output_dir).join("discovery_metadata.json");
 
     if !metadata_path.exists() {
         println!("\n❌ No discovery metadata found.");
@@ -42,7 +184,25 @@ pub async fn show_discovery_stats(output_dir: &str) -> Result<()> {
     }
 
     let contents = fs::read_to_string(&metadata_path)?;
-    let metadata: DiscoveryMetadataCollection = serde_json::from_str(&contents)?;
+    let mut metadata: DiscoveryMetadataCollection = serde_json::from_str(&contents)?;
+    if include_empty_systems {
+        for system in crate::config::init_organ_systems() {
+            if !metadata
+                .systems
+                .iter()
+                .any(|sys| sys.system_code == system.id)
+            {
+                metadata.systems.push(DiscoveryMetadata {
+                    system_code: system.id,
+                    discovered_count: 0,
+                    discovery_timestamp: String::new(),
+                    candidates_tested: 0,
+                    hit_rate: 0.0,
+                    question_types_found: Vec::new(),
+                });
+            }
+        }
+    }
 
     println!("\n=== MKSAP Discovery Statistics ===\n");
     println!("Last Updated: {}\n", metadata.last_updated);
@@ -82,9 +242,134 @@ pub async fn show_discovery_stats(output_dir: &str) -> Result<()> {
     Ok(())
 }
 
+/// Write discovery and validation statistics in Prometheus textfile-collector format
+/// (for node_exporter's `--collector.textfile.directory`).
+///
+/// The file is written atomically so the collector never reads a partial file.
+pub fn write_metrics_textfile(output_dir: &str, metrics_path: &Path) -> Result<()> {
+    let discovery = load_discovery_metadata(output_dir)?;
+    let validation = DataValidator::validate_extraction(output_dir)?;
+    let metrics = render_prometheus_metrics(discovery.as_ref(), &validation);
+
+    if let Some(parent) = metrics_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    write_atomic(metrics_path, metrics)?;
+    info!("Wrote Prometheus metrics to {}", metrics_path.display());
+    Ok(())
+}
+
+fn load_discovery_metadata(output_dir: &str) -> Result<Option<DiscoveryMetadataCollection>> {
+    let metadata_path = checkpoint_dir(output_dir).join("discovery_metadata.json");
+    if !metadata_path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&metadata_path)?;
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
+fn render_prometheus_metrics(
+    discovery: Option<&DiscoveryMetadataCollection>,
+    validation: &ValidationResult,
+) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, samples: Vec<(Option<&str>, f64)>| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (system, value) in samples {
+            match system {
+                Some(system) => {
+                    let _ = writeln!(
+                        out,
+                        "{}{{system=\"{}\"}} {}",
+                        name,
+                        escape_label_value(system),
+                        value
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "{} {}", name, value);
+                }
+            }
+        }
+    };
+
+    if let Some(discovery) = discovery {
+        let systems = &discovery.systems;
+        gauge(
+            "mksap_discovered_total",
+            "Questions discovered per system.",
+            systems
+                .iter()
+                .map(|s| (Some(s.system_code.as_str()), s.discovered_count as f64))
+                .collect(),
+        );
+        gauge(
+            "mksap_candidates_tested_total",
+            "Question ID candidates tested during discovery per system.",
+            systems
+                .iter()
+                .map(|s| (Some(s.system_code.as_str()), s.candidates_tested as f64))
+                .collect(),
+        );
+        gauge(
+            "mksap_discovery_hit_ratio",
+            "Discovered / candidates tested per system.",
+            systems
+                .iter()
+                .map(|s| (Some(s.system_code.as_str()), s.hit_rate))
+                .collect(),
+        );
+    }
+
+    let systems = &validation.systems_verified;
+    gauge(
+        "mksap_extracted_questions",
+        "Question directories found on disk per system.",
+        systems
+            .iter()
+            .map(|s| (Some(s.system_id.as_str()), s.found_count as f64))
+            .collect(),
+    );
+    gauge(
+        "mksap_valid_questions",
+        "Questions passing validation per system.",
+        systems
+            .iter()
+            .map(|s| (Some(s.system_id.as_str()), s.valid_count as f64))
+            .collect(),
+    );
+    gauge(
+        "mksap_invalid_questions",
+        "Questions failing validation.",
+        vec![(None, validation.invalid_questions.len() as f64)],
+    );
+    gauge(
+        "mksap_placeholder_critiques",
+        "Questions with empty or placeholder critiques.",
+        vec![(None, validation.placeholder_critiques.len() as f64)],
+    );
+    gauge(
+        "mksap_metrics_generated_timestamp_seconds",
+        "Unix time these metrics were generated.",
+        vec![(None, chrono::Utc::now().timestamp() as f64)],
+    );
+
+    out
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 pub fn count_discovered_ids(output_dir: &str, category_code: &str) -> usize {
-    let checkpoint_path = format!("{}/.checkpoints/{}_ids.txt", output_dir, category_code);
-    match read_checkpoint_lines(Path::new(&checkpoint_path)) {
+    let checkpoint_path = checkpoint_dir(output_dir).join(format!("{}_ids.txt", category_code));
+    match read_checkpoint_lines(&checkpoint_path) {
         Ok(ids) => ids.len(),
         Err(_) => 0,
     }
@@ -96,3 +381,92 @@ pub fn total_discovered_ids(output_dir: &str, categories: &[Category]) -> usize
         .map(|category| count_discovered_ids(output_dir, &category.code))
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DiscoveryMetadata, DiscoveryMetadataCollection};
+
+    fn write_discovery_metadata(output_dir: &Path) {
+        let mut collection = DiscoveryMetadataCollection::default();
+        collection.systems.push(DiscoveryMetadata {
+            system_code: "cv".to_string(),
+            discovered_count: 3,
+            discovery_timestamp: "2025-01-01T00:00:00Z".to_string(),
+            candidates_tested: 100,
+            hit_rate: 0.03,
+            question_types_found: vec!["mcq".to_string()],
+        });
+        let checkpoint_dir = output_dir.join(".checkpoints");
+        fs::create_dir_all(&checkpoint_dir).unwrap();
+        fs::write(
+            checkpoint_dir.join("discovery_metadata.json"),
+            serde_json::to_string(&collection).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_progress_history_appends_one_line_per_invocation() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        write_discovery_metadata(temp.path());
+        fs::create_dir_all(temp.path().join("cv").join("cvmcq24001")).unwrap();
+
+        append_progress_snapshot(output_dir).unwrap();
+        fs::create_dir_all(temp.path().join("cv").join("cvmcq24002")).unwrap();
+        append_progress_snapshot(output_dir).unwrap();
+
+        let history = fs::read_to_string(temp.path().join(PROGRESS_HISTORY_FILE)).unwrap();
+        let snapshots: Vec<ProgressSnapshot> = history
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].systems["cv"].found, 1);
+        assert_eq!(snapshots[1].systems["cv"].found, 2);
+        assert!(snapshots
+            .iter()
+            .all(|snapshot| snapshot.total_discovered == 3));
+        assert!(snapshots[0].timestamp <= snapshots[1].timestamp);
+    }
+
+    #[test]
+    fn test_metrics_textfile_is_well_formed_exposition() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        write_discovery_metadata(temp.path());
+        fs::create_dir_all(temp.path().join("cv").join("cvmcq24001")).unwrap();
+        let metrics_path = temp.path().join("metrics").join("mksap.prom");
+
+        write_metrics_textfile(output_dir, &metrics_path).unwrap();
+        let metrics = fs::read_to_string(&metrics_path).unwrap();
+
+        let sample_re = regex::Regex::new(
+            r#"^[a-zA-Z_:][a-zA-Z0-9_:]*(\{system="[^"\\]*"\})? -?[0-9]+(\.[0-9]+)?(e-?[0-9]+)?$"#,
+        )
+        .unwrap();
+        let mut typed = std::collections::HashSet::new();
+        for line in metrics.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                assert_eq!(kind, "gauge");
+                typed.insert(name.to_string());
+            } else if !line.starts_with("# HELP ") {
+                assert!(sample_re.is_match(line), "malformed sample: {}", line);
+                let name = line.split(['{', ' ']).next().unwrap();
+                assert!(typed.contains(name), "sample before TYPE: {}", line);
+            }
+        }
+
+        assert!(metrics.contains("mksap_discovered_total{system=\"cv\"} 3\n"));
+        assert!(metrics.contains("mksap_candidates_tested_total{system=\"cv\"} 100\n"));
+        assert!(metrics.contains("mksap_discovery_hit_ratio{system=\"cv\"} 0.03\n"));
+        assert!(metrics.contains("mksap_extracted_questions{system=\"cv\"} 1\n"));
+    }
+
+    #[test]
+    fn test_metric_label_values_are_escaped() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
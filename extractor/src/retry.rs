@@ -5,6 +5,7 @@ use std::fs;
 use std::path::Path;
 use tracing::{error, info, warn};
 
+use super::workflow::QuestionOutcome;
 use super::{MKSAPExtractor, CHECKPOINT_DIR_NAME};
 use crate::io::{checkpoint_system_id, read_checkpoint_lines, scan_question_directories};
 
@@ -28,7 +29,7 @@ impl MKSAPExtractor {
             return Ok(0);
         }
 
-        let concurrency = Self::concurrency_limit();
+        let concurrency = self.concurrency;
         info!(
             "Retrying {} missing/failed entries (concurrency: {})...",
             targets.len(),
@@ -39,7 +40,7 @@ impl MKSAPExtractor {
         let mut processed = 0usize;
         let mut recovered = 0usize;
 
-        let mut stream = stream::iter(targets.into_iter())
+        let mut stream = stream::iter(targets)
             .map(|(category_code, question_id)| async move {
                 (
                     question_id.clone(),
@@ -59,8 +60,13 @@ impl MKSAPExtractor {
             }
 
             match result {
-                Ok(true) => recovered += 1,
-                Ok(false) => warn!("Missing question {} still returned 404", question_id),
+                Ok(QuestionOutcome::Extracted | QuestionOutcome::Unchanged) => recovered += 1,
+                Ok(QuestionOutcome::Retired) => {
+                    info!("{} is invalidated; filed under retired/", question_id)
+                }
+                Ok(QuestionOutcome::NotFound) => {
+                    warn!("Missing question {} still returned 404", question_id)
+                }
                 Err(e) => error!("Error re-extracting {}: {}", question_id, e),
             }
         }
@@ -1,21 +1,108 @@
 //! Command execution orchestration for extraction and media workflows.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use futures::future;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
 use std::fs;
-use std::path::Path;
-use tracing::{debug, error, info};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
 
-use crate::assets::{asset_discovery, asset_download, svg_download};
+use crate::assets::{
+    asset_discovery, asset_download, calibrate, media_dimensions, svg_download, svg_rasterize,
+};
 use crate::cli::MediaOptions;
+use crate::error::{find_extract_error, ExtractError};
+use crate::extractor::checkpoint_dir;
+use crate::extractor::CategoryExtraction;
+use crate::progress::Progress;
 use crate::reporting::{count_discovered_ids, total_discovered_ids};
 use crate::utils::log_progress;
 use crate::{Category, MKSAPExtractor};
 
+/// Outcome of extracting one category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CategoryResult {
+    pub code: String,
+    /// Questions written during this run.
+    pub new: usize,
+    /// Discovered questions that were already on disk.
+    pub already: usize,
+    /// Invalidated questions filed under `retired/` rather than extracted.
+    pub retired: usize,
+    /// Questions on disk that `--since` found unchanged; also counted in `already`.
+    pub unchanged: usize,
+    /// Set when the category's extraction failed.
+    pub error: Option<String>,
+}
+
+/// Structured result of [`run_extraction_with_summary`], in category completion order.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExtractionSummary {
+    pub per_category: Vec<CategoryResult>,
+    pub total_new: usize,
+    pub total_existing: usize,
+    pub total_retired: usize,
+    pub total_unchanged: usize,
+    /// Categories whose extraction failed.
+    pub total_failed: usize,
+    pub elapsed: Duration,
+    /// Set when Ctrl-C stopped the run early; categories never started are absent.
+    pub interrupted: bool,
+    /// Set when any category hit a missing or expired session.
+    pub auth_expired: bool,
+}
+
+impl ExtractionSummary {
+    /// Add one category's outcome; `discovered` is its discovered ID count (0 if unknown).
+    fn record(&mut self, code: &str, outcome: Result<CategoryExtraction>, discovered: usize) {
+        self.auth_expired |= match &outcome {
+            Ok(counts) => counts.auth_failures > 0,
+            Err(err) => matches!(find_extract_error(err), Some(ExtractError::Auth(_))),
+        };
+        let result = match outcome {
+            Ok(counts) => {
+                let fetched = counts.extracted + counts.retired;
+                let discovered = if discovered == 0 { fetched } else { discovered };
+                CategoryResult {
+                    code: code.to_string(),
+                    new: counts.extracted,
+                    already: discovered.saturating_sub(fetched),
+                    retired: counts.retired,
+                    unchanged: counts.unchanged,
+                    error: None,
+                }
+            }
+            Err(err) => CategoryResult {
+                code: code.to_string(),
+                new: 0,
+                already: 0,
+                retired: 0,
+                unchanged: 0,
+                error: Some(format!("{:#}", err)),
+            },
+        };
+        self.total_new += result.new;
+        self.total_existing += result.already;
+        self.total_retired += result.retired;
+        self.total_unchanged += result.unchanged;
+        if result.error.is_some() {
+            self.total_failed += 1;
+        }
+        self.per_category.push(result);
+    }
+}
+
+/// Extract every category and log the outcome. See [`run_extraction_with_summary`].
+///
+/// With a profiling extractor, the `profile_top` slowest questions are listed at the end.
 pub async fn run_extraction(
     extractor: &MKSAPExtractor,
     categories: &[Category],
     output_dir: &str,
     refresh_existing: bool,
+    profile_top: usize,
 ) -> Result<()> {
     debug!("\n=== PHASE 2: FULL CATEGORY EXTRACTION ===");
     info!(
@@ -25,88 +112,181 @@ pub async fn run_extraction(
     if refresh_existing {
         info!("Refresh mode enabled: re-downloading existing question JSON.");
     }
+    info!("Concurrency: {} requests", extractor.concurrency());
+    let run_started = chrono::Utc::now();
 
-    let mut total_extracted = 0;
-    let start_time = std::time::Instant::now();
-
-    for (idx, category) in categories.iter().enumerate() {
-        log_progress(
-            idx + 1,
-            categories.len(),
-            &format!("Processing: {}", category.name),
-        );
+    let summary =
+        run_extraction_with_summary(extractor, categories, output_dir, refresh_existing).await?;
 
-        match extractor.extract_category(category, refresh_existing).await {
-            Ok(count) => {
-                total_extracted += count;
-
-                let total_discovered = count_discovered_ids(output_dir, &category.code);
-                let total_discovered = if total_discovered == 0 {
-                    count
-                } else {
-                    total_discovered
-                };
-                let already_extracted = total_discovered.saturating_sub(count);
-
-                info!(
-                    "✓ {}: Extracted {} new, {} already extracted",
-                    category.code, count, already_extracted
-                );
-            }
-            Err(e) => {
-                error!("✗ Extraction failed: {}", e);
-            }
+    for (idx, result) in summary.per_category.iter().enumerate() {
+        let name = categories
+            .iter()
+            .find(|category| category.code == result.code)
+            .map(|category| category.name.as_str())
+            .unwrap_or(&result.code);
+        log_progress(idx + 1, categories.len(), &format!("Finished: {}", name));
+        match &result.error {
+            None => info!(
+                "✓ {}: Extracted {} new, {} already extracted, {} invalidated skipped",
+                result.code, result.new, result.already, result.retired
+            ),
+            Some(err) => error!("✗ {}: Extraction failed: {}", result.code, err),
         }
     }
 
-    let total_questions = total_discovered_ids(output_dir, categories);
+    // Nothing after this point can succeed without a session, and the run must not be
+    // recorded for `--since last`
+    if summary.auth_expired {
+        return Err(ExtractError::Auth("session expired during extraction".to_string()).into());
+    }
 
-    let elapsed = start_time.elapsed();
+    if summary.interrupted {
+        let started: Vec<&str> = summary
+            .per_category
+            .iter()
+            .map(|result| result.code.as_str())
+            .collect();
+        let not_started = categories.len().saturating_sub(started.len());
+        warn!("\n=== EXTRACTION INTERRUPTED ===");
+        warn!(
+            "Saved {} new questions across {} systems ({}); {} systems not started",
+            summary.total_new,
+            started.len(),
+            started.join(", "),
+            not_started
+        );
+        warn!(
+            "All in-flight questions were written and discovery checkpoints in {} are up to date; \
+             re-run to resume",
+            checkpoint_dir(output_dir).display()
+        );
+        return Ok(());
+    }
+
+    let total_questions = total_discovered_ids(output_dir, categories);
     info!("\n=== EXTRACTION COMPLETE ===");
     info!("Total questions available: {}", total_questions);
-    info!("  New extracted: {}", total_extracted);
+    info!("  New extracted: {}", summary.total_new);
     info!(
         "  Already extracted: {}",
-        total_questions.saturating_sub(total_extracted)
+        total_questions.saturating_sub(summary.total_new + summary.total_retired)
+    );
+    if summary.total_unchanged > 0 {
+        info!(
+            "  Unchanged since --since: {} (already extracted)",
+            summary.total_unchanged
+        );
+    }
+    if summary.total_retired > 0 {
+        info!(
+            "  Invalidated skipped: {} (saved to mksap_data_failed/retired/)",
+            summary.total_retired
+        );
+    }
+    if summary.total_failed > 0 {
+        info!("  Failed categories: {}", summary.total_failed);
+    }
+    info!(
+        "Time elapsed: {:.2} minutes",
+        summary.elapsed.as_secs_f64() / 60.0
     );
-    info!("Time elapsed: {:.2} minutes", elapsed.as_secs_f64() / 60.0);
     info!("Output directory: {}", output_dir);
+    if let Some(limit) = extractor.limit() {
+        warn!(
+            "Sample run: at most {} questions per system were extracted (--limit)",
+            limit
+        );
+    }
+    if let Some(profile) = extractor.profile() {
+        profile.log_slowest(profile_top);
+    }
+    // `--since last` picks up from here, so only record full runs that reached every system
+    if summary.total_failed == 0 && extractor.limit().is_none() {
+        crate::since::record_last_run(output_dir, run_started)?;
+    }
 
     Ok(())
 }
 
+/// Extract every category and return per-category outcomes instead of logging them.
+///
+/// Category failures are reported in the summary, not as an `Err`.
+pub async fn run_extraction_with_summary(
+    extractor: &MKSAPExtractor,
+    categories: &[Category],
+    output_dir: &str,
+    refresh_existing: bool,
+) -> Result<ExtractionSummary> {
+    let start_time = std::time::Instant::now();
+    crate::shutdown::enable_graceful_shutdown();
+
+    // Categories run side by side; the extractor's request semaphore bounds total API load
+    let category_workers = extractor.concurrency().clamp(1, categories.len().max(1));
+    // Each category adds its pending questions to the total once discovery finishes
+    let progress = Progress::new(0, "questions processed", 10);
+    let progress = &progress;
+    let mut stream = stream::iter(categories)
+        .take_while(|_| future::ready(!crate::shutdown::shutdown_requested()))
+        .map(|category| async move {
+            let result = extractor
+                .extract_category_with_progress(category, refresh_existing, progress)
+                .await;
+            (category, result)
+        })
+        .buffer_unordered(category_workers);
+
+    let mut summary = ExtractionSummary::default();
+    while let Some((category, result)) = stream.next().await {
+        let discovered = count_discovered_ids(output_dir, &category.code);
+        summary.record(&category.code, result, discovered);
+    }
+
+    progress.finish();
+    summary.elapsed = start_time.elapsed();
+    summary.interrupted = crate::shutdown::shutdown_requested();
+    Ok(summary)
+}
+
 pub async fn run_media_discovery(options: &MediaOptions) -> Result<()> {
+    if options.calibrate {
+        return run_discovery_calibration(options).await;
+    }
+
     info!("Starting media discovery via API");
     info!("Base URL: {}", options.base_url);
     info!("Concurrent requests: {}", options.concurrent_requests);
-    info!("Output file: {}", options.discovery_file);
-
-    let client = crate::assets::build_client()?;
-    let results = asset_discovery::discover_media_questions(
-        &client,
-        &options.base_url,
-        options.concurrent_requests,
-    )
-    .await?;
+    info!("Checkpoint directory: {}", options.checkpoint_dir);
 
-    let output_path = Path::new(&options.discovery_file);
+    let discovery_options = asset_discovery::DiscoveryOptions::from_media_options(options)?;
+    let output_path = discovery_options.output_file.as_path();
+    info!("Output file: {}", output_path.display());
     if let Some(parent) = output_path.parent() {
         if !parent.as_os_str().is_empty() {
             fs::create_dir_all(parent)?;
         }
     }
+    let partial_path = discovery_options.partial_path.as_path();
+
+    let client = crate::assets::build_client()?;
+    let results = asset_discovery::discover_media_questions(&client, &discovery_options).await?;
 
     results.save_to_file(output_path)?;
-    info!("Saved discovery results to {}", options.discovery_file);
+    info!("Saved discovery results to {}", output_path.display());
+    if options.limit.is_some() {
+        info!(
+            "Sample results only; {} is unchanged and still drives media-download",
+            options.discovery_file
+        );
+    }
+    if partial_path.exists() {
+        fs::remove_file(partial_path)?;
+    }
 
-    let report = results.generate_report();
-    let report_path = output_path.with_extension("txt");
-    fs::write(&report_path, &report)?;
-    info!("Saved text report to {}", report_path.display());
+    let (report_path, report) = write_discovery_report(&results, output_path)?;
 
     if !results.metadata.statistics.video_question_ids.is_empty() {
         info!(
-            "Video files are not downloaded automatically. Use the VIDEO QUESTION IDS in {} for manual downloads.",
+            "Video files are only downloaded with media-download --download-videos; otherwise use the VIDEO QUESTION IDS in {} for manual downloads.",
             report_path.display()
         );
     }
@@ -115,35 +295,141 @@ pub async fn run_media_discovery(options: &MediaOptions) -> Result<()> {
     Ok(())
 }
 
+/// Probe discovery concurrency levels against the API and print the recommended one.
+async fn run_discovery_calibration(options: &MediaOptions) -> Result<()> {
+    info!(
+        "Calibrating discovery concurrency against {}",
+        options.base_url
+    );
+    if options.rps > 0.0 {
+        warn!(
+            "--rps {} caps every concurrency level at that rate, so the throughput curve will flatten; drop --rps to measure the server",
+            options.rps
+        );
+    }
+    let mut question_ids = asset_discovery::load_all_question_ids_from_checkpoints(
+        Path::new(&options.checkpoint_dir),
+        Path::new(&options.data_dir),
+    )?;
+    if let Some(filter) = options.question_type_filter()? {
+        question_ids.retain(|question_id| filter.matches(question_id));
+    }
+
+    let client = crate::assets::build_client()?;
+    let samples = calibrate::calibrate_concurrency(
+        &client,
+        &options.base_url,
+        &question_ids,
+        &calibrate::CALIBRATION_LEVELS,
+    )
+    .await?;
+
+    match calibrate::recommend_concurrency(&samples, calibrate::MAX_ERROR_RATE) {
+        Some(concurrency) => println!(
+            "\nRecommended: --concurrent-requests {} (error rate stayed under {:.0}%)",
+            concurrency,
+            calibrate::MAX_ERROR_RATE * 100.0
+        ),
+        None => println!(
+            "\nEven a single request at a time failed more than {:.0}% of requests; \
+             check authentication and connectivity before running discovery",
+            calibrate::MAX_ERROR_RATE * 100.0
+        ),
+    }
+    Ok(())
+}
+
+/// Rebuild the text report next to an existing discovery JSON without re-running discovery.
+pub async fn run_discovery_report(input: &str) -> Result<()> {
+    let input_path = Path::new(input);
+    let results = asset_discovery::DiscoveryResults::load_from_file(input_path)
+        .with_context(|| format!("Failed to load discovery results from {}", input))?;
+    let (_, report) = write_discovery_report(&results, input_path)?;
+    println!("\n{}", report);
+    Ok(())
+}
+
+/// Write the discovery text report beside `discovery_path` (`.json` -> `.txt`).
+fn write_discovery_report(
+    results: &asset_discovery::DiscoveryResults,
+    discovery_path: &Path,
+) -> Result<(PathBuf, String)> {
+    let report = results.generate_report();
+    let report_path = discovery_path.with_extension("txt");
+    fs::write(&report_path, &report)?;
+    info!("Saved text report to {}", report_path.display());
+    Ok((report_path, report))
+}
+
+pub async fn run_media_dimensions(options: &MediaOptions) -> Result<()> {
+    info!("Scanning media metadata dimensions in {}", options.data_dir);
+
+    let report = media_dimensions::collect_media_dimensions(&options.data_dir)?;
+    let text = report.generate_report();
+    let report_path = Path::new(&options.data_dir).join("media_dimensions.txt");
+    fs::write(&report_path, &text)?;
+    info!("Saved dimensions report to {}", report_path.display());
+
+    println!("\n{}", text);
+    Ok(())
+}
+
 pub async fn run_media_download(options: &MediaOptions) -> Result<()> {
     if !options.all && options.question_id.is_none() {
         info!("No question filter provided; downloading for all discovered questions.");
     }
 
-    let client = crate::assets::build_client()?;
-    asset_download::run_media_download(
+    let download_options = asset_download::MediaDownloadOptions::from_media_options(options)?;
+    let client = crate::assets::build_download_client(options.max_redirects)?;
+    if options.dry_run {
+        let mut plan = asset_download::plan_media_download(&client, &download_options).await?;
+        plan.estimate_sizes(&client).await;
+        println!("\n{}", plan.render());
+        return Ok(());
+    }
+    asset_download::run_media_download(&client, &download_options).await?;
+
+    info!("Media download completed.");
+    Ok(())
+}
+
+pub async fn run_retry_media_failures(options: &MediaOptions) -> Result<()> {
+    let client = crate::assets::build_download_client(options.max_redirects)?;
+    let (recovered, remaining) = asset_download::retry_media_failures(
         &client,
         &options.base_url,
         &options.data_dir,
-        &options.discovery_file,
-        options.question_id.as_deref(),
-        !options.skip_figures,
-        !options.skip_tables,
+        asset_download::MediaKinds::from_media_options(options),
     )
     .await?;
-
-    info!("Media download completed.");
+    info!(
+        "Media failure retry complete ({} recovered, {} still failing)",
+        recovered, remaining
+    );
     Ok(())
 }
 
 pub async fn run_svg_browser(options: &MediaOptions) -> Result<()> {
-    info!("Video files require manual download; browser step handles SVGs only.");
+    info!("Browser step handles SVGs only; use media-download --download-videos for videos.");
 
     if !options.all && options.question_id.is_none() {
         info!("No question filter provided; downloading for all SVG questions.");
     }
 
-    let client = crate::assets::build_client()?;
+    let client = crate::assets::build_download_client(options.max_redirects)?;
+    if options.dry_run {
+        if options.skip_svgs {
+            info!("SVGs skipped; nothing to plan.");
+            return Ok(());
+        }
+        let plan = svg_download::plan_svg_download(
+            &options.data_dir,
+            &options.discovery_file,
+            options.question_id.as_deref(),
+        )?;
+        println!("\n{}", plan.render());
+        return Ok(());
+    }
     svg_download::run_svg_download(
         &client,
         &options.base_url,
@@ -157,9 +443,117 @@ pub async fn run_svg_browser(options: &MediaOptions) -> Result<()> {
         options.username.clone(),
         options.password.clone(),
         options.login_timeout_secs,
+        options.webdriver_connect_attempts,
+        options.browser_concurrency,
     )
     .await?;
 
     info!("SVG browser download completed.");
+    if options.rasterize_svgs {
+        run_svg_rasterize(options).await?;
+    }
+    Ok(())
+}
+
+pub async fn run_svg_rasterize(options: &MediaOptions) -> Result<()> {
+    info!("Rasterizing SVGs to PNG at {} DPI", options.svg_dpi);
+    let workers = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(4);
+    svg_rasterize::rasterize_svgs(&options.data_dir, options.svg_dpi, workers).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::asset_discovery::{DiscoveryResults, DiscoveryStatistics};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_extraction_summary_tallies_categories() {
+        let mut summary = ExtractionSummary::default();
+        let counts = |extracted, retired| CategoryExtraction {
+            extracted,
+            retired,
+            ..Default::default()
+        };
+        summary.record("cv", Ok(counts(3, 0)), 10);
+        summary.record("en", Ok(counts(2, 0)), 0);
+        summary.record("gi", Err(anyhow::anyhow!("discovery failed")), 7);
+        summary.record("hm", Ok(counts(1, 2)), 5);
+
+        assert_eq!(summary.total_new, 6);
+        assert_eq!(summary.total_existing, 9);
+        assert_eq!(summary.total_retired, 2);
+        assert_eq!(summary.total_failed, 1);
+        assert!(!summary.auth_expired);
+        assert_eq!(
+            summary.per_category[0],
+            CategoryResult {
+                code: "cv".to_string(),
+                new: 3,
+                already: 7,
+                retired: 0,
+                unchanged: 0,
+                error: None
+            }
+        );
+        assert_eq!(summary.per_category[3].already, 2);
+        assert_eq!(summary.per_category[1].already, 0);
+        assert_eq!(
+            summary.per_category[2].error.as_deref(),
+            Some("discovery failed")
+        );
+
+        // Questions rejected for an expired session flag the run, as does a failed category
+        summary.record(
+            "id",
+            Ok(CategoryExtraction {
+                auth_failures: 1,
+                ..counts(0, 0)
+            }),
+            3,
+        );
+        assert!(summary.auth_expired);
+        let mut summary = ExtractionSummary::default();
+        summary.record(
+            "cv",
+            Err(ExtractError::Auth("session expired".to_string()).into()),
+            3,
+        );
+        assert!(summary.auth_expired);
+    }
+
+    #[tokio::test]
+    async fn test_discovery_report_regenerated_from_saved_json() {
+        let temp = tempfile::tempdir().unwrap();
+        let discovery_path = temp.path().join("media_discovery.json");
+        let statistics = DiscoveryStatistics {
+            total_questions_scanned: 10,
+            total_questions_with_media: 4,
+            total_questions_without_media: 6,
+            percentage_with_media: 40.0,
+            questions_with_figures: 3,
+            total_figure_references: 5,
+            video_question_ids: vec!["cvvdx24001".to_string()],
+            ..Default::default()
+        };
+        let results = DiscoveryResults::new(
+            HashMap::new(),
+            statistics,
+            "https://mksap.acponline.org".to_string(),
+            10,
+        );
+        results.save_to_file(&discovery_path).unwrap();
+
+        run_discovery_report(discovery_path.to_str().unwrap())
+            .await
+            .unwrap();
+
+        let report = fs::read_to_string(temp.path().join("media_discovery.txt")).unwrap();
+        assert_eq!(report, results.generate_report());
+        assert!(report.contains(&results.metadata.timestamp));
+        assert!(report.contains("cvvdx24001"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Offline corpus search (`search <query>`): case-insensitive substring (or `--regex`) match
+//! over one field of every extracted question, printing each hit with a short snippet.
+
+use anyhow::{bail, Context, Result};
+use regex::{Regex, RegexBuilder};
+use std::fs;
+use tracing::warn;
+
+use crate::assets::asset_store::collect_question_entries;
+use crate::models::QuestionData;
+
+/// Characters of context kept on each side of a match.
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    Stem,
+    Critique,
+    Options,
+    KeyPoints,
+    References,
+    All,
+}
+
+impl SearchField {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().replace('-', "_").as_str() {
+            "stem" => Ok(SearchField::Stem),
+            "critique" => Ok(SearchField::Critique),
+            "options" => Ok(SearchField::Options),
+            "key_points" => Ok(SearchField::KeyPoints),
+            "references" => Ok(SearchField::References),
+            "all" => Ok(SearchField::All),
+            _ => bail!(
+                "Unknown search field '{}': expected stem, critique, options, key_points, references or all",
+                value
+            ),
+        }
+    }
+
+    /// `(field name, text)` pairs searched for this field, in display order.
+    fn texts(self, question: &QuestionData) -> Vec<(&'static str, String)> {
+        let fields = match self {
+            SearchField::All => vec![
+                SearchField::Stem,
+                SearchField::Options,
+                SearchField::Critique,
+                SearchField::KeyPoints,
+                SearchField::References,
+            ],
+            field => vec![field],
+        };
+        fields
+            .into_iter()
+            .flat_map(|field| match field {
+                SearchField::Stem => vec![
+                    ("stem", question.question_stem.clone()),
+                    ("stem", question.question_text.clone()),
+                ],
+                SearchField::Critique => vec![("critique", question.critique.clone())],
+                SearchField::Options => question
+                    .options
+                    .iter()
+                    .map(|option| ("options", format!("{}. {}", option.letter, option.text)))
+                    .collect(),
+                SearchField::KeyPoints => question
+                    .key_points
+                    .iter()
+                    .map(|point| ("key_points", point.clone()))
+                    .collect(),
+                SearchField::References => vec![("references", question.references.clone())],
+                SearchField::All => Vec::new(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub question_id: String,
+    pub system: String,
+    /// Field of the first match.
+    pub field: &'static str,
+    pub snippet: String,
+}
+
+/// Case-insensitive matcher for `query`; a plain substring unless `regex` is set.
+pub fn build_search_pattern(query: &str, regex: bool) -> Result<Regex> {
+    if query.trim().is_empty() {
+        bail!("Search query is empty");
+    }
+    let pattern = if regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .with_context(|| format!("Invalid search pattern '{}'", query))
+}
+
+/// Every question under `data_dir` (or only `system`) whose `field` matches, sorted by ID.
+pub fn search_questions(
+    data_dir: &str,
+    pattern: &Regex,
+    field: SearchField,
+    system: Option<&str>,
+) -> Result<Vec<SearchHit>> {
+    let mut hits = Vec::new();
+    for entry in collect_question_entries(data_dir)? {
+        let entry_system = entry
+            .question_dir
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if system.is_some_and(|system| system != entry_system) {
+            continue;
+        }
+        let question = fs::read_to_string(&entry.json_path)
+            .context("read failed")
+            .and_then(|text| {
+                serde_json::from_str::<QuestionData>(&text).context("not valid question JSON")
+            });
+        let question = match question {
+            Ok(question) => question,
+            Err(err) => {
+                warn!("Skipping {}: {:#}", entry.json_path.display(), err);
+                continue;
+            }
+        };
+        let found = field.texts(&question).into_iter().find_map(|(name, text)| {
+            pattern
+                .find(&text)
+                .map(|found| (name, snippet(&text, found.start(), found.end())))
+        });
+        if let Some((field, snippet)) = found {
+            hits.push(SearchHit {
+                question_id: question.question_id,
+                system: entry_system,
+                field,
+                snippet,
+            });
+        }
+    }
+    hits.sort_by(|a, b| a.question_id.cmp(&b.question_id));
+    Ok(hits)
+}
+
+pub fn run_search(
+    data_dir: &str,
+    query: &str,
+    regex: bool,
+    field: SearchField,
+    system: Option<&str>,
+) -> Result<()> {
+    if let Some(system) = system {
+        if crate::config::get_organ_system_by_id(system).is_none() {
+            bail!("Unknown system code: {}", system);
+        }
+    }
+    let pattern = build_search_pattern(query, regex)?;
+    let hits = search_questions(data_dir, &pattern, field, system)?;
+    for hit in &hits {
+        println!("{}  [{}]  {}", hit.question_id, hit.field, hit.snippet);
+    }
+    println!("\n{} matching questions", hits.len());
+    Ok(())
+}
+
+/// The match at `start..end` with surrounding context on one line.
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let before: String = {
+        let mut chars: Vec<char> = text[..start]
+            .chars()
+            .rev()
+            .take(SNIPPET_CONTEXT_CHARS)
+            .collect();
+        chars.reverse();
+        chars.into_iter().collect()
+    };
+    let after: String = text[end..].chars().take(SNIPPET_CONTEXT_CHARS).collect();
+    let mut snippet = String::new();
+    if before.len() < start {
+        snippet.push('…');
+    }
+    snippet.push_str(&before);
+    snippet.push_str(&text[start..end]);
+    snippet.push_str(&after);
+    if end + after.len() < text.len() {
+        snippet.push('…');
+    }
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::write_question_with;
+
+    #[test]
+    fn test_search_matches_field_case_insensitively_and_scopes_by_system() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        write_question_with(
+            root,
+            "cv",
+            "cvmcq24001",
+            serde_json::json!({
+                "question_stem": "A 64-year-old man takes Metoprolol daily.",
+                "key_points": ["Beta-blockers reduce mortality."]
+            }),
+        );
+        write_question_with(
+            root,
+            "en",
+            "enmcq24001",
+            serde_json::json!({
+                "question_stem": "A woman with metoprolol-induced fatigue.",
+                "key_points": ["Check TSH."]
+            }),
+        );
+        let data_dir = root.to_str().unwrap();
+
+        let pattern = build_search_pattern("METOPROLOL", false).unwrap();
+        let hits = search_questions(data_dir, &pattern, SearchField::Stem, None).unwrap();
+        let ids: Vec<_> = hits.iter().map(|hit| hit.question_id.as_str()).collect();
+        assert_eq!(ids, vec!["cvmcq24001", "enmcq24001"]);
+        assert_eq!(hits[0].field, "stem");
+        assert_eq!(hits[0].snippet, "A 64-year-old man takes Metoprolol daily.");
+
+        let hits = search_questions(data_dir, &pattern, SearchField::Stem, Some("en")).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].system, "en");
+
+        let pattern = build_search_pattern(r"beta-?blockers?", true).unwrap();
+        assert!(
+            search_questions(data_dir, &pattern, SearchField::Stem, None)
+                .unwrap()
+                .is_empty()
+        );
+        let hits = search_questions(data_dir, &pattern, SearchField::All, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].field, "key_points");
+    }
+
+    #[test]
+    fn test_snippet_trims_long_text_around_match() {
+        let text = format!("{} aspirin {}", "x ".repeat(50), "y ".repeat(50));
+        let start = text.find("aspirin").unwrap();
+        let snippet = snippet(&text, start, start + "aspirin".len());
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("x aspirin y"));
+        assert!(snippet.chars().count() < 90);
+    }
+}
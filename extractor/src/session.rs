@@ -1,8 +1,106 @@
 use anyhow::{Context, Result};
+use regex::Regex;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+/// Session cookie read from `--cookies-file`, which takes precedence over `MKSAP_SESSION`.
+static COOKIES_FILE_SESSION: OnceLock<String> = OnceLock::new();
+
+/// Session cookie name from [`crate::app::SiteConfig`] (`MKSAP_COOKIE_NAME`).
+pub fn session_cookie_name() -> String {
+    crate::app::site_config().session_cookie_name.clone()
+}
+
+/// Describe a mismatch between the cookie name's edition (`_mksap19_session` -> 19) and the
+/// edition apparent in the base URL (`https://mksap20.acponline.org` -> 20).
+///
+/// Returns `None` when they match or either side has no recognizable edition.
+pub fn edition_mismatch(cookie_name: &str, base_url: &str) -> Option<String> {
+    let cookie_edition = parse_edition(cookie_name)?;
+    let url_edition = parse_edition(base_url)?;
+    if cookie_edition == url_edition {
+        return None;
+    }
+    Some(format!(
+        "Session cookie {} is for MKSAP {} but base URL {} looks like MKSAP {}; \
+         requests may fail authentication (set MKSAP_COOKIE_NAME or MKSAP_BASE_URL)",
+        cookie_name, cookie_edition, base_url, url_edition
+    ))
+}
+
+fn parse_edition(value: &str) -> Option<u32> {
+    let edition_re = Regex::new(r"(?i)mksap[_-]?(\d{2})").unwrap();
+    edition_re
+        .captures(&value.to_ascii_lowercase())
+        .and_then(|caps| caps[1].parse().ok())
+}
+
+/// Read the session cookie from a browser-exported `cookies.txt` for the rest of the run.
+/// Only the first call takes effect.
+pub(crate) fn configure_cookies_file(path: Option<&str>, base_url: &str) -> Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let cookie = load_cookies_from_netscape(Path::new(path), base_url)?;
+    info!("Using session cookie from {}", path);
+    if COOKIES_FILE_SESSION.set(cookie).is_err() {
+        warn!("Cookies file already configured; ignoring {}", path);
+    }
+    Ok(())
+}
+
+/// Extract the session cookie for `base_url`'s host from a Netscape `cookies.txt` export
+/// (the format written by browser cookie-export extensions and `curl -c`).
+pub fn load_cookies_from_netscape(path: &Path, base_url: &str) -> Result<String> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let cookie_name = session_cookie_name();
+    let host = crate::app::SiteConfig::cookie_domain(base_url);
+    parse_netscape_cookie(&text, &cookie_name, host.as_deref()).with_context(|| {
+        format!(
+            "No {} cookie for {} in {}",
+            cookie_name,
+            host.as_deref().unwrap_or(base_url),
+            path.display()
+        )
+    })
+}
+
+/// Value of `cookie_name` from Netscape cookie lines, preferring the most specific domain
+/// matching `host`. `#HttpOnly_` lines are cookies; other `#` lines are comments.
+fn parse_netscape_cookie(text: &str, cookie_name: &str, host: Option<&str>) -> Option<String> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim_end_matches('\r');
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.trim().is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, _, _, _, _, name, value] = fields.as_slice() else {
+                return None;
+            };
+            let domain = domain.trim_start_matches('.');
+            let matches_host = host.is_none_or(|host| {
+                host.eq_ignore_ascii_case(domain)
+                    || host
+                        .to_ascii_lowercase()
+                        .ends_with(&format!(".{}", domain.to_ascii_lowercase()))
+            });
+            (*name == cookie_name && matches_host && !value.trim().is_empty())
+                .then(|| (domain.len(), value.trim().to_string()))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, value)| value)
+}
 
 pub fn load_session_cookie() -> Option<String> {
+    if let Some(cookie) = COOKIES_FILE_SESSION.get() {
+        return Some(cookie.clone());
+    }
+
     if let Ok(session_cookie) = std::env::var("MKSAP_SESSION") {
         let trimmed = session_cookie.trim().to_string();
         if !trimmed.is_empty() {
@@ -35,3 +133,61 @@ fn session_cookie_path() -> Result<PathBuf> {
     let home = std::env::var("HOME").context("HOME not set")?;
     Ok(PathBuf::from(home).join(".mksap_session"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edition_mismatch_matching_or_unknown_editions() {
+        assert_eq!(
+            edition_mismatch("_mksap19_session", "https://mksap19.acponline.org"),
+            None
+        );
+        assert_eq!(
+            edition_mismatch("_mksap20_session", "https://example.org/mksap20/api"),
+            None
+        );
+        // Default URL carries no edition, so nothing to compare against
+        assert_eq!(
+            edition_mismatch("_mksap19_session", "https://mksap.acponline.org"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_netscape_cookie_picks_session_for_host() {
+        let text = "# Netscape HTTP Cookie File\n\
+            # https://curl.se/docs/http-cookies.html\n\
+            \n\
+            .acponline.org\tTRUE\t/\tTRUE\t1893456000\t_mksap19_session\tparent-domain\n\
+            #HttpOnly_mksap.acponline.org\tFALSE\t/\tTRUE\t1893456000\t_mksap19_session\texact-host\r\n\
+            other.example.org\tFALSE\t/\tFALSE\t0\t_mksap19_session\tother-site\n\
+            mksap.acponline.org\tFALSE\t/\tTRUE\t0\t_ga\ttracking\n";
+
+        assert_eq!(
+            parse_netscape_cookie(text, "_mksap19_session", Some("mksap.acponline.org")).as_deref(),
+            Some("exact-host")
+        );
+        assert_eq!(
+            parse_netscape_cookie(text, "_mksap19_session", Some("www.acponline.org")).as_deref(),
+            Some("parent-domain")
+        );
+        assert_eq!(
+            parse_netscape_cookie(text, "_mksap20_session", Some("mksap.acponline.org")),
+            None
+        );
+        assert_eq!(
+            parse_netscape_cookie(text, "_mksap19_session", Some("acponline.org.evil.test")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_edition_mismatch_flags_different_editions() {
+        let warning = edition_mismatch("_mksap20_session", "https://mksap19.acponline.org")
+            .expect("mismatch should be reported");
+        assert!(warning.contains("MKSAP 20"));
+        assert!(warning.contains("MKSAP 19"));
+    }
+}
@@ -0,0 +1,227 @@
+//! Optional `mksap.toml` config file (`--config path`).
+//!
+//! Lets long-lived settings live in one file instead of being repeated as flags
+//! or scattered through `.env`. Without a config file nothing changes.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::env;
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing::info;
+
+use crate::cli::{has_flag, parse_arg_value, parse_bool_arg};
+
+/// Loaded when present in the working directory and no `--config` is given.
+pub const DEFAULT_CONFIG_PATH: &str = "mksap.toml";
+
+static FILE_CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Settings read from `mksap.toml`; every field is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub base_url: Option<String>,
+    /// Data directory for every command (same as `--output-dir`/`--data-dir`).
+    pub output_dir: Option<String>,
+    /// Maximum in-flight API requests for extraction (same as `--concurrency`).
+    pub concurrency: Option<usize>,
+    pub discovery_file: Option<String>,
+    pub webdriver_url: Option<String>,
+    /// Write question and discovery JSON on one line (same as `--compact-json`).
+    pub compact_json: Option<bool>,
+    pub login: LoginConfig,
+}
+
+/// `[login]` table: browser login settings for the SVG step.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoginConfig {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub interactive: Option<bool>,
+    pub headless: Option<bool>,
+    pub timeout_secs: Option<u64>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Merge this file with the command line and environment.
+    ///
+    /// Precedence, highest first:
+    /// 1. CLI flags (`--base-url`, `--output-dir`/`--data-dir`, `--concurrency`, `--discovery-file`,
+    ///    `--webdriver-url`, `--compact-json`, `--username`, `--password`,
+    ///    `--interactive-login`, `--headless`, `--login-timeout-secs`)
+    /// 2. values from this config file
+    /// 3. environment variables, for the settings that have one (`MKSAP_BASE_URL`,
+    ///    `MKSAP_OUTPUT_DIR`, `MKSAP_CONCURRENCY`)
+    /// 4. built-in defaults, which callers apply to fields still `None`
+    pub fn resolve(&self, args: &[String]) -> Config {
+        self.resolve_with(args, |key| env::var(key).ok())
+    }
+
+    fn resolve_with(&self, args: &[String], lookup: impl Fn(&str) -> Option<String>) -> Config {
+        let env_value = |key: &str| {
+            lookup(key)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let cli_bool = |flag: &str| {
+            (has_flag(args, flag) || parse_arg_value(args, flag).is_some())
+                .then(|| parse_bool_arg(args, flag, true))
+        };
+
+        Config {
+            base_url: parse_arg_value(args, "--base-url")
+                .or_else(|| self.base_url.clone())
+                .or_else(|| env_value("MKSAP_BASE_URL"))
+                .map(|url| url.trim_end_matches('/').to_string()),
+            output_dir: parse_arg_value(args, "--output-dir")
+                .or_else(|| parse_arg_value(args, "--data-dir"))
+                .or_else(|| self.output_dir.clone())
+                .or_else(|| env_value("MKSAP_OUTPUT_DIR")),
+            concurrency: parse_arg_value(args, "--concurrency")
+                .and_then(|value| value.parse().ok())
+                .or(self.concurrency)
+                .or_else(|| env_value("MKSAP_CONCURRENCY").and_then(|value| value.parse().ok()))
+                .filter(|value| *value > 0),
+            discovery_file: parse_arg_value(args, "--discovery-file")
+                .or_else(|| self.discovery_file.clone()),
+            webdriver_url: parse_arg_value(args, "--webdriver-url")
+                .or_else(|| self.webdriver_url.clone()),
+            compact_json: cli_bool("--compact-json").or(self.compact_json),
+            login: LoginConfig {
+                username: parse_arg_value(args, "--username")
+                    .or_else(|| self.login.username.clone()),
+                password: parse_arg_value(args, "--password")
+                    .or_else(|| self.login.password.clone()),
+                interactive: cli_bool("--interactive-login").or(self.login.interactive),
+                headless: cli_bool("--headless").or(self.login.headless),
+                timeout_secs: parse_arg_value(args, "--login-timeout-secs")
+                    .and_then(|value| value.parse().ok())
+                    .or(self.login.timeout_secs),
+            },
+        }
+    }
+}
+
+/// Load `--config path`, or `mksap.toml` when it exists; otherwise use an empty config.
+pub(crate) fn configure_config(path: Option<&str>) -> Result<()> {
+    let config = match path {
+        Some(path) => Config::load(Path::new(path))?,
+        None if Path::new(DEFAULT_CONFIG_PATH).is_file() => {
+            Config::load(Path::new(DEFAULT_CONFIG_PATH))?
+        }
+        None => return Ok(()),
+    };
+    info!("Loaded config file {}", path.unwrap_or(DEFAULT_CONFIG_PATH));
+    FILE_CONFIG.set(config).ok();
+    Ok(())
+}
+
+/// The loaded config file, or an empty config when none was loaded.
+pub(crate) fn file_config() -> &'static Config {
+    FILE_CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_config_file() {
+        let config = Config::parse(
+            r#"
+            base_url = "https://mksap20.acponline.org"
+            concurrency = 4
+
+            [login]
+            username = "user@example.com"
+            headless = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.base_url.as_deref(),
+            Some("https://mksap20.acponline.org")
+        );
+        assert_eq!(config.concurrency, Some(4));
+        assert_eq!(config.login.username.as_deref(), Some("user@example.com"));
+        assert_eq!(config.login.headless, Some(false));
+        assert!(Config::parse("unknown_key = 1").is_err());
+    }
+
+    #[test]
+    fn test_resolve_precedence_cli_then_file_then_env() {
+        let config = Config {
+            base_url: Some("https://file.example".to_string()),
+            concurrency: Some(4),
+            login: LoginConfig {
+                headless: Some(false),
+                ..LoginConfig::default()
+            },
+            ..Config::default()
+        };
+        let env = |key: &str| match key {
+            "MKSAP_BASE_URL" => Some("https://env.example".to_string()),
+            "MKSAP_CONCURRENCY" => Some("8".to_string()),
+            _ => None,
+        };
+
+        let resolved = config.resolve_with(&args(&["run"]), env);
+        assert_eq!(resolved.base_url.as_deref(), Some("https://file.example"));
+        assert_eq!(resolved.concurrency, Some(4));
+        assert_eq!(resolved.login.headless, Some(false));
+
+        let resolved = config.resolve_with(
+            &args(&["run", "--base-url=https://cli.example/", "--headless"]),
+            env,
+        );
+        assert_eq!(resolved.base_url.as_deref(), Some("https://cli.example"));
+        assert_eq!(resolved.login.headless, Some(true));
+
+        let resolved = Config::default().resolve_with(&args(&["run"]), env);
+        assert_eq!(resolved.base_url.as_deref(), Some("https://env.example"));
+        assert_eq!(resolved.concurrency, Some(8));
+        assert_eq!(resolved.login.headless, None);
+    }
+
+    #[test]
+    fn test_output_dir_precedence() {
+        let env = |key: &str| (key == "MKSAP_OUTPUT_DIR").then(|| "/env/data".to_string());
+        let config = Config {
+            output_dir: Some("/file/data".to_string()),
+            ..Config::default()
+        };
+
+        let resolved = Config::default().resolve_with(&args(&["validate"]), env);
+        assert_eq!(resolved.output_dir.as_deref(), Some("/env/data"));
+        let resolved = config.resolve_with(&args(&["validate"]), env);
+        assert_eq!(resolved.output_dir.as_deref(), Some("/file/data"));
+        let resolved = config.resolve_with(&args(&["validate", "--data-dir", "/cli/data"]), env);
+        assert_eq!(resolved.output_dir.as_deref(), Some("/cli/data"));
+        let resolved = config.resolve_with(
+            &args(&[
+                "validate",
+                "--data-dir",
+                "/cli/data",
+                "--output-dir=/cli/out",
+            ]),
+            env,
+        );
+        assert_eq!(resolved.output_dir.as_deref(), Some("/cli/out"));
+    }
+}
@@ -0,0 +1,58 @@
+//! Ctrl-C handling.
+//!
+//! While an extraction is running, the first Ctrl-C only requests a stop: no new
+//! questions or categories are started, in-flight writes finish, and the run ends
+//! with a summary. A second Ctrl-C (or any Ctrl-C outside extraction) exits at once.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+static GRACEFUL: AtomicBool = AtomicBool::new(false);
+static REMOVE_ON_EXIT: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Install the Ctrl-C listener; call once from inside the runtime.
+pub(crate) fn install_ctrl_c_handler() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if GRACEFUL.load(Ordering::SeqCst) && !SHUTDOWN_REQUESTED.swap(true, Ordering::SeqCst) {
+                warn!(
+                    "Interrupt received: finishing in-flight questions before stopping \
+                     (press Ctrl-C again to quit immediately)"
+                );
+                continue;
+            }
+            force_exit();
+        }
+    });
+}
+
+/// Let the next Ctrl-C request a clean stop instead of exiting.
+pub(crate) fn enable_graceful_shutdown() {
+    GRACEFUL.store(true, Ordering::SeqCst);
+}
+
+/// True once Ctrl-C asked the current extraction to stop.
+pub(crate) fn shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Delete `path` (e.g. the run lock) if the process is force-quit.
+pub(crate) fn remove_on_forced_exit(path: PathBuf) {
+    REMOVE_ON_EXIT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(path);
+}
+
+fn force_exit() -> ! {
+    let paths = REMOVE_ON_EXIT.lock().unwrap_or_else(|e| e.into_inner());
+    for path in paths.iter() {
+        if std::fs::remove_file(path).is_ok() {
+            info!("Interrupted; released lock {}", path.display());
+        }
+    }
+    std::process::exit(130);
+}
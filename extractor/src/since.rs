@@ -0,0 +1,137 @@
+//! `run --since <YYYY-MM-DD|last>`: only re-extract questions the API reports as updated
+//! after a cutoff.
+//!
+//! Every completed `run` records its start time in `.checkpoints/last_run.txt`, so
+//! `--since last` picks up whatever changed after the previous successful run.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::extractor::checkpoint_dir;
+
+pub const LAST_RUN_FILE_NAME: &str = "last_run.txt";
+
+pub fn last_run_path(output_dir: &str) -> PathBuf {
+    checkpoint_dir(output_dir).join(LAST_RUN_FILE_NAME)
+}
+
+/// Start time of the last completed run, if one was recorded.
+pub fn load_last_run(output_dir: &str) -> Option<DateTime<Utc>> {
+    let text = fs::read_to_string(last_run_path(output_dir)).ok()?;
+    DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+pub fn record_last_run(output_dir: &str, started: DateTime<Utc>) -> Result<()> {
+    let path = last_run_path(output_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, format!("{}\n", started.to_rfc3339()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// A parsed `--since` value. `last` is looked up when each run starts, so a watch cycle
+/// picks up the run recorded by the cycle before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinceCutoff {
+    Last,
+    Date(DateTime<Utc>),
+}
+
+impl SinceCutoff {
+    pub fn parse(value: &str) -> Result<Self> {
+        if value.eq_ignore_ascii_case("last") {
+            return Ok(SinceCutoff::Last);
+        }
+        match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            Ok(date) => Ok(SinceCutoff::Date(
+                date.and_time(Default::default()).and_utc(),
+            )),
+            Err(_) => bail!(
+                "Invalid --since value '{}': expected YYYY-MM-DD or 'last'",
+                value
+            ),
+        }
+    }
+
+    /// Cutoff for a run starting now. `last` without a recorded run warns and disables the
+    /// filter.
+    pub fn resolve(self, output_dir: &str) -> Option<DateTime<Utc>> {
+        match self {
+            SinceCutoff::Date(date) => Some(date),
+            SinceCutoff::Last => {
+                let last = load_last_run(output_dir);
+                if last.is_none() {
+                    warn!(
+                        "--since last: no previous run recorded in {}; running without a date filter",
+                        last_run_path(output_dir).display()
+                    );
+                }
+                last
+            }
+        }
+    }
+}
+
+/// Parse an update timestamp from the API (RFC 3339, `YYYY-MM-DD` or `MM/DD/YYYY`) or a
+/// `Last-Modified` header (RFC 2822 / HTTP date).
+pub fn parse_update_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc2822(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d", "%m/%d/%Y"].iter().find_map(|format| {
+        NaiveDate::parse_from_str(value, format)
+            .ok()
+            .map(|date| date.and_time(Default::default()).and_utc())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since_cutoff_dates_and_last_run() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+
+        let date = SinceCutoff::parse("2025-03-01")
+            .unwrap()
+            .resolve(output_dir)
+            .unwrap();
+        assert_eq!(date.to_rfc3339(), "2025-03-01T00:00:00+00:00");
+        assert!(SinceCutoff::parse("03/01/2025").is_err());
+        let last = SinceCutoff::parse("last").unwrap();
+        assert_eq!(last.resolve(output_dir), None);
+
+        // The same `last` cutoff follows each newly recorded run
+        let started = parse_update_timestamp("2025-04-02T10:30:00Z").unwrap();
+        record_last_run(output_dir, started).unwrap();
+        assert_eq!(last.resolve(output_dir), Some(started));
+        let next = parse_update_timestamp("2025-04-03T10:30:00Z").unwrap();
+        record_last_run(output_dir, next).unwrap();
+        assert_eq!(last.resolve(output_dir), Some(next));
+    }
+
+    #[test]
+    fn test_parse_update_timestamp_formats() {
+        let expected = parse_update_timestamp("2025-10-21T00:00:00Z").unwrap();
+        assert_eq!(parse_update_timestamp("2025-10-21"), Some(expected));
+        assert_eq!(parse_update_timestamp("10/21/2025"), Some(expected));
+        assert_eq!(
+            parse_update_timestamp("Tue, 21 Oct 2025 00:00:00 GMT"),
+            Some(expected)
+        );
+        assert_eq!(parse_update_timestamp("soon"), None);
+    }
+}
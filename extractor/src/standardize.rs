@@ -1,28 +1,92 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use regex::Regex;
-use std::fs;
-use std::path::Path;
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use tracing::{error, info, warn};
 
+use crate::cli::StandardizeOptions;
 use crate::config;
+use crate::extractor::checkpoint_dir;
 use crate::models::QuestionData;
+use crate::utils::DirScanner;
+
+const PROGRESS_FILE_NAME: &str = "standardize_progress.txt";
 
 #[derive(Debug, Default)]
 pub struct StandardizationStats {
     pub total_files: usize,
+    pub files_skipped_resumed: usize,
     pub files_reordered: usize,
     pub files_whitespace_compacted: usize,
+    pub files_whitespace_normalized: usize,
+    pub files_media_rewritten: usize,
+    pub files_media_repaired: usize,
+    pub media_files_renamed: usize,
     pub files_unchanged: usize,
     pub media_validated: usize,
     pub media_missing: Vec<String>,
     pub errors: Vec<(String, String)>,
 }
 
-pub async fn run_standardization(
+/// Media base rewrite (`--rewrite-media-base old=new`), e.g. for a changed CDN host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaBaseRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+impl MediaBaseRewrite {
+    /// Parse `old=new`; `new` may be empty to turn absolute URLs into relative paths.
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.split_once('=') {
+            Some((from, to)) if !from.trim().is_empty() => Ok(Self {
+                from: from.trim().to_string(),
+                to: to.trim().to_string(),
+            }),
+            _ => bail!(
+                "Invalid --rewrite-media-base value '{}' (expected old=new)",
+                value
+            ),
+        }
+    }
+
+    /// The rewritten reference, or `None` when it does not start with the old base.
+    fn apply(&self, reference: &str) -> Option<String> {
+        let rest = reference.strip_prefix(&self.from)?;
+        if self.to.is_empty() {
+            Some(rest.trim_start_matches('/').to_string())
+        } else {
+            Some(format!("{}{}", self.to, rest))
+        }
+    }
+}
+
+pub async fn run_standardization(output_dir: &str, options: &StandardizeOptions) -> Result<()> {
+    let stats = standardize_questions(output_dir, options)?;
+    print_standardization_report(&stats, options.dry_run);
+    Ok(())
+}
+
+fn standardize_questions(
     output_dir: &str,
-    dry_run: bool,
-    system_filter: Option<&str>,
-) -> Result<()> {
+    options: &StandardizeOptions,
+) -> Result<StandardizationStats> {
+    let StandardizeOptions {
+        dry_run,
+        force,
+        normalize_whitespace,
+        repair_media_paths,
+        ..
+    } = *options;
+    let system_filter = options.system_filter.as_deref();
+    let media_rewrite = options
+        .rewrite_media_base
+        .as_deref()
+        .map(MediaBaseRewrite::parse)
+        .transpose()?;
+    let media_rewrite = media_rewrite.as_ref();
     let mut stats = StandardizationStats::default();
 
     // Load all organ systems from config
@@ -36,6 +100,29 @@ pub async fn run_standardization(
         info!("Processing only system: {}\n", filter);
     }
 
+    let progress_path = progress_path(output_dir);
+    if force && !dry_run && progress_path.exists() {
+        fs::remove_file(&progress_path).with_context(|| {
+            format!("Failed to remove standardize progress: {:?}", progress_path)
+        })?;
+        info!("--force: cleared standardize progress checkpoint\n");
+    }
+    // A rewrite, repair or whitespace pass must reach questions standardized by earlier
+    // runs too
+    let completed =
+        if force || normalize_whitespace || media_rewrite.is_some() || repair_media_paths {
+            HashSet::new()
+        } else {
+            load_progress(&progress_path)?
+        };
+    if !completed.is_empty() {
+        info!(
+            "Resuming standardization: {} questions already standardized\n",
+            completed.len()
+        );
+    }
+
+    let mut scanner = DirScanner::from_config();
     for system in systems {
         // Apply filter if provided
         if let Some(filter) = system_filter {
@@ -52,33 +139,25 @@ pub async fn run_standardization(
         info!("Processing system: {} ({})", system.id, system.name);
 
         // Process all question directories
-        let entries = match fs::read_dir(&system_dir) {
-            Ok(entries) => entries,
+        let question_dirs = match scanner.subdirs(&system_dir) {
+            Ok(dirs) => dirs,
             Err(e) => {
                 error!("Failed to read system directory {}: {}", system.id, e);
                 continue;
             }
         };
 
-        for entry in entries {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(e) => {
-                    warn!("Failed to read directory entry: {}", e);
-                    continue;
-                }
-            };
-
-            let question_dir = entry.path();
-            if !question_dir.is_dir() {
-                continue;
-            }
-
+        for question_dir in question_dirs {
             let question_id = question_dir
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or_default();
 
+            if completed.contains(question_id) {
+                stats.files_skipped_resumed += 1;
+                continue;
+            }
+
             let json_path = question_dir.join(format!("{}.json", question_id));
 
             if !json_path.exists() {
@@ -86,8 +165,21 @@ pub async fn run_standardization(
                 continue;
             }
 
-            match process_question_json(&json_path, &question_dir, dry_run, &mut stats) {
-                Ok(_) => stats.total_files += 1,
+            match process_question_json(
+                &json_path,
+                &question_dir,
+                dry_run,
+                normalize_whitespace,
+                media_rewrite,
+                repair_media_paths,
+                &mut stats,
+            ) {
+                Ok(_) => {
+                    stats.total_files += 1;
+                    if !dry_run {
+                        record_progress(&progress_path, question_id)?;
+                    }
+                }
                 Err(e) => {
                     stats.errors.push((question_id.to_string(), e.to_string()));
                     error!("Error processing {}: {}", question_id, e);
@@ -96,7 +188,48 @@ pub async fn run_standardization(
         }
     }
 
-    print_standardization_report(&stats, dry_run);
+    // The checkpoint only resumes an interrupted run; once the whole corpus went through
+    // cleanly, the next run (e.g. after `run --refresh`) must start over
+    if !dry_run && system_filter.is_none() && stats.errors.is_empty() && progress_path.exists() {
+        fs::remove_file(&progress_path).with_context(|| {
+            format!("Failed to remove standardize progress: {:?}", progress_path)
+        })?;
+    }
+
+    Ok(stats)
+}
+
+fn progress_path(output_dir: &str) -> PathBuf {
+    checkpoint_dir(output_dir).join(PROGRESS_FILE_NAME)
+}
+
+fn load_progress(path: &Path) -> Result<HashSet<String>> {
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read standardize progress: {:?}", path))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn record_progress(path: &Path, question_id: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Append one ID per question so an interrupted run keeps everything written so far
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open standardize progress: {:?}", path))?;
+    writeln!(file, "{}", question_id)?;
     Ok(())
 }
 
@@ -104,6 +237,9 @@ fn process_question_json(
     json_path: &Path,
     question_dir: &Path,
     dry_run: bool,
+    normalize_whitespace: bool,
+    media_rewrite: Option<&MediaBaseRewrite>,
+    repair_media_paths: bool,
     stats: &mut StandardizationStats,
 ) -> Result<()> {
     // 1. Read original JSON
@@ -114,29 +250,57 @@ fn process_question_json(
     let mut question: QuestionData = serde_json::from_str(&original_content)
         .with_context(|| format!("Failed to parse JSON file: {:?}", json_path))?;
 
-    // 3. Compact whitespace in HTML fields
-    let whitespace_changed = compact_whitespace(&mut question);
-    if whitespace_changed {
-        stats.files_whitespace_compacted += 1;
+    // 3. Compact whitespace in HTML fields (or the broader normalization when requested,
+    //    which also keeps meaningful line breaks in critique/references)
+    let whitespace_changed = if normalize_whitespace {
+        let changed = normalize_text_fields(&mut question);
+        if changed {
+            stats.files_whitespace_normalized += 1;
+        }
+        changed
+    } else {
+        let changed = compact_whitespace(&mut question);
+        if changed {
+            stats.files_whitespace_compacted += 1;
+        }
+        changed
+    };
+
+    // 4. Rewrite media references under a changed base
+    let media_rewritten =
+        media_rewrite.is_some_and(|rewrite| rewrite_media_base(&mut question, rewrite));
+    if media_rewritten {
+        stats.files_media_rewritten += 1;
+    }
+
+    // 5. Normalize separators/extension case and match files on disk to the references
+    let media_repaired = repair_media_paths && {
+        let (changed, renamed) = repair_media_references(&mut question, question_dir, dry_run)?;
+        stats.media_files_renamed += renamed;
+        changed
+    };
+    if media_repaired {
+        stats.files_media_repaired += 1;
     }
+    let content_changed = whitespace_changed || media_rewritten || media_repaired;
 
-    // 4. Validate media file existence
+    // 6. Validate media file existence
     validate_media_files(&question, question_dir, stats)?;
 
-    // 5. Re-serialize (automatically uses current struct field order)
+    // 7. Re-serialize (automatically uses current struct field order)
     let standardized_content =
-        serde_json::to_string_pretty(&question).context("Failed to serialize standardized JSON")?;
+        crate::utils::to_json_string(&question).context("Failed to serialize standardized JSON")?;
 
-    // 6. Detect if field order changed
+    // 8. Detect if field order changed
     let ordering_changed = !fields_match_order(&original_content, &standardized_content);
     if ordering_changed {
         stats.files_reordered += 1;
     }
 
-    // 7. Write if changed (atomic write to prevent corruption)
-    if (ordering_changed || whitespace_changed) && !dry_run {
+    // 9. Write if changed (atomic write to prevent corruption)
+    if (ordering_changed || content_changed) && !dry_run {
         atomic_write(json_path, &standardized_content)?;
-    } else if !ordering_changed && !whitespace_changed {
+    } else if !ordering_changed && !content_changed {
         stats.files_unchanged += 1;
     }
 
@@ -172,6 +336,247 @@ fn compact_whitespace(question: &mut QuestionData) -> bool {
     changed
 }
 
+fn normalize_text_fields(question: &mut QuestionData) -> bool {
+    let mut changed = false;
+
+    let mut apply = |field: &mut String, preserve_line_breaks: bool| {
+        let normalized = normalize_whitespace(field, preserve_line_breaks);
+        if normalized != *field {
+            *field = normalized;
+            changed = true;
+        }
+    };
+
+    apply(&mut question.question_text, false);
+    apply(&mut question.question_stem, false);
+    apply(&mut question.educational_objective, false);
+    apply(&mut question.critique, true);
+    apply(&mut question.references, true);
+    for reference in &mut question.references_list {
+        apply(&mut reference.text, false);
+    }
+    for point in &mut question.key_points {
+        apply(point, false);
+    }
+
+    changed
+}
+
+/// Convert NBSP-style spaces to plain spaces, collapse runs and trim.
+///
+/// With `preserve_line_breaks`, each line is normalized separately and single blank
+/// lines (paragraph breaks) are kept; longer runs of blank lines collapse to one.
+fn normalize_whitespace(text: &str, preserve_line_breaks: bool) -> String {
+    // NBSP-style spaces and tabs become plain spaces so compact_html_whitespace folds
+    // single ones too, not just runs
+    let text: String = text
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() && (c != '\n' || !preserve_line_breaks) {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    if !preserve_line_breaks {
+        return compact_html_whitespace(&text);
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines().map(compact_html_whitespace) {
+        let previous_blank = lines.last().is_none_or(|last| last.is_empty());
+        if line.is_empty() && previous_blank {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+/// Rewrite `media` paths and string values in `media_metadata` that start with the old base.
+fn rewrite_media_base(question: &mut QuestionData, rewrite: &MediaBaseRewrite) -> bool {
+    let mut changed = false;
+
+    let media = &mut question.media;
+    for reference in media
+        .tables
+        .iter_mut()
+        .chain(media.images.iter_mut())
+        .chain(media.svgs.iter_mut())
+        .chain(media.videos.iter_mut())
+    {
+        if let Some(rewritten) = rewrite.apply(reference) {
+            *reference = rewritten;
+            changed = true;
+        }
+    }
+
+    if let Some(metadata) = question.media_metadata.as_mut() {
+        changed |= rewrite_json_strings(metadata, rewrite);
+    }
+
+    changed
+}
+
+fn rewrite_json_strings(value: &mut serde_json::Value, rewrite: &MediaBaseRewrite) -> bool {
+    match value {
+        serde_json::Value::String(text) => match rewrite.apply(text) {
+            Some(rewritten) => {
+                *text = rewritten;
+                true
+            }
+            None => false,
+        },
+        serde_json::Value::Array(items) => items.iter_mut().fold(false, |changed, item| {
+            rewrite_json_strings(item, rewrite) | changed
+        }),
+        serde_json::Value::Object(object) => object.values_mut().fold(false, |changed, item| {
+            rewrite_json_strings(item, rewrite) | changed
+        }),
+        _ => false,
+    }
+}
+
+/// Normalize `media` paths and path-like strings in `media_metadata` (`/` separators,
+/// lowercase extensions), renaming files on disk whose name differs only in case.
+///
+/// Returns whether the JSON changed and how many files were (or would be) renamed.
+fn repair_media_references(
+    question: &mut QuestionData,
+    question_dir: &Path,
+    dry_run: bool,
+) -> Result<(bool, usize)> {
+    let mut changed = false;
+    let mut renamed = 0;
+
+    let media = &mut question.media;
+    for reference in media
+        .tables
+        .iter_mut()
+        .chain(media.images.iter_mut())
+        .chain(media.svgs.iter_mut())
+        .chain(media.videos.iter_mut())
+    {
+        if reference.contains("://") {
+            continue;
+        }
+        let normalized = normalize_media_path(reference);
+        if normalized != *reference {
+            *reference = normalized;
+            changed = true;
+        }
+        if reconcile_media_file(question_dir, reference, dry_run)? {
+            renamed += 1;
+        }
+    }
+
+    if let Some(metadata) = question.media_metadata.as_mut() {
+        changed |= repair_json_paths(metadata);
+    }
+
+    Ok((changed, renamed))
+}
+
+/// `figures\F1.JPG` -> `figures/F1.jpg`; the file stem keeps its case.
+fn normalize_media_path(reference: &str) -> String {
+    let path = reference.replace('\\', "/");
+    let name_start = path.rfind('/').map_or(0, |index| index + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let (stem, extension) = path.split_at(name_start + dot);
+            format!("{}{}", stem, extension.to_ascii_lowercase())
+        }
+        _ => path,
+    }
+}
+
+/// Strings in `media_metadata` that look like relative file paths (no whitespace, a
+/// directory separator and a short alphanumeric extension).
+fn is_media_path_like(text: &str) -> bool {
+    if text.contains("://") || text.chars().any(char::is_whitespace) {
+        return false;
+    }
+    if !text.contains(['/', '\\']) {
+        return false;
+    }
+    let name = text.rsplit(['/', '\\']).next().unwrap_or_default();
+    name.rsplit_once('.').is_some_and(|(stem, extension)| {
+        !stem.is_empty()
+            && (1..=5).contains(&extension.len())
+            && extension.chars().all(|ch| ch.is_ascii_alphanumeric())
+    })
+}
+
+fn repair_json_paths(value: &mut serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::String(text) if is_media_path_like(text) => {
+            let normalized = normalize_media_path(text);
+            let changed = normalized != *text;
+            *text = normalized;
+            changed
+        }
+        serde_json::Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |changed, item| repair_json_paths(item) | changed),
+        serde_json::Value::Object(object) => object
+            .values_mut()
+            .fold(false, |changed, item| repair_json_paths(item) | changed),
+        _ => false,
+    }
+}
+
+/// Rename a file whose name matches `reference` only case-insensitively so the
+/// reference resolves exactly. Returns true when a rename was (or would be) made.
+fn reconcile_media_file(question_dir: &Path, reference: &str, dry_run: bool) -> Result<bool> {
+    let target = question_dir.join(reference);
+    let (Some(parent), Some(name)) = (target.parent(), target.file_name()) else {
+        return Ok(false);
+    };
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Ok(false);
+    };
+
+    let mut case_match = None;
+    for entry in entries.flatten() {
+        let entry_name = entry.file_name();
+        if entry_name == name {
+            return Ok(false);
+        }
+        if entry_name
+            .to_string_lossy()
+            .eq_ignore_ascii_case(&name.to_string_lossy())
+        {
+            case_match = Some(entry.path());
+        }
+    }
+
+    let Some(source) = case_match else {
+        return Ok(false);
+    };
+    info!(
+        "{} {} -> {}",
+        if dry_run { "Would rename" } else { "Renaming" },
+        source.display(),
+        target.display()
+    );
+    if !dry_run {
+        fs::rename(&source, &target).with_context(|| {
+            format!(
+                "Failed to rename {} to {}",
+                source.display(),
+                target.display()
+            )
+        })?;
+    }
+    Ok(true)
+}
+
 fn compact_html_whitespace(html: &str) -> String {
     // Replace 2+ consecutive whitespace chars with single space
     let re = Regex::new(r"\s{2,}").unwrap();
@@ -225,20 +630,11 @@ fn check_media_file(
 }
 
 fn atomic_write(target_path: &Path, content: &str) -> Result<()> {
-    let temp_path = target_path.with_extension("json.tmp");
-
-    // Write to temp file
-    fs::write(&temp_path, content).context("Failed to write temp file")?;
-
-    // Validate temp file is valid JSON
-    let validation = fs::read_to_string(&temp_path).context("Failed to read temp file")?;
-    serde_json::from_str::<serde_json::Value>(&validation)
-        .context("Temp file validation failed - invalid JSON")?;
+    // Refuse to replace the original with anything that is not valid JSON
+    serde_json::from_str::<serde_json::Value>(content)
+        .context("Standardized content is not valid JSON")?;
 
-    // Atomic rename (original untouched until this point)
-    fs::rename(&temp_path, target_path).context("Failed to rename temp file to target")?;
-
-    Ok(())
+    crate::io::write_atomic(target_path, content)
 }
 
 fn fields_match_order(json1: &str, json2: &str) -> bool {
@@ -262,11 +658,35 @@ fn fields_match_order(json1: &str, json2: &str) -> bool {
 fn print_standardization_report(stats: &StandardizationStats, dry_run: bool) {
     info!("\n=== STANDARDIZATION REPORT ===");
     info!("Total files processed: {}", stats.total_files);
+    if stats.files_skipped_resumed > 0 {
+        info!(
+            "Skipped (already standardized, resumed): {} (use --force to redo)",
+            stats.files_skipped_resumed
+        );
+    }
     info!("Files with reordered fields: {}", stats.files_reordered);
     info!(
         "Files with compacted whitespace: {}",
         stats.files_whitespace_compacted
     );
+    if stats.files_whitespace_normalized > 0 {
+        info!(
+            "Files with normalized whitespace: {}",
+            stats.files_whitespace_normalized
+        );
+    }
+    if stats.files_media_rewritten > 0 {
+        info!(
+            "Files with rewritten media base: {}",
+            stats.files_media_rewritten
+        );
+    }
+    if stats.files_media_repaired > 0 || stats.media_files_renamed > 0 {
+        info!(
+            "Files with repaired media paths: {} ({} media files renamed)",
+            stats.files_media_repaired, stats.media_files_renamed
+        );
+    }
     info!("Files unchanged: {}", stats.files_unchanged);
     info!("Media files validated: {}", stats.media_validated);
     info!("Media files missing: {}", stats.media_missing.len());
@@ -296,6 +716,7 @@ fn print_standardization_report(stats: &StandardizationStats, dry_run: bool) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::export::test_support::{write_question, write_question_with};
 
     #[test]
     fn test_compact_whitespace_basic() {
@@ -331,4 +752,281 @@ mod tests {
         let json2 = "";
         assert!(fields_match_order(json1, json2));
     }
+
+    #[test]
+    fn test_resume_skips_already_standardized_questions() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+        let pending = write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24002",
+            serde_json::json!({ "question_text": "Which   test  is next?" }),
+        );
+
+        // Simulate an interrupted run that only got through the first question
+        record_progress(&progress_path(output_dir), "cvmcq24001").unwrap();
+
+        let stats = standardize_questions(output_dir, &StandardizeOptions::default()).unwrap();
+        assert_eq!(stats.files_skipped_resumed, 1);
+        assert_eq!(stats.total_files, 1);
+        assert!(fs::read_to_string(&pending)
+            .unwrap()
+            .contains("Which test is next?"));
+
+        // A filtered run leaves the checkpoint for the rest of the corpus
+        record_progress(&progress_path(output_dir), "cvmcq24001").unwrap();
+        standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                system_filter: Some("cv".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let progress = load_progress(&progress_path(output_dir)).unwrap();
+        assert!(progress.contains("cvmcq24001"));
+        assert!(progress.contains("cvmcq24002"));
+
+        // A clean, complete run clears it, so the next run reprocesses everything
+        standardize_questions(output_dir, &StandardizeOptions::default()).unwrap();
+        assert!(!progress_path(output_dir).exists());
+        let stats = standardize_questions(output_dir, &StandardizeOptions::default()).unwrap();
+        assert_eq!(stats.files_skipped_resumed, 0);
+        assert_eq!(stats.total_files, 2);
+    }
+
+    #[test]
+    fn test_force_redoes_standardized_questions() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+        record_progress(&progress_path(output_dir), "cvmcq24001").unwrap();
+
+        let stats = standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                force: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.files_skipped_resumed, 0);
+        assert_eq!(stats.total_files, 1);
+    }
+
+    #[test]
+    fn test_normalize_whitespace_collapses_runs_and_nbsp() {
+        assert_eq!(
+            normalize_whitespace("  Chest\u{00a0}\u{00a0}pain   and\tdyspnea. \n", false),
+            "Chest pain and dyspnea."
+        );
+    }
+
+    #[test]
+    fn test_normalize_whitespace_preserves_critique_line_breaks() {
+        let critique =
+            "First  paragraph.\u{00a0}\nSame paragraph.\n\n\n\n  Second paragraph.  \n\n";
+        assert_eq!(
+            normalize_whitespace(critique, true),
+            "First paragraph.\nSame paragraph.\n\nSecond paragraph."
+        );
+    }
+
+    #[test]
+    fn test_normalize_whitespace_step_respects_dry_run() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        let json_path = write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24001",
+            serde_json::json!({ "question_text": "Which   test  is next?" }),
+        );
+        let original = fs::read_to_string(&json_path).unwrap();
+        // Already standardized without the whitespace step
+        record_progress(&progress_path(output_dir), "cvmcq24001").unwrap();
+
+        let stats = standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                dry_run: true,
+                normalize_whitespace: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.files_skipped_resumed, 0);
+        assert_eq!(stats.files_whitespace_normalized, 1);
+        assert_eq!(fs::read_to_string(&json_path).unwrap(), original);
+
+        standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                normalize_whitespace: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let question: QuestionData =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(question.question_text, "Which test is next?");
+    }
+
+    #[test]
+    fn test_dry_run_does_not_record_progress() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+
+        standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(!progress_path(output_dir).exists());
+    }
+
+    #[test]
+    fn test_media_base_rewrite_parse() {
+        let rewrite = MediaBaseRewrite::parse("https://old.cdn.example/media=").unwrap();
+        assert_eq!(rewrite.from, "https://old.cdn.example/media");
+        assert_eq!(rewrite.to, "");
+        assert!(MediaBaseRewrite::parse("no-separator").is_err());
+        assert!(MediaBaseRewrite::parse("=https://new.example").is_err());
+    }
+
+    #[test]
+    fn test_rewrite_media_base_only_touches_matching_references() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        let json_path = write_question_with(temp.path(), "cv", "cvmcq24001", serde_json::json!({}));
+        let mut value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        value["media"]["images"] = serde_json::json!([
+            "https://old.cdn.example/media/figures/f1.png",
+            "figures/f2.png"
+        ]);
+        value["media"]["videos"] = serde_json::json!(["https://other.example/v.mp4"]);
+        value["media_metadata"] = serde_json::json!({
+            "figures": [{ "figure_id": "f1", "file": "https://old.cdn.example/media/figures/f1.png" }]
+        });
+        fs::write(&json_path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+
+        let rewrite = "https://old.cdn.example/media=https://new.cdn.example";
+        let stats = standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                rewrite_media_base: Some(rewrite.to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.files_media_rewritten, 1);
+
+        let question: QuestionData =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(
+            question.media.images,
+            vec!["https://new.cdn.example/figures/f1.png", "figures/f2.png"]
+        );
+        assert_eq!(question.media.videos, vec!["https://other.example/v.mp4"]);
+        assert_eq!(
+            question.media_metadata.unwrap()["figures"][0]["file"],
+            "https://new.cdn.example/figures/f1.png"
+        );
+
+        // An empty replacement turns absolute URLs into question-relative paths
+        let rewrite = "https://new.cdn.example=";
+        standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                rewrite_media_base: Some(rewrite.to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let question: QuestionData =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(
+            question.media.images,
+            vec!["figures/f1.png", "figures/f2.png"]
+        );
+    }
+
+    #[test]
+    fn test_normalize_media_path() {
+        assert_eq!(normalize_media_path("figures\\F1.JPG"), "figures/F1.jpg");
+        assert_eq!(normalize_media_path("tables/t1.html"), "tables/t1.html");
+        assert_eq!(normalize_media_path("svgs/.hidden"), "svgs/.hidden");
+        assert!(is_media_path_like("figures\\f1.PNG"));
+        assert!(!is_media_path_like("A/B testing. Done"));
+        assert!(!is_media_path_like("https://cdn.example/f1.png"));
+    }
+
+    #[test]
+    fn test_repair_media_paths_renames_files_and_normalizes_references() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_str().unwrap();
+        let json_path = write_question_with(temp.path(), "cv", "cvmcq24001", serde_json::json!({}));
+        let question_dir = json_path.parent().unwrap();
+        fs::create_dir_all(question_dir.join("figures")).unwrap();
+        fs::write(question_dir.join("figures").join("F1.JPG"), b"jpg").unwrap();
+        fs::write(question_dir.join("figures").join("f2.png"), b"png").unwrap();
+
+        let mut value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        value["media"]["images"] = serde_json::json!(["figures\\F1.JPG", "figures/f2.PNG"]);
+        value["media_metadata"] = serde_json::json!({
+            "figures": [{ "file": "figures\\F1.JPG", "caption": "A/B ratio. See text" }]
+        });
+        fs::write(&json_path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+
+        let stats = standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                dry_run: true,
+                repair_media_paths: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.files_media_repaired, 1);
+        assert_eq!(stats.media_files_renamed, 1);
+        assert!(question_dir.join("figures").join("F1.JPG").exists());
+
+        let stats = standardize_questions(
+            output_dir,
+            &StandardizeOptions {
+                repair_media_paths: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.files_media_repaired, 1);
+        assert_eq!(stats.media_files_renamed, 1);
+        assert!(stats.media_missing.is_empty(), "{:?}", stats.media_missing);
+
+        let question: QuestionData =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(
+            question.media.images,
+            vec!["figures/F1.jpg", "figures/f2.png"]
+        );
+        for reference in &question.media.images {
+            let on_disk: Vec<String> = fs::read_dir(question_dir.join("figures"))
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            let name = reference.rsplit('/').next().unwrap();
+            assert!(on_disk.iter().any(|file| file == name), "{:?}", on_disk);
+        }
+        let metadata = question.media_metadata.unwrap();
+        assert_eq!(metadata["figures"][0]["file"], "figures/F1.jpg");
+        assert_eq!(metadata["figures"][0]["caption"], "A/B ratio. See text");
+    }
 }
@@ -0,0 +1,185 @@
+//! Offline corpus overview (`stats`): per-system question counts and content coverage,
+//! computed from the extracted JSON without touching the network.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+
+use crate::assets::asset_store::collect_question_entries;
+use crate::models::QuestionData;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SystemStats {
+    pub questions: usize,
+    /// Questions listing at least one table, image, SVG or video in `media`.
+    pub with_media: usize,
+    pub without_media: usize,
+    pub high_value_care: usize,
+    pub hospitalist: usize,
+    pub average_options: f64,
+    pub with_critique_links: usize,
+    #[serde(skip)]
+    total_options: usize,
+}
+
+impl SystemStats {
+    fn add(&mut self, question: &QuestionData) {
+        let media = &question.media;
+        let has_media = !(media.tables.is_empty()
+            && media.images.is_empty()
+            && media.svgs.is_empty()
+            && media.videos.is_empty());
+
+        self.questions += 1;
+        if has_media {
+            self.with_media += 1;
+        } else {
+            self.without_media += 1;
+        }
+        self.high_value_care += usize::from(question.metadata.high_value_care);
+        self.hospitalist += usize::from(question.metadata.hospitalist);
+        self.total_options += question.options.len();
+        self.with_critique_links += usize::from(!question.critique_links.is_empty());
+        self.average_options = self.total_options as f64 / self.questions as f64;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CorpusStats {
+    pub systems: BTreeMap<String, SystemStats>,
+    pub total: SystemStats,
+    /// Question JSON files that could not be read as `QuestionData`.
+    pub unreadable: usize,
+}
+
+/// Tally every question under `data_dir`, grouped by its `category`.
+pub fn compute_corpus_stats(data_dir: &str) -> Result<CorpusStats> {
+    let mut stats = CorpusStats::default();
+    for entry in collect_question_entries(data_dir)? {
+        let question = fs::read_to_string(&entry.json_path)
+            .context("read failed")
+            .and_then(|text| {
+                serde_json::from_str::<QuestionData>(&text).context("not valid question JSON")
+            });
+        match question {
+            Ok(question) => {
+                stats
+                    .systems
+                    .entry(question.category.clone())
+                    .or_default()
+                    .add(&question);
+                stats.total.add(&question);
+            }
+            Err(err) => {
+                warn!("Skipping {}: {:#}", entry.json_path.display(), err);
+                stats.unreadable += 1;
+            }
+        }
+    }
+    Ok(stats)
+}
+
+/// Compact fixed-width table, one row per system plus a total row.
+pub fn render_corpus_stats(stats: &CorpusStats) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<8} {:>9} {:>8} {:>10} {:>5} {:>11} {:>11} {:>12}",
+        "System",
+        "Questions",
+        "Media",
+        "No media",
+        "HVC",
+        "Hospitalist",
+        "Avg options",
+        "Crit. links"
+    );
+    let _ = writeln!(out, "{}", "-".repeat(81));
+    let rows = stats
+        .systems
+        .iter()
+        .map(|(system, row)| (system.as_str(), row))
+        .chain(std::iter::once(("TOTAL", &stats.total)));
+    for (system, row) in rows {
+        let _ = writeln!(
+            out,
+            "{:<8} {:>9} {:>8} {:>10} {:>5} {:>11} {:>11.2} {:>12}",
+            system,
+            row.questions,
+            row.with_media,
+            row.without_media,
+            row.high_value_care,
+            row.hospitalist,
+            row.average_options,
+            row.with_critique_links
+        );
+    }
+    if stats.unreadable > 0 {
+        let _ = writeln!(
+            out,
+            "\n{} question files could not be parsed",
+            stats.unreadable
+        );
+    }
+    out
+}
+
+pub fn write_corpus_stats_json(stats: &CorpusStats, out_path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(stats)?;
+    fs::write(out_path, json).with_context(|| format!("Failed to write {}", out_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_question, write_question};
+
+    #[test]
+    fn test_corpus_stats_per_system_and_total() {
+        let temp = tempfile::tempdir().unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+        write_question(temp.path(), "en", "enmcq24001");
+
+        let mut plain = sample_question("cv", "cvmcq24002");
+        plain["media"]["images"] = serde_json::json!([]);
+        plain["metadata"]["high_value_care"] = true.into();
+        plain["options"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({ "letter": "C", "text": "Third", "peer_percentage": 0 }));
+        plain["critique_links"] = serde_json::json!([]);
+        let dir = temp.path().join("cv").join("cvmcq24002");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("cvmcq24002.json"), plain.to_string()).unwrap();
+
+        let broken = temp.path().join("en").join("enmcq24002");
+        fs::create_dir_all(&broken).unwrap();
+        fs::write(broken.join("enmcq24002.json"), "{").unwrap();
+
+        let stats = compute_corpus_stats(temp.path().to_str().unwrap()).unwrap();
+        let cv = &stats.systems["cv"];
+        assert_eq!((cv.questions, cv.with_media, cv.without_media), (2, 1, 1));
+        assert_eq!(cv.high_value_care, 1);
+        assert_eq!(cv.average_options, 2.5);
+        assert_eq!(cv.with_critique_links, 1);
+        assert_eq!(stats.systems["en"].questions, 1);
+        assert_eq!(stats.total.questions, 3);
+        assert_eq!(stats.unreadable, 1);
+
+        let table = render_corpus_stats(&stats);
+        assert!(table
+            .lines()
+            .any(|line| line.starts_with("TOTAL") && line.contains(" 3 ")));
+
+        let out = temp.path().join("stats.json");
+        write_corpus_stats_json(&stats, &out).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(out).unwrap()).unwrap();
+        assert_eq!(json["systems"]["cv"]["average_options"], 2.5);
+        assert!(json["total"].get("total_options").is_none());
+    }
+}
@@ -1,12 +1,18 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use regex::Regex;
 use serde_json::Value;
+use std::future::Future;
 use std::time::{Duration, Instant};
 use thirtyfour::prelude::*;
 use tracing::{info, warn};
 
+use crate::app::SiteConfig;
 use crate::session;
 
+/// First delay between WebDriver connection attempts; doubles per attempt.
+const CONNECT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const CONNECT_MAX_BACKOFF: Duration = Duration::from_secs(4);
+
 #[derive(Clone, Debug)]
 pub struct BrowserOptions {
     pub base_url: String,
@@ -17,6 +23,8 @@ pub struct BrowserOptions {
     pub password: Option<String>,
     pub login_timeout: Duration,
     pub session_cookie: Option<String>,
+    /// WebDriver connection attempts before giving up (at least one).
+    pub connect_attempts: u32,
 }
 
 #[derive(Default, Debug)]
@@ -29,6 +37,48 @@ pub struct BrowserMedia {
 pub struct BrowserSession {
     driver: WebDriver,
     base_url: String,
+    cookie_name: String,
+}
+
+/// Retry `connect` with exponential backoff so a WebDriver that is still starting
+/// gets a few seconds to come up before we give up.
+async fn connect_with_retry<T, E, F, Fut>(
+    webdriver_url: &str,
+    attempts: u32,
+    initial_backoff: Duration,
+    mut connect: F,
+) -> Result<T>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let attempts = attempts.max(1);
+    let mut backoff = initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match connect().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < attempts => {
+                warn!(
+                    "WebDriver at {} not ready (attempt {}/{}): {}; retrying in {:?}",
+                    webdriver_url, attempt, attempts, err, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(CONNECT_MAX_BACKOFF);
+                attempt += 1;
+            }
+            Err(err) => {
+                anyhow::bail!(
+                    "Failed to connect to WebDriver at {} after {} attempts: {}. \
+                     Start ChromeDriver there or pass --webdriver-url <url>.",
+                    webdriver_url,
+                    attempts,
+                    err
+                );
+            }
+        }
+    }
 }
 
 impl BrowserSession {
@@ -39,9 +89,13 @@ impl BrowserSession {
         }
         caps.add_chrome_arg("--disable-gpu")?;
         caps.add_chrome_arg("--window-size=1280,900")?;
-        let driver = WebDriver::new(&options.webdriver_url, caps)
-            .await
-            .with_context(|| format!("Failed to connect to {}", options.webdriver_url))?;
+        let driver = connect_with_retry(
+            &options.webdriver_url,
+            options.connect_attempts,
+            CONNECT_INITIAL_BACKOFF,
+            || WebDriver::new(&options.webdriver_url, caps.clone()),
+        )
+        .await?;
         driver
             .set_implicit_wait_timeout(Duration::from_secs(2))
             .await?;
@@ -49,6 +103,7 @@ impl BrowserSession {
         let session = BrowserSession {
             driver,
             base_url: options.base_url.clone(),
+            cookie_name: session::session_cookie_name(),
         };
 
         if let Some(cookie) = options.session_cookie.as_ref() {
@@ -58,13 +113,42 @@ impl BrowserSession {
         Ok(session)
     }
 
-    pub async fn ensure_login(&self, options: &BrowserOptions) -> Result<()> {
+    /// Connect `size` sessions (at least one) to the same WebDriver. The first handles
+    /// login; the rest get the session cookie it ended up with, injected per session.
+    pub async fn connect_pool(options: &BrowserOptions, size: usize) -> Result<Vec<Self>> {
+        let first = Self::connect(options).await?;
+        let logged_in = first.ensure_login(options).await?;
+        let mut pool = vec![first];
+        if size > 1 {
+            // A cookie from the login just made replaces the one passed in, which may be
+            // the stale cookie that made the login necessary
+            let options = BrowserOptions {
+                session_cookie: logged_in
+                    .or_else(|| options.session_cookie.clone())
+                    .or_else(session::load_session_cookie),
+                ..options.clone()
+            };
+            for _ in 1..size {
+                pool.push(Self::connect(&options).await?);
+            }
+            info!(
+                "Connected {} browser sessions to {}",
+                pool.len(),
+                options.webdriver_url
+            );
+        }
+        Ok(pool)
+    }
+
+    /// Log in interactively when the session has no cookie. Returns the cookie captured
+    /// (and saved) by that login, if one happened.
+    pub async fn ensure_login(&self, options: &BrowserOptions) -> Result<Option<String>> {
         if self.has_session_cookie().await? {
-            return Ok(());
+            return Ok(None);
         }
 
         if !options.interactive_login {
-            return Ok(());
+            return Ok(None);
         }
 
         info!("Opening browser for MKSAP login...");
@@ -87,14 +171,15 @@ impl BrowserSession {
         }
 
         info!("Waiting for login session cookie...");
-        if let Some(cookie) = self.wait_for_session_cookie(options.login_timeout).await? {
-            session::save_session_cookie(&cookie)?;
+        let cookie = self.wait_for_session_cookie(options.login_timeout).await?;
+        if let Some(cookie) = cookie.as_deref() {
+            session::save_session_cookie(cookie)?;
             info!("Saved session cookie to ~/.mksap_session");
         } else {
             warn!("Login timeout; session cookie not detected.");
         }
 
-        Ok(())
+        Ok(cookie)
     }
 
     pub async fn extract_media(&self, question_id: &str, want_svgs: bool) -> Result<BrowserMedia> {
@@ -126,7 +211,7 @@ impl BrowserSession {
         let cookies = self.driver.get_all_cookies().await?;
         Ok(cookies
             .iter()
-            .any(|cookie| cookie.name() == "_mksap19_session"))
+            .any(|cookie| cookie.name() == self.cookie_name))
     }
 
     async fn wait_for_session_cookie(&self, timeout: Duration) -> Result<Option<String>> {
@@ -135,7 +220,7 @@ impl BrowserSession {
             let cookies = self.driver.get_all_cookies().await?;
             if let Some(cookie) = cookies
                 .iter()
-                .find(|cookie| cookie.name() == "_mksap19_session")
+                .find(|cookie| cookie.name() == self.cookie_name)
             {
                 return Ok(Some(cookie.value().to_string()));
             }
@@ -147,15 +232,18 @@ impl BrowserSession {
     }
 
     async fn inject_session_cookie(&self, cookie_value: &str) -> Result<()> {
+        let cookie_name = self.cookie_name.clone();
+        let domain = SiteConfig::cookie_domain(&self.base_url)
+            .unwrap_or_else(|| "mksap.acponline.org".to_string());
         self.driver.goto(&self.base_url).await?;
-        let cookie = Cookie::build("_mksap19_session", cookie_value.to_string())
-            .domain("mksap.acponline.org")
+        let cookie = Cookie::build(cookie_name.clone(), cookie_value.to_string())
+            .domain(domain)
             .path("/")
             .secure(true)
             .http_only(true)
             .finish();
         self.driver.add_cookie(cookie).await?;
-        match self.driver.get_named_cookie("_mksap19_session").await {
+        match self.driver.get_named_cookie(&cookie_name).await {
             Ok(stored) => {
                 if stored.value() != cookie_value {
                     warn!(
@@ -267,3 +355,58 @@ fn is_svg_url(url: &str) -> bool {
     let lower = url.to_ascii_lowercase();
     lower.contains(".svg")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn test_connect_retry_waits_for_listener() {
+        // Reserve a port, then free it so the first attempts are refused
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let attempts = AtomicU32::new(0);
+
+        let result = connect_with_retry("stub", 5, Duration::from_millis(10), || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 2 {
+                // The "WebDriver" comes up just before the third attempt
+                let listener = TcpListener::bind(addr).await.unwrap();
+                tokio::spawn(async move {
+                    let _ = listener.accept().await;
+                });
+            }
+            TcpStream::connect(addr).await
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_connect_retry_gives_up_with_webdriver_hint() {
+        let attempts = AtomicU32::new(0);
+
+        let err = connect_with_retry(
+            "http://localhost:9515",
+            3,
+            Duration::from_millis(1),
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>("connection refused")
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let message = err.to_string();
+        assert!(message.contains("after 3 attempts"));
+        assert!(message.contains("--webdriver-url"));
+    }
+}
@@ -1,17 +1,24 @@
 use anyhow::{Context, Result};
+use futures::future;
 use regex::Regex;
 use reqwest::Client;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::io::Read;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use std::time::Duration;
 use tracing::{info, warn};
 
 use super::asset_discovery::{DiscoveryResults, QuestionMedia};
 use super::asset_metadata::{extract_html_text, for_each_metadata_item, resolve_metadata_id};
 use super::asset_store::{
-    collect_question_entry_map, select_targets, update_question_json, MediaUpdate, SvgMetadata,
+    collect_question_entry_map, select_targets, update_question_json, MediaUpdate, QuestionEntry,
+    SvgMetadata,
 };
+use super::media_plan::{MediaPlan, PlannedMedia};
 use super::svg_browser::{dedupe_urls, extract_svg_urls, BrowserOptions, BrowserSession};
 use crate::session;
 
@@ -29,6 +36,8 @@ pub async fn run_svg_download(
     username: Option<String>,
     password: Option<String>,
     login_timeout_secs: u64,
+    connect_attempts: u32,
+    browser_concurrency: usize,
 ) -> Result<()> {
     if !download_svgs {
         warn!("Browser download requested without SVGs enabled.");
@@ -86,113 +95,238 @@ pub async fn run_svg_download(
         password,
         login_timeout: Duration::from_secs(login_timeout_secs),
         session_cookie,
+        connect_attempts,
     };
 
-    let browser = BrowserSession::connect(&options).await?;
-    browser.ensure_login(&options).await?;
-
-    for (idx, qid) in targets.iter().enumerate() {
-        if idx > 0 && (idx % 10) == 0 {
-            info!("Progress: {}/{}", idx, targets.len());
+    let browsers = BrowserSession::connect_pool(&options, browser_concurrency).await?;
+    let total = targets.len();
+    let started = AtomicUsize::new(0);
+    let (browsers, entry_map, media_by_id, svg_metadata_by_id, started) = (
+        &browsers,
+        &entry_map,
+        &media_by_id,
+        &svg_metadata_by_id,
+        &started,
+    );
+    // Each question is taken from the queue by exactly one session, so its JSON is only
+    // ever updated by one writer
+    drain_work_queue(browsers.len(), targets, |worker, qid| async move {
+        let index = started.fetch_add(1, Ordering::SeqCst);
+        if index > 0 && (index % 10) == 0 {
+            info!("Progress: {}/{}", index, total);
         }
 
-        let Some(entry) = entry_map.get(qid) else {
+        let Some(entry) = entry_map.get(&qid) else {
             warn!("Question {} not found in data directory; skipping", qid);
-            continue;
+            return Ok(());
         };
-
-        let Some(expected_media) = media_by_id.get(qid) else {
-            continue;
+        let Some(expected_media) = media_by_id.get(&qid) else {
+            return Ok(());
         };
+        process_browser_question(
+            &browsers[worker],
+            client,
+            entry,
+            expected_media,
+            svg_metadata_by_id,
+            download_svgs,
+        )
+        .await
+    })
+    .await
+}
 
-        let browser_media = browser
-            .extract_media(qid, download_svgs)
-            .await
-            .with_context(|| format!("Failed to extract media from {}", qid))?;
-
-        let caption_map = extract_caption_map(&browser_media.page_html);
-
-        let mut update = MediaUpdate::default();
-        let mut seen_svg_files = HashSet::new();
-        let mut seen_svg_metadata = HashSet::new();
-
-        if download_svgs {
-            let urls = dedupe_urls(browser_media.svg_urls);
-            let expected_svg_ids: Vec<String> = expected_media
-                .svgs
-                .iter()
-                .map(|svg| svg.svg_id.clone())
-                .collect();
-
-            let (assignments, leftovers) =
-                assign_ids_to_urls(&expected_svg_ids, &urls, &caption_map);
-            let mut remaining_ids: VecDeque<String> = leftovers.into();
-
-            for assignment in assignments {
-                let path = download_svg(client, &entry.question_dir, &assignment.url).await?;
-
-                push_unique(&mut update.svgs, &mut seen_svg_files, path.clone());
-
-                if !assignment.id.is_empty() && seen_svg_metadata.insert(assignment.id.clone()) {
-                    let mut metadata = svg_metadata_by_id
-                        .get(&assignment.id)
-                        .cloned()
-                        .unwrap_or_else(|| fallback_svg_metadata(&assignment.id));
-                    metadata.file = path;
-                    if metadata.caption.is_none() {
-                        metadata.caption = assignment.caption.clone();
-                    }
-                    if metadata.title.is_none() {
-                        metadata.title = assignment.caption;
-                    }
-                    update.metadata.svgs.push(metadata);
-                }
-            }
+/// Hand `items` to `workers` concurrent workers (numbered from 0) from a shared queue: each
+/// takes the next item as soon as it finishes its previous one. Stops at the first error.
+async fn drain_work_queue<T, F, Fut>(workers: usize, items: Vec<T>, handle: F) -> Result<()>
+where
+    F: Fn(usize, T) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let queue = Mutex::new(VecDeque::from(items));
+    let (queue, handle) = (&queue, &handle);
+    future::try_join_all((0..workers.max(1)).map(|worker| async move {
+        loop {
+            let next = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front();
+            let Some(item) = next else {
+                return Ok::<(), anyhow::Error>(());
+            };
+            handle(worker, item).await?;
+        }
+    }))
+    .await?;
+    Ok(())
+}
 
-            for (index, svg_markup) in browser_media.inline_svgs.iter().enumerate() {
-                let path = save_inline_svg(&entry.question_dir, index, svg_markup)?;
-
-                push_unique(&mut update.svgs, &mut seen_svg_files, path.clone());
-
-                let svg_id = remaining_ids
-                    .pop_front()
-                    .unwrap_or_else(|| format!("inline_svg_{}", index + 1));
-
-                if seen_svg_metadata.insert(svg_id.clone()) {
-                    let mut metadata = svg_metadata_by_id
-                        .get(&svg_id)
-                        .cloned()
-                        .unwrap_or_else(|| fallback_svg_metadata(&svg_id));
-                    metadata.file = path;
-                    if metadata.title.is_none() {
-                        metadata.title = extract_inline_svg_title(svg_markup);
-                    }
-                    update.metadata.svgs.push(metadata);
+/// Load one question page in `browser`, save its SVGs and record them in the question JSON.
+async fn process_browser_question(
+    browser: &BrowserSession,
+    client: &Client,
+    entry: &QuestionEntry,
+    expected_media: &QuestionMedia,
+    svg_metadata_by_id: &HashMap<String, SvgMetadata>,
+    download_svgs: bool,
+) -> Result<()> {
+    let qid = &entry.question_id;
+    let browser_media = browser
+        .extract_media(qid, download_svgs)
+        .await
+        .with_context(|| format!("Failed to extract media from {}", qid))?;
+
+    let caption_map = extract_caption_map(&browser_media.page_html);
+
+    let mut update = MediaUpdate::default();
+    let mut seen_svg_files = HashSet::new();
+    let mut seen_svg_metadata = HashSet::new();
+
+    if download_svgs {
+        let urls = dedupe_urls(browser_media.svg_urls);
+        let expected_svg_ids: Vec<String> = expected_media
+            .svgs
+            .iter()
+            .map(|svg| svg.svg_id.clone())
+            .collect();
+
+        let (assignments, leftovers) = assign_ids_to_urls(&expected_svg_ids, &urls, &caption_map);
+        let mut remaining_ids: VecDeque<String> = leftovers.into();
+
+        for assignment in assignments {
+            let path = download_svg(client, &entry.question_dir, &assignment.url).await?;
+
+            push_unique(&mut update.svgs, &mut seen_svg_files, path.clone());
+
+            if !assignment.id.is_empty() && seen_svg_metadata.insert(assignment.id.clone()) {
+                let mut metadata = svg_metadata_by_id
+                    .get(&assignment.id)
+                    .cloned()
+                    .unwrap_or_else(|| fallback_svg_metadata(&assignment.id));
+                metadata.file = path;
+                if metadata.caption.is_none() {
+                    metadata.caption = assignment.caption.clone();
                 }
+                if metadata.title.is_none() {
+                    metadata.title = assignment.caption;
+                }
+                update.metadata.svgs.push(metadata);
             }
+        }
+
+        for (index, svg_markup) in browser_media.inline_svgs.iter().enumerate() {
+            let path = save_inline_svg(&entry.question_dir, index, svg_markup)?;
+
+            push_unique(&mut update.svgs, &mut seen_svg_files, path.clone());
+
+            let svg_id = remaining_ids
+                .pop_front()
+                .unwrap_or_else(|| format!("inline_svg_{}", index + 1));
 
-            for leftover_id in remaining_ids {
-                if seen_svg_metadata.insert(leftover_id.clone()) {
-                    update
-                        .metadata
-                        .svgs
-                        .push(fallback_svg_metadata(&leftover_id));
+            if seen_svg_metadata.insert(svg_id.clone()) {
+                let mut metadata = svg_metadata_by_id
+                    .get(&svg_id)
+                    .cloned()
+                    .unwrap_or_else(|| fallback_svg_metadata(&svg_id));
+                metadata.file = path;
+                if metadata.title.is_none() {
+                    metadata.title = extract_inline_svg_title(svg_markup);
                 }
+                update.metadata.svgs.push(metadata);
             }
         }
 
-        if update.svgs.is_empty() && update.metadata.is_empty() {
-            continue;
+        for leftover_id in remaining_ids {
+            if seen_svg_metadata.insert(leftover_id.clone()) {
+                update
+                    .metadata
+                    .svgs
+                    .push(fallback_svg_metadata(&leftover_id));
+            }
         }
+    }
 
-        if let Err(err) = update_question_json(&entry.json_path, &update) {
-            warn!("Failed to update {}: {}", qid, err);
-        }
+    if update.svgs.is_empty() && update.metadata.is_empty() {
+        return Ok(());
     }
 
+    if let Err(err) = update_question_json(&entry.json_path, &update) {
+        warn!("Failed to update {}: {}", qid, err);
+    }
     Ok(())
 }
 
+/// Dry-run plan for the browser step: each discovery-expected SVG of the target questions
+/// and whether `media_metadata` already records a file for it on disk.
+pub fn plan_svg_download(
+    data_dir: &str,
+    discovery_file: &str,
+    question_id: Option<&str>,
+) -> Result<MediaPlan> {
+    let discovery_path = Path::new(discovery_file);
+    let results = DiscoveryResults::load_from_file(discovery_path).with_context(|| {
+        format!(
+            "Failed to read discovery results from {}",
+            discovery_path.display()
+        )
+    })?;
+    let svg_ids: HashSet<String> = results
+        .questions
+        .iter()
+        .filter(|(_, media)| !media.svgs.is_empty())
+        .map(|(question_id, _)| question_id.clone())
+        .collect();
+    let targets = select_targets(question_id, &svg_ids, "discovery file")?;
+
+    let entry_map = collect_question_entry_map(data_dir)?;
+    let mut plan = MediaPlan {
+        questions: targets.len(),
+        items: Vec::new(),
+    };
+    for qid in &targets {
+        let (Some(expected), Some(entry)) = (results.questions.get(qid), entry_map.get(qid)) else {
+            warn!(
+                "{} has no discovery entry or question directory; not planned",
+                qid
+            );
+            continue;
+        };
+        let recorded = recorded_svg_files(&entry.json_path);
+        for svg in &expected.svgs {
+            let on_disk = recorded
+                .get(&svg.svg_id)
+                .is_some_and(|file| entry.question_dir.join(file).exists());
+            plan.items.push(PlannedMedia {
+                question_id: qid.clone(),
+                kind: "svg",
+                media_id: svg.svg_id.clone(),
+                url: None,
+                on_disk,
+                bytes: None,
+            });
+        }
+    }
+    Ok(plan)
+}
+
+/// SVG files recorded in a question's `media_metadata`, keyed by SVG ID.
+fn recorded_svg_files(json_path: &Path) -> HashMap<String, String> {
+    let Ok(text) = std::fs::read_to_string(json_path) else {
+        return HashMap::new();
+    };
+    let Ok(json) = serde_json::from_str::<Value>(&text) else {
+        return HashMap::new();
+    };
+    json.pointer("/media_metadata/svgs")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|svg| {
+            Some((
+                svg.get("svg_id")?.as_str()?.to_string(),
+                svg.get("file")?.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
 struct AssignedUrl {
     id: String,
     url: String,
@@ -265,6 +399,7 @@ fn fallback_svg_metadata(svg_id: &str) -> SvgMetadata {
         file: None,
         title: None,
         caption: None,
+        png: None,
     }
 }
 
@@ -272,7 +407,7 @@ async fn load_svg_metadata(
     client: &Client,
     base_url: &str,
 ) -> Result<HashMap<String, SvgMetadata>> {
-    let metadata = super::fetch_content_metadata(client, base_url).await?;
+    let metadata = super::fetch_content_metadata_cached(client, base_url).await?;
     let mut svgs_by_id = HashMap::new();
 
     for_each_metadata_item(&metadata, "svgs", |fallback_id, svg| {
@@ -299,6 +434,7 @@ fn insert_svg_metadata(
             file: None,
             title,
             caption,
+            png: None,
         },
     );
 }
@@ -356,24 +492,69 @@ fn extract_figcaption(block: &str) -> Option<String> {
     }
 }
 
+/// Attempts per SVG before a non-SVG response (e.g. an HTML error page) is given up on.
+const SVG_DOWNLOAD_ATTEMPTS: usize = 2;
+/// Leading bytes of a download checked by [`looks_like_svg`].
+const SVG_SNIFF_BYTES: u64 = 512;
+
 async fn download_svg(client: &Client, question_dir: &Path, url: &str) -> Result<Option<String>> {
     let filename = filename_from_url(url);
     let dest_dir = question_dir.join("svgs");
     std::fs::create_dir_all(&dest_dir)?;
     let dest_path = dest_dir.join(&filename);
 
-    if !dest_path.exists() {
-        let bytes = client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .bytes()
-            .await?;
-        std::fs::write(&dest_path, bytes)?;
+    if dest_path.exists() {
+        let existing = std::fs::read(&dest_path)?;
+        if looks_like_svg(None, &existing) {
+            return Ok(Some(relative_path("svgs", &filename)));
+        }
+        warn!("Removing non-SVG file {}", dest_path.display());
+        std::fs::remove_file(&dest_path)?;
     }
 
-    Ok(Some(relative_path("svgs", &filename)))
+    for attempt in 1..=SVG_DOWNLOAD_ATTEMPTS {
+        super::rate_limit::acquire().await;
+        let response = crate::http::send_download(client, url).await?;
+        let response = crate::http::ensure_not_redirect(response)?.error_for_status()?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let (part, _) = crate::http::stream_to_part(response, &dest_path).await?;
+        let mut head = Vec::new();
+        std::fs::File::open(&part)?
+            .take(SVG_SNIFF_BYTES)
+            .read_to_end(&mut head)?;
+
+        if looks_like_svg(content_type.as_deref(), &head) {
+            std::fs::rename(&part, &dest_path)?;
+            return Ok(Some(relative_path("svgs", &filename)));
+        }
+        std::fs::remove_file(&part)?;
+        warn!(
+            "Response for {} is not an SVG (content-type: {}), attempt {}/{}",
+            url,
+            content_type.as_deref().unwrap_or("none"),
+            attempt,
+            SVG_DOWNLOAD_ATTEMPTS
+        );
+    }
+
+    Ok(None)
+}
+
+/// Accept an `image/svg+xml` content type, or a body that starts like an SVG document.
+fn looks_like_svg(content_type: Option<&str>, body: &[u8]) -> bool {
+    if content_type.is_some_and(|value| value.to_ascii_lowercase().starts_with("image/svg+xml")) {
+        return true;
+    }
+    let head = String::from_utf8_lossy(&body[..body.len().min(SVG_SNIFF_BYTES as usize)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if head.starts_with("<svg") {
+        return true;
+    }
+    head.starts_with("<?xml") && head.contains("<svg")
 }
 
 fn save_inline_svg(question_dir: &Path, index: usize, svg: &str) -> Result<Option<String>> {
@@ -400,3 +581,119 @@ fn filename_from_url(url: &str) -> String {
 fn relative_path(dir: &str, filename: &str) -> String {
     Path::new(dir).join(filename).to_string_lossy().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `/html.svg` as an HTML error page and `/real.svg` as an SVG (no content type).
+    async fn spawn_svg_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let read = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..read]);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/");
+                    let (content_type, body) = if path == "/real.svg" {
+                        (
+                            "application/octet-stream",
+                            "<?xml version=\"1.0\"?><svg></svg>",
+                        )
+                    } else {
+                        ("text/html", "<!DOCTYPE html><html>Sign in</html>")
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n{}",
+                        content_type,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_html_response_is_not_saved_as_svg() {
+        let base_url = spawn_svg_server().await;
+        let temp = tempfile::tempdir().unwrap();
+        let client = Client::new();
+
+        let path = download_svg(&client, temp.path(), &format!("{}/html.svg", base_url))
+            .await
+            .unwrap();
+        assert_eq!(path, None);
+        assert!(!temp.path().join("svgs").join("html.svg").exists());
+
+        let path = download_svg(&client, temp.path(), &format!("{}/real.svg", base_url))
+            .await
+            .unwrap();
+        assert_eq!(path.as_deref(), Some("svgs/real.svg"));
+        assert!(
+            std::fs::read_to_string(temp.path().join("svgs").join("real.svg"))
+                .unwrap()
+                .contains("<svg")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_work_queue_hands_each_item_to_one_worker() {
+        let handled = Mutex::new(Vec::new());
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        drain_work_queue(3, (0..20).collect(), |worker, item: usize| {
+            let (handled, in_flight, peak) = (&handled, &in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                handled.lock().unwrap().push((worker, item));
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+
+        let handled = handled.into_inner().unwrap();
+        let mut items: Vec<usize> = handled.iter().map(|(_, item)| *item).collect();
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+        let workers: HashSet<usize> = handled.iter().map(|(worker, _)| *worker).collect();
+        assert_eq!(workers, HashSet::from([0, 1, 2]));
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+
+        let result = drain_work_queue(2, vec![1, 2, 3], |_, item: usize| async move {
+            anyhow::ensure!(item != 2, "page {} failed", item);
+            Ok(())
+        })
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_looks_like_svg_checks_content_type_and_body() {
+        assert!(looks_like_svg(Some("image/svg+xml; charset=utf-8"), b""));
+        assert!(looks_like_svg(
+            None,
+            b"  <svg xmlns=\"http://www.w3.org/2000/svg\"/>"
+        ));
+        assert!(!looks_like_svg(
+            Some("text/html"),
+            b"<html><body>404</body></html>"
+        ));
+        assert!(!looks_like_svg(None, b"<?xml version=\"1.0\"?><html/>"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Optional PNG rendering of downloaded SVGs (`--rasterize-svgs`).
+//!
+//! Each SVG listed in a question's `media.svgs` is rendered with `resvg` to a PNG
+//! beside it, and the PNG path is recorded on the matching `media_metadata.svgs` entry.
+
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+use super::asset_store::{collect_question_entries, QuestionEntry};
+
+pub const DEFAULT_SVG_DPI: f32 = 96.0;
+
+/// resvg lays out SVG user units at 96 DPI.
+const SVG_BASE_DPI: f32 = 96.0;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RasterizeSummary {
+    pub rendered: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl RasterizeSummary {
+    fn add(&mut self, other: RasterizeSummary) {
+        self.rendered += other.rendered;
+        self.skipped += other.skipped;
+        self.failed += other.failed;
+    }
+}
+
+/// Render every question's SVGs to PNG, running `workers` questions in parallel.
+pub async fn rasterize_svgs(data_dir: &str, dpi: f32, workers: usize) -> Result<RasterizeSummary> {
+    let entries = collect_question_entries(data_dir)?;
+    let mut results = stream::iter(entries)
+        .map(|entry| {
+            tokio::task::spawn_blocking(move || {
+                let result = rasterize_question(&entry, dpi);
+                (entry.question_id, result)
+            })
+        })
+        .buffer_unordered(workers.max(1));
+
+    let mut summary = RasterizeSummary::default();
+    while let Some(joined) = results.next().await {
+        let (question_id, result) = joined?;
+        match result {
+            Ok(question_summary) => summary.add(question_summary),
+            Err(err) => {
+                warn!("Failed to rasterize SVGs for {}: {:#}", question_id, err);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    info!(
+        "SVG rasterization: {} rendered, {} already present, {} failed",
+        summary.rendered, summary.skipped, summary.failed
+    );
+    Ok(summary)
+}
+
+fn rasterize_question(entry: &QuestionEntry, dpi: f32) -> Result<RasterizeSummary> {
+    let mut summary = RasterizeSummary::default();
+    let content = fs::read_to_string(&entry.json_path)
+        .with_context(|| format!("Failed to read {}", entry.json_path.display()))?;
+    let mut question: Value = serde_json::from_str(&content)?;
+
+    let svgs: Vec<String> = question
+        .pointer("/media/svgs")
+        .and_then(|svgs| svgs.as_array())
+        .map(|svgs| {
+            svgs.iter()
+                .filter_map(|svg| svg.as_str().map(|svg| svg.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if svgs.is_empty() {
+        return Ok(summary);
+    }
+
+    let mut changed = false;
+    for svg in svgs {
+        let png = Path::new(&svg)
+            .with_extension("png")
+            .to_string_lossy()
+            .to_string();
+        let svg_path = entry.question_dir.join(&svg);
+        let png_path = entry.question_dir.join(&png);
+        if is_up_to_date(&svg_path, &png_path) {
+            summary.skipped += 1;
+        } else {
+            match rasterize_svg(&svg_path, &png_path, dpi) {
+                Ok(_) => summary.rendered += 1,
+                Err(err) => {
+                    warn!("Skipping {} in {}: {:#}", svg, entry.question_id, err);
+                    summary.failed += 1;
+                    continue;
+                }
+            }
+        }
+        changed |= record_png(&mut question, &svg, &png);
+    }
+
+    if changed {
+        fs::write(&entry.json_path, crate::utils::to_json_string(&question)?)?;
+    }
+    Ok(summary)
+}
+
+/// A PNG newer than its SVG is kept, so re-runs only render new or changed SVGs.
+fn is_up_to_date(svg_path: &Path, png_path: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    match (modified(svg_path), modified(png_path)) {
+        (Some(svg), Some(png)) => png >= svg,
+        _ => false,
+    }
+}
+
+/// Set `png` on the `media_metadata.svgs` entry for `svg`; returns whether anything changed.
+fn record_png(question: &mut Value, svg: &str, png: &str) -> bool {
+    let Some(entries) = question
+        .pointer_mut("/media_metadata/svgs")
+        .and_then(|svgs| svgs.as_array_mut())
+    else {
+        return false;
+    };
+    let mut changed = false;
+    for entry in entries {
+        if entry.get("file").and_then(|file| file.as_str()) == Some(svg)
+            && entry.get("png").and_then(|file| file.as_str()) != Some(png)
+        {
+            entry["png"] = Value::String(png.to_string());
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Render `svg_path` to `png_path` at `dpi`, returning the PNG dimensions.
+pub fn rasterize_svg(svg_path: &Path, png_path: &Path, dpi: f32) -> Result<(u32, u32)> {
+    let data =
+        fs::read(svg_path).with_context(|| format!("Failed to read {}", svg_path.display()))?;
+    let options = resvg::usvg::Options {
+        dpi,
+        ..Default::default()
+    };
+    let tree = resvg::usvg::Tree::from_data(&data, &options).context("Invalid SVG")?;
+
+    let scale = dpi / SVG_BASE_DPI;
+    let size = tree.size();
+    let width = (size.width() * scale).ceil() as u32;
+    let height = (size.height() * scale).ceil() as u32;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .with_context(|| format!("Invalid PNG size {}x{}", width, height))?;
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .save_png(png_path)
+        .with_context(|| format!("Failed to write {}", png_path.display()))?;
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">
+        <rect width="20" height="10" fill="#c00"/>
+    </svg>"##;
+
+    fn png_dimensions(bytes: &[u8]) -> (u32, u32) {
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        let width = u32::from_be_bytes(bytes[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(bytes[20..24].try_into().unwrap());
+        (width, height)
+    }
+
+    #[test]
+    fn test_simple_svg_rasterizes_at_requested_dpi() {
+        let temp = tempfile::tempdir().unwrap();
+        let svg_path = temp.path().join("shape.svg");
+        let png_path = temp.path().join("shape.png");
+        fs::write(&svg_path, SIMPLE_SVG).unwrap();
+
+        assert_eq!(
+            rasterize_svg(&svg_path, &png_path, 192.0).unwrap(),
+            (40, 20)
+        );
+        let png = fs::read(&png_path).unwrap();
+        assert!(png.len() > 24);
+        assert_eq!(png_dimensions(&png), (40, 20));
+
+        fs::write(&svg_path, "<html>not an svg</html>").unwrap();
+        assert!(rasterize_svg(&svg_path, &png_path, 96.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rasterize_svgs_records_png_in_metadata() {
+        let temp = tempfile::tempdir().unwrap();
+        let question_dir = temp.path().join("cv").join("cvmcq24001");
+        fs::create_dir_all(question_dir.join("svgs")).unwrap();
+        fs::write(question_dir.join("svgs/shape.svg"), SIMPLE_SVG).unwrap();
+        fs::write(question_dir.join("svgs/broken.svg"), "<html/>").unwrap();
+        let question = serde_json::json!({
+            "question_id": "cvmcq24001",
+            "media": { "svgs": ["svgs/shape.svg", "svgs/broken.svg"] },
+            "media_metadata": { "svgs": [{ "svg_id": "cvsvg001", "file": "svgs/shape.svg" }] }
+        });
+        let json_path = question_dir.join("cvmcq24001.json");
+        fs::write(&json_path, question.to_string()).unwrap();
+
+        let summary = rasterize_svgs(temp.path().to_str().unwrap(), DEFAULT_SVG_DPI, 2)
+            .await
+            .unwrap();
+        assert_eq!((summary.rendered, summary.failed), (1, 1));
+        assert!(question_dir.join("svgs/shape.png").exists());
+
+        let updated: Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(
+            updated["media_metadata"]["svgs"][0]["png"],
+            "svgs/shape.png"
+        );
+    }
+}
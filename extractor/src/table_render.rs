@@ -1,10 +1,25 @@
 use serde_json::Value;
 
-pub fn render_table_html(value: &Value) -> String {
-    let html = render_node(value);
+/// Render table JSON to indented HTML, with `caption` (the table title HTML) placed in
+/// the table's `<caption>` so the saved file is readable on its own.
+pub fn render_captioned_table_html(value: &Value, caption: Option<&str>) -> String {
+    let mut html = render_node(value);
+    let caption = caption.map(str::trim).filter(|caption| !caption.is_empty());
+    if let (Some(caption), Some(open_end)) = (caption, table_open_tag_end(&html)) {
+        html.insert_str(open_end, &format!("<caption>{}</caption>", caption));
+    }
     pretty_format_html(&html)
 }
 
+/// Byte offset just past the first `<table ...>` opening tag.
+fn table_open_tag_end(html: &str) -> Option<usize> {
+    let start = html
+        .match_indices("<table")
+        .map(|(start, _)| start)
+        .find(|start| matches!(html.as_bytes().get(start + 6), Some(b'>' | b' ')))?;
+    html[start..].find('>').map(|offset| start + offset + 1)
+}
+
 pub fn render_node(value: &Value) -> String {
     match value {
         Value::String(text) => escape_html(text),
@@ -44,7 +59,7 @@ fn render_attrs(attrs: Option<&Value>) -> String {
             pairs.push((key, val_str));
         }
     }
-    pairs.sort_by(|(left, _), (right, _)| left.cmp(right));
+    pairs.sort_by_key(|(key, _)| *key);
     pairs
         .into_iter()
         .map(|(key, val_str)| format!(" {}=\"{}\"", key, escape_html(val_str)))
@@ -148,3 +163,27 @@ fn is_block_tag(tag: &str) -> bool {
         "table" | "thead" | "tbody" | "tfoot" | "tr" | "th" | "td" | "caption"
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caption_inserted_inside_table() {
+        let table = serde_json::json!({
+            "tagName": "div",
+            "children": [{
+                "tagName": "table",
+                "attrs": { "class": "tbl" },
+                "children": [{ "tagName": "tr", "children": [{ "tagName": "th", "children": ["Drug"] }] }]
+            }]
+        });
+        let html = render_captioned_table_html(&table, Some(" Table 1. <i>Agents</i> "));
+        assert!(html.contains("<table class=\"tbl\">\n  <caption>\n    Table 1.<i>"));
+        assert!(html.contains("</caption>\n  <tr>"));
+        assert_eq!(
+            render_captioned_table_html(&table, Some("  ")),
+            pretty_format_html(&render_node(&table))
+        );
+    }
+}
@@ -1,8 +1,17 @@
 //! Shared helper utilities for CLI and extraction workflows.
 
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use tracing::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, info, warn};
+
+static FOLLOW_SYMLINKS: AtomicBool = AtomicBool::new(false);
+static COMPACT_JSON: AtomicBool = AtomicBool::new(false);
 
 pub fn parse_env<T: FromStr>(key: &str, default: T) -> T {
     env::var(key)
@@ -11,6 +20,169 @@ pub fn parse_env<T: FromStr>(key: &str, default: T) -> T {
         .unwrap_or(default)
 }
 
+/// The first `limit` IDs in sorted order (`--limit`), so sample runs are repeatable.
+pub(crate) fn first_sorted_ids(ids: impl IntoIterator<Item = String>, limit: usize) -> Vec<String> {
+    let mut ids: Vec<String> = ids.into_iter().collect();
+    ids.sort();
+    ids.truncate(limit);
+    ids
+}
+
 pub fn log_progress(current: usize, total: usize, message: &str) {
     info!("\n[{}/{}] {}", current, total, message);
 }
+
+/// Set whether directory scans follow symlinked directories (`--follow-symlinks`).
+pub(crate) fn configure_follow_symlinks(follow: bool) {
+    FOLLOW_SYMLINKS.store(follow, Ordering::Relaxed);
+}
+
+/// Write question and discovery JSON without indentation (`--compact-json`).
+pub(crate) fn configure_compact_json(compact: bool) {
+    COMPACT_JSON.store(compact, Ordering::Relaxed);
+}
+
+/// Serialize a question or discovery file: pretty-printed by default, single-line with
+/// `--compact-json`. Readers parse either form.
+pub(crate) fn to_json_string<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    json_string(value, COMPACT_JSON.load(Ordering::Relaxed))
+}
+
+fn json_string<T: Serialize + ?Sized>(value: &T, compact: bool) -> serde_json::Result<String> {
+    if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    }
+}
+
+/// Lists subdirectories for system/question scans without double-counting.
+///
+/// Symlinked directories are skipped unless following is enabled; when following,
+/// each canonical target is visited at most once, which also breaks symlink cycles.
+pub(crate) struct DirScanner {
+    follow_symlinks: bool,
+    visited: HashSet<PathBuf>,
+}
+
+impl DirScanner {
+    pub(crate) fn new(follow_symlinks: bool) -> Self {
+        Self {
+            follow_symlinks,
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Scanner honoring the configured `--follow-symlinks` setting.
+    pub(crate) fn from_config() -> Self {
+        Self::new(FOLLOW_SYMLINKS.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn subdirs(&mut self, path: &Path) -> Result<Vec<PathBuf>> {
+        let mut candidates = Vec::new();
+        for entry in fs::read_dir(path).context("Failed to read directory")? {
+            let entry = entry.context("Failed to read directory entry")?;
+            let is_symlink = entry
+                .file_type()
+                .context("Failed to read directory entry type")?
+                .is_symlink();
+            candidates.push((is_symlink, entry.path()));
+        }
+        // Real directories first, so a symlink never shadows the directory it points at
+        candidates.sort();
+
+        let mut dirs = Vec::new();
+        for (is_symlink, entry_path) in candidates {
+            if is_symlink && !self.follow_symlinks {
+                debug!("Skipping symlinked path {}", entry_path.display());
+                continue;
+            }
+            if !entry_path.is_dir() {
+                continue;
+            }
+
+            let canonical = match fs::canonicalize(&entry_path) {
+                Ok(canonical) => canonical,
+                Err(err) => {
+                    warn!("Skipping unresolvable {}: {}", entry_path.display(), err);
+                    continue;
+                }
+            };
+            if !self.visited.insert(canonical) {
+                warn!(
+                    "Skipping {}: directory already scanned via another path",
+                    entry_path.display()
+                );
+                continue;
+            }
+
+            dirs.push(entry_path);
+        }
+
+        Ok(dirs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_question_dir_is_not_double_counted() {
+        let temp = tempfile::tempdir().unwrap();
+        let system = temp.path().join("cv");
+        fs::create_dir_all(system.join("cvmcq24001")).unwrap();
+        std::os::unix::fs::symlink(system.join("cvmcq24001"), system.join("cvmcq24999")).unwrap();
+
+        let skipped = DirScanner::new(false).subdirs(&system).unwrap();
+        assert_eq!(skipped, vec![system.join("cvmcq24001")]);
+
+        let followed = DirScanner::new(true).subdirs(&system).unwrap();
+        assert_eq!(followed, vec![system.join("cvmcq24001")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop_is_visited_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let system = temp.path().join("cv");
+        fs::create_dir_all(&system).unwrap();
+        // cv/loop -> cv: following it would otherwise rescan the system forever
+        std::os::unix::fs::symlink(&system, system.join("loop")).unwrap();
+        fs::create_dir_all(system.join("cvmcq24001")).unwrap();
+
+        let mut scanner = DirScanner::new(true);
+        let systems = scanner.subdirs(temp.path()).unwrap();
+        assert_eq!(systems, vec![system.clone()]);
+        let questions = scanner.subdirs(&system).unwrap();
+        assert_eq!(questions, vec![system.join("cvmcq24001")]);
+
+        // A symlink only to a target outside the scan is still followed
+        let external = tempfile::tempdir().unwrap();
+        fs::create_dir_all(external.path().join("cvmcq24002")).unwrap();
+        std::os::unix::fs::symlink(
+            external.path().join("cvmcq24002"),
+            system.join("cvmcq24002"),
+        )
+        .unwrap();
+        let questions = DirScanner::new(true).subdirs(&system).unwrap();
+        assert!(questions.contains(&system.join("cvmcq24002")));
+    }
+
+    #[test]
+    fn test_compact_json_round_trips_like_pretty() {
+        let value = serde_json::json!({ "question_id": "cvmcq24001", "options": ["A", "B"] });
+        let pretty = json_string(&value, false).unwrap();
+        let compact = json_string(&value, true).unwrap();
+        assert!(pretty.contains('\n'));
+        assert_eq!(
+            compact,
+            r#"{"question_id":"cvmcq24001","options":["A","B"]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&pretty).unwrap()
+        );
+    }
+}
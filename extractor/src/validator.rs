@@ -1,15 +1,42 @@
 use crate::config;
+use crate::extractor::checkpoint_dir;
 use crate::models::DiscoveryMetadataCollection;
+use crate::utils::DirScanner;
 use anyhow::Result;
 /// Validation module for verifying extracted MKSAP data
 /// This module scans the mksap_data folder and verifies that extracted questions
 /// match the specification structure and contain required fields
+use regex::Regex;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 use tracing::{error, warn};
 
+/// Found/discovered ratio at which a system counts as nearly complete.
+pub(crate) const NEAR_COMPLETE_THRESHOLD: f64 = 0.9;
+
+/// How far a system's extraction has got relative to what discovery expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Completion {
+    Complete,
+    NearlyComplete,
+    Partial,
+    Missing,
+}
+
+impl Completion {
+    pub(crate) fn of(found: usize, expected: usize, threshold: f64) -> Self {
+        match (found, expected) {
+            (f, e) if f >= e => Completion::Complete,
+            (f, e) if f > 0 && (f as f64) >= (e as f64 * threshold) => Completion::NearlyComplete,
+            (f, _) if f > 0 => Completion::Partial,
+            _ => Completion::Missing,
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ValidationResult {
     pub total_questions: usize,
@@ -19,6 +46,13 @@ pub struct ValidationResult {
     pub missing_json: Vec<String>,
     pub parse_errors: Vec<String>,
     pub schema_invalid: Vec<String>,
+    /// Non-empty `question_stem` but no `options`: almost always a transform miss, so these
+    /// count as invalid and become re-extraction targets.
+    pub missing_options: Vec<String>,
+    /// Soft warning: critique is empty or placeholder text (question still counts as valid).
+    pub placeholder_critiques: Vec<String>,
+    /// Questions whose `media` paths do not exist on disk (only with `--check-media`).
+    pub missing_media: Vec<(String, Vec<String>)>,
     pub systems_verified: Vec<SystemValidation>,
 }
 
@@ -33,6 +67,33 @@ pub struct SystemValidation {
     pub issues: Vec<String>,
 }
 
+impl ValidationResult {
+    /// Add every configured organ system that has no discovery metadata yet, with zero
+    /// counts, so completeness audits list all systems (`--include-empty-systems`).
+    pub fn include_empty_systems(&mut self) {
+        for system in config::init_organ_systems() {
+            if self
+                .systems_verified
+                .iter()
+                .any(|verified| verified.system_id == system.id)
+            {
+                continue;
+            }
+            self.systems_verified.push(SystemValidation {
+                system_id: system.id,
+                system_name: system.name,
+                found_count: 0,
+                discovered_count: 0,
+                discovery_timestamp: String::new(),
+                valid_count: 0,
+                issues: Vec::new(),
+            });
+        }
+        self.systems_verified
+            .sort_by(|a, b| a.system_id.cmp(&b.system_id));
+    }
+}
+
 pub struct DataValidator;
 
 enum ValidationOutcome {
@@ -40,8 +101,32 @@ enum ValidationOutcome {
     SchemaInvalid,
     MissingJson,
     ParseError(String),
+    MissingOptions,
+    MissingMediaFiles(Vec<String>),
 }
 
+/// Issues worth a curator's attention that do not make a question invalid.
+#[derive(Debug, PartialEq, Eq)]
+enum SoftWarning {
+    PlaceholderCritique,
+}
+
+/// Critique text (lowercased, trailing punctuation removed) known to stand in for a real critique.
+const PLACEHOLDER_CRITIQUES: &[&str] = &[
+    "critique not available",
+    "critique unavailable",
+    "no critique",
+    "no critique available",
+    "not available",
+    "n/a",
+    "na",
+    "none",
+    "tbd",
+    "todo",
+    "coming soon",
+    "placeholder",
+];
+
 impl DataValidator {
     fn normalize_system_id(system_id: &str) -> &str {
         system_id
@@ -53,6 +138,25 @@ impl DataValidator {
 
     /// Scan the entire mksap_data directory and validate all extracted questions
     pub fn validate_extraction(mksap_data_dir: &str) -> Result<ValidationResult> {
+        Self::validate_extraction_with_media(mksap_data_dir, false)
+    }
+
+    /// Like `validate_extraction`, optionally also checking that every path listed in
+    /// each question's `media` object exists under its question directory.
+    pub fn validate_extraction_with_media(
+        mksap_data_dir: &str,
+        check_media: bool,
+    ) -> Result<ValidationResult> {
+        Self::validate_extraction_excluding(mksap_data_dir, check_media, &[])
+    }
+
+    /// Like `validate_extraction_with_media`, leaving out the `excluded` system codes
+    /// entirely (their questions are not scanned and they get no per-system entry).
+    pub fn validate_extraction_excluding(
+        mksap_data_dir: &str,
+        check_media: bool,
+        excluded: &[String],
+    ) -> Result<ValidationResult> {
         let mut result = ValidationResult {
             total_questions: 0,
             valid_questions: 0,
@@ -61,6 +165,9 @@ impl DataValidator {
             missing_json: Vec::new(),
             parse_errors: Vec::new(),
             schema_invalid: Vec::new(),
+            missing_options: Vec::new(),
+            placeholder_critiques: Vec::new(),
+            missing_media: Vec::new(),
             systems_verified: Vec::new(),
         };
         let path = Path::new(mksap_data_dir);
@@ -70,9 +177,7 @@ impl DataValidator {
         }
 
         // Load discovery metadata
-        let metadata_path = Path::new(mksap_data_dir)
-            .join(".checkpoints")
-            .join("discovery_metadata.json");
+        let metadata_path = checkpoint_dir(mksap_data_dir).join("discovery_metadata.json");
 
         let discovery_metadata = if metadata_path.exists() {
             let contents = fs::read_to_string(&metadata_path)?;
@@ -105,21 +210,19 @@ impl DataValidator {
         }
 
         // Iterate through all organ systems
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let system_path = entry.path();
-
-            if !system_path.is_dir() {
-                continue;
-            }
-
+        let mut scanner = DirScanner::from_config();
+        for system_path in scanner.subdirs(path)? {
             let system_id = system_path
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("unknown")
                 .to_string();
             let system_id = Self::normalize_system_id(&system_id).to_string();
-            if system_id == ".checkpoints" {
+            // Hidden dirs (.checkpoints, .media_store) and badges/ hold no questions
+            if system_id.starts_with('.') || system_id == crate::badges::BADGES_DIR_NAME {
+                continue;
+            }
+            if excluded.contains(&system_id) {
                 continue;
             }
 
@@ -135,14 +238,7 @@ impl DataValidator {
             };
 
             // Scan all questions in this system
-            for question_entry in fs::read_dir(&system_path)? {
-                let question_entry = question_entry?;
-                let question_path = question_entry.path();
-
-                if !question_path.is_dir() {
-                    continue;
-                }
-
+            for question_path in scanner.subdirs(&system_path)? {
                 let question_id = question_path
                     .file_name()
                     .and_then(|n| n.to_str())
@@ -153,7 +249,16 @@ impl DataValidator {
                 result.total_questions += 1;
 
                 // Validate this question
-                match Self::validate_question_detailed(&question_path, &question_id) {
+                let (outcome, soft_warnings) =
+                    Self::validate_question_detailed(&question_path, &question_id, check_media);
+                for soft_warning in soft_warnings {
+                    match soft_warning {
+                        SoftWarning::PlaceholderCritique => {
+                            result.placeholder_critiques.push(question_id.clone());
+                        }
+                    }
+                }
+                match outcome {
                     ValidationOutcome::Valid => {
                         result.valid_questions += 1;
                         system_validation.valid_count += 1;
@@ -170,11 +275,27 @@ impl DataValidator {
                         result.invalid_questions.push(question_id.clone());
                         result.parse_errors.push(question_id.clone());
                         warn!("Question {} parse error: {}", question_id, error);
+                        crate::events::emit("parse_failed", Some(&question_id), &error);
+                    }
+                    ValidationOutcome::MissingOptions => {
+                        crate::events::emit(
+                            "missing_options",
+                            Some(&question_id),
+                            "question_stem present but options empty",
+                        );
+                        result.invalid_questions.push(question_id.clone());
+                        result.missing_options.push(question_id.clone());
+                    }
+                    ValidationOutcome::MissingMediaFiles(paths) => {
+                        crate::events::emit("missing_media", Some(&question_id), paths.join(", "));
+                        result.invalid_questions.push(question_id.clone());
+                        result.missing_media.push((question_id.clone(), paths));
                     }
                 }
             }
         }
 
+        system_map.retain(|system_id, _| !excluded.contains(system_id));
         let mut systems: Vec<SystemValidation> = system_map.into_values().collect();
         systems.sort_by(|a, b| a.system_id.cmp(&b.system_id));
         for system_validation in systems.iter_mut() {
@@ -207,9 +328,11 @@ impl DataValidator {
 
     /// Validate a single question's JSON structure
     pub fn validate_question(question_path: &Path, question_id: &str) -> Result<bool> {
-        match Self::validate_question_detailed(question_path, question_id) {
+        match Self::validate_question_detailed(question_path, question_id, false).0 {
             ValidationOutcome::Valid => Ok(true),
-            ValidationOutcome::SchemaInvalid => Ok(false),
+            ValidationOutcome::SchemaInvalid
+            | ValidationOutcome::MissingOptions
+            | ValidationOutcome::MissingMediaFiles(_) => Ok(false),
             ValidationOutcome::MissingJson => Err(anyhow::anyhow!(
                 "Missing JSON file: {}",
                 question_path
@@ -220,24 +343,36 @@ impl DataValidator {
         }
     }
 
-    fn validate_question_detailed(question_path: &Path, question_id: &str) -> ValidationOutcome {
+    fn validate_question_detailed(
+        question_path: &Path,
+        question_id: &str,
+        check_media: bool,
+    ) -> (ValidationOutcome, Vec<SoftWarning>) {
         let json_file = question_path.join(format!("{}.json", question_id));
 
         // Check if JSON file exists
         if !json_file.exists() {
-            return ValidationOutcome::MissingJson;
+            return (ValidationOutcome::MissingJson, Vec::new());
         }
 
         // Parse and validate JSON structure
         let json_content = match fs::read_to_string(&json_file) {
             Ok(content) => content,
-            Err(e) => return ValidationOutcome::ParseError(e.to_string()),
+            Err(e) => return (ValidationOutcome::ParseError(e.to_string()), Vec::new()),
         };
         let value: Value = match serde_json::from_str(&json_content) {
             Ok(parsed) => parsed,
-            Err(e) => return ValidationOutcome::ParseError(e.to_string()),
+            Err(e) => return (ValidationOutcome::ParseError(e.to_string()), Vec::new()),
         };
 
+        let mut soft_warnings = Vec::new();
+        if let Some(critique) = value.get("critique").and_then(|c| c.as_str()) {
+            if Self::is_placeholder_critique(critique) {
+                warn!("Question {} has empty or placeholder critique", question_id);
+                soft_warnings.push(SoftWarning::PlaceholderCritique);
+            }
+        }
+
         // Check required fields per specification
         let required_fields = [
             "question_id",
@@ -280,11 +415,85 @@ impl DataValidator {
             }
         }
 
-        if all_valid {
-            ValidationOutcome::Valid
-        } else {
-            ValidationOutcome::SchemaInvalid
+        if !all_valid {
+            return (ValidationOutcome::SchemaInvalid, soft_warnings);
+        }
+
+        if Self::has_stem_without_options(&value) {
+            warn!(
+                "Question {} has a question_stem but no options (likely extraction failure)",
+                question_id
+            );
+            return (ValidationOutcome::MissingOptions, soft_warnings);
         }
+
+        if check_media {
+            let missing = Self::missing_media_files(&value, question_path);
+            if !missing.is_empty() {
+                warn!(
+                    "Question {} references {} missing media file(s)",
+                    question_id,
+                    missing.len()
+                );
+                return (ValidationOutcome::MissingMediaFiles(missing), soft_warnings);
+            }
+        }
+
+        (ValidationOutcome::Valid, soft_warnings)
+    }
+
+    fn has_stem_without_options(value: &Value) -> bool {
+        let has_stem = value
+            .get("question_stem")
+            .and_then(|stem| stem.as_str())
+            .is_some_and(|stem| !stem.trim().is_empty());
+        let has_options = value
+            .get("options")
+            .and_then(|options| options.as_array())
+            .is_some_and(|options| !options.is_empty());
+        has_stem && !has_options
+    }
+
+    /// Paths in the `media` object that do not exist relative to the question directory.
+    fn missing_media_files(value: &Value, question_path: &Path) -> Vec<String> {
+        let mut paths = Vec::new();
+        if let Some(media) = value.get("media") {
+            Self::collect_strings(media, &mut paths);
+        }
+        paths
+            .into_iter()
+            .filter(|path| !path.is_empty() && !question_path.join(path).exists())
+            .collect()
+    }
+
+    fn collect_strings(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::String(text) => out.push(text.clone()),
+            Value::Array(items) => items
+                .iter()
+                .for_each(|item| Self::collect_strings(item, out)),
+            Value::Object(map) => map
+                .values()
+                .for_each(|item| Self::collect_strings(item, out)),
+            _ => {}
+        }
+    }
+
+    /// True when a critique is blank (ignoring HTML tags) or matches a known placeholder.
+    fn is_placeholder_critique(critique: &str) -> bool {
+        static TAG_RE: OnceLock<Regex> = OnceLock::new();
+        let tag_re = TAG_RE.get_or_init(|| Regex::new(r"<[^>]*>").unwrap());
+        let text = tag_re.replace_all(critique, " ");
+        let normalized = text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let normalized = normalized.trim_end_matches(['.', '!', ':']).trim();
+
+        normalized.is_empty()
+            || PLACEHOLDER_CRITIQUES.contains(&normalized)
+            || normalized.starts_with("lorem ipsum")
     }
 
     fn validate_required_fields(value: &Value, question_id: &str, required: &[&str]) -> bool {
@@ -320,9 +529,17 @@ impl DataValidator {
         report.push_str(&format!("Missing JSON: {}\n", result.missing_json.len()));
         report.push_str(&format!("Parse Errors: {}\n", result.parse_errors.len()));
         report.push_str(&format!(
-            "Schema Invalid: {}\n\n",
+            "Schema Invalid: {}\n",
             result.schema_invalid.len()
         ));
+        report.push_str(&format!(
+            "Stem Without Options: {}\n",
+            result.missing_options.len()
+        ));
+        report.push_str(&format!(
+            "Missing Media Files: {}\n\n",
+            result.missing_media.len()
+        ));
 
         report.push_str("=== PER-SYSTEM SUMMARY ===\n");
         for system in &result.systems_verified {
@@ -367,6 +584,35 @@ impl DataValidator {
             let mut schema_invalid = result.schema_invalid.clone();
             schema_invalid.sort();
             Self::append_issue_list(&mut report, "Schema Invalid", &schema_invalid);
+
+            let mut missing_options = result.missing_options.clone();
+            missing_options.sort();
+            Self::append_issue_list(
+                &mut report,
+                "Stem Without Options (re-extract)",
+                &missing_options,
+            );
+
+            if !result.missing_media.is_empty() {
+                let mut missing_media = result.missing_media.clone();
+                missing_media.sort();
+                report.push_str(&format!("Missing Media Files ({}):\n", missing_media.len()));
+                for (question_id, paths) in &missing_media {
+                    report.push_str(&format!("  - {}: {}\n", question_id, paths.join(", ")));
+                }
+            }
+        }
+
+        if !result.placeholder_critiques.is_empty() {
+            report.push_str("\n=== SOFT WARNINGS (RE-EXTRACTION CANDIDATES) ===\n");
+
+            let mut placeholder_critiques = result.placeholder_critiques.clone();
+            placeholder_critiques.sort();
+            Self::append_issue_list(
+                &mut report,
+                "Empty/Placeholder Critique",
+                &placeholder_critiques,
+            );
         }
 
         report
@@ -396,7 +642,7 @@ impl DataValidator {
             } else {
                 0.0
             };
-            let status = Self::determine_status(found, discovered, 0.9);
+            let status = Self::determine_status(found, discovered, NEAR_COMPLETE_THRESHOLD);
 
             comparison.push_str(&format!(
                 "{} {} ({}): {}/{} questions ({:.1}% of discovered)\n",
@@ -419,12 +665,179 @@ impl DataValidator {
         comparison
     }
 
+    /// Markdown version of `generate_report` plus the specification comparison, with each
+    /// system's discovery details (timestamp, hit rate, question types) when `discovery` is
+    /// available. Suited to pasting into a PR or issue.
+    pub fn generate_discovery_report(
+        result: &ValidationResult,
+        discovery: Option<&DiscoveryMetadataCollection>,
+    ) -> String {
+        use std::fmt::Write;
+
+        let mut report = String::new();
+        let _ = writeln!(
+            report,
+            "# MKSAP Data Validation Report
+"
+        );
+        if let Some(discovery) = discovery {
+            let _ = writeln!(
+                report,
+                "Discovery metadata last updated: {}\n",
+                discovery.last_updated
+            );
+        }
+
+        let _ = writeln!(report, "| Questions | Count |\n|---|---:|");
+        let _ = writeln!(report, "| Found | {} |", result.total_questions);
+        let _ = writeln!(report, "| Valid | {} |", result.valid_questions);
+        let _ = writeln!(report, "| Invalid | {} |", result.invalid_questions.len());
+        let _ = writeln!(report, "| Missing JSON | {} |", result.missing_json.len());
+        let _ = writeln!(report, "| Parse errors | {} |", result.parse_errors.len());
+        let _ = writeln!(
+            report,
+            "| Schema invalid | {} |",
+            result.schema_invalid.len()
+        );
+        let _ = writeln!(
+            report,
+            "| Stem without options | {} |",
+            result.missing_options.len()
+        );
+        let _ = writeln!(
+            report,
+            "| Missing media files | {} |",
+            result.missing_media.len()
+        );
+
+        let _ = writeln!(report, "\n## Systems\n");
+        let _ = writeln!(
+            report,
+            "| | System | Name | Found | Discovered | Valid | Coverage | Discovered at | Hit rate | Types |"
+        );
+        let _ = writeln!(report, "|---|---|---|---:|---:|---:|---:|---|---:|---|");
+        let mut systems = result.systems_verified.clone();
+        systems.sort_by(|a, b| a.system_id.cmp(&b.system_id));
+        let (mut total_found, mut total_discovered, mut total_valid) = (0, 0, 0);
+        for system in &systems {
+            let metadata = discovery.and_then(|discovery| {
+                discovery
+                    .systems
+                    .iter()
+                    .find(|metadata| metadata.system_code == system.system_id)
+            });
+            let found = system.found_count;
+            let discovered = system.discovered_count;
+            total_found += found;
+            total_discovered += discovered;
+            total_valid += system.valid_count;
+            let _ = writeln!(
+                report,
+                "| {} | `{}` | {} | {} | {} | {} | {} | {} | {} | {} |",
+                Self::determine_status(found, discovered, NEAR_COMPLETE_THRESHOLD),
+                Self::display_system_id(&system.system_id),
+                system.system_name,
+                found,
+                discovered,
+                system.valid_count,
+                Self::coverage(found, discovered),
+                system.discovery_timestamp,
+                metadata
+                    .map(|metadata| format!("{:.2}%", metadata.hit_rate * 100.0))
+                    .unwrap_or_default(),
+                metadata
+                    .map(|metadata| metadata.question_types_found.join(", "))
+                    .unwrap_or_default(),
+            );
+        }
+        let _ = writeln!(
+            report,
+            "| | **Total** | | **{}** | **{}** | **{}** | **{}** | | | |",
+            total_found,
+            total_discovered,
+            total_valid,
+            Self::coverage(total_found, total_discovered)
+        );
+
+        let issues: Vec<_> = systems
+            .iter()
+            .flat_map(|system| {
+                system
+                    .issues
+                    .iter()
+                    .map(move |issue| (&system.system_id, issue))
+            })
+            .collect();
+        if !issues.is_empty() {
+            let _ = writeln!(report, "\n## System Issues\n");
+            for (system_id, issue) in issues {
+                let _ = writeln!(report, "- `{}`: {}", system_id, issue);
+            }
+        }
+
+        if !result.invalid_questions.is_empty() {
+            let _ = writeln!(report, "\n## Invalid Questions");
+            Self::append_markdown_list(&mut report, "Missing JSON", &result.missing_json);
+            Self::append_markdown_list(&mut report, "Parse Errors", &result.parse_errors);
+            Self::append_markdown_list(&mut report, "Schema Invalid", &result.schema_invalid);
+            Self::append_markdown_list(
+                &mut report,
+                "Stem Without Options (re-extract)",
+                &result.missing_options,
+            );
+            if !result.missing_media.is_empty() {
+                let mut missing_media = result.missing_media.clone();
+                missing_media.sort();
+                let _ = writeln!(
+                    report,
+                    "\n### Missing Media Files ({})\n",
+                    missing_media.len()
+                );
+                for (question_id, paths) in &missing_media {
+                    let _ = writeln!(report, "- `{}`: {}", question_id, paths.join(", "));
+                }
+            }
+        }
+
+        if !result.placeholder_critiques.is_empty() {
+            let _ = writeln!(report, "\n## Soft Warnings (Re-extraction Candidates)");
+            Self::append_markdown_list(
+                &mut report,
+                "Empty/Placeholder Critique",
+                &result.placeholder_critiques,
+            );
+        }
+
+        report
+    }
+
+    fn coverage(found: usize, discovered: usize) -> String {
+        if discovered > 0 {
+            format!("{:.1}%", (found as f64 / discovered as f64) * 100.0)
+        } else {
+            "0.0%".to_string()
+        }
+    }
+
+    fn append_markdown_list(report: &mut String, label: &str, ids: &[String]) {
+        use std::fmt::Write;
+
+        if ids.is_empty() {
+            return;
+        }
+        let mut ids = ids.to_vec();
+        ids.sort();
+        let _ = writeln!(report, "\n### {} ({})\n", label, ids.len());
+        let ids: Vec<String> = ids.iter().map(|id| format!("`{}`", id)).collect();
+        let _ = writeln!(report, "{}", ids.join(", "));
+    }
+
     fn determine_status(found: usize, expected: usize, threshold: f64) -> &'static str {
-        match (found, expected) {
-            (f, e) if f >= e => "✓",
-            (f, e) if f > 0 && (f as f64) >= (e as f64 * threshold) => "◐",
-            (f, _) if f > 0 => "⚠",
-            _ => "✗",
+        match Completion::of(found, expected, threshold) {
+            Completion::Complete => "✓",
+            Completion::NearlyComplete => "◐",
+            Completion::Partial => "⚠",
+            Completion::Missing => "✗",
         }
     }
 
@@ -441,3 +854,189 @@ impl DataValidator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{write_question, write_question_with};
+
+    #[test]
+    fn test_placeholder_critique_flags_empty_and_boilerplate() {
+        assert!(DataValidator::is_placeholder_critique(""));
+        assert!(DataValidator::is_placeholder_critique("  <p> </p> "));
+        assert!(DataValidator::is_placeholder_critique(
+            "Critique not available."
+        ));
+        assert!(DataValidator::is_placeholder_critique("<p>N/A</p>"));
+        assert!(!DataValidator::is_placeholder_critique(
+            "<p>The most appropriate next step is echocardiography.</p>"
+        ));
+    }
+
+    #[test]
+    fn test_placeholder_critique_is_soft_warning() {
+        let temp = tempfile::tempdir().unwrap();
+        let critique = |text: &str| serde_json::json!({ "critique": text });
+        let placeholder = write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24001",
+            critique("Critique not available"),
+        );
+        let real = write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24002",
+            critique("Aspirin reduces risk."),
+        );
+
+        let (outcome, warnings) = DataValidator::validate_question_detailed(
+            placeholder.parent().unwrap(),
+            "cvmcq24001",
+            false,
+        );
+        assert!(matches!(outcome, ValidationOutcome::Valid));
+        assert_eq!(warnings, vec![SoftWarning::PlaceholderCritique]);
+
+        let (outcome, warnings) =
+            DataValidator::validate_question_detailed(real.parent().unwrap(), "cvmcq24002", false);
+        assert!(matches!(outcome, ValidationOutcome::Valid));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_stem_without_options_is_flagged() {
+        let temp = tempfile::tempdir().unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+        let path = write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24002",
+            serde_json::json!({ "options": [] }),
+        );
+
+        let (outcome, _) = DataValidator::validate_question_detailed(
+            &temp.path().join("cv").join("cvmcq24001"),
+            "cvmcq24001",
+            false,
+        );
+        assert!(matches!(outcome, ValidationOutcome::Valid));
+
+        let question_dir = path.parent().unwrap();
+        let (outcome, _) =
+            DataValidator::validate_question_detailed(question_dir, "cvmcq24002", false);
+        assert!(matches!(outcome, ValidationOutcome::MissingOptions));
+        assert!(!DataValidator::validate_question(question_dir, "cvmcq24002").unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validation_skips_symlinked_question_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let checkpoint_dir = temp.path().join(".checkpoints");
+        fs::create_dir_all(&checkpoint_dir).unwrap();
+        let mut collection = DiscoveryMetadataCollection::default();
+        collection.systems.push(crate::models::DiscoveryMetadata {
+            system_code: "cv".to_string(),
+            discovered_count: 1,
+            discovery_timestamp: String::new(),
+            candidates_tested: 1,
+            hit_rate: 1.0,
+            question_types_found: Vec::new(),
+        });
+        fs::write(
+            checkpoint_dir.join("discovery_metadata.json"),
+            serde_json::to_string(&collection).unwrap(),
+        )
+        .unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+        std::os::unix::fs::symlink(
+            temp.path().join("cv").join("cvmcq24001"),
+            temp.path().join("cv").join("cvmcq24999"),
+        )
+        .unwrap();
+
+        let result = DataValidator::validate_extraction(temp.path().to_str().unwrap()).unwrap();
+        assert_eq!(result.total_questions, 1);
+        assert_eq!(result.valid_questions, 1);
+    }
+
+    #[test]
+    fn test_include_empty_systems_lists_systems_without_directories() {
+        let temp = tempfile::tempdir().unwrap();
+        let checkpoint_dir = temp.path().join(".checkpoints");
+        fs::create_dir_all(&checkpoint_dir).unwrap();
+        let mut collection = DiscoveryMetadataCollection::default();
+        collection.systems.push(crate::models::DiscoveryMetadata {
+            system_code: "cv".to_string(),
+            discovered_count: 1,
+            discovery_timestamp: String::new(),
+            candidates_tested: 1,
+            hit_rate: 1.0,
+            question_types_found: Vec::new(),
+        });
+        fs::write(
+            checkpoint_dir.join("discovery_metadata.json"),
+            serde_json::to_string(&collection).unwrap(),
+        )
+        .unwrap();
+        write_question(temp.path(), "cv", "cvmcq24001");
+
+        let mut result = DataValidator::validate_extraction(temp.path().to_str().unwrap()).unwrap();
+        assert_eq!(result.systems_verified.len(), 1);
+        assert!(!DataValidator::generate_report(&result).contains(" en: "));
+
+        result.include_empty_systems();
+        assert_eq!(
+            result.systems_verified.len(),
+            config::init_organ_systems().len()
+        );
+        let report = DataValidator::generate_report(&result);
+        assert!(report.contains("✓ OK cv: 1/1 questions (1 valid"));
+        assert!(report.contains("✓ OK en: 0/0 questions (0 valid, 0.0% of discovered)"));
+
+        let markdown = DataValidator::generate_discovery_report(&result, Some(&collection));
+        assert!(markdown.starts_with("# MKSAP Data Validation Report\n"));
+        assert!(markdown.contains(
+            "| ✓ | `cv` | Cardiovascular Medicine | 1 | 1 | 1 | 100.0% |  | 100.00% |  |"
+        ));
+        assert!(markdown.contains("| `en` | "));
+        assert!(markdown.contains("| | **Total** | | **1** | **1** | **1** | **100.0%** | | | |"));
+        assert!(!markdown.contains("## Invalid Questions"));
+    }
+
+    #[test]
+    fn test_check_media_reports_missing_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let json_path = write_question_with(
+            temp.path(),
+            "cv",
+            "cvmcq24001",
+            serde_json::json!({
+                "media": {
+                    "images": ["figures/present.png", "figures/absent.png"],
+                    "tables": ["tables/absent.html"],
+                    "svgs": [],
+                    "videos": []
+                }
+            }),
+        );
+        let question_dir = json_path.parent().unwrap();
+        fs::create_dir_all(question_dir.join("figures")).unwrap();
+        fs::write(question_dir.join("figures").join("present.png"), b"png").unwrap();
+
+        let (outcome, _) =
+            DataValidator::validate_question_detailed(question_dir, "cvmcq24001", false);
+        assert!(matches!(outcome, ValidationOutcome::Valid));
+
+        let (outcome, _) =
+            DataValidator::validate_question_detailed(question_dir, "cvmcq24001", true);
+        match outcome {
+            ValidationOutcome::MissingMediaFiles(mut paths) => {
+                paths.sort();
+                assert_eq!(paths, vec!["figures/absent.png", "tables/absent.html"]);
+            }
+            _ => panic!("expected missing media files"),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Quick corruption check (`verify-json`): parse every `*.json` under the data directory and
+//! report syntax errors with their line and column, grouped by system. Much faster than
+//! `validate` since nothing beyond JSON syntax is checked.
+
+use anyhow::{bail, Context, Result};
+use serde::de::IgnoredAny;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::DirScanner;
+
+/// Group for files directly in the data directory rather than a system folder.
+const ROOT_GROUP: &str = "(data dir)";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonParseFailure {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VerifyJsonReport {
+    pub files_checked: usize,
+    /// Failures by system code, each list sorted by path.
+    pub failures: BTreeMap<String, Vec<JsonParseFailure>>,
+}
+
+impl VerifyJsonReport {
+    pub fn failure_count(&self) -> usize {
+        self.failures.values().map(Vec::len).sum()
+    }
+}
+
+/// Parse every JSON file under `output_dir` (or only `output_dir/<system>`).
+pub fn verify_json(output_dir: &str, system: Option<&str>) -> Result<VerifyJsonReport> {
+    let root = Path::new(output_dir);
+    let start = match system {
+        Some(system) => root.join(system),
+        None => root.to_path_buf(),
+    };
+    if !start.is_dir() {
+        bail!("Directory not found: {}", start.display());
+    }
+
+    let mut files = Vec::new();
+    collect_json_files(&start, &mut DirScanner::from_config(), &mut files)?;
+
+    let mut report = VerifyJsonReport {
+        files_checked: files.len(),
+        ..VerifyJsonReport::default()
+    };
+    for path in files {
+        let bytes =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let Err(err) = serde_json::from_slice::<IgnoredAny>(&bytes) else {
+            continue;
+        };
+        let group = system_group(root, &path);
+        let message = err.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(index) => message[..index].to_string(),
+            None => message,
+        };
+        report
+            .failures
+            .entry(group)
+            .or_default()
+            .push(JsonParseFailure {
+                path,
+                line: err.line(),
+                column: err.column(),
+                message,
+            });
+    }
+    for failures in report.failures.values_mut() {
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    Ok(report)
+}
+
+pub fn run_verify_json(output_dir: &str, system: Option<&str>) -> Result<()> {
+    if let Some(system) = system {
+        if crate::config::get_organ_system_by_id(system).is_none() {
+            bail!("Unknown system code: {}", system);
+        }
+    }
+    let report = verify_json(output_dir, system)?;
+
+    println!("\n=== JSON Syntax Check ===\n");
+    println!("Checked {} JSON files", report.files_checked);
+    for (group, failures) in &report.failures {
+        println!("\n{} ({} failed)", group, failures.len());
+        for failure in failures {
+            println!(
+                "  {}:{}:{}: {}",
+                failure.path.display(),
+                failure.line,
+                failure.column,
+                failure.message
+            );
+        }
+    }
+
+    let failed = report.failure_count();
+    if failed > 0 {
+        bail!(
+            "{} of {} JSON files failed to parse",
+            failed,
+            report.files_checked
+        );
+    }
+    println!("✅ Every JSON file parses\n");
+    Ok(())
+}
+
+fn collect_json_files(
+    dir: &Path,
+    scanner: &mut DirScanner,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    for subdir in scanner.subdirs(dir)? {
+        collect_json_files(&subdir, scanner, files)?;
+    }
+    Ok(())
+}
+
+/// The top-level folder under the data directory, i.e. the system code.
+fn system_group(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let mut components = relative.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => first.as_os_str().to_string_lossy().into_owned(),
+        _ => ROOT_GROUP.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_json_reports_line_and_column_by_system() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let write = |relative: &str, text: &str| {
+            let path = root.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        };
+        write(
+            "cv/cvmcq24001/cvmcq24001.json",
+            r#"{"question_id": "cvmcq24001"}"#,
+        );
+        write(
+            "cv/cvmcq24002/cvmcq24002.json",
+            "{\n  \"question_id\": \"cvmcq24002\"\n  \"x\": 1\n}",
+        );
+        write("en/enmcq24001/enmcq24001.json", "{\"question_id\": ");
+        write("media_discovery.json", "[1, 2,]");
+        write("cv/cvmcq24002/notes.txt", "not json");
+
+        let report = verify_json(root.to_str().unwrap(), None).unwrap();
+        assert_eq!(report.files_checked, 4);
+        assert_eq!(report.failure_count(), 3);
+        let groups: Vec<_> = report.failures.keys().map(String::as_str).collect();
+        assert_eq!(groups, vec!["(data dir)", "cv", "en"]);
+
+        let cv = &report.failures["cv"][0];
+        assert!(cv.path.ends_with("cvmcq24002.json"));
+        assert_eq!((cv.line, cv.column), (3, 3));
+        assert_eq!(cv.message, "expected `,` or `}`");
+
+        let report = verify_json(root.to_str().unwrap(), Some("cv")).unwrap();
+        assert_eq!(report.files_checked, 2);
+        assert_eq!(report.failures.keys().collect::<Vec<_>>(), vec!["cv"]);
+    }
+}
@@ -0,0 +1,185 @@
+//! Watch mode (`--watch --interval <dur>`): re-run extraction or media discovery on an
+//! interval to keep a mirror current.
+//!
+//! The run lock is held for the whole loop, so other mutating commands wait for the
+//! watcher to stop. Ctrl-C during a cycle finishes it gracefully; Ctrl-C while waiting
+//! ends the loop at once. An expired session ends it too, since every later cycle would
+//! fail the same way.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+use crate::error::{find_extract_error, ExtractError};
+
+/// Default `--interval` for `--watch`.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often the wait between cycles checks for a stop request.
+const STOP_POLL: Duration = Duration::from_millis(200);
+
+/// Parse an interval like `90`, `90s`, `15m`, `6h` or `1d` (bare numbers are seconds).
+pub fn parse_interval(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let seconds = match unit {
+        "" | "s" => number,
+        "m" => number.checked_mul(60)?,
+        "h" => number.checked_mul(60 * 60)?,
+        "d" => number.checked_mul(24 * 60 * 60)?,
+        _ => return None,
+    };
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+/// Run `cycle` once, or with an `interval` repeatedly until `should_stop` returns true.
+///
+/// Without an interval a failing cycle returns its error. In watch mode the error is
+/// logged and the next cycle still runs, so a transient outage does not end the watcher;
+/// only an [`ExtractError::Auth`] ends the loop with that error. Returns the number of
+/// cycles run.
+pub(crate) async fn repeat_until_stopped<F, Fut>(
+    interval: Option<Duration>,
+    should_stop: impl Fn() -> bool,
+    mut cycle: F,
+) -> Result<usize>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let Some(interval) = interval else {
+        cycle().await?;
+        return Ok(1);
+    };
+
+    let mut cycles = 0;
+    loop {
+        cycles += 1;
+        info!("=== WATCH CYCLE {} ===", cycles);
+        let started = Instant::now();
+        match cycle().await {
+            Ok(()) => info!(
+                "Watch cycle {} finished in {:.1}s",
+                cycles,
+                started.elapsed().as_secs_f64()
+            ),
+            Err(err) if matches!(find_extract_error(&err), Some(ExtractError::Auth(_))) => {
+                error!(
+                    "Watch cycle {} lost the session; stopping watch mode (log in again to resume)",
+                    cycles
+                );
+                return Err(err);
+            }
+            Err(err) => error!("Watch cycle {} failed: {:#}", cycles, err),
+        }
+
+        if should_stop() {
+            break;
+        }
+        info!("Next cycle in {}s (Ctrl-C to stop)", interval.as_secs());
+        if !wait_unless_stopped(interval, &should_stop).await {
+            break;
+        }
+    }
+
+    info!("Watch mode stopped after {} cycles", cycles);
+    Ok(cycles)
+}
+
+/// Sleep for `duration`, returning false as soon as `should_stop` becomes true.
+async fn wait_unless_stopped(duration: Duration, should_stop: &impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        if should_stop() {
+            return false;
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        tokio::time::sleep(remaining.min(STOP_POLL)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_parse_interval_units() {
+        assert_eq!(parse_interval("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_interval("15m"), Some(Duration::from_secs(900)));
+        assert_eq!(parse_interval("6h"), Some(Duration::from_secs(21_600)));
+        assert_eq!(parse_interval("1d"), Some(Duration::from_secs(86_400)));
+        assert_eq!(parse_interval("0s"), None);
+        assert_eq!(parse_interval("5x"), None);
+        assert_eq!(parse_interval("m"), None);
+    }
+
+    #[tokio::test]
+    async fn test_watch_runs_cycles_until_stopped() {
+        let runs = AtomicUsize::new(0);
+        let cycles = repeat_until_stopped(
+            Some(Duration::from_millis(5)),
+            || runs.load(Ordering::SeqCst) >= 3,
+            || async {
+                // A failing cycle is logged and does not end the watcher
+                if runs.fetch_add(1, Ordering::SeqCst) == 1 {
+                    anyhow::bail!("transient outage");
+                }
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(cycles, 3);
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stop_request_interrupts_the_wait() {
+        let stop_after = Instant::now() + Duration::from_millis(50);
+        let started = Instant::now();
+        let cycles = repeat_until_stopped(
+            Some(Duration::from_secs(3600)),
+            || Instant::now() >= stop_after,
+            || async { Ok(()) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(cycles, 1);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let once = repeat_until_stopped(
+            None,
+            || false,
+            || async { anyhow::bail!("single run fails") },
+        )
+        .await;
+        assert!(once.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_session_ends_the_watch() {
+        let runs = AtomicUsize::new(0);
+        let result = repeat_until_stopped(
+            Some(Duration::from_millis(5)),
+            || false,
+            || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Err(ExtractError::Auth("session expired".to_string()).into())
+            },
+        )
+        .await;
+        assert!(matches!(
+            find_extract_error(&result.unwrap_err()),
+            Some(ExtractError::Auth(_))
+        ));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
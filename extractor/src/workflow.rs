@@ -1,14 +1,46 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::future;
 use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
 use std::fs;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
+use crate::error::{find_extract_error, ExtractError};
 use crate::models::{ApiQuestionResponse, MediaFiles, QuestionData};
 use serde_json::Value;
 
 use super::MKSAPExtractor;
+use crate::progress::Progress;
+
+/// Tries per question when the API answers 429 or 5xx.
+const QUESTION_ATTEMPTS: u32 = 3;
+
+/// Per-category counts from [`MKSAPExtractor::extract_category_with_progress`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CategoryExtraction {
+    /// Active questions written.
+    pub extracted: usize,
+    /// Invalidated questions filed under `retired/` instead of the data tree.
+    pub retired: usize,
+    /// Existing questions `--since` found unchanged and left as they were.
+    pub unchanged: usize,
+    /// Questions rejected because the session is missing or expired.
+    pub auth_failures: usize,
+}
+
+/// What fetching a single question produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum QuestionOutcome {
+    Extracted,
+    /// The API marks the question invalidated; it was saved under `retired/`.
+    Retired,
+    /// `--since` found the existing copy up to date; nothing was written.
+    Unchanged,
+    NotFound,
+}
 
 impl MKSAPExtractor {
     pub async fn extract_category(
@@ -16,10 +48,22 @@ impl MKSAPExtractor {
         category: &crate::config::Category,
         refresh_existing: bool,
     ) -> Result<usize> {
+        self.extract_category_with_progress(category, refresh_existing, &Progress::hidden())
+            .await
+            .map(|counts| counts.extracted)
+    }
+
+    /// Like [`Self::extract_category`], reporting each finished question to `progress`.
+    pub(crate) async fn extract_category_with_progress(
+        &self,
+        category: &crate::config::Category,
+        refresh_existing: bool,
+        progress: &Progress,
+    ) -> Result<CategoryExtraction> {
         debug!("Extracting: {}", category.name);
 
         let existing_ids = self.load_existing_question_ids(&category.code)?;
-        let concurrency = Self::concurrency_limit();
+        let concurrency = self.concurrency;
 
         // Phase 1: Discovery - find all valid questions
         debug!(
@@ -55,83 +99,188 @@ impl MKSAPExtractor {
             valid_ids.len(),
             concurrency
         );
-        let mut questions_extracted = 0;
-        let targets: Vec<String> = if refresh_existing {
-            valid_ids
-        } else {
-            valid_ids
-                .into_iter()
-                .filter(|question_id| !existing_ids.contains(question_id))
-                .collect()
-        };
+        let mut counts = CategoryExtraction::default();
+        // Dedupe so no two workers ever write the same question directory
+        let mut seen = HashSet::new();
+        // With `--since`, existing questions stay in so each one is checked for updates
+        let keep_existing = refresh_existing || self.since.is_some();
+        let mut targets: Vec<String> = valid_ids
+            .into_iter()
+            .filter(|question_id| keep_existing || !existing_ids.contains(question_id))
+            .filter(|question_id| seen.insert(question_id.clone()))
+            .collect();
+        if let Some(limit) = self.limit.filter(|limit| *limit < targets.len()) {
+            info!(
+                "{}: --limit {} active, extracting {} of {} pending questions",
+                category.code,
+                limit,
+                limit,
+                targets.len()
+            );
+            targets = crate::utils::first_sorted_ids(targets, limit);
+        }
 
-        let total_to_process = targets.len();
-        let mut processed = 0usize;
+        progress.inc_length(targets.len());
 
-        let mut stream = stream::iter(targets.into_iter())
+        // Stop handing out questions once Ctrl-C is pressed; in-flight ones still finish
+        let mut stream = stream::iter(targets)
+            .take_while(|_| future::ready(!crate::shutdown::shutdown_requested()))
             .map(|question_id| async move {
-                (
-                    question_id.clone(),
-                    self.extract_question(&category.code, &question_id, refresh_existing)
-                        .await,
-                )
+                let started = Instant::now();
+                let result = self
+                    .extract_question(&category.code, &question_id, refresh_existing)
+                    .await;
+                (question_id, result, started.elapsed())
             })
             .buffer_unordered(concurrency);
 
-        while let Some((question_id, result)) = stream.next().await {
-            processed += 1;
-            if processed.is_multiple_of(10) || processed == total_to_process {
-                info!(
-                    "Progress: {}/{} questions processed",
-                    processed, total_to_process
-                );
+        while let Some((question_id, result, elapsed)) = stream.next().await {
+            progress.inc();
+            if let Some(profile) = self.profile() {
+                let outcome = match &result {
+                    Ok(QuestionOutcome::Extracted) => "ok",
+                    Ok(QuestionOutcome::Retired) => "retired",
+                    Ok(QuestionOutcome::Unchanged) => "unchanged",
+                    Ok(QuestionOutcome::NotFound) => "not_found",
+                    Err(_) => "error",
+                };
+                profile.record(&question_id, &category.code, elapsed, outcome);
             }
 
             match result {
-                Ok(true) => {
-                    questions_extracted += 1;
+                Ok(QuestionOutcome::Extracted) => {
+                    counts.extracted += 1;
                 }
-                Ok(false) => {
+                Ok(QuestionOutcome::Retired) => {
+                    counts.retired += 1;
+                }
+                Ok(QuestionOutcome::Unchanged) => {
+                    counts.unchanged += 1;
+                }
+                Ok(QuestionOutcome::NotFound) => {
                     warn!(
                         "Question {} returned 404 despite being in discovery list",
                         question_id
                     );
+                    crate::events::emit(
+                        "question_not_found",
+                        Some(&question_id),
+                        "404 despite being in discovery list",
+                    );
                 }
                 Err(e) => {
+                    if matches!(find_extract_error(&e), Some(ExtractError::Auth(_))) {
+                        counts.auth_failures += 1;
+                    }
                     error!("Error extracting {}: {}", question_id, e);
+                    crate::events::emit(
+                        "extraction_failed",
+                        Some(&question_id),
+                        format!("{:#}", e),
+                    );
                 }
             }
         }
 
-        // Skip count will be included in per-system summary from main.rs
+        if counts.retired > 0 {
+            info!(
+                "{}: {} invalidated questions filed under retired/",
+                category.code, counts.retired
+            );
+        }
+        if counts.unchanged > 0 {
+            info!(
+                "{}: {} existing questions unchanged since --since",
+                category.code, counts.unchanged
+            );
+        }
 
-        Ok(questions_extracted)
+        Ok(counts)
     }
 
+    /// Fetch and save one question, retrying rate limits and 5xx responses.
     pub(super) async fn extract_question(
         &self,
         category_code: &str,
         question_id: &str,
         refresh_existing: bool,
-    ) -> Result<bool> {
+    ) -> Result<QuestionOutcome> {
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .extract_question_once(category_code, question_id, refresh_existing)
+                .await;
+            let Err(err) = &result else {
+                return result;
+            };
+            let Some(transient) = find_extract_error(err).filter(|err| err.is_retriable()) else {
+                return result;
+            };
+            if attempt >= QUESTION_ATTEMPTS || crate::shutdown::shutdown_requested() {
+                return result;
+            }
+            let delay = transient.retry_delay(attempt);
+            warn!(
+                "{} for {}; retrying in {:.1}s",
+                transient,
+                question_id,
+                delay.as_secs_f32()
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn extract_question_once(
+        &self,
+        category_code: &str,
+        question_id: &str,
+        refresh_existing: bool,
+    ) -> Result<QuestionOutcome> {
         let json_path = self.question_json_path(category_code, question_id);
+        let api_url = crate::endpoints::question_json(&self.base_url, question_id);
+        // With `--since`, an existing question is re-fetched unless it is known to be unchanged
+        let mut check_since = None;
         if !refresh_existing
             && json_path.exists()
             && Self::is_valid_question_json(&json_path, question_id)
         {
-            cleanup_learning_plan_topic(&json_path);
-            info!("Skipping extraction for {} (already exists)", question_id);
-            return Ok(true);
+            match self.since {
+                Some(since) if self.modified_since(&api_url, since).await => {
+                    check_since = Some(since);
+                }
+                Some(since) => {
+                    cleanup_learning_plan_topic(&json_path);
+                    info!(
+                        "Skipping extraction for {} (not modified since {})",
+                        question_id,
+                        since.format("%Y-%m-%d")
+                    );
+                    return Ok(QuestionOutcome::Unchanged);
+                }
+                None => {
+                    cleanup_learning_plan_topic(&json_path);
+                    info!("Skipping extraction for {} (already exists)", question_id);
+                    return Ok(QuestionOutcome::Extracted);
+                }
+            }
+        }
+        if !refresh_existing
+            && Self::is_valid_question_json(&self.retired_json_path(question_id), question_id)
+        {
+            debug!("Skipping {} (already filed as retired)", question_id);
+            return Ok(QuestionOutcome::Retired);
         }
 
-        let api_url = crate::endpoints::question_json(&self.base_url, question_id);
-
+        // Held until the question is written; bounds in-flight requests across categories
+        let _permit = self.request_permits.acquire().await?;
         let response =
             crate::http::send_with_timeout(self.client.get(&api_url), Duration::from_secs(30))
                 .await?;
 
         match response.status() {
             status if status.is_success() => {
+                let fetched_at = chrono::Utc::now().to_rfc3339();
                 let json_text = response.text().await?;
 
                 let api_response: ApiQuestionResponse = match serde_json::from_str(&json_text) {
@@ -151,22 +300,44 @@ impl MKSAPExtractor {
                             &e.to_string(),
                         )
                         .ok();
-                        return Err(anyhow::anyhow!(
-                            "Failed to parse question JSON for {}: {}",
-                            question_id,
-                            e
-                        ));
+                        return Err(ExtractError::Parse {
+                            what: format!("question JSON for {}", question_id),
+                            source: e,
+                        }
+                        .into());
                     }
                 };
 
-                // Skip retired/invalidated questions
-                if api_response.invalidated {
-                    info!("Skipping retired question: {}", question_id);
-                    return Ok(true);
+                if let Some(since) = check_since {
+                    let updated = api_response
+                        .updated_at
+                        .as_deref()
+                        .and_then(crate::since::parse_update_timestamp);
+                    if updated.is_some_and(|updated| updated < since) {
+                        info!(
+                            "Skipping extraction for {} (not updated since {})",
+                            question_id,
+                            since.format("%Y-%m-%d")
+                        );
+                        return Ok(QuestionOutcome::Unchanged);
+                    }
                 }
 
+                let invalidated = api_response.invalidated;
                 let mut question = api_response.into_question_data(category_code.to_string());
-                if refresh_existing {
+                question.provenance.source_url = api_url.clone();
+                question.provenance.http_status = status.as_u16();
+                question.provenance.fetched_at = fetched_at;
+
+                // Retired questions stay out of the active tree so counts and validation
+                // only ever see current content
+                if invalidated {
+                    info!("Filing invalidated question {} under retired/", question_id);
+                    self.save_retired_question(category_code, &question)?;
+                    return Ok(QuestionOutcome::Retired);
+                }
+
+                if refresh_existing || check_since.is_some() {
                     merge_existing_media(&mut question, &json_path);
                 }
 
@@ -174,26 +345,46 @@ impl MKSAPExtractor {
                 self.quarantine_if_invalid(category_code, &question.question_id)
                     .ok();
 
-                Ok(true)
+                Ok(QuestionOutcome::Extracted)
             }
             reqwest::StatusCode::NOT_FOUND => {
                 // Expected with brute force
-                Ok(false)
+                Ok(QuestionOutcome::NotFound)
             }
-            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
-                warn!("Authentication expired for {}", question_id);
-                Err(anyhow::anyhow!("Authentication expired"))
-            }
-            reqwest::StatusCode::TOO_MANY_REQUESTS => {
-                warn!("Rate limited, backing off...");
-                sleep(Duration::from_secs(60)).await;
-                Err(anyhow::anyhow!("Rate limited"))
+            status => {
+                let err = ExtractError::from_status(status, &api_url, response.headers());
+                if let ExtractError::Auth(_) = err {
+                    warn!("Authentication expired for {}", question_id);
+                }
+                Err(err.into())
             }
-            status => Err(anyhow::anyhow!("API error {}", status)),
         }
     }
 }
 
+impl MKSAPExtractor {
+    /// HEAD the question and compare `Last-Modified` with `since`. Without a usable header
+    /// the question may have changed, so it is fetched and its `updatedAt` checked instead.
+    async fn modified_since(&self, api_url: &str, since: DateTime<Utc>) -> bool {
+        let Ok(_permit) = self.request_permits.acquire().await else {
+            return true;
+        };
+        let response =
+            crate::http::send_with_timeout(self.client.head(api_url), Duration::from_secs(30))
+                .await;
+        let last_modified = response.ok().and_then(|response| {
+            let value = response
+                .headers()
+                .get(reqwest::header::LAST_MODIFIED)?
+                .to_str()
+                .ok()?
+                .to_string();
+            crate::since::parse_update_timestamp(&value)
+        });
+        last_modified.is_none_or(|modified| modified >= since)
+    }
+}
+
 fn merge_existing_media(question: &mut QuestionData, json_path: &std::path::Path) {
     let text = match fs::read_to_string(json_path) {
         Ok(text) => text,
@@ -227,9 +418,16 @@ fn cleanup_learning_plan_topic(json_path: &std::path::Path) {
         Err(_) => return,
     };
 
+    // Only drop the legacy empty placeholder; populated topics are real data.
     let removed = value
         .get_mut("related_content")
         .and_then(|value| value.as_object_mut())
+        .filter(|object| {
+            object
+                .get("learning_plan_topic")
+                .and_then(|topic| topic.as_str())
+                .is_some_and(|topic| topic.trim().is_empty())
+        })
         .and_then(|object| object.remove("learning_plan_topic"))
         .is_some();
 
@@ -237,15 +435,252 @@ fn cleanup_learning_plan_topic(json_path: &std::path::Path) {
         return;
     }
 
-    let pretty = match serde_json::to_string_pretty(&value) {
-        Ok(pretty) => pretty,
+    let json = match crate::utils::to_json_string(&value) {
+        Ok(json) => json,
         Err(_) => return,
     };
 
-    if fs::write(json_path, pretty).is_ok() {
+    if fs::write(json_path, json).is_ok() {
         debug!(
-            "Removed related_content.learning_plan_topic from {}",
+            "Removed empty related_content.learning_plan_topic from {}",
             json_path.display()
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Category;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_invalidated_questions_are_filed_under_retired() {
+        let fixture = include_str!("../tests/fixtures/question_cvmcq24001.json");
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24001.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(fixture, "application/json"))
+            .mount(&server)
+            .await;
+        let retired = fixture
+            .replace("cvmcq24001", "cvmcq24002")
+            .replace("\"invalidated\": false", "\"invalidated\": true");
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24002.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(retired, "application/json"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().join("data");
+        let checkpoints = output_dir.join(".checkpoints");
+        fs::create_dir_all(&checkpoints).unwrap();
+        fs::write(checkpoints.join("cv_ids.txt"), "cvmcq24001\ncvmcq24002").unwrap();
+
+        let extractor = MKSAPExtractor::new(&server.uri(), output_dir.to_str().unwrap()).unwrap();
+        let category = Category {
+            code: "cv".to_string(),
+            name: "Cardiovascular Medicine".to_string(),
+            question_prefix: "cv".to_string(),
+        };
+
+        let counts = extractor
+            .extract_category_with_progress(&category, false, &Progress::hidden())
+            .await
+            .unwrap();
+        assert_eq!(
+            counts,
+            CategoryExtraction {
+                extracted: 1,
+                retired: 1,
+                unchanged: 0,
+                auth_failures: 0
+            }
+        );
+        let saved: QuestionData = serde_json::from_str(
+            &fs::read_to_string(output_dir.join("cv/cvmcq24001/cvmcq24001.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            saved.provenance.source_url,
+            format!("{}/api/questions/cvmcq24001.json", server.uri())
+        );
+        assert_eq!(saved.provenance.http_status, 200);
+        assert!(!saved.provenance.fetched_at.is_empty());
+        assert!(!output_dir.join("cv/cvmcq24002").exists());
+        let retired_json = temp
+            .path()
+            .join("mksap_data_failed/retired/cvmcq24002/cvmcq24002.json");
+        assert!(retired_json.exists());
+
+        // A second run recognises the retired copy without fetching it again
+        let counts = extractor
+            .extract_category_with_progress(&category, false, &Progress::hidden())
+            .await
+            .unwrap();
+        assert_eq!(counts.retired, 1);
+    }
+
+    #[tokio::test]
+    async fn test_limit_extracts_first_sorted_questions() {
+        let fixture = include_str!("../tests/fixtures/question_cvmcq24001.json");
+        let server = MockServer::start().await;
+        for id in ["cvmcq24001", "cvmcq24002", "cvmcq24003"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/questions/{}.json", id)))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_raw(fixture.replace("cvmcq24001", id), "application/json"),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().join("data");
+        let checkpoints = output_dir.join(".checkpoints");
+        fs::create_dir_all(&checkpoints).unwrap();
+        fs::write(
+            checkpoints.join("cv_ids.txt"),
+            "cvmcq24003\ncvmcq24001\ncvmcq24002",
+        )
+        .unwrap();
+
+        let extractor = MKSAPExtractor::new(&server.uri(), output_dir.to_str().unwrap())
+            .unwrap()
+            .with_limit(2);
+        let category = Category {
+            code: "cv".to_string(),
+            name: "Cardiovascular Medicine".to_string(),
+            question_prefix: "cv".to_string(),
+        };
+        let counts = extractor
+            .extract_category_with_progress(&category, false, &Progress::hidden())
+            .await
+            .unwrap();
+        assert_eq!(counts.extracted, 2);
+        assert!(output_dir.join("cv/cvmcq24001/cvmcq24001.json").exists());
+        assert!(output_dir.join("cv/cvmcq24002/cvmcq24002.json").exists());
+        assert!(!output_dir.join("cv/cvmcq24003/cvmcq24003.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_since_refetches_only_updated_questions() {
+        let fixture = include_str!("../tests/fixtures/question_cvmcq24001.json");
+        let server = MockServer::start().await;
+        // 24001 answers HEAD with an old Last-Modified; the others give no usable header
+        Mock::given(method("HEAD"))
+            .and(path("/api/questions/cvmcq24001.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Last-Modified", "Wed, 01 Jan 2025 00:00:00 GMT"),
+            )
+            .mount(&server)
+            .await;
+        for (id, updated) in [
+            ("cvmcq24001", "2025-01-01"),
+            ("cvmcq24002", "2025-07-01T08:00:00Z"),
+            ("cvmcq24003", "2025-02-01T08:00:00Z"),
+        ] {
+            let body = fixture.replace("cvmcq24001", id).replace(
+                "\"invalidated\": false,",
+                &format!("\"invalidated\": false, \"updatedAt\": \"{}\",", updated),
+            );
+            Mock::given(method("GET"))
+                .and(path(format!("/api/questions/{}.json", id)))
+                .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+                .mount(&server)
+                .await;
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().join("data");
+        let checkpoints = output_dir.join(".checkpoints");
+        fs::create_dir_all(&checkpoints).unwrap();
+        fs::write(
+            checkpoints.join("cv_ids.txt"),
+            "cvmcq24001\ncvmcq24002\ncvmcq24003",
+        )
+        .unwrap();
+        let output_dir = output_dir.to_str().unwrap();
+        let category = Category {
+            code: "cv".to_string(),
+            name: "Cardiovascular Medicine".to_string(),
+            question_prefix: "cv".to_string(),
+        };
+        let extractor = MKSAPExtractor::new(&server.uri(), output_dir).unwrap();
+        let counts = extractor
+            .extract_category_with_progress(&category, false, &Progress::hidden())
+            .await
+            .unwrap();
+        assert_eq!(counts.extracted, 3);
+
+        // The second run starts with every question on disk
+        let since = crate::since::parse_update_timestamp("2025-06-01").unwrap();
+        let extractor = MKSAPExtractor::new(&server.uri(), output_dir)
+            .unwrap()
+            .with_since(since);
+        let counts = extractor
+            .extract_category_with_progress(&category, false, &Progress::hidden())
+            .await
+            .unwrap();
+        assert_eq!((counts.extracted, counts.unchanged), (1, 2));
+
+        let requests = server.received_requests().await.unwrap();
+        let gets = |id: &str| {
+            requests
+                .iter()
+                .filter(|request| request.method.as_str() == "GET")
+                .filter(|request| request.url.path() == format!("/api/questions/{}.json", id))
+                .count()
+        };
+        assert_eq!(gets("cvmcq24001"), 1);
+        assert_eq!(gets("cvmcq24002"), 2);
+        assert_eq!(gets("cvmcq24003"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried_but_auth_errors_are_not() {
+        let fixture = include_str!("../tests/fixtures/question_cvmcq24001.json");
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24001.json"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24001.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(fixture, "application/json"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/questions/cvmcq24002.json"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().join("data");
+        let extractor = MKSAPExtractor::new(&server.uri(), output_dir.to_str().unwrap()).unwrap();
+
+        let outcome = extractor
+            .extract_question("cv", "cvmcq24001", false)
+            .await
+            .unwrap();
+        assert_eq!(outcome, QuestionOutcome::Extracted);
+
+        let err = extractor
+            .extract_question("cv", "cvmcq24002", false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            find_extract_error(&err),
+            Some(ExtractError::Auth(_))
+        ));
+    }
+}